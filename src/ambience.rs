@@ -0,0 +1,244 @@
+use macroquad::prelude::*;
+
+use crate::level::{Biome, LevelDef};
+use crate::world::{self, GridPos, Tile};
+
+/// Blades in one tuft, and how far their tips sway in still air.
+const TUFT_BLADES: usize = 3;
+const TUFT_HEIGHT: f32 = 5.0;
+const TUFT_SWAY: f32 = 1.2;
+/// One grass tile in this many grows a tuft.
+const TUFT_ODDS: u32 = 3;
+
+/// Seconds between flocks, at random in this range.
+const FLOCK_EVERY: (f32, f32) = (8.0, 18.0);
+const FLOCK_SIZE: (usize, usize) = (1, 4);
+const BIRD_SPEED: (f32, f32) = (55.0, 85.0);
+/// How far past the level's sides birds start and finish.
+const BIRD_MARGIN: f32 = 80.0;
+const BIRD_SPAN: f32 = 4.0;
+const BIRD_COLOR: Color = Color::new(0.12, 0.12, 0.15, 0.85);
+
+/// Seconds between fish jumping, at random in this range.
+const FISH_EVERY: (f32, f32) = (2.5, 6.0);
+const JUMP_TIME: f32 = 0.7;
+const JUMP_HEIGHT: f32 = 9.0;
+const JUMP_LENGTH: f32 = 12.0;
+const FISH_COLOR: Color = Color::new(0.75, 0.80, 0.85, 1.0);
+const SPLASH_COLOR: Color = Color::new(0.85, 0.92, 1.0, 0.8);
+
+struct Bird {
+    pos: Vec2,
+    speed: f32,
+    /// Offsets the wingbeat, so a flock doesn't flap in step.
+    phase: f32,
+}
+
+struct Jump {
+    from: Vec2,
+    /// -1 jumps to the left, 1 to the right.
+    heading: f32,
+    elapsed: f32,
+}
+
+/// Life about the level that has nothing to do with the puzzle: grass
+/// swaying on the banks, birds passing over and fish jumping in the river.
+/// Purely cosmetic.
+pub struct Ambience {
+    birds: Vec<Bird>,
+    flock_in: f32,
+    jumps: Vec<Jump>,
+    fish_in: f32,
+    time: f32,
+}
+
+impl Ambience {
+    pub fn new() -> Self {
+        Self {
+            birds: Vec::new(),
+            flock_in: rand::gen_range(FLOCK_EVERY.0, FLOCK_EVERY.1),
+            jumps: Vec::new(),
+            fish_in: rand::gen_range(FISH_EVERY.0, FISH_EVERY.1),
+            time: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, level: &LevelDef, dt: f32) {
+        self.time += dt;
+        let (min_x, min_y, max_x, _) = level.iso_bounds();
+
+        self.flock_in -= dt;
+        if self.flock_in <= 0.0 {
+            self.flock_in = rand::gen_range(FLOCK_EVERY.0, FLOCK_EVERY.1);
+            let rightward = rand::gen_range(0, 2) == 0;
+            let speed = rand::gen_range(BIRD_SPEED.0, BIRD_SPEED.1);
+            let (x, speed) = if rightward {
+                (min_x - BIRD_MARGIN, speed)
+            } else {
+                (max_x + BIRD_MARGIN, -speed)
+            };
+            let y = rand::gen_range(min_y - 60.0, min_y + 40.0);
+            for i in 0..rand::gen_range(FLOCK_SIZE.0, FLOCK_SIZE.1) {
+                // A loose V behind the leader.
+                let back = i as f32 * 10.0 * -speed.signum();
+                let side = if i % 2 == 0 { 1.0 } else { -1.0 } * i.div_ceil(2) as f32 * 7.0;
+                self.birds.push(Bird {
+                    pos: vec2(x + back, y + side),
+                    speed,
+                    phase: rand::gen_range(0.0, std::f32::consts::TAU),
+                });
+            }
+        }
+        for bird in &mut self.birds {
+            bird.pos.x += bird.speed * dt;
+        }
+        self.birds.retain(|bird| {
+            (min_x - BIRD_MARGIN * 2.0..=max_x + BIRD_MARGIN * 2.0).contains(&bird.pos.x)
+        });
+
+        self.fish_in -= dt;
+        if self.fish_in <= 0.0 {
+            self.fish_in = rand::gen_range(FISH_EVERY.0, FISH_EVERY.1);
+            let water: Vec<GridPos> = land_and_water(level)
+                .filter(|&(_, tile)| tile == Tile::Water)
+                .map(|(pos, _)| pos)
+                .collect();
+            if !water.is_empty() {
+                let pos = water[rand::gen_range(0, water.len())];
+                let (x, y) = world::grid_to_iso(pos);
+                self.jumps.push(Jump {
+                    from: vec2(x, y),
+                    heading: if rand::gen_range(0, 2) == 0 {
+                        -1.0
+                    } else {
+                        1.0
+                    },
+                    elapsed: 0.0,
+                });
+            }
+        }
+        for jump in &mut self.jumps {
+            jump.elapsed += dt;
+        }
+        self.jumps.retain(|jump| jump.elapsed < JUMP_TIME + 0.3);
+    }
+
+    /// Draw what lives on the ground and in the water, over the tiles and
+    /// under everything standing on them. `tint` is the light.
+    pub fn draw_ground(&self, level: &LevelDef, tint: Color) {
+        let wind = level.weather.wind;
+        let color = lit(tuft_color(level.biome), tint);
+        for (pos, tile) in land_and_water(level) {
+            if tile != Tile::Grass || level.is_shore(pos) {
+                continue;
+            }
+            let hash = tile_hash(pos);
+            if !hash.is_multiple_of(TUFT_ODDS) {
+                continue;
+            }
+            let (cx, cy) = world::grid_to_iso(pos);
+            // Somewhere in the middle of the tile, the same every frame.
+            let dx = ((hash >> 4) % 21) as f32 - 10.0;
+            let dy = ((hash >> 9) % 7) as f32 - 3.0;
+            let base = vec2(cx + dx, cy + dy);
+            let phase = (hash >> 13) as f32 * 0.01;
+            let sway = (self.time * 2.2 + phase).sin() * TUFT_SWAY * (1.0 + wind.abs() * 2.0)
+                + wind * TUFT_SWAY * 1.5;
+            for blade in 0..TUFT_BLADES {
+                let spread = blade as f32 - (TUFT_BLADES - 1) as f32 / 2.0;
+                let root = base + vec2(spread * 1.5, 0.0);
+                let tip = root + vec2(spread * 1.5 + sway, -TUFT_HEIGHT + spread.abs());
+                draw_line(root.x, root.y, tip.x, tip.y, 1.0, color);
+            }
+        }
+
+        for jump in &self.jumps {
+            let t = jump.elapsed / JUMP_TIME;
+            if t < 1.0 {
+                let x = jump.from.x + jump.heading * JUMP_LENGTH * t;
+                let y = jump.from.y - JUMP_HEIGHT * 4.0 * t * (1.0 - t);
+                draw_ellipse(
+                    x,
+                    y,
+                    3.0,
+                    1.5,
+                    jump.heading * (t - 0.5) * 60.0,
+                    lit(FISH_COLOR, tint),
+                );
+            }
+            // A ring where it left the water, and one where it went back in.
+            let splashes = [
+                (jump.from, jump.elapsed),
+                (
+                    jump.from + vec2(jump.heading * JUMP_LENGTH, 0.0),
+                    jump.elapsed - JUMP_TIME,
+                ),
+            ];
+            for (at, age) in splashes {
+                if (0.0..0.3).contains(&age) {
+                    let ring = Color {
+                        a: SPLASH_COLOR.a * (1.0 - age / 0.3),
+                        ..lit(SPLASH_COLOR, tint)
+                    };
+                    draw_ellipse_lines(
+                        at.x,
+                        at.y,
+                        2.0 + age * 20.0,
+                        1.0 + age * 8.0,
+                        0.0,
+                        1.0,
+                        ring,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Draw the birds, over the whole world.
+    pub fn draw_sky(&self, tint: Color) {
+        let color = lit(BIRD_COLOR, tint);
+        for bird in &self.birds {
+            let flap = (self.time * 9.0 + bird.phase).sin() * BIRD_SPAN * 0.6;
+            let Vec2 { x, y } = bird.pos;
+            draw_line(x - BIRD_SPAN, y - flap, x, y, 1.2, color);
+            draw_line(x, y, x + BIRD_SPAN, y - flap, 1.2, color);
+        }
+    }
+}
+
+fn land_and_water(level: &LevelDef) -> impl Iterator<Item = (GridPos, Tile)> + '_ {
+    (0..level.rows).flat_map(move |row| {
+        (0..level.cols).map(move |col| {
+            let pos = GridPos::new(col, row);
+            (pos, level.tile(pos))
+        })
+    })
+}
+
+/// A number that's the same for a tile every frame, and unrelated to its
+/// neighbours'.
+fn tile_hash(pos: GridPos) -> u32 {
+    let mut h =
+        (pos.col as u32).wrapping_mul(0x9e37_79b1) ^ (pos.row as u32).wrapping_mul(0x85eb_ca77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^ (h >> 12)
+}
+
+fn tuft_color(biome: Biome) -> Color {
+    match biome {
+        Biome::Summer => Color::new(0.22, 0.50, 0.14, 1.0),
+        Biome::Autumn => Color::new(0.70, 0.55, 0.22, 1.0),
+        Biome::Winter => Color::new(0.55, 0.60, 0.50, 1.0),
+        Biome::Swamp => Color::new(0.30, 0.36, 0.18, 1.0),
+    }
+}
+
+fn lit(color: Color, tint: Color) -> Color {
+    Color::new(
+        color.r * tint.r,
+        color.g * tint.g,
+        color.b * tint.b,
+        color.a,
+    )
+}
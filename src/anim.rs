@@ -1,137 +1,643 @@
-use crate::game::{Entity, EntityLocation, GameState, PlayerLocation};
-use crate::world;
+use crate::clips::{self, Playback};
+use crate::emote;
+use crate::events::GameEvent;
+use crate::game::{
+    BoatId, BoatState, EntityId, EntityLocation, Farmer, GamePhase, GameState, LoseReason,
+    PlayerLocation,
+};
+use crate::render;
+use crate::tween::{self, Ease, Tween};
+use crate::world::{self, GridPos};
 
 const MOVE_SPEED: f32 = 350.0;
 const FOLLOWER_SPEED: f32 = 300.0;
-const WALK_FRAME_DURATION: f32 = 0.12;
+/// Idle animals amble between tiles.
+const STROLL_SPEED: f32 = 40.0;
+/// Walking pace of anyone a cutscene moves.
+const SCRIPTED_SPEED: f32 = 90.0;
 const SNAP_DISTANCE: f32 = 128.0;
 const ARRIVE_THRESHOLD: f32 = 0.5;
+/// Seconds an entity springs about for after being picked up.
+const POP_TIME: f32 = 0.35;
+/// How far it stretches at the start of the pop.
+const POP_STRETCH: f32 = 0.3;
+/// Seconds the boat wobbles for after bumping into the dock.
+const BUMP_TIME: f32 = 0.45;
+/// How far it squashes on impact.
+const BUMP_SQUASH: f32 = 0.12;
+/// Seconds the win and lose banners take to slide in.
+const BANNER_TIME: f32 = 0.5;
+/// Seconds to walk between the dock and the boat's edge.
+const EDGE_WALK_TIME: f32 = 0.15;
+/// Seconds a hop over the gunwale takes.
+const HOP_TIME: f32 = 0.3;
+/// How high the hop arcs, in pixels.
+const HOP_HEIGHT: f32 = 10.0;
+/// How far from the dock toward the seat the boat's edge is.
+const EDGE_SHARE: f32 = 0.55;
+/// Seconds the camera gets to pan to the bank before the eater moves.
+const LOSS_LEAD_TIME: f32 = 0.6;
+/// How fast the eater goes for its prey.
+const CHASE_SPEED: f32 = 140.0;
+/// Seconds the prey takes to disappear once caught.
+const EAT_TIME: f32 = 1.0;
+/// How far short of the prey the eater stops to eat.
+const BITE_REACH: f32 = 10.0;
 
+#[derive(Debug, Clone)]
 pub struct EntityAnim {
     pub pos: (f32, f32),
     pub moving: bool,
     pub facing_right: bool,
+    /// Set while walking between neighbouring tiles on its own.
+    pub strolling: bool,
+    /// Tiles a follower still has to walk through to reach the player.
+    pub path: Vec<GridPos>,
+    pub clip: Playback,
+    /// Squash and stretch, springing back to 0 after a pick-up.
+    pub pop: Tween,
+    /// Set while climbing into or out of a boat.
+    pub hop: Option<Hop>,
+    /// The boat it rode and its seat there, last update.
+    aboard: Option<(BoatId, (f32, f32))>,
+    last_location: Option<EntityLocation>,
 }
 
+/// Player two's sprite in co-op.
+#[derive(Debug, Clone)]
+pub struct PartnerAnim {
+    pub pos: (f32, f32),
+    pub moving: bool,
+    pub facing_right: bool,
+    pub clip: Playback,
+}
+
+/// One boat's sprite.
+#[derive(Debug, Clone)]
+pub struct BoatAnim {
+    pub clip: Playback,
+    /// Squash and stretch, wobbling back to 0 after the boat docks.
+    pub bump: Tween,
+}
+
+/// Someone climbing into a boat: a few steps to the water's edge and a
+/// hop over it into their seat. Climbing out is the same backwards.
+#[derive(Debug, Clone, Copy)]
+pub struct Hop {
+    /// Where they stand on the dock.
+    shore: (f32, f32),
+    /// Where they sit aboard.
+    seat: (f32, f32),
+    boarding: bool,
+    elapsed: f32,
+}
+
+impl Hop {
+    fn new(shore: (f32, f32), seat: (f32, f32), boarding: bool) -> Self {
+        Self {
+            shore,
+            seat,
+            boarding,
+            elapsed: 0.0,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.elapsed >= EDGE_WALK_TIME + HOP_TIME
+    }
+
+    /// Whether they're walking rather than in the air, and how far through
+    /// that leg they are.
+    fn leg(&self) -> (bool, f32) {
+        // Boarding walks then hops; getting off hops then walks.
+        let (walking, t) = match self.boarding {
+            true if self.elapsed < EDGE_WALK_TIME => (true, self.elapsed / EDGE_WALK_TIME),
+            true => (false, (self.elapsed - EDGE_WALK_TIME) / HOP_TIME),
+            false if self.elapsed < HOP_TIME => (false, self.elapsed / HOP_TIME),
+            false => (true, (self.elapsed - HOP_TIME) / EDGE_WALK_TIME),
+        };
+        (walking, t.min(1.0))
+    }
+
+    /// How high off the ground they are, in pixels.
+    pub fn lift(&self) -> f32 {
+        match self.leg() {
+            (true, _) => 0.0,
+            (false, t) => (t * std::f32::consts::PI).sin() * HOP_HEIGHT,
+        }
+    }
+
+    /// Where they are now, and whether they're walking rather than in the
+    /// air.
+    fn position(&self) -> ((f32, f32), bool) {
+        let edge = (
+            tween::lerp(self.shore.0, self.seat.0, EDGE_SHARE),
+            tween::lerp(self.shore.1, self.seat.1, EDGE_SHARE),
+        );
+        let (walking, t) = self.leg();
+        let (from, to) = match (self.boarding, walking) {
+            (true, true) => (self.shore, edge),
+            (true, false) => (edge, self.seat),
+            (false, false) => (self.seat, edge),
+            (false, true) => (edge, self.shore),
+        };
+        let pos = (
+            tween::lerp(from.0, to.0, t),
+            tween::lerp(from.1, to.1, t) - self.lift(),
+        );
+        (pos, walking)
+    }
+
+    /// Move the sprite along. Returns false once the hop is over.
+    fn update(&mut self, pos: &mut (f32, f32), moving: &mut bool, dt: f32) -> bool {
+        self.elapsed += dt;
+        let (at, walking) = self.position();
+        *pos = at;
+        *moving = walking;
+        !self.is_done()
+    }
+}
+
+/// A loss to something getting eaten, played out before the banner: a
+/// beat for the camera to find the bank, the eater running at its prey,
+/// and the prey shrinking away as it's eaten.
+#[derive(Debug, Clone, Copy)]
+pub struct LossScene {
+    pub eater: EntityId,
+    pub prey: EntityId,
+    elapsed: f32,
+    /// Seconds since the eater reached its prey; None until then.
+    eating: Option<f32>,
+}
+
+impl LossScene {
+    fn new(eater: EntityId, prey: EntityId) -> Self {
+        Self {
+            eater,
+            prey,
+            elapsed: 0.0,
+            eating: None,
+        }
+    }
+
+    /// Whether the eater has caught its prey.
+    pub fn is_biting(&self) -> bool {
+        self.eating.is_some()
+    }
+
+    /// Whether the prey is gone and the banner can come in.
+    pub fn is_done(&self) -> bool {
+        self.eating.is_some_and(|t| t >= EAT_TIME)
+    }
+
+    /// How much of the prey is left to draw, 1 down to 0.
+    pub fn prey_left(&self) -> f32 {
+        1.0 - self
+            .eating
+            .map_or(0.0, |t| Ease::SmoothStep.apply(t / EAT_TIME))
+    }
+
+    /// Move the eater along, or eat.
+    fn update(&mut self, entities: &mut [EntityAnim], dt: f32) {
+        self.elapsed += dt;
+        if let Some(t) = &mut self.eating {
+            *t += dt;
+            return;
+        }
+        if self.elapsed < LOSS_LEAD_TIME {
+            return;
+        }
+        let prey = entities[self.prey.0].pos;
+        let eater = &mut entities[self.eater.0];
+        let side = if eater.pos.0 <= prey.0 { -1.0 } else { 1.0 };
+        let target = (prey.0 + side * BITE_REACH, prey.1);
+        let dx = target.0 - eater.pos.0;
+        eater.moving = move_toward(&mut eater.pos, target, CHASE_SPEED, dt);
+        if eater.moving {
+            if dx.abs() > 0.1 {
+                eater.facing_right = dx > 0.0;
+            }
+            return;
+        }
+        eater.facing_right = side < 0.0;
+        self.eating = Some(0.0);
+    }
+}
+
+/// Someone a cutscene can walk about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Puppet {
+    Player,
+    Entity(EntityId),
+}
+
+#[derive(Debug, Clone)]
 pub struct AnimState {
     pub player_pos: (f32, f32),
     pub player_moving: bool,
     pub player_facing_right: bool,
-    pub walk_timer: f32,
-    pub walk_frame: usize,
-    pub entities: [(Entity, EntityAnim); 3],
+    pub partner: Option<PartnerAnim>,
+    /// Seconds since the player last moved or did anything.
+    pub player_idle: f32,
+    /// Where the player was and who they led, last update.
+    last_player: (PlayerLocation, Option<EntityId>),
+    /// Set while the player climbs into or out of a boat.
+    pub player_hop: Option<Hop>,
+    pub player_clip: Playback,
+    /// Indexed by `BoatId`, like `GameState::boats`.
+    pub boats: Vec<BoatAnim>,
+    /// How much of the way the win or lose banner has left to slide; 0
+    /// once it's in place.
+    pub banner_out: Tween,
+    /// Set from a loss to something getting eaten until the next run.
+    pub loss: Option<LossScene>,
+    /// Indexed by `EntityId`, like `GameState::entities`.
+    pub entities: Vec<EntityAnim>,
 }
 
 impl AnimState {
-    pub fn new() -> Self {
+    /// Start with everyone standing on their level's starting tiles.
+    pub fn new(state: &GameState) -> Self {
         Self {
-            player_pos: world::grid_to_iso(world::PLAYER_START),
+            player_pos: world::grid_to_iso(state.level.player_start),
             player_moving: false,
             player_facing_right: true,
-            walk_timer: 0.0,
-            walk_frame: 0,
-            entities: [
-                (
-                    Entity::Wolf,
-                    EntityAnim {
-                        pos: world::grid_to_iso(world::WOLF_START),
-                        moving: false,
-                        facing_right: true,
-                    },
-                ),
-                (
-                    Entity::Sheep,
-                    EntityAnim {
-                        pos: world::grid_to_iso(world::SHEEP_START),
-                        moving: false,
-                        facing_right: true,
-                    },
-                ),
-                (
-                    Entity::Cabbage,
-                    EntityAnim {
-                        pos: world::grid_to_iso(world::CABBAGE_START),
-                        moving: false,
-                        facing_right: true,
-                    },
-                ),
-            ],
-        }
-    }
-
-    pub fn reset(&mut self) {
-        *self = Self::new();
+            partner: state.partner.as_ref().map(|partner| PartnerAnim {
+                pos: match partner.location {
+                    PlayerLocation::OnLand(pos) => world::grid_to_iso(pos),
+                    PlayerLocation::OnBoat(boat) => {
+                        world::grid_to_iso(state.level.boats[boat.0].docks.1)
+                    }
+                },
+                moving: false,
+                facing_right: false,
+                clip: Playback::new(),
+            }),
+            player_idle: 0.0,
+            last_player: (state.player.location, state.player.follower),
+            player_hop: None,
+            player_clip: Playback::new(),
+            boats: state
+                .boats
+                .iter()
+                .map(|_| BoatAnim {
+                    clip: Playback::new(),
+                    bump: Tween::settled(0.0),
+                })
+                .collect(),
+            banner_out: Tween::settled(0.0),
+            loss: None,
+            entities: state
+                .level
+                .entities
+                .iter()
+                .map(|&(_, pos)| EntityAnim {
+                    pos: world::grid_to_iso(pos),
+                    moving: false,
+                    facing_right: true,
+                    strolling: false,
+                    path: Vec::new(),
+                    clip: Playback::new(),
+                    pop: Tween::settled(0.0),
+                    hop: None,
+                    aboard: None,
+                    last_location: None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn reset(&mut self, state: &GameState) {
+        *self = Self::new(state);
+    }
+
+    /// Whether someone is climbing into or out of a boat. The player's
+    /// input waits until they're done.
+    pub fn is_hopping(&self) -> bool {
+        self.player_hop.is_some() || self.entities.iter().any(|anim| anim.hop.is_some())
     }
 
     pub fn update(&mut self, state: &GameState, dt: f32) {
         // --- Player position ---
-        match state.player {
-            PlayerLocation::OnLand(pos) => {
-                let target = world::grid_to_iso(pos);
-                let dx = target.0 - self.player_pos.0;
-                self.player_moving = lerp_toward(&mut self.player_pos, target, MOVE_SPEED, dt);
-                if self.player_moving && dx.abs() > 0.1 {
-                    self.player_facing_right = dx > 0.0;
-                }
+        match (self.last_player.0, state.player.location) {
+            (PlayerLocation::OnLand(_), PlayerLocation::OnBoat(boat)) => {
+                let seat = render::player_seat(state, boat);
+                self.player_hop = Some(Hop::new(self.player_pos, seat, true));
             }
-            PlayerLocation::OnBoat => {
-                self.player_moving = false;
+            (PlayerLocation::OnBoat(boat), PlayerLocation::OnLand(pos)) => {
+                let seat = render::player_seat(state, boat);
+                self.player_hop = Some(Hop::new(world::grid_to_iso(pos), seat, false));
             }
+            _ => {}
         }
-
-        // --- Walk cycle timer ---
-        let anyone_moving =
-            self.player_moving || self.entities.iter().any(|(_, e)| e.moving);
-        if anyone_moving {
-            self.walk_timer += dt;
-            if self.walk_timer >= WALK_FRAME_DURATION {
-                self.walk_timer -= WALK_FRAME_DURATION;
-                self.walk_frame = 1 - self.walk_frame;
+        if let Some(hop) = &mut self.player_hop {
+            if !hop.update(&mut self.player_pos, &mut self.player_moving, dt) {
+                self.player_hop = None;
             }
         } else {
-            self.walk_frame = 0;
-            self.walk_timer = 0.0;
+            self.player_moving = walk_toward(
+                &state.player,
+                &mut self.player_pos,
+                &mut self.player_facing_right,
+                dt,
+            );
+        }
+        if let (Some(partner), Some(anim)) = (&state.partner, &mut self.partner) {
+            anim.moving = walk_toward(partner, &mut anim.pos, &mut anim.facing_right, dt);
+        }
+        let player = (state.player.location, state.player.follower);
+        if self.player_moving || player != self.last_player {
+            self.player_idle = 0.0;
+        } else {
+            self.player_idle += dt;
         }
+        self.last_player = player;
 
         // --- Entity positions ---
-        for (entity, anim) in &mut self.entities {
-            if state.follower == Some(*entity) {
-                match state.player {
-                    PlayerLocation::OnLand(_) => {
-                        let target =
-                            (self.player_pos.0 - 10.0, self.player_pos.1 + 4.0);
+        for (entity, anim) in state.entities.iter().zip(&mut self.entities) {
+            let aboard = aboard(state, entity.id);
+            match (anim.aboard, aboard) {
+                (None, Some(boat)) => {
+                    let seat = render::entity_seat(state, boat, entity.id);
+                    anim.hop = Some(Hop::new(anim.pos, seat, true));
+                }
+                (Some((boat, seat)), None) => {
+                    let shore = match state.boat(boat).state {
+                        BoatState::Docked(region) => state.level.dock(boat.0, region),
+                        BoatState::Crossing { from, .. } => state.level.dock(boat.0, from),
+                    };
+                    anim.hop = Some(Hop::new(world::grid_to_iso(shore), seat, false));
+                }
+                _ => {}
+            }
+            anim.aboard = aboard.map(|boat| (boat, render::entity_seat(state, boat, entity.id)));
+            if self.loss.is_some_and(|scene| scene.eater == entity.id) {
+                continue;
+            }
+            if let Some(hop) = &mut anim.hop {
+                if !hop.update(&mut anim.pos, &mut anim.moving, dt) {
+                    anim.hop = None;
+                }
+                anim.last_location = None;
+                continue;
+            }
+            let pace = state.level.kind(entity.kind).speed;
+            let leader = match &state.partner {
+                Some(partner) if partner.follower == Some(entity.id) => {
+                    self.partner.as_ref().map(|anim| (partner, anim.pos))
+                }
+                _ if state.player.follower == Some(entity.id) => {
+                    Some((&state.player, self.player_pos))
+                }
+                _ => None,
+            };
+            if let Some((farmer, leader_pos)) = leader {
+                match farmer.location {
+                    PlayerLocation::OnLand(player_tile) => {
+                        // Next to the player, close in directly; further
+                        // off, walk the tiles around any obstacles.
+                        let here = world::iso_to_grid(anim.pos.0, anim.pos.1);
+                        anim.path = if world::is_adjacent(here, player_tile) {
+                            Vec::new()
+                        } else {
+                            world::find_path(here, player_tile, |pos| state.level.is_land(pos))
+                                .unwrap_or_default()
+                        };
+                        let target = match anim.path.first() {
+                            Some(&next) => world::grid_to_iso(next),
+                            None => (leader_pos.0 - 10.0, leader_pos.1 + 4.0),
+                        };
                         let dx = target.0 - anim.pos.0;
-                        anim.moving =
-                            lerp_toward(&mut anim.pos, target, FOLLOWER_SPEED, dt);
+                        anim.moving = lerp_toward(&mut anim.pos, target, FOLLOWER_SPEED * pace, dt);
                         if dx.abs() > 0.1 {
                             anim.facing_right = dx > 0.0;
                         }
                     }
-                    PlayerLocation::OnBoat => {
+                    PlayerLocation::OnBoat(_) => {
                         anim.moving = false;
+                        anim.path.clear();
                     }
                 }
             } else {
-                match state.entity_location(*entity) {
+                match entity.location {
                     EntityLocation::OnBank { pos, .. } => {
+                        // A step between neighbouring bank tiles is a stroll.
+                        if let Some(EntityLocation::OnBank { pos: from, .. }) = anim.last_location
+                            && from != pos
+                        {
+                            anim.strolling = world::is_adjacent(from, pos);
+                        }
                         let target = world::grid_to_iso(pos);
-                        anim.moving =
-                            lerp_toward(&mut anim.pos, target, MOVE_SPEED, dt);
+                        let speed = if anim.strolling {
+                            STROLL_SPEED * pace
+                        } else {
+                            MOVE_SPEED
+                        };
+                        let dx = target.0 - anim.pos.0;
+                        anim.moving = lerp_toward(&mut anim.pos, target, speed, dt);
+                        if !anim.moving {
+                            anim.strolling = false;
+                        } else if anim.strolling && dx.abs() > 0.1 {
+                            anim.facing_right = dx > 0.0;
+                        }
                     }
                     _ => {
                         anim.moving = false;
                     }
                 }
             }
+            anim.last_location = if leader.is_some() {
+                None
+            } else {
+                Some(entity.location)
+            };
+        }
+
+        // --- Loss scene ---
+        for &event in state.events() {
+            if let GameEvent::Lost(LoseReason::Eaten { eater, eaten }) = event {
+                let pairs = state.tense_pairs();
+                let pair = pairs
+                    .iter()
+                    .find(|&&(a, b)| state.kind_of(a) == eater && state.kind_of(b) == eaten)
+                    .or(pairs.first());
+                self.loss = pair.map(|&(eater, prey)| LossScene::new(eater, prey));
+            }
+        }
+        if let Some(scene) = &mut self.loss {
+            let was_done = scene.is_done();
+            scene.update(&mut self.entities, dt);
+            // The lose banner waits for the prey to go.
+            if scene.is_done() && !was_done {
+                self.banner_out = Tween::new(1.0, 0.0, BANNER_TIME, Ease::OutBounce);
+            }
+        }
+
+        // --- Clips ---
+        let dozing = emote::player_emote(state, self).is_some();
+        let eating: Vec<EntityId> = match (state.phase, &self.loss) {
+            (_, Some(scene)) if scene.is_biting() => vec![scene.eater],
+            (_, Some(_)) => Vec::new(),
+            (GamePhase::Lost(LoseReason::Eaten { .. }), None) => state
+                .tense_pairs()
+                .into_iter()
+                .map(|(eater, _)| eater)
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.play_clips(dt, dozing, &eating);
+        for (boat, anim) in state.boats.iter().zip(&mut self.boats) {
+            let rowing = matches!(boat.state, BoatState::Crossing { .. });
+            let clip = if rowing { clips::ROW } else { clips::IDLE };
+            // Hurrying the crossing speeds up the strokes with it.
+            anim.clip.play(clip, dt * state.crossing_time_scale);
+        }
+
+        // --- Pops, bumps and banners ---
+        for anim in &mut self.entities {
+            anim.pop.update(dt);
+        }
+        for anim in &mut self.boats {
+            anim.bump.update(dt);
+        }
+        self.banner_out.update(dt);
+        for &event in state.events() {
+            match event {
+                GameEvent::PickedUp { entity, .. } => {
+                    self.entities[entity.0].pop =
+                        Tween::new(POP_STRETCH, 0.0, POP_TIME, Ease::OutElastic);
+                }
+                GameEvent::Docked { boat, .. } => {
+                    self.boats[boat.0].bump =
+                        Tween::new(-BUMP_SQUASH, 0.0, BUMP_TIME, Ease::OutElastic);
+                }
+                // A win swings in and settles; a loss slams in and bounces.
+                GameEvent::Won => {
+                    self.banner_out = Tween::new(1.0, 0.0, BANNER_TIME, Ease::OutBack)
+                }
+                GameEvent::Lost(_) if self.loss.is_some() => {
+                    self.banner_out = Tween::settled(1.0);
+                }
+                GameEvent::Lost(_) => {
+                    self.banner_out = Tween::new(1.0, 0.0, BANNER_TIME, Ease::OutBounce);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// These sprites as drawn `alpha` of the way on from `prev`, a tick
+    /// earlier. Anyone who jumped, by a reset or a snap, is drawn where
+    /// they landed.
+    pub fn blended(&self, prev: &AnimState, alpha: f32) -> AnimState {
+        let mut shown = self.clone();
+        blend(&mut shown.player_pos, prev.player_pos, alpha);
+        if let (Some(partner), Some(was)) = (&mut shown.partner, &prev.partner) {
+            blend(&mut partner.pos, was.pos, alpha);
+        }
+        if shown.entities.len() == prev.entities.len() {
+            for (anim, was) in shown.entities.iter_mut().zip(&prev.entities) {
+                blend(&mut anim.pos, was.pos, alpha);
+            }
+        }
+        shown
+    }
+
+    pub fn entity_anim(&self, id: EntityId) -> &EntityAnim {
+        &self.entities[id.0]
+    }
+
+    /// Walk `who` toward `target` for a cutscene, whatever the game state
+    /// says. Returns whether they're still on their way. Call
+    /// `advance_clips` once a frame after moving everyone.
+    pub fn walk_scripted(&mut self, who: Puppet, target: (f32, f32), dt: f32) -> bool {
+        let (pos, facing_right, moving) = match who {
+            Puppet::Player => (
+                &mut self.player_pos,
+                &mut self.player_facing_right,
+                &mut self.player_moving,
+            ),
+            Puppet::Entity(id) => {
+                let anim = &mut self.entities[id.0];
+                (&mut anim.pos, &mut anim.facing_right, &mut anim.moving)
+            }
+        };
+        let dx = target.0 - pos.0;
+        *moving = move_toward(pos, target, SCRIPTED_SPEED, dt);
+        if *moving && dx.abs() > 0.1 {
+            *facing_right = dx > 0.0;
+        }
+        *moving
+    }
+
+    /// Play the walk cycle for whoever is moving and idle for the rest.
+    pub fn advance_clips(&mut self, dt: f32) {
+        self.play_clips(dt, false, &[]);
+    }
+
+    /// Walk cycles for whoever is moving; otherwise the player sleeps if
+    /// `dozing`, the entities in `eating` eat, and everyone else idles.
+    fn play_clips(&mut self, dt: f32, dozing: bool, eating: &[EntityId]) {
+        let clip = |moving: bool, still: &'static str| if moving { clips::WALK } else { still };
+        let still = if dozing { clips::SLEEP } else { clips::IDLE };
+        self.player_clip.play(clip(self.player_moving, still), dt);
+        if let Some(partner) = &mut self.partner {
+            partner.clip.play(clip(partner.moving, clips::IDLE), dt);
+        }
+        for (i, anim) in self.entities.iter_mut().enumerate() {
+            let still = if eating.contains(&EntityId(i)) {
+                clips::EAT
+            } else {
+                clips::IDLE
+            };
+            anim.clip.play(clip(anim.moving, still), dt);
         }
     }
+}
+
+/// The boat `id` rides, carried or beside the player aboard.
+fn aboard(state: &GameState, id: EntityId) -> Option<BoatId> {
+    let led = state.player.follower == Some(id);
+    state
+        .boat_carrying(id)
+        .or(state.player_boat().filter(|_| led))
+}
 
-    pub fn entity_anim(&self, entity: Entity) -> &EntityAnim {
-        self.entities
-            .iter()
-            .find(|(e, _)| *e == entity)
-            .map(|(_, a)| a)
-            .unwrap()
+/// Move a farmer's sprite along their walk, or toward their tile. Returns
+/// whether they're moving.
+fn walk_toward(
+    farmer: &Farmer,
+    current: &mut (f32, f32),
+    facing_right: &mut bool,
+    dt: f32,
+) -> bool {
+    let PlayerLocation::OnLand(pos) = farmer.location else {
+        return false;
+    };
+    // A gliding farmer stands wherever on the tile they've got to.
+    let target = farmer.free.unwrap_or_else(|| world::grid_to_iso(pos));
+    let dx = target.0 - current.0;
+    match farmer.walk {
+        // Mid-step, track the walk exactly so consecutive steps blend into
+        // one path.
+        Some(walk) if dx.hypot(target.1 - current.1) < SNAP_DISTANCE => {
+            let from = world::grid_to_iso(walk.from);
+            let to = world::grid_to_iso(walk.to);
+            let t = walk.progress.min(1.0);
+            let step_dx = to.0 - from.0;
+            if step_dx.abs() > 0.1 {
+                *facing_right = step_dx > 0.0;
+            }
+            *current = (tween::lerp(from.0, to.0, t), tween::lerp(from.1, to.1, t));
+            true
+        }
+        _ => {
+            let moving = lerp_toward(current, target, MOVE_SPEED, dt);
+            if moving && dx.abs() > 0.1 {
+                *facing_right = dx > 0.0;
+            }
+            moving
+        }
     }
 }
 
@@ -141,14 +647,18 @@ fn lerp_toward(
     speed: f32,
     dt: f32,
 ) -> bool {
-    let dx = target.0 - current.0;
-    let dy = target.1 - current.1;
-    let dist = (dx * dx + dy * dy).sqrt();
-
-    if dist > SNAP_DISTANCE {
+    if (target.0 - current.0).hypot(target.1 - current.1) > SNAP_DISTANCE {
         *current = target;
         return false;
     }
+    move_toward(current, target, speed, dt)
+}
+
+/// Like `lerp_toward`, but walks any distance.
+fn move_toward(current: &mut (f32, f32), target: (f32, f32), speed: f32, dt: f32) -> bool {
+    let dx = target.0 - current.0;
+    let dy = target.1 - current.1;
+    let dist = (dx * dx + dy * dy).sqrt();
 
     if dist < ARRIVE_THRESHOLD {
         *current = target;
@@ -165,3 +675,11 @@ fn lerp_toward(
         true
     }
 }
+
+/// Draw `pos` between `from` and itself, unless it jumped there.
+fn blend(pos: &mut (f32, f32), from: (f32, f32), alpha: f32) {
+    let (dx, dy) = (pos.0 - from.0, pos.1 - from.1);
+    if dx.hypot(dy) < SNAP_DISTANCE {
+        *pos = (from.0 + dx * alpha, from.1 + dy * alpha);
+    }
+}
@@ -0,0 +1,1540 @@
+use std::sync::Arc;
+
+use macroquad::prelude::*;
+
+use crate::events::GameEvent;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::hot_reload;
+use crate::screens::{Screen, ScreenStack, Transition};
+use crate::{
+    ambience, anim, audio, camera, campaign, cli, console, cutscene, debug_overlay, dialogue,
+    endless, frame_advance, game, generator, ghost, hud, input, interaction, job, leaderboard,
+    level, lighting, mutators, narrator, pacing, particles, pause, postfx, profiler, race, render,
+    replay, rng, savestate, settings, solver, speedrun, stats, timestep, undo, upgrades, weather,
+    wipe,
+};
+
+/// Seconds the win banner stays up before the next level starts.
+const ADVANCE_DELAY: f32 = 4.0;
+
+/// Both farmers' input for a tick where nobody pressed anything.
+const NO_INPUT: (input::InputEvent, input::InputEvent) =
+    (input::InputEvent::None, input::InputEvent::None);
+
+/// A change made under a screen wipe, while nothing can be seen.
+enum Cut {
+    /// Start the level over, closing the pause menu.
+    Restart,
+    /// Start playing this level, closing any menus.
+    Level(Arc<level::LevelDef>),
+    /// Open or close a screen.
+    Screen(Transition),
+}
+
+/// Everything the running game keeps between frames, and the stack of
+/// screens that decides what each frame does with it.
+pub struct App {
+    campaign: campaign::Campaign,
+    scripts: cutscene::Scripts,
+    ferryman: dialogue::Ferryman,
+    atlas: render::SpriteAtlas,
+    thumbnails: render::Thumbnails,
+    settings: settings::Settings,
+    stats: stats::Stats,
+    speedrun: speedrun::Speedrun,
+    leaderboard: leaderboard::Leaderboard,
+    audio: audio::Audio,
+    rumble: input::Rumble,
+    state: game::GameState,
+    anim: anim::AnimState,
+    input_state: input::InputState,
+    render_options: render::RenderOptions,
+    narrator: narrator::Narrator,
+    save_slots: savestate::SaveSlots,
+    undo: undo::UndoStack,
+    /// Picked on the level select for the levels started after.
+    mutators: mutators::Mutators,
+    /// The boat upgrades the levels started from here on get: the
+    /// campaign's, or a replay's while it plays.
+    upgrades: upgrades::Upgrades,
+    frame_advance: frame_advance::FrameAdvance,
+    timestep: timestep::FixedStep,
+    /// The game and its animation as they were before the latest tick,
+    /// for drawing between ticks.
+    shown_from: Option<(game::GameState, anim::AnimState)>,
+    pending_input: (input::InputEvent, input::InputEvent),
+    hud_layout: hud::HudLayout,
+    toast: hud::Toast,
+    screens: ScreenStack,
+    /// Asked for during the frame; made once it's over.
+    transitions: Vec<Transition>,
+    /// Whether the level is outside the campaign: generated, or opened
+    /// from the command line.
+    standalone: bool,
+    /// The endless run being played, if the level is one of its rounds.
+    endless: Option<endless::EndlessRun>,
+    effects: particles::Effects,
+    weather_fx: weather::WeatherFx,
+    ambience: ambience::Ambience,
+    camera_rig: camera::CameraRig,
+    crossing_camera: camera::CrossingCamera,
+    camera_fx: camera::CameraFx,
+    post_fx: postfx::PostFx,
+    lighting: lighting::Lighting,
+    wipe: wipe::ScreenWipe<Cut>,
+    hints_enabled: bool,
+    /// A new theme was picked; the atlas reloads next frame.
+    theme_changed: bool,
+    debug_overlay: debug_overlay::DebugOverlay,
+    pacer: pacing::FramePacer,
+    console: console::Console,
+    #[cfg(not(target_arch = "wasm32"))]
+    asset_watcher: Option<hot_reload::AssetWatcher>,
+    hud_info: render::HudInfo,
+    /// The shortest solution to the level being played, from its start.
+    solution: job::Job<Option<Vec<solver::Crossing>>>,
+    /// A generated puzzle being drawn, to cut to once it's ready.
+    generating: Option<job::Job<Arc<level::LevelDef>>>,
+    /// The hint being shown, and what it was worked out for.
+    hint: Option<(solver::HintKey, job::Job<Option<String>>)>,
+    recorder: replay::Recorder,
+    playback: Option<replay::Playback>,
+    ghost: Option<ghost::Ghost>,
+    quit: bool,
+}
+
+impl App {
+    /// Load everything and apply the launch options: straight into a level
+    /// or a replay, or to the level select after the intro.
+    pub async fn load(args: cli::Args) -> Self {
+        let campaign = campaign::Campaign::load().await;
+        let scripts = cutscene::Scripts::load().await;
+        let ferryman = dialogue::Ferryman::load().await;
+        let settings = settings::Settings::load();
+        let atlas = render::SpriteAtlas::load(
+            &campaign.sprite_sets(),
+            &settings.theme.name,
+            render::draw_loading_screen,
+        )
+        .await;
+        let thumbnails = render::Thumbnails::render(&campaign, &atlas);
+        let state = game::GameState::new(campaign.current());
+        let input_state = input::InputState::new(input::KeyMap::from_bindings(&settings.keys));
+        let mut narrator = narrator::Narrator::new();
+        narrator.set_enabled(settings.accessibility.narration, &state);
+        let mut hud_layout = hud::HudLayout::from_preset(hud::HudPreset::Default);
+        hud_layout.apply(&settings.hud);
+        let mut app = Self {
+            stats: stats::Stats::load(),
+            speedrun: speedrun::Speedrun::new(),
+            leaderboard: leaderboard::Leaderboard::new(&settings.leaderboard),
+            audio: audio::Audio::load(settings.audio.volume).await,
+            rumble: input::Rumble::new(),
+            anim: anim::AnimState::new(&state),
+            render_options: render::RenderOptions {
+                high_contrast: settings.accessibility.high_contrast,
+                ..Default::default()
+            },
+            narrator,
+            save_slots: savestate::SaveSlots::new(),
+            undo: undo::UndoStack::new(),
+            mutators: mutators::Mutators::default(),
+            upgrades: campaign.upgrades(),
+            frame_advance: frame_advance::FrameAdvance::new(),
+            timestep: timestep::FixedStep::new(),
+            shown_from: None,
+            pending_input: NO_INPUT,
+            hud_layout,
+            toast: hud::Toast::new(),
+            screens: ScreenStack::new(),
+            transitions: Vec::new(),
+            standalone: false,
+            endless: None,
+            effects: particles::Effects::new(),
+            weather_fx: weather::WeatherFx::new(),
+            ambience: ambience::Ambience::new(),
+            camera_rig: camera::CameraRig::new(render::view_rect()),
+            crossing_camera: camera::CrossingCamera::new(settings.effects.crossing_camera),
+            camera_fx: camera::CameraFx::new(),
+            post_fx: postfx::PostFx::new(&settings.effects),
+            lighting: lighting::Lighting::new(),
+            wipe: wipe::ScreenWipe::new(),
+            theme_changed: false,
+            hints_enabled: false,
+            debug_overlay: debug_overlay::DebugOverlay::new(),
+            pacer: pacing::FramePacer::new(),
+            console: console::Console::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            asset_watcher: hot_reload::AssetWatcher::new(),
+            hud_info: render::HudInfo {
+                keys: render::KeyLabels::new(input_state.key_map()),
+                ..Default::default()
+            },
+            solution: solve(&state),
+            generating: None,
+            hint: None,
+            recorder: replay::Recorder::new(),
+            playback: None,
+            ghost: None,
+            quit: false,
+            campaign,
+            scripts,
+            ferryman,
+            atlas,
+            thumbnails,
+            settings,
+            state,
+            input_state,
+        };
+        app.launch(&args).await;
+        app
+    }
+
+    async fn launch(&mut self, args: &cli::Args) {
+        if let Some(seed) = args.seed {
+            self.state.rng = rng::Rng::new(seed);
+        }
+        let replay = args.replay.as_deref().and_then(|path| {
+            replay::Replay::load(path)
+                .inspect_err(|e| error!("{e}; not playing it back"))
+                .ok()
+        });
+        if let Some(path) = &args.ghost {
+            match replay::Replay::load(path) {
+                Ok(run) => self.ghost = Some(ghost::Ghost::new(run)),
+                Err(e) => error!("{e}; racing without a ghost"),
+            }
+        }
+        if let Some(replay) = &replay {
+            replay.prepare(&mut self.state);
+            // Starting the level would put the level select's picks back.
+            self.mutators = self.state.mutators;
+            self.upgrades = self.state.upgrades;
+            self.input_state.set_coop(self.state.coop);
+            self.input_state.set_analog(self.state.analog);
+        }
+        // A level chosen on the command line skips the level select.
+        let mut started = false;
+        if let Some(path) = &args.level {
+            let parsed = match load_string(path).await {
+                Ok(source) => level::LevelDef::parse(&source).map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match parsed {
+                Ok(level) => {
+                    self.standalone = true;
+                    self.start_level(Arc::new(level));
+                    started = true;
+                }
+                Err(e) => error!("{path}: {e}"),
+            }
+        } else if let Some(replay) = &replay {
+            let named = |entry: &campaign::CampaignLevel| entry.level.name == replay.level;
+            match self.campaign.levels().iter().position(named) {
+                Some(index) => {
+                    self.campaign.select(index);
+                    self.start_level(self.campaign.current());
+                    started = true;
+                }
+                None => error!("no campaign level is called {:?}", replay.level),
+            }
+        }
+        if let Some(replay) = replay
+            && started
+        {
+            // Playing it back shouldn't record it over again.
+            self.recorder.discard();
+            self.playback = Some(replay::Playback::new(replay));
+        }
+        // Straight to the level select otherwise, after the intro.
+        if !started {
+            self.screens
+                .apply(Transition::Push(Screen::Menu(campaign::LevelSelect::new(
+                    0, false,
+                ))));
+            if let Some(intro) = &self.scripts.intro {
+                let focus = render::view_rect().center();
+                let scene = cutscene::Cutscene::new(intro, &self.anim, focus);
+                self.screens
+                    .apply(Transition::Push(Screen::Cutscene(Box::new(scene))));
+            }
+            self.change_screens();
+        }
+    }
+
+    /// Run one frame: whatever is on top of the screen stack gets it.
+    /// Returns false once the player has quit.
+    pub async fn frame(&mut self) -> bool {
+        let dt = get_frame_time();
+        let time = get_time() as f32;
+        let animating = self.is_animating();
+        self.pacer.pace(&self.settings.window, animating, dt);
+
+        profiler::begin_frame();
+        self.debug_overlay.begin_frame(dt);
+        self.update_window();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.hot_reload(dt).await;
+        if std::mem::take(&mut self.theme_changed) {
+            self.reload_sprites().await;
+            self.thumbnails = render::Thumbnails::render(&self.campaign, &self.atlas);
+        }
+
+        self.input_state.set_contexts(self.screens.input_contexts());
+        let mut screen = self.screens.take_top();
+        match &mut screen {
+            Screen::Cutscene(scene) => self.cutscene_frame(scene, dt, time),
+            Screen::Race(versus) => self.race_frame(versus, dt, time),
+            _ => self.game_frame(&mut screen, dt, time),
+        }
+        self.screens.put_back(screen);
+        if let Some(level) = self.generating.as_ref().and_then(job::Job::get) {
+            let level = Arc::clone(level);
+            self.generating = None;
+            self.wipe(wipe::WipeStyle::Iris, Cut::Level(level));
+        }
+        if let Some(cut) = self.wipe.update(dt) {
+            self.cut(cut);
+        }
+        self.change_screens();
+        !self.quit
+    }
+
+    /// Make the transitions the frame asked for, then hold the game in
+    /// the phase the menus over it call for.
+    fn change_screens(&mut self) {
+        for transition in std::mem::take(&mut self.transitions) {
+            self.screens.apply(transition);
+        }
+        match self.screens.held_phase() {
+            Some(phase) => self.state.phase = phase,
+            // Back from the menus: carry on.
+            None if matches!(
+                self.state.phase,
+                game::GamePhase::Menu | game::GamePhase::Paused
+            ) =>
+            {
+                self.state.phase = game::GamePhase::Playing;
+            }
+            None => {}
+        }
+    }
+
+    /// Keep the game's own layer in step with its phase: a win or loss
+    /// puts up its banner, a restart takes it down.
+    fn follow_game(&mut self, screen: &mut Screen) {
+        match (&*screen, self.state.phase) {
+            (Screen::Playing | Screen::Lost, game::GamePhase::Won) => {
+                // Co-op shortens some puzzles and assist plays them for the
+                // player, so neither counts; upgraded wins count, but set
+                // no best.
+                if !self.standalone && !self.state.coop && !self.state.assist {
+                    let best = self.sets_records().then_some(self.state.crossing_count);
+                    match self.campaign.record_win(best) {
+                        Ok(unlocked) => {
+                            if !unlocked.is_empty() {
+                                let names: Vec<&str> = unlocked.iter().map(|u| u.name()).collect();
+                                self.toast.show(format!(
+                                    "Boat upgrade unlocked: {}; switch it on in the level select",
+                                    names.join(", ")
+                                ));
+                            }
+                        }
+                        Err(e) => self.toast.show(format!("Couldn't save progress: {e}")),
+                    }
+                }
+                self.hud_info.solution = self.solution.wait().cloned().flatten();
+                *screen = Screen::Won {
+                    advance_in: ADVANCE_DELAY,
+                };
+            }
+            (Screen::Playing | Screen::Won { .. }, game::GamePhase::Lost(_)) => {
+                // One loss and an endless run is over.
+                if let Some(run) = &mut self.endless
+                    && let Err(e) = run.finish(self.state.assist)
+                {
+                    self.toast
+                        .show(format!("Couldn't save best endless run: {e}"));
+                }
+                *screen = Screen::Lost;
+            }
+            (Screen::Won { .. } | Screen::Lost, game::GamePhase::Playing) => {
+                *screen = Screen::Playing;
+            }
+            _ => {}
+        }
+    }
+
+    // --- WINDOW ---
+
+    /// F11 and the smallest window size. The camera keeps its center and
+    /// zoom, so only the edges of the view move.
+    fn update_window(&mut self) {
+        if is_key_pressed(KeyCode::F11) {
+            self.settings.window.fullscreen = !self.settings.window.fullscreen;
+            apply_setting(
+                settings::SettingsChange::Fullscreen,
+                &self.settings,
+                &mut self.audio,
+            );
+            if let Err(e) = self.settings.save() {
+                self.toast.show(format!("Couldn't save settings: {e}"));
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.settings.window.fullscreen {
+            let (min_width, min_height) = crate::MIN_WINDOW_SIZE;
+            let (width, height) = (screen_width(), screen_height());
+            if width < min_width || height < min_height {
+                request_new_screen_size(width.max(min_width), height.max(min_height));
+            }
+        }
+    }
+
+    // --- HOT RELOAD ---
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn hot_reload(&mut self, dt: f32) {
+        let Some(changes) = self
+            .asset_watcher
+            .as_mut()
+            .and_then(|watcher| watcher.poll(dt))
+        else {
+            return;
+        };
+        let sets = self.campaign.sprite_sets();
+        let mut current_changed = false;
+        for key in &changes.levels {
+            match self.campaign.reload(key) {
+                Some(Ok(index)) => {
+                    current_changed |= index == self.campaign.current_index();
+                    self.toast.show(format!("Reloaded {key}"));
+                }
+                Some(Err(e)) => {
+                    error!("{key}: {e}; keeping the old version");
+                    self.toast.show(format!("Couldn't reload {key}: {e}"));
+                }
+                None => {}
+            }
+        }
+        // New kinds in a level bring sprites the atlas doesn't have yet.
+        let sets_changed = self.campaign.sprite_sets() != sets;
+        if changes.sprites || sets_changed {
+            self.reload_sprites().await;
+            self.toast.show("Reloaded sprites".to_string());
+        }
+        self.thumbnails = render::Thumbnails::render(&self.campaign, &self.atlas);
+        if current_changed && !self.standalone {
+            // Restart on the new layout, but stay behind any open menu.
+            let phase = self.state.phase;
+            self.start_level(self.campaign.current());
+            if matches!(phase, game::GamePhase::Menu | game::GamePhase::Paused) {
+                self.state.phase = phase;
+            }
+        }
+    }
+
+    /// Rebuild the atlas for the campaign's sprite sets in the chosen
+    /// theme.
+    async fn reload_sprites(&mut self) {
+        let sets = self.campaign.sprite_sets();
+        let theme = &self.settings.theme.name;
+        self.atlas = render::SpriteAtlas::load(&sets, theme, render::draw_loading_screen).await;
+    }
+
+    // --- CUTSCENE ---
+
+    /// Plays over whatever is behind until it ends or a key skips it.
+    fn cutscene_frame(&mut self, scene: &mut cutscene::Cutscene, dt: f32, time: f32) {
+        if input::any_pressed() {
+            scene.skip();
+        }
+        scene.update(&self.state, dt);
+        let (focus, zoom) = scene.camera();
+        self.camera_rig.set_zoom(zoom);
+        let input = input::CameraInput::default();
+        self.camera_rig
+            .update(&input, &self.state.level, focus, render::view_rect(), dt);
+        render::setup_camera(
+            &self.camera_rig,
+            &self.crossing_camera,
+            &self.camera_fx,
+            None,
+        );
+        clear_background(Color::new(0.05, 0.06, 0.12, 1.0));
+        let options = &self.render_options;
+        render::draw_world(
+            &self.state,
+            &self.atlas,
+            scene.anim(),
+            options,
+            None,
+            None,
+            time,
+        );
+        render::setup_hud_camera();
+        render::draw_cutscene(scene);
+        if scene.is_done() {
+            self.transitions.push(Transition::Pop);
+            self.camera_rig = camera::CameraRig::new(render::view_rect());
+        }
+    }
+
+    // --- VERSUS RACE ---
+
+    /// Takes over the whole frame; the game waits paused behind it.
+    fn race_frame(&mut self, versus: &mut race::Race, dt: f32, time: f32) {
+        let leave = race_step(versus, &mut self.input_state, &mut self.timestep, dt);
+        let keys = &self.hud_info.keys;
+        render::draw_race(versus, &self.atlas, keys, &self.render_options, time);
+        profiler::draw_overlay();
+        if leave {
+            self.transitions.push(Transition::Pop);
+            self.input_state.set_coop(self.state.coop);
+        }
+    }
+
+    // --- THE GAME, AND THE MENUS OVER IT ---
+
+    /// A frame of the level, with `screen` (the game itself or a menu
+    /// over it) taking the input.
+    fn game_frame(&mut self, screen: &mut Screen, dt: f32, time: f32) {
+        // --- INPUT ---
+        let input_span = profiler::scope("input");
+        let paused = matches!(
+            self.state.phase,
+            game::GamePhase::Paused | game::GamePhase::Menu
+        );
+        // The console keeps the keyboard for the frame it closes on too,
+        // and so does a conversation.
+        let typing = self.console.is_open() || self.ferryman.is_talking();
+        if let Some(line) = self.console.update() {
+            match self.console.run(&line, &mut self.state, &self.campaign) {
+                Some(console::ConsoleEffect::Changed) => {
+                    self.anim.reset(&self.state);
+                    self.recorder.discard();
+                }
+                Some(console::ConsoleEffect::LoadLevel(index)) => {
+                    self.campaign.select(index);
+                    self.standalone = false;
+                    self.endless = None;
+                    self.start_level(self.campaign.current());
+                }
+                None => {}
+            }
+        }
+        self.input_state
+            .update_touch(render::screen_to_hud, render::view_rect());
+        self.hud_info.keys.device = self.input_state.device();
+        self.input_state.set_locked(self.anim.is_hopping());
+        let choices = interaction::target_choices(&self.state, game::PlayerId::One);
+        self.input_state.set_choices(choices.len());
+        if self.ferryman.is_talking() {
+            self.input_state.push_context(input::InputContext::Menu);
+        } else if typing {
+            self.input_state.push_context(input::InputContext::Console);
+        }
+        let mut event = self.input_state.poll(dt);
+        // Trying again after an endless run is lost starts a new run.
+        if event == input::InputEvent::Restart
+            && self
+                .endless
+                .as_ref()
+                .is_some_and(endless::EndlessRun::is_over)
+        {
+            event = input::InputEvent::None;
+            self.new_endless_run();
+        }
+        let partner_event = self.input_state.poll_partner(dt);
+        let camera_input = self.input_state.camera_input();
+        match screen {
+            Screen::Menu(select) => self.level_select_input(select),
+            Screen::Mutators(menu) => self.mutator_input(menu),
+            Screen::Upgrades(menu) => self.upgrade_input(menu),
+            Screen::Paused(menu) => self.pause_input(menu, &mut event),
+            Screen::Settings(settings) => self.settings_input(settings),
+            Screen::Rules => self.rules_input(),
+            Screen::Playing | Screen::Won { .. } | Screen::Lost => self.play_input(),
+            Screen::Cutscene(_) | Screen::Race(_) => {}
+        }
+        drop(input_span);
+        self.debug_overlay.record_event(event);
+        self.handle_event(event);
+        self.toast.update(dt);
+
+        // --- UPDATE ---
+        let update_span = profiler::scope("update");
+        let events = self.update_game(event, partner_event, typing, paused, dt);
+        // Presses kept for later only make sense in the run they were for.
+        if self.state.phase != game::GamePhase::Playing || events.contains(&GameEvent::Started) {
+            self.input_state.clear_buffer();
+        }
+        self.follow_game(screen);
+        if let Screen::Won { advance_in } = screen {
+            *advance_in -= dt;
+            // Once, not every frame the next level takes to draw or wipe
+            // to.
+            let due = *advance_in <= 0.0 || event == input::InputEvent::Interact;
+            if due && !self.wipe.is_pending() && self.generating.is_none() {
+                self.advance();
+            }
+        }
+        self.hud_info.advance_in = match screen {
+            Screen::Won { advance_in } => Some(*advance_in),
+            _ => None,
+        };
+        self.hud_info.last_level =
+            self.endless.is_none() && (self.standalone || self.campaign.is_last());
+        self.hud_info.banner_out = self.anim.banner_out.value();
+        self.hud_info.can_rewind =
+            self.undo.can_rewind() && self.state.mutators.allows_undo() && self.endless.is_none();
+
+        let refused = events.iter().find_map(|event| match *event {
+            GameEvent::CrossingRefused(reason) => Some(reason),
+            _ => None,
+        });
+        if let Some(reason) = refused {
+            self.toast.show(reason.warning(&self.state.level));
+        }
+        self.narrator.observe(&self.state);
+        self.ferryman.observe(&self.state);
+        // A win is scored against the fewest crossings, so it waits for a
+        // solve that's somehow still going.
+        let solution = if events.contains(&GameEvent::Won) {
+            self.solution.wait()
+        } else {
+            self.solution.get()
+        };
+        self.hud_info.min_crossings = solution.and_then(|found| found.as_ref().map(Vec::len));
+        self.state.min_crossings = self.hud_info.min_crossings.map(|min| min as u32);
+        let optimal = self.hud_info.min_crossings;
+        if let Err(e) = self.stats.observe(&self.state, &events, optimal, dt) {
+            self.toast.show(format!("Couldn't save stats: {e}"));
+        }
+        self.hud_info.score = self.stats.score(&self.state, optimal);
+        if let Err(e) = self.speedrun.observe(&self.state, &events, dt) {
+            self.toast.show(format!("Couldn't save personal best: {e}"));
+        }
+        let solve = self.stats.last_solve().filter(|_| self.sets_records());
+        if let Err(e) = self.leaderboard.observe(&self.state, solve) {
+            self.toast.show(format!("Couldn't save leaderboard: {e}"));
+        }
+        self.audio.observe(&self.state, &self.anim, &events, dt);
+        self.rumble.observe(&self.state, &events, dt);
+        let effects_dt = if paused { 0.0 } else { dt };
+        let boat_pos = render::boat_world_pos(&self.state);
+        self.effects
+            .update(&self.state, &events, &self.anim, boat_pos, effects_dt);
+        self.weather_fx.update(&self.state.level, effects_dt);
+        self.ambience.update(&self.state.level, effects_dt);
+        self.lighting.update(&self.state, effects_dt);
+        self.update_hint();
+        let (shown, shown_anim) = match &self.shown_from {
+            Some((was, was_anim)) => {
+                let alpha = self.timestep.alpha();
+                (
+                    self.state.blended(was, alpha),
+                    self.anim.blended(was_anim, alpha),
+                )
+            }
+            None => (self.state.clone(), self.anim.clone()),
+        };
+        let focus = render::camera_focus(&shown, &shown_anim);
+        let view = render::view_rect();
+        self.camera_rig
+            .update(&camera_input, &self.state.level, focus, view, dt);
+        self.crossing_camera
+            .update(render::crossing_boat_pos(&shown), dt);
+        self.camera_fx.update(&self.state, &events, effects_dt);
+        self.post_fx.update(self.state.phase, dt);
+        drop(update_span);
+
+        // --- RENDER ---
+        let render_span = profiler::scope("render");
+        render::setup_camera(
+            &self.camera_rig,
+            &self.crossing_camera,
+            &self.camera_fx,
+            self.post_fx.target(),
+        );
+        clear_background(Color::new(0.05, 0.06, 0.12, 1.0));
+        let options = &self.render_options;
+        render::draw_world(
+            &shown,
+            &self.atlas,
+            &shown_anim,
+            options,
+            Some(&self.effects),
+            Some(&self.ambience),
+            time,
+        );
+        if let Some(ghost) = &self.ghost {
+            render::draw_ghost(ghost, &self.atlas);
+        }
+        let lantern = self.state.inventory.lantern_lit;
+        self.weather_fx.draw(&self.state.level, focus, lantern);
+        self.ambience.draw_sky(render::world_tint(&shown));
+        render::setup_hud_camera();
+        self.post_fx.apply(render::view_rect());
+        if let Some(target) = self.lighting.target() {
+            render::setup_camera(
+                &self.camera_rig,
+                &self.crossing_camera,
+                &self.camera_fx,
+                Some(target),
+            );
+            self.lighting.draw_lights(&shown, &shown_anim);
+            render::setup_hud_camera();
+            self.lighting.apply(render::view_rect());
+        }
+        self.draw_hud(screen);
+        if self.frame_advance.is_active() {
+            render::draw_frame_advance_banner(&self.frame_advance.status_line());
+        }
+        render::setup_hud_camera();
+        self.wipe.draw(render::view_rect(), time);
+
+        set_default_camera();
+        drop(render_span);
+        profiler::draw_overlay();
+        self.debug_overlay.draw(&self.state);
+        if self.settings.window.show_fps {
+            let idle = self.pacer.is_idle(&self.settings.window);
+            self.debug_overlay.draw_fps(idle);
+        }
+        self.console.draw();
+    }
+
+    /// Whether a win sets the campaign's best crossings and goes up on the
+    /// leaderboard: only campaign runs played the way they were meant to
+    /// be, with no co-op partner, assist or boat upgrades.
+    fn sets_records(&self) -> bool {
+        !self.standalone && !self.state.coop && !self.state.assist && self.state.upgrades.is_empty()
+    }
+
+    /// Whether something plays out on screen without the player: a
+    /// cutscene, a replay, a crossing or a wipe.
+    fn is_animating(&self) -> bool {
+        self.screens.has_cutscene()
+            || self.playback.is_some()
+            || self.state.crossing_boat().is_some()
+            || self.wipe.is_running()
+            || self.generating.is_some()
+    }
+
+    /// The level select: start the chosen level, or go back or quit.
+    fn level_select_input(&mut self, select: &mut campaign::LevelSelect) {
+        let menu_input = self.input_state.menu_input(render::screen_to_hud);
+        let choice = select.handle(&menu_input, render::view_rect(), &self.campaign);
+        if choice.is_some() {
+            // Whatever was picked instead of the puzzle being drawn.
+            self.generating = None;
+        }
+        let level = match choice {
+            Some(campaign::LevelChoice::Play(index)) => {
+                self.campaign.select(index);
+                self.standalone = false;
+                self.endless = None;
+                self.campaign.current()
+            }
+            Some(campaign::LevelChoice::Daily) => {
+                self.standalone = true;
+                self.endless = None;
+                self.generating = Some(job::Job::spawn(|| Arc::new(generator::daily())));
+                return;
+            }
+            Some(campaign::LevelChoice::Random) => {
+                self.standalone = true;
+                self.endless = None;
+                self.generating = Some(job::Job::spawn(|| Arc::new(generator::random())));
+                return;
+            }
+            Some(campaign::LevelChoice::Endless) => {
+                self.new_endless_run();
+                return;
+            }
+            Some(campaign::LevelChoice::Mutators) => {
+                let menu = campaign::MutatorMenu::new();
+                self.transitions
+                    .push(Transition::Push(Screen::Mutators(menu)));
+                return;
+            }
+            Some(campaign::LevelChoice::Upgrades) => {
+                let menu = campaign::UpgradeMenu::new();
+                self.transitions
+                    .push(Transition::Push(Screen::Upgrades(menu)));
+                return;
+            }
+            Some(campaign::LevelChoice::Back) => {
+                self.wipe(wipe::WipeStyle::Fade, Cut::Screen(Transition::Pop));
+                return;
+            }
+            Some(campaign::LevelChoice::Quit) => {
+                self.quit = true;
+                return;
+            }
+            None => return,
+        };
+        self.wipe(wipe::WipeStyle::Iris, Cut::Level(level));
+    }
+
+    /// The mutators page: its picks hold for the levels started after.
+    fn mutator_input(&mut self, menu: &mut campaign::MutatorMenu) {
+        let menu_input = self.input_state.menu_input(render::screen_to_hud);
+        if menu.handle(&menu_input, render::view_rect(), &mut self.mutators) {
+            self.transitions.push(Transition::Pop);
+        }
+    }
+
+    /// The boat upgrades page: its picks are saved, and hold for the
+    /// levels started after.
+    fn upgrade_input(&mut self, menu: &mut campaign::UpgradeMenu) {
+        let menu_input = self.input_state.menu_input(render::screen_to_hud);
+        match menu.handle(&menu_input, render::view_rect(), &self.campaign) {
+            Some(campaign::UpgradeChoice::Toggle(upgrade)) => {
+                if let Err(e) = self.campaign.toggle_upgrade(upgrade) {
+                    self.toast.show(format!("Couldn't save progress: {e}"));
+                }
+                self.upgrades = self.campaign.upgrades();
+            }
+            Some(campaign::UpgradeChoice::Locked(upgrade)) => {
+                let more = upgrade.stars() - self.campaign.stars();
+                self.toast
+                    .show(format!("{more} more stars to unlock {}", upgrade.name()));
+            }
+            Some(campaign::UpgradeChoice::Back) => self.transitions.push(Transition::Pop),
+            None => {}
+        }
+    }
+
+    /// The pause menu. Its quick toggles hand `event` on to the keys that
+    /// do the same.
+    fn pause_input(&mut self, menu: &mut pause::PauseMenu, event: &mut input::InputEvent) {
+        let menu_input = self.input_state.menu_input(render::screen_to_hud);
+        match menu.handle(&menu_input, render::view_rect()) {
+            Some(pause::MenuCommand::Resume) => self.transitions.push(Transition::Pop),
+            Some(pause::MenuCommand::Restart) => self.restart(),
+            Some(pause::MenuCommand::Quit) => self.quit = true,
+            Some(pause::MenuCommand::StartRace) => {
+                let versus = Box::new(race::Race::new(
+                    Arc::clone(&self.state.level),
+                    self.state.analog,
+                ));
+                self.transitions
+                    .push(Transition::Push(Screen::Race(versus)));
+                self.input_state.set_coop(true);
+            }
+            Some(pause::MenuCommand::LevelSelect) => {
+                let select = campaign::LevelSelect::new(self.campaign.current_index(), true);
+                let open = Transition::Push(Screen::Menu(select));
+                self.wipe(wipe::WipeStyle::Fade, Cut::Screen(open));
+            }
+            Some(pause::MenuCommand::ToggleTimedMode) => {
+                self.state.timed = !self.state.timed;
+                self.restart();
+                let status = if self.state.timed { "on" } else { "off" };
+                self.toast.show(format!("Timed mode {status}; restarted"));
+            }
+            Some(pause::MenuCommand::ToggleCoop) => {
+                self.state.coop = !self.state.coop;
+                self.input_state.set_coop(self.state.coop);
+                self.restart();
+                self.solution = solve(&self.state);
+                self.toast.show(if self.state.coop {
+                    "Co-op on; restarted. Player two: arrow keys and Enter".to_string()
+                } else {
+                    "Co-op off; restarted".to_string()
+                });
+            }
+            Some(pause::MenuCommand::ToggleAnalog) => {
+                self.state.analog = !self.state.analog;
+                self.input_state.set_analog(self.state.analog);
+                self.restart();
+                let status = if self.state.analog { "on" } else { "off" };
+                self.toast
+                    .show(format!("Analog movement {status}; restarted"));
+            }
+            Some(pause::MenuCommand::ToggleSpeedrun) => {
+                self.toast.show(self.speedrun.toggle());
+                self.restart();
+            }
+            Some(pause::MenuCommand::ToggleAssist) => {
+                self.state.assist = !self.state.assist;
+                self.restart();
+                self.toast.show(if self.state.assist {
+                    "Assist on; restarted. The boat won't leave if something would be eaten; \
+                     runs set no records"
+                        .to_string()
+                } else {
+                    "Assist off; restarted".to_string()
+                });
+            }
+            Some(pause::MenuCommand::ToggleSound) => *event = input::InputEvent::ToggleMute,
+            Some(pause::MenuCommand::ToggleBadges) => *event = input::InputEvent::ToggleBadges,
+            Some(pause::MenuCommand::ToggleNarration) => {
+                *event = input::InputEvent::ToggleNarration;
+            }
+            Some(pause::MenuCommand::ToggleHints) => *event = input::InputEvent::ToggleHint,
+            Some(pause::MenuCommand::CycleHudPreset) => {
+                *event = input::InputEvent::CycleHudPreset;
+            }
+            Some(pause::MenuCommand::OpenDisplaySettings) => {
+                let screen = settings::SettingsScreen::new();
+                self.transitions
+                    .push(Transition::Push(Screen::Settings(screen)));
+            }
+            Some(pause::MenuCommand::ShowRules) => {
+                self.transitions.push(Transition::Push(Screen::Rules));
+            }
+            None => {}
+        }
+    }
+
+    /// Start the level over from the pause menu, which closes on it.
+    fn restart(&mut self) {
+        self.wipe(wipe::WipeStyle::Ripple, Cut::Restart);
+    }
+
+    /// Cover the screen to make `cut`, and uncover it on the result.
+    fn wipe(&mut self, style: wipe::WipeStyle, cut: Cut) {
+        if let Some(earlier) = self.wipe.start(style, cut) {
+            self.cut(earlier);
+        }
+    }
+
+    /// Make the change a wipe was hiding.
+    fn cut(&mut self, cut: Cut) {
+        match cut {
+            Cut::Restart => {
+                self.state.reset();
+                self.anim.reset(&self.state);
+                self.undo.clear();
+                self.recorder.begin(&self.state);
+                self.transitions.push(Transition::Pop);
+            }
+            Cut::Level(level) => {
+                self.start_level(level);
+                self.transitions.push(Transition::PopToGame);
+            }
+            Cut::Screen(transition) => self.transitions.push(transition),
+        }
+    }
+
+    /// Any of back, confirm or F1 again closes the rules.
+    fn rules_input(&mut self) {
+        let menu_input = self.input_state.menu_input(render::screen_to_hud);
+        if menu_input.back || menu_input.confirm || menu_input.key == Some(KeyCode::F1) {
+            self.transitions.push(Transition::Pop);
+        }
+    }
+
+    /// The display and sound settings: put each change into effect.
+    fn settings_input(&mut self, screen: &mut settings::SettingsScreen) {
+        let menu_input = self.input_state.menu_input(render::screen_to_hud);
+        let Some(change) = screen.handle(&menu_input, render::view_rect(), &mut self.settings)
+        else {
+            return;
+        };
+        match change {
+            settings::SettingsChange::Close => self.transitions.push(Transition::Pop),
+            settings::SettingsChange::Effects => {
+                self.post_fx.set_options(&self.settings.effects);
+                self.crossing_camera.enabled = self.settings.effects.crossing_camera;
+            }
+            settings::SettingsChange::Accessibility => {
+                let accessibility = &self.settings.accessibility;
+                self.render_options.high_contrast = accessibility.high_contrast;
+                self.narrator
+                    .set_enabled(accessibility.narration, &self.state);
+            }
+            settings::SettingsChange::Hud => self.hud_layout.apply(&self.settings.hud),
+            // Swapped at the top of the next frame, where loading can wait.
+            settings::SettingsChange::Theme => self.theme_changed = true,
+            _ => {}
+        }
+        let rebound = change == settings::SettingsChange::KeyBindings;
+        if let Some(message) = apply_setting(change, &self.settings, &mut self.audio) {
+            self.toast.show(message);
+        }
+        if rebound {
+            let key_map = input::KeyMap::from_bindings(&self.settings.keys);
+            self.input_state.set_key_map(key_map);
+            self.hud_info.keys = render::KeyLabels::new(self.input_state.key_map());
+        }
+    }
+
+    /// Playing, or looking at the result: conversations with the
+    /// ferryman, and clicks on the world.
+    fn play_input(&mut self) {
+        if self.ferryman.is_talking() {
+            let menu_input = self.input_state.menu_input(render::screen_to_hud);
+            let choices = render::dialogue_choices(render::view_rect());
+            let outcome = self.ferryman.handle(&menu_input, choices, &mut self.state);
+            if outcome == Some(dialogue::DialogueOutcome::PaidForHint) {
+                // The moves it cost aren't in the recording.
+                self.recorder.discard();
+            }
+        } else if let Some(screen) = self.input_state.clicked() {
+            // Plan from where the player is headed, not where they stand.
+            // The walk itself plays out in `step`, so replays see it.
+            let mut headed = self.state.clone();
+            headed.finish_walking();
+            let point = render::screen_to_world(&self.camera_rig, screen);
+            let target = render::pick(&headed, &self.atlas, &self.anim, point);
+            if let Some(plan) = interaction::plan_click(&headed, target) {
+                self.input_state.follow_plan(&plan);
+            }
+        }
+    }
+
+    /// The keys that work on any game screen: toggles, debug tools, save
+    /// states, volume and pausing.
+    fn handle_event(&mut self, event: input::InputEvent) {
+        match event {
+            input::InputEvent::ToggleBadges => {
+                self.render_options.shape_badges = !self.render_options.shape_badges;
+            }
+            input::InputEvent::ToggleNarration => {
+                self.narrator.toggle(&self.state);
+                self.settings.accessibility.narration = self.narrator.is_enabled();
+                if let Err(e) = self.settings.save() {
+                    warn!("{e}");
+                }
+            }
+            input::InputEvent::ToggleProfiler => profiler::toggle(),
+            input::InputEvent::ToggleDebugOverlay => self.debug_overlay.toggle(),
+            input::InputEvent::OpenConsole => self.console.open(),
+            input::InputEvent::SaveState => {
+                self.toast
+                    .show(self.save_slots.save(&self.state, &self.anim));
+            }
+            input::InputEvent::Rewind | input::InputEvent::LoadState
+                if !self.state.mutators.allows_undo() =>
+            {
+                self.toast.show("No undo on this run".to_string());
+            }
+            input::InputEvent::Rewind | input::InputEvent::LoadState if self.endless.is_some() => {
+                self.toast.show("No undo in an endless run".to_string());
+            }
+            input::InputEvent::LoadState => {
+                if self.save_slots.can_restore() {
+                    self.stats.taint();
+                    self.speedrun.taint();
+                }
+                let message = self.save_slots.restore(&mut self.state, &mut self.anim);
+                self.toast.show(message);
+                self.recorder.discard();
+                self.input_state.set_coop(self.state.coop);
+                self.input_state.set_analog(self.state.analog);
+                self.input_state
+                    .set_reversed(self.state.mutators.reverses_controls());
+                self.solution = solve(&self.state);
+                self.undo.clear();
+            }
+            input::InputEvent::Rewind if matches!(self.state.phase, game::GamePhase::Lost(_)) => {
+                if let Some(before) = self.undo.rewind() {
+                    self.stats.taint();
+                    self.speedrun.taint();
+                    self.state = before;
+                    self.anim.reset(&self.state);
+                    self.recorder.discard();
+                    self.toast
+                        .show("Rewound to before that crossing".to_string());
+                }
+            }
+            input::InputEvent::NextSaveSlot => self.toast.show(self.save_slots.select_next()),
+            input::InputEvent::ToggleFrameAdvance => self.frame_advance.toggle(),
+            input::InputEvent::ToggleHint => {
+                self.hints_enabled = !self.hints_enabled;
+                let status = if self.hints_enabled { "on" } else { "off" };
+                self.toast.show(format!("Hints {status}"));
+            }
+            input::InputEvent::ToggleSpectator if self.state.coop => {
+                self.toast
+                    .show("Spectator mode needs the arrow keys player two has".to_string());
+            }
+            input::InputEvent::ToggleSpectator => {
+                let spectating = !self.input_state.is_spectating();
+                self.input_state.set_spectating(spectating);
+                self.toast.show(if spectating {
+                    format!(
+                        "Spectating: {} pans the camera, arrows move",
+                        self.hud_info.keys.moves
+                    )
+                } else {
+                    "Spectator mode off".to_string()
+                });
+            }
+            input::InputEvent::Pause if self.state.phase == game::GamePhase::Playing => {
+                let menu = pause::PauseMenu::new();
+                self.transitions
+                    .push(Transition::Push(Screen::Paused(menu)));
+            }
+            input::InputEvent::ShowRules if self.state.phase == game::GamePhase::Playing => {
+                self.transitions.push(Transition::Push(Screen::Rules));
+            }
+            input::InputEvent::ToggleMute => self.toast.show(self.audio.toggle_mute()),
+            input::InputEvent::VolumeDown | input::InputEvent::VolumeUp => {
+                let steps = if event == input::InputEvent::VolumeUp {
+                    1.0
+                } else {
+                    -1.0
+                };
+                self.toast.show(self.audio.adjust_volume(steps));
+                self.settings.audio.volume = self.audio.volume();
+                if let Err(e) = self.settings.save() {
+                    warn!("{e}");
+                }
+            }
+            input::InputEvent::CycleHudPreset => {
+                let preset = self.hud_layout.preset.next();
+                self.hud_layout.set_preset(preset);
+                self.toast.show(format!("HUD: {}", preset.name()));
+            }
+            _ => {}
+        }
+    }
+
+    /// Run the ticks due this frame; the frame's input goes to the first
+    /// one, and replays give up one recorded frame per tick. Returns what
+    /// happened in the game.
+    fn update_game(
+        &mut self,
+        event: input::InputEvent,
+        partner_event: input::InputEvent,
+        typing: bool,
+        paused: bool,
+        dt: f32,
+    ) -> Vec<GameEvent> {
+        let held = if typing {
+            Default::default()
+        } else {
+            (self.input_state.held(), self.input_state.partner_held())
+        };
+        if self.state.phase == game::GamePhase::Menu {
+            self.end_playback();
+        }
+        let mut resolved = None;
+        let mut events = Vec::new();
+        let ticks = if self.frame_advance.is_active() {
+            self.timestep.reset();
+            self.shown_from = None;
+            self.pending_input = NO_INPUT;
+            self.frame_advance.queue(event);
+            if self.input_state.frame_advance_pressed() {
+                let tick = self.frame_advance.take_tick(held);
+                let input = (tick.event, input::InputEvent::None);
+                self.recorder
+                    .record(&self.state, input, tick.held, frame_advance::TICK);
+                resolved = step(
+                    &mut self.state,
+                    &mut self.anim,
+                    input,
+                    tick.held,
+                    frame_advance::TICK,
+                );
+                events.extend(self.state.take_events());
+            }
+            0
+        } else {
+            self.timestep.advance(dt)
+        };
+        // Input from a frame with no tick due waits for the next one.
+        if event.is_gameplay() {
+            self.pending_input.0 = event;
+        }
+        if partner_event.is_gameplay() {
+            self.pending_input.1 = partner_event;
+        }
+        for _ in 0..ticks {
+            self.shown_from = Some((self.state.clone(), self.anim.clone()));
+            let replayed = match &mut self.playback {
+                Some(frames) if !paused => frames.next(),
+                _ => None,
+            };
+            if replayed.is_none() && !paused && self.playback.is_some() {
+                self.end_playback();
+                self.toast.show("Replay finished".to_string());
+            }
+            let tick = if let Some(frame) = replayed {
+                let input = (frame.event.to_input(), frame.partner.to_input());
+                step(
+                    &mut self.state,
+                    &mut self.anim,
+                    input,
+                    frame.held(),
+                    frame.dt,
+                )
+            } else {
+                let input = std::mem::replace(&mut self.pending_input, NO_INPUT);
+                self.recorder
+                    .record(&self.state, input, held, timestep::STEP);
+                step(&mut self.state, &mut self.anim, input, held, timestep::STEP)
+            };
+            resolved = resolved.or(tick);
+            if let Some((was, _)) = &self.shown_from {
+                self.undo.observe(was, self.state.events());
+            }
+            events.extend(self.state.take_events());
+        }
+        if let Some((action, result)) = resolved {
+            self.debug_overlay
+                .record_action(&self.state, action, result);
+        }
+        if let Some((game::Action::Talk, Ok(()))) = resolved {
+            self.ferryman.talk(&self.state);
+        }
+        if let Some(ghost) = &mut self.ghost {
+            ghost.sync(
+                &self.state,
+                self.recorder.progress(),
+                |state, anim, frame| {
+                    let input = (frame.event.to_input(), frame.partner.to_input());
+                    step(state, anim, input, frame.held(), frame.dt);
+                    // Nothing plays the ghost's sounds or effects.
+                    state.take_events();
+                },
+            );
+        }
+        if let Some(message) = self.recorder.observe(&self.state) {
+            self.toast.show(message);
+        }
+        events.extend(self.state.take_events());
+        events
+    }
+
+    /// Move on from a won level: to the next one, or an endless run's next
+    /// round, or back to the level select, with the ending if that was the
+    /// last.
+    fn advance(&mut self) {
+        let select = campaign::LevelSelect::new(self.campaign.current_index(), false);
+        if let Some(run) = &mut self.endless {
+            let score = self.stats.score(&self.state, self.hud_info.min_crossings);
+            run.clear_round(score, self.state.assist);
+            self.generating = Some(run.level());
+        } else if self.standalone {
+            let open = Transition::Push(Screen::Menu(select));
+            self.wipe(wipe::WipeStyle::Fade, Cut::Screen(open));
+        } else if self.campaign.advance() {
+            self.wipe(wipe::WipeStyle::Iris, Cut::Level(self.campaign.current()));
+        } else {
+            self.transitions
+                .push(Transition::Push(Screen::Menu(select)));
+            self.toast.show("Campaign complete!".to_string());
+            if let Some(ending) = &self.scripts.ending {
+                let focus = render::camera_focus(&self.state, &self.anim);
+                let scene = cutscene::Cutscene::new(ending, &self.anim, focus);
+                self.transitions
+                    .push(Transition::Push(Screen::Cutscene(Box::new(scene))));
+            }
+        }
+    }
+
+    /// The HUD, then whichever menu `screen` is.
+    fn draw_hud(&self, screen: &Screen) {
+        let (state, layout) = (&self.state, &self.hud_layout);
+        render::setup_layout_camera(layout);
+        render::draw_hud(state, &self.hud_info, layout, &self.render_options);
+        render::draw_speedrun_timer(&self.speedrun, state, layout);
+        if let Some(ghost) = &self.ghost {
+            render::draw_ghost_timer(ghost, state, layout);
+        }
+        if let Some(run) = &self.endless {
+            render::draw_endless_score(run, state, layout);
+        }
+        if matches!(screen, Screen::Won { .. } | Screen::Lost) {
+            render::draw_solution_diagram(state, &self.hud_info, &self.atlas, layout);
+            render::draw_stats_summary(&self.stats, state, layout);
+            render::draw_leaderboard(&self.leaderboard, state, layout);
+        }
+        if let Some(message) = self.toast.message() {
+            render::draw_toast(message, layout);
+        }
+        render::draw_narration(&self.narrator, layout);
+        // Touch buttons and menus keep to the unscaled view they're
+        // clicked in.
+        render::setup_hud_camera();
+        if matches!(screen, Screen::Playing) {
+            render::draw_touch_controls(self.input_state.touch_pad());
+        }
+        render::draw_dialogue(&self.ferryman);
+        match screen {
+            Screen::Menu(select) => {
+                render::draw_level_select(select, &self.campaign, &self.thumbnails, self.mutators);
+            }
+            Screen::Settings(settings) => render::draw_settings_screen(settings, &self.settings),
+            Screen::Paused(menu) => render::draw_pause_menu(menu),
+            Screen::Mutators(menu) => render::draw_mutator_menu(menu, self.mutators),
+            Screen::Upgrades(menu) => render::draw_upgrade_menu(menu, &self.campaign),
+            Screen::Rules => render::draw_rules(&self.state, &self.atlas),
+            _ => {}
+        }
+        if self.generating.is_some() {
+            render::draw_generating();
+        }
+    }
+
+    /// Start a new endless run, outside the campaign, cutting to its
+    /// first round once it's drawn.
+    fn new_endless_run(&mut self) {
+        let run = endless::EndlessRun::new();
+        self.generating = Some(run.level());
+        self.endless = Some(run);
+        self.standalone = true;
+    }
+
+    /// Stop playing a replay back, if one is, and give the levels after it
+    /// the player's own mutators and upgrades again.
+    fn end_playback(&mut self) {
+        if self.playback.take().is_some() {
+            self.mutators = mutators::Mutators::default();
+            self.upgrades = self.campaign.upgrades();
+        }
+    }
+
+    /// Swap in `level` and start playing, and recording, it from scratch.
+    fn start_level(&mut self, level: Arc<level::LevelDef>) {
+        self.state.mutators = self.mutators;
+        self.state.upgrades = self.upgrades;
+        self.input_state
+            .set_reversed(self.mutators.reverses_controls());
+        self.state.change_level(level);
+        self.anim.reset(&self.state);
+        self.undo.clear();
+        self.solution = solve(&self.state);
+        self.recorder.begin(&self.state);
+    }
+
+    /// Ask for a new hint whenever the game has moved on from the last
+    /// one's, showing the last until the new one is in.
+    fn update_hint(&mut self) {
+        if !self.hints_enabled {
+            self.hint = None;
+            self.hud_info.hint = None;
+            return;
+        }
+        let key = solver::HintKey::of(&self.state);
+        if self.hint.as_ref().is_none_or(|(was, _)| *was != key) {
+            let state = self.state.clone();
+            let job = job::Job::spawn(move || solver::hint(&state));
+            self.hint = Some((key, job));
+        }
+        if let Some(hint) = self.hint.as_ref().and_then(|(_, job)| job.get()) {
+            self.hud_info.hint = hint.clone();
+        }
+    }
+}
+
+/// Start working out the shortest solution to the level `state` plays.
+fn solve(state: &game::GameState) -> job::Job<Option<Vec<solver::Crossing>>> {
+    let state = state.clone();
+    job::Job::spawn(move || solver::game_solution(&state))
+}
+
+/// Put a change from the settings screen into effect. Returns a line for
+/// the toast, if there is something to tell the player.
+fn apply_setting(
+    change: settings::SettingsChange,
+    settings: &settings::Settings,
+    audio: &mut audio::Audio,
+) -> Option<String> {
+    match change {
+        settings::SettingsChange::Resolution => {
+            let window = &settings.window;
+            request_new_screen_size(window.width as f32, window.height as f32);
+            None
+        }
+        settings::SettingsChange::Fullscreen => {
+            set_fullscreen(settings.window.fullscreen);
+            None
+        }
+        settings::SettingsChange::Vsync => Some("VSync takes effect after a restart".to_string()),
+        settings::SettingsChange::Volume => {
+            audio.set_volume(settings.audio.volume);
+            None
+        }
+        settings::SettingsChange::FramePacing
+        | settings::SettingsChange::Effects
+        | settings::SettingsChange::Theme
+        | settings::SettingsChange::Accessibility
+        | settings::SettingsChange::Hud => None,
+        settings::SettingsChange::KeyBindings => settings
+            .save()
+            .err()
+            .map(|e| format!("Couldn't save settings: {e}")),
+        settings::SettingsChange::Message(message) => Some(message),
+        settings::SettingsChange::Close => settings
+            .save()
+            .err()
+            .map(|e| format!("Couldn't save settings: {e}")),
+    }
+}
+
+/// Advance the simulation by `dt` with one frame's worth of input: the
+/// player's event and player two's, and what each holds down. Returns the
+/// action an interaction resolved to, with how it went.
+fn step(
+    state: &mut game::GameState,
+    anim: &mut anim::AnimState,
+    (event, partner): (input::InputEvent, input::InputEvent),
+    (held, partner_held): (input::HeldInput, input::HeldInput),
+    dt: f32,
+) -> Option<(game::Action, Result<(), game::RuleError>)> {
+    let mut resolved = None;
+    match state.phase {
+        game::GamePhase::Playing => {
+            state.glide(game::PlayerId::One, held.glide, dt);
+            state.glide(game::PlayerId::Two, partner_held.glide, dt);
+            match partner {
+                input::InputEvent::Move(dir) => {
+                    state.try_move(game::PlayerId::Two, dir);
+                }
+                input::InputEvent::Interact => resolved = interact(state, game::PlayerId::Two),
+                _ => {}
+            }
+            match event {
+                input::InputEvent::Move(dir) => {
+                    state.try_move_player(dir);
+                    state.set_target(game::PlayerId::One, None);
+                }
+                input::InputEvent::Interact => {
+                    resolved = interact(state, game::PlayerId::One).or(resolved);
+                }
+                input::InputEvent::CycleTarget => {
+                    interaction::cycle_target(state, game::PlayerId::One);
+                }
+                input::InputEvent::CrossRiver => {
+                    if state.start_crossing()
+                        && let Some(reason) = state.check_eating_rules()
+                    {
+                        state.lose(reason);
+                    }
+                }
+                input::InputEvent::UseItem(item) => {
+                    state.use_item(item);
+                }
+                input::InputEvent::Restart => {
+                    state.reset();
+                    anim.reset(state);
+                }
+                _ => {}
+            }
+
+            let time_scale = if held.hurry {
+                game::HURRY_TIME_SCALE
+            } else {
+                1.0
+            };
+            state.update_walking(dt);
+            state.update_items(dt);
+            state.update_crossing(dt, time_scale, held.steer);
+            if state.phase == game::GamePhase::Playing
+                && let Some(reason) = state.check_sinking()
+            {
+                state.lose(reason);
+            }
+            state.update_wandering(dt);
+            state.update_patrol(dt);
+            if state.phase == game::GamePhase::Playing
+                && let Some(reason) = state.check_caught()
+            {
+                state.lose(reason);
+            }
+            state.update_hunger(dt);
+            if state.phase == game::GamePhase::Playing
+                && let Some(reason) = state.check_hunger()
+            {
+                state.lose(reason);
+            }
+            if state.phase == game::GamePhase::Playing
+                && let Some(reason) = state.mutators.check(state)
+            {
+                state.lose(reason);
+            }
+            if state.phase == game::GamePhase::Playing
+                && state.is_on_bridge()
+                && let Some(reason) = state.check_eating_rules()
+            {
+                state.lose(reason);
+            }
+            if state.phase == game::GamePhase::Playing && state.advance_day(dt) {
+                state.lose(game::LoseReason::Nightfall);
+            }
+            let _anim_span = profiler::scope("update::anim");
+            anim.update(state, dt);
+        }
+        game::GamePhase::Menu | game::GamePhase::Paused => {}
+        game::GamePhase::Won | game::GamePhase::Lost(_) => {
+            if event == input::InputEvent::Restart {
+                state.reset();
+                anim.reset(state);
+            } else {
+                anim.update(state, dt);
+            }
+        }
+    }
+    resolved
+}
+
+/// One frame of a versus race: each player's input steps their own game.
+/// Returns true when the players leave the race.
+fn race_step(
+    race: &mut race::Race,
+    input_state: &mut input::InputState,
+    timestep: &mut timestep::FixedStep,
+    dt: f32,
+) -> bool {
+    let choices = interaction::target_choices(&race.racers[0].state, game::PlayerId::One);
+    input_state.set_choices(choices.len());
+    let events = [input_state.poll(dt), input_state.poll_partner(dt)];
+    match events[0] {
+        input::InputEvent::Pause => return true,
+        input::InputEvent::Restart if race.winner.is_some() => {
+            race.restart();
+            return false;
+        }
+        _ => {}
+    }
+    // Each racer is player one in their own game.
+    let held = [
+        (input_state.held(), input::HeldInput::default()),
+        (input_state.partner_held(), input::HeldInput::default()),
+    ];
+    let ticks = timestep.advance(dt);
+    for (racer, (event, held)) in race.racers.iter_mut().zip(events.into_iter().zip(held)) {
+        if event.is_gameplay() {
+            racer.pending = event;
+        }
+        for _ in 0..ticks {
+            let event = std::mem::replace(&mut racer.pending, input::InputEvent::None);
+            let events = (event, input::InputEvent::None);
+            step(
+                &mut racer.state,
+                &mut racer.anim,
+                events,
+                held,
+                timestep::STEP,
+            );
+            racer.state.take_events();
+        }
+    }
+    race.update(dt);
+    false
+}
+
+/// Carry out what `who`'s interact key does, where they were headed.
+fn interact(
+    state: &mut game::GameState,
+    who: game::PlayerId,
+) -> Option<(game::Action, Result<(), game::RuleError>)> {
+    state.finish_walking();
+    let action = interaction::resolve_for(state, who)?;
+    let result = state.apply_as(who, action);
+    state.set_target(who, None);
+    match result {
+        Ok(()) if state.check_win() => state.win(),
+        Ok(()) => {}
+        Err(e) => warn!("{action:?} refused: {e}"),
+    }
+    Some((action, result))
+}
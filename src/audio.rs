@@ -0,0 +1,208 @@
+use crate::anim::AnimState;
+use crate::events::GameEvent;
+use crate::game::{GameState, PlayerId};
+use crate::kinds::EntityKind;
+
+/// Seconds between oar strokes while the boat is crossing at normal speed.
+const STROKE_INTERVAL: f32 = 0.5;
+const VOLUME_STEP: f32 = 0.1;
+
+/// Every sound effect the game plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    Footstep,
+    Splash,
+    Wolf,
+    Sheep,
+    Cabbage,
+    Win,
+    Lose,
+}
+
+#[cfg(feature = "audio")]
+impl Sound {
+    const ALL: [Sound; 7] = [
+        Sound::Footstep,
+        Sound::Splash,
+        Sound::Wolf,
+        Sound::Sheep,
+        Sound::Cabbage,
+        Sound::Win,
+        Sound::Lose,
+    ];
+
+    fn path(self) -> &'static str {
+        match self {
+            Sound::Footstep => "assets/sounds/footstep.wav",
+            Sound::Splash => "assets/sounds/splash.wav",
+            Sound::Wolf => "assets/sounds/wolf.wav",
+            Sound::Sheep => "assets/sounds/sheep.wav",
+            Sound::Cabbage => "assets/sounds/cabbage.wav",
+            Sound::Win => "assets/sounds/win.wav",
+            Sound::Lose => "assets/sounds/lose.wav",
+        }
+    }
+}
+
+impl Sound {
+    /// The voice a kind names in its `sound` field.
+    fn for_kind(kind: &EntityKind) -> Option<Sound> {
+        match kind.sound.as_deref()? {
+            "wolf" => Some(Sound::Wolf),
+            "sheep" => Some(Sound::Sheep),
+            "cabbage" => Some(Sound::Cabbage),
+            _ => None,
+        }
+    }
+}
+
+/// Loaded sound effects, indexed like `Sound::ALL`. Sounds that fail to
+/// load are skipped with a warning rather than stopping the game.
+#[cfg(feature = "audio")]
+struct Bank {
+    sounds: Vec<Option<macroquad::audio::Sound>>,
+}
+
+#[cfg(feature = "audio")]
+impl Bank {
+    async fn load() -> Self {
+        let mut sounds = Vec::with_capacity(Sound::ALL.len());
+        for sound in Sound::ALL {
+            match macroquad::audio::load_sound(sound.path()).await {
+                Ok(loaded) => sounds.push(Some(loaded)),
+                Err(e) => {
+                    macroquad::logging::warn!("{}: {e}", sound.path());
+                    sounds.push(None);
+                }
+            }
+        }
+        Self { sounds }
+    }
+
+    fn play(&self, sound: Sound, volume: f32) {
+        let index = Sound::ALL.iter().position(|&s| s == sound).unwrap();
+        if let Some(loaded) = &self.sounds[index] {
+            macroquad::audio::play_sound(
+                loaded,
+                macroquad::audio::PlaySoundParams {
+                    looped: false,
+                    volume,
+                },
+            );
+        }
+    }
+}
+
+/// Stand-in when the game is built without the `audio` feature.
+#[cfg(not(feature = "audio"))]
+struct Bank;
+
+#[cfg(not(feature = "audio"))]
+impl Bank {
+    async fn load() -> Self {
+        Bank
+    }
+
+    fn play(&self, _sound: Sound, _volume: f32) {}
+}
+
+/// Plays sound effects in response to game state changes, with a master
+/// volume and mute.
+pub struct Audio {
+    bank: Bank,
+    volume: f32,
+    muted: bool,
+    stroke_timer: f32,
+    /// Whether the loss scene's bite has been heard.
+    bitten: bool,
+}
+
+impl Audio {
+    pub async fn load(volume: f32) -> Self {
+        Self {
+            bank: Bank::load().await,
+            volume,
+            muted: false,
+            stroke_timer: 0.0,
+            bitten: false,
+        }
+    }
+
+    pub fn toggle_mute(&mut self) -> String {
+        self.muted = !self.muted;
+        if self.muted {
+            "Sound muted".to_string()
+        } else {
+            self.volume_line()
+        }
+    }
+
+    /// Change the master volume by `steps` increments. Unmutes.
+    pub fn adjust_volume(&mut self, steps: f32) -> String {
+        self.volume = (self.volume + steps * VOLUME_STEP).clamp(0.0, 1.0);
+        self.muted = false;
+        self.volume_line()
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    fn volume_line(&self) -> String {
+        format!("Volume {:.0}%", self.volume * 100.0)
+    }
+
+    /// Play the cues for this frame's `events`, the oars while a boat is
+    /// crossing, and the eater and the sting once a loss scene's eater
+    /// bites.
+    pub fn observe(&mut self, state: &GameState, anim: &AnimState, events: &[GameEvent], dt: f32) {
+        for &event in events {
+            match event {
+                GameEvent::Started => self.bitten = false,
+                GameEvent::Won => self.play(Sound::Win),
+                // The scene plays the sting when it gets to the bite.
+                GameEvent::Lost(_) if anim.loss.is_some() => {}
+                GameEvent::Lost(_) => self.play(Sound::Lose),
+                GameEvent::Moved {
+                    who: PlayerId::One, ..
+                } => self.play(Sound::Footstep),
+                GameEvent::PickedUp { entity, .. } => {
+                    if let Some(sound) = Sound::for_kind(state.kind(entity)) {
+                        self.play(sound);
+                    }
+                }
+                GameEvent::CrossingStarted(_) => self.stroke_timer = 0.0,
+                _ => {}
+            }
+        }
+
+        if let Some(scene) = &anim.loss
+            && scene.is_biting()
+            && !self.bitten
+        {
+            self.bitten = true;
+            if let Some(sound) = Sound::for_kind(state.kind(scene.eater)) {
+                self.play(sound);
+            }
+            self.play(Sound::Lose);
+        }
+
+        if state.crossing_boat().is_some() {
+            self.stroke_timer -= dt * state.crossing_time_scale;
+            if self.stroke_timer <= 0.0 {
+                self.stroke_timer += STROKE_INTERVAL;
+                self.play(Sound::Splash);
+            }
+        }
+    }
+
+    fn play(&self, sound: Sound) {
+        if !self.muted && self.volume > 0.0 {
+            self.bank.play(sound, self.volume);
+        }
+    }
+}
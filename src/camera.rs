@@ -0,0 +1,331 @@
+use macroquad::prelude::*;
+
+use crate::events::GameEvent;
+use crate::game::GameState;
+use crate::input::CameraInput;
+use crate::level::LevelDef;
+use crate::tween::Ease;
+
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 3.0;
+const ZOOM_STEP: f32 = 1.15;
+/// Half-size of the box around the view center the focus may roam in
+/// before the camera starts to follow, at zoom 1.
+const DEADZONE: Vec2 = vec2(60.0, 30.0);
+/// How far ahead of a moving player the camera leans.
+const LOOK_AHEAD: f32 = 40.0;
+const LOOK_AHEAD_RATE: f32 = 3.0;
+const FOLLOW_RATE: f32 = 5.0;
+/// World pixels a second the spectator camera pans, at zoom 1.
+const PAN_SPEED: f32 = 400.0;
+/// Room above the top tiles for trees and characters.
+const SPRITE_HEADROOM: f32 = 50.0;
+
+/// Camera that can zoom in on the level and softly follow the player.
+/// While the whole level fits on screen it stays put, matching the
+/// classic fixed framing.
+#[derive(Debug, Clone)]
+pub struct CameraRig {
+    center: Vec2,
+    zoom: f32,
+    look_ahead: Vec2,
+    last_focus: Option<Vec2>,
+    /// Set while the player has dragged the view away; cleared as soon as
+    /// the focus moves again.
+    free_look: bool,
+}
+
+impl CameraRig {
+    pub fn new(base_view: Rect) -> Self {
+        Self {
+            center: base_view.center(),
+            zoom: MIN_ZOOM,
+            look_ahead: Vec2::ZERO,
+            last_focus: None,
+            free_look: false,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        input: &CameraInput,
+        level: &LevelDef,
+        focus: Vec2,
+        base_view: Rect,
+        dt: f32,
+    ) {
+        let bounds = level_bounds(level);
+        if input.reset {
+            *self = Self::new(base_view);
+        }
+        if input.zoom_steps != 0.0 {
+            self.zoom = (self.zoom * ZOOM_STEP.powf(input.zoom_steps)).clamp(MIN_ZOOM, MAX_ZOOM);
+        }
+
+        let view_size = self.view_size(base_view);
+        if let Some(drag) = input.drag {
+            // Drag is in screen pixels; convert to world units.
+            let world_per_px = view_size.x / screen_width();
+            self.center -= drag * world_per_px;
+            self.free_look = true;
+        }
+        self.center += input.pan * PAN_SPEED / self.zoom * dt;
+
+        let velocity = match self.last_focus {
+            Some(last) if dt > 0.0 => (focus - last) / dt,
+            _ => Vec2::ZERO,
+        };
+        self.last_focus = Some(focus);
+        if input.spectating {
+            self.free_look = true;
+        } else if velocity.length_squared() > 1.0 {
+            self.free_look = false;
+        }
+
+        let lead = velocity.normalize_or_zero() * LOOK_AHEAD;
+        self.look_ahead += (lead - self.look_ahead) * ease(LOOK_AHEAD_RATE, dt);
+
+        if !self.free_look {
+            let desired = self.follow_target(focus + self.look_ahead, base_view, view_size, bounds);
+            self.center += (desired - self.center) * ease(FOLLOW_RATE, dt);
+        }
+        self.center = clamp_to_level(self.center, view_size, bounds);
+    }
+
+    /// Zoom to `zoom`, within the usual limits, as a cutscene asks.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// The region of world space currently on screen.
+    pub fn view(&self, base_view: Rect) -> Rect {
+        let size = self.view_size(base_view);
+        Rect::new(
+            self.center.x - size.x / 2.0,
+            self.center.y - size.y / 2.0,
+            size.x,
+            size.y,
+        )
+    }
+
+    fn view_size(&self, base_view: Rect) -> Vec2 {
+        vec2(base_view.w, base_view.h) / self.zoom
+    }
+
+    /// Per axis: hold the classic framing when the level fits, otherwise
+    /// keep `focus` inside the deadzone.
+    fn follow_target(&self, focus: Vec2, base_view: Rect, view_size: Vec2, bounds: Rect) -> Vec2 {
+        let deadzone = DEADZONE / self.zoom;
+        let base_center = base_view.center();
+
+        let axis = |center: f32, focus: f32, base: f32, view: f32, level: f32, dz: f32| {
+            if view >= level {
+                base
+            } else if focus > center + dz {
+                focus - dz
+            } else if focus < center - dz {
+                focus + dz
+            } else {
+                center
+            }
+        };
+
+        vec2(
+            axis(
+                self.center.x,
+                focus.x,
+                base_center.x,
+                view_size.x,
+                bounds.w,
+                deadzone.x,
+            ),
+            axis(
+                self.center.y,
+                focus.y,
+                base_center.y,
+                view_size.y,
+                bounds.h,
+                deadzone.y,
+            ),
+        )
+    }
+}
+
+/// Keep the view inside the level, or the level inside the view when the
+/// view is the larger of the two.
+fn clamp_to_level(center: Vec2, view_size: Vec2, bounds: Rect) -> Vec2 {
+    let axis = |c: f32, min: f32, max: f32, half: f32| {
+        let (a, b) = (min + half, max - half);
+        c.clamp(a.min(b), a.max(b))
+    };
+    vec2(
+        axis(center.x, bounds.left(), bounds.right(), view_size.x / 2.0),
+        axis(center.y, bounds.top(), bounds.bottom(), view_size.y / 2.0),
+    )
+}
+
+/// The world-space area a level's tiles and sprites cover.
+pub fn level_bounds(level: &LevelDef) -> Rect {
+    let (min_x, min_y, max_x, max_y) = level.iso_bounds();
+    Rect::new(
+        min_x,
+        min_y - SPRITE_HEADROOM,
+        max_x - min_x,
+        max_y - min_y + SPRITE_HEADROOM,
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Crossing camera
+// ---------------------------------------------------------------------------
+
+/// How much closer the crossing camera is than the overview.
+const CROSSING_ZOOM: f32 = 1.6;
+/// How far below the middle of the close-up the boat sits, as a share of
+/// its height, leaving the water ahead in view.
+const WATERLINE: f32 = 0.15;
+const CROSSING_RATE: f32 = 2.5;
+
+/// An optional closer camera that rides along with a crossing boat and
+/// eases back out to the overview once it docks.
+#[derive(Debug, Clone)]
+pub struct CrossingCamera {
+    pub enabled: bool,
+    /// 0 showing the overview, easing to 1 for the close-up.
+    blend: f32,
+    boat: Vec2,
+}
+
+impl CrossingCamera {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            blend: 0.0,
+            boat: Vec2::ZERO,
+        }
+    }
+
+    /// Follow the boat at `boat` while one is crossing; None once it docks.
+    pub fn update(&mut self, boat: Option<Vec2>, dt: f32) {
+        let target = if self.enabled && boat.is_some() {
+            1.0
+        } else {
+            0.0
+        };
+        self.blend += (target - self.blend) * ease(CROSSING_RATE, dt);
+        if let Some(boat) = boat {
+            self.boat = boat;
+        }
+    }
+
+    /// How far to go from the overview toward `close_up`, 0 to 1.
+    pub fn blend(&self) -> f32 {
+        Ease::SmoothStep.apply(self.blend)
+    }
+
+    /// The close-up on the boat, for an `overview` of the level.
+    pub fn close_up(&self, overview: Rect) -> Rect {
+        let size = vec2(overview.w, overview.h) / CROSSING_ZOOM;
+        let center = self.boat - vec2(0.0, size.y * WATERLINE);
+        Rect::new(
+            center.x - size.x / 2.0,
+            center.y - size.y / 2.0,
+            size.x,
+            size.y,
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Camera effects
+// ---------------------------------------------------------------------------
+
+/// Trauma lost per second; shake strength is trauma squared.
+const TRAUMA_DECAY: f32 = 1.2;
+const LOSS_TRAUMA: f32 = 0.8;
+const MAX_SHAKE: f32 = 10.0;
+/// Degrees of roll at full trauma.
+const MAX_SHAKE_ROLL: f32 = 2.0;
+const SWAY_RATE: f32 = 2.0;
+const SWAY_BOB: f32 = 1.5;
+const SWAY_ROLL: f32 = 0.6;
+const PUNCH_DECAY: f32 = 4.0;
+/// How much a full punch zooms in, as a fraction of the view.
+const PUNCH_ZOOM: f32 = 0.04;
+
+/// Cosmetic motion layered over the rig's view: a shake when the game is
+/// lost, a gentle sway while the boat crosses, and a punch when it docks.
+/// Effects add up, then decay on their own.
+#[derive(Debug, Clone)]
+pub struct CameraFx {
+    trauma: f32,
+    /// 0 on land, easing to 1 while the boat is crossing.
+    sway: f32,
+    punch: f32,
+    time: f32,
+}
+
+impl CameraFx {
+    pub fn new() -> Self {
+        Self {
+            trauma: 0.0,
+            sway: 0.0,
+            punch: 0.0,
+            time: 0.0,
+        }
+    }
+
+    /// Start a shake; `amount` is added to the current trauma, capped at 1.
+    pub fn shake(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    pub fn punch(&mut self) {
+        self.punch = 1.0;
+    }
+
+    /// Trigger effects for this frame's `events` and advance them by `dt`.
+    /// Pass zero while paused to freeze them.
+    pub fn update(&mut self, state: &GameState, events: &[GameEvent], dt: f32) {
+        for event in events {
+            match event {
+                GameEvent::Lost(_) => self.shake(LOSS_TRAUMA),
+                GameEvent::Docked { .. } => self.punch(),
+                _ => {}
+            }
+        }
+        let crossing = state.crossing_boat().is_some();
+
+        self.time += dt;
+        self.trauma = (self.trauma - TRAUMA_DECAY * dt).max(0.0);
+        self.punch = (self.punch - PUNCH_DECAY * dt).max(0.0);
+        let sway_target = if crossing { 1.0 } else { 0.0 };
+        self.sway += (sway_target - self.sway) * ease(SWAY_RATE, dt);
+    }
+
+    /// `view` moved, zoomed and rolled by the active effects. The roll is
+    /// in degrees, for `Camera2D::rotation`.
+    pub fn apply(&self, view: Rect) -> (Rect, f32) {
+        let t = self.time;
+        let shake = self.trauma * self.trauma;
+        // Sums of unrelated sines stand in for noise.
+        let jitter = vec2(
+            (t * 37.0).sin() + (t * 23.0 + 1.3).sin(),
+            (t * 41.0 + 0.7).sin() + (t * 19.0 + 2.1).sin(),
+        ) * 0.5;
+        let bob = vec2(0.0, (t * 1.6).sin() * SWAY_BOB);
+        let offset = jitter * shake * MAX_SHAKE + bob * self.sway;
+        let shake_roll = (t * 29.0).sin() * shake * MAX_SHAKE_ROLL;
+        let sway_roll = (t * 1.1).sin() * SWAY_ROLL * self.sway;
+
+        let size = vec2(view.w, view.h) * (1.0 - PUNCH_ZOOM * self.punch);
+        let corner = view.center() + offset - size / 2.0;
+        let rect = Rect::new(corner.x, corner.y, size.x, size.y);
+        (rect, shake_roll + sway_roll)
+    }
+}
+
+/// Frame-rate independent smoothing factor for exponential easing.
+fn ease(rate: f32, dt: f32) -> f32 {
+    1.0 - (-rate * dt).exp()
+}
@@ -0,0 +1,521 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use macroquad::logging::error;
+use macroquad::prelude::{Rect, load_string};
+use serde::{Deserialize, Serialize};
+
+use crate::job::Job;
+use crate::kinds::SpriteSet;
+use crate::level::{self, LevelDef};
+use crate::mutators::{self, Mutators};
+use crate::pause::{self, MenuInput};
+use crate::solver;
+use crate::storage;
+use crate::upgrades::{Upgrade, Upgrades};
+
+const PROGRESS_PATH: &str = "progress.ron";
+
+/// Levels the web build plays, since it can't list the levels directory.
+#[cfg(target_arch = "wasm32")]
+const BUNDLED_LEVELS: [&str; 9] = [
+    "assets/levels/01_first_crossing.ron",
+    "assets/levels/02_classic.ron",
+    "assets/levels/03_double_harvest.ron",
+    "assets/levels/04_wolf_pack.ron",
+    "assets/levels/05_undertow.ron",
+    "assets/levels/06_island_hop.ron",
+    "assets/levels/07_barge_and_raft.ron",
+    "assets/levels/08_lean_winter.ron",
+    "assets/levels/09_night_watch.ron",
+];
+
+/// The best result on one level so far. Having a record at all means the
+/// level has been solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelRecord {
+    pub best_crossings: u32,
+}
+
+/// Saved results, keyed by level file name, and the boat upgrades
+/// switched on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Progress {
+    levels: BTreeMap<String, LevelRecord>,
+    /// Levels so far only solved with boat upgrades on, which set no best
+    /// crossings.
+    upgraded: BTreeSet<String>,
+    /// The upgrades' names.
+    upgrades: Vec<String>,
+}
+
+impl Progress {
+    fn load() -> Self {
+        let parsed = storage::read(PROGRESS_PATH).and_then(|source| match source {
+            Some(source) => ron::from_str(&source).map_err(|e| format!("{PROGRESS_PATH}: {e}")),
+            None => Ok(Self::default()),
+        });
+        parsed.unwrap_or_else(|e| {
+            error!("{e}; starting with no progress");
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        storage::write(PROGRESS_PATH, &source)
+    }
+}
+
+/// A level in the campaign and the file it came from.
+pub struct CampaignLevel {
+    /// File name, used to key saved progress.
+    pub key: String,
+    pub level: Arc<LevelDef>,
+    /// The fewest crossings it takes, being worked out in the background.
+    optimal: Job<Option<usize>>,
+}
+
+impl CampaignLevel {
+    fn new(key: String, level: LevelDef) -> Self {
+        let level = Arc::new(level);
+        let solved = Arc::clone(&level);
+        Self {
+            key,
+            level,
+            optimal: Job::spawn(move || solver::min_crossings(&solved)),
+        }
+    }
+}
+
+/// Every level under `assets/levels/`, played in file name order, with
+/// the player's progress through them.
+pub struct Campaign {
+    levels: Vec<CampaignLevel>,
+    current: usize,
+    progress: Progress,
+    /// Earned across the levels, from `progress`.
+    stars: u32,
+}
+
+impl Campaign {
+    /// Load every level file. Files that fail to load are logged and left
+    /// out; if none load, the campaign is just the built-in classic level.
+    pub async fn load() -> Self {
+        let mut levels = Vec::new();
+        for path in level_paths() {
+            let parsed = match load_string(&path).await {
+                Ok(source) => LevelDef::parse(&source).map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match parsed {
+                Ok(level) => levels.push(CampaignLevel::new(file_name(&path).to_string(), level)),
+                Err(e) => error!("{path}: {e}; skipping it"),
+            }
+        }
+        if levels.is_empty() {
+            error!("no levels could be loaded; using the built-in level");
+            levels.push(CampaignLevel::new(
+                file_name(level::CLASSIC_PATH).to_string(),
+                LevelDef::classic(),
+            ));
+        }
+        let mut campaign = Self {
+            levels,
+            current: 0,
+            progress: Progress::load(),
+            stars: 0,
+        };
+        campaign.stars = campaign.count_stars();
+        campaign
+    }
+
+    /// Read the level file named `key` again, after it changed on disk.
+    /// Returns its index, or None if it isn't one of the campaign's levels.
+    /// On error the old version stays. The stars are counted again at the
+    /// next win, when the new version's solve is long done.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload(&mut self, key: &str) -> Option<Result<usize, String>> {
+        let index = self.levels.iter().position(|entry| entry.key == key)?;
+        let path = format!("{}/{key}", level::LEVELS_DIR);
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| LevelDef::parse(&source).map_err(|e| e.to_string()));
+        Some(parsed.map(|level| {
+            self.levels[index] = CampaignLevel::new(key.to_string(), level);
+            index
+        }))
+    }
+
+    pub fn levels(&self) -> &[CampaignLevel] {
+        &self.levels
+    }
+
+    /// Every sprite set the campaign's kinds use, once each.
+    pub fn sprite_sets(&self) -> Vec<SpriteSet> {
+        let mut sets: Vec<SpriteSet> = Vec::new();
+        for entry in &self.levels {
+            let kinds = &entry.level.kinds;
+            for id in kinds.ids() {
+                let set = &kinds.get(id).sprites;
+                if !sets.contains(set) {
+                    sets.push(set.clone());
+                }
+            }
+        }
+        sets
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn current(&self) -> Arc<LevelDef> {
+        Arc::clone(&self.levels[self.current].level)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.current = index.min(self.levels.len() - 1);
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.current + 1 == self.levels.len()
+    }
+
+    /// Move on to the next level. False when this was the last one.
+    pub fn advance(&mut self) -> bool {
+        if self.is_last() {
+            return false;
+        }
+        self.current += 1;
+        true
+    }
+
+    pub fn record(&self, index: usize) -> Option<LevelRecord> {
+        self.progress.levels.get(&self.levels[index].key).copied()
+    }
+
+    /// Whether level `index` has only been solved with boat upgrades on.
+    pub fn is_upgraded_solve(&self, index: usize) -> bool {
+        self.record(index).is_none() && self.progress.upgraded.contains(&self.levels[index].key)
+    }
+
+    /// Note a solve of the current level, in `crossings` if they count
+    /// towards its best, and save progress. Returns the upgrades the stars
+    /// newly unlock, for the player to switch on if they like.
+    pub fn record_win(&mut self, crossings: Option<u32>) -> Result<Vec<Upgrade>, String> {
+        let key = self.levels[self.current].key.clone();
+        match crossings {
+            Some(crossings) => {
+                let record = self.progress.levels.entry(key).or_insert(LevelRecord {
+                    best_crossings: crossings,
+                });
+                record.best_crossings = record.best_crossings.min(crossings);
+            }
+            None => {
+                self.progress.upgraded.insert(key);
+            }
+        }
+        let was = Upgrades::unlocked(self.stars);
+        self.stars = self.count_stars();
+        let unlocked: Vec<Upgrade> = Upgrades::unlocked(self.stars)
+            .iter()
+            .filter(|&upgrade| !was.is_on(upgrade))
+            .collect();
+        self.progress.save()?;
+        Ok(unlocked)
+    }
+
+    /// Stars earned over the campaign: each solved level's, for its best
+    /// crossings against the fewest it can be solved in. A level only
+    /// solved with upgrades on earns one.
+    pub fn stars(&self) -> u32 {
+        self.stars
+    }
+
+    /// Waits for any level's solve still going, which only happens while
+    /// the campaign is loading.
+    fn count_stars(&self) -> u32 {
+        self.levels
+            .iter()
+            .filter_map(|entry| {
+                let Some(record) = self.progress.levels.get(&entry.key) else {
+                    return self.progress.upgraded.contains(&entry.key).then_some(1);
+                };
+                let optimal = (*entry.optimal.wait()?)?;
+                Some(u32::from(solver::star_rating(
+                    record.best_crossings,
+                    optimal,
+                )))
+            })
+            .sum()
+    }
+
+    /// The boat upgrades switched on, of those the stars have unlocked.
+    pub fn upgrades(&self) -> Upgrades {
+        Upgrades::from_names(&self.progress.upgrades).only(Upgrades::unlocked(self.stars))
+    }
+
+    /// Switch `upgrade` on or off, and save progress.
+    pub fn toggle_upgrade(&mut self, upgrade: Upgrade) -> Result<(), String> {
+        let mut on = self.upgrades();
+        on.toggle(upgrade);
+        self.progress.upgrades = on.names();
+        self.progress.save()
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn level_paths() -> Vec<String> {
+    let dir = level::LEVELS_DIR;
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("{dir}: {e}");
+            return Vec::new();
+        }
+    };
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".ron"))
+        .map(|name| format!("{dir}/{name}"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(target_arch = "wasm32")]
+fn level_paths() -> Vec<String> {
+    BUNDLED_LEVELS.iter().map(|path| path.to_string()).collect()
+}
+
+/// What the player picked on the level select screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelChoice {
+    Play(usize),
+    /// Today's generated puzzle.
+    Daily,
+    /// A new generated puzzle.
+    Random,
+    /// Generated puzzles one after another until the first loss.
+    Endless,
+    /// Pick the mutators to play under.
+    Mutators,
+    /// Switch the unlocked boat upgrades on and off.
+    Upgrades,
+    /// Back to the pause menu.
+    Back,
+    Quit,
+}
+
+/// Rows after the campaign's levels for the generated puzzles.
+const GENERATED_ROWS: [&str; 3] = ["Daily Puzzle", "Random Puzzle", "Endless Run"];
+
+/// The list of levels with their saved results, then the generated
+/// puzzles. Shown at launch, where the last row quits, and from the pause
+/// menu, where it goes back.
+pub struct LevelSelect {
+    selected: usize,
+    from_pause: bool,
+}
+
+impl LevelSelect {
+    pub fn new(selected: usize, from_pause: bool) -> Self {
+        Self {
+            selected,
+            from_pause,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        "Select a level"
+    }
+
+    pub fn items(&self, campaign: &Campaign, chosen: Mutators) -> (Vec<String>, usize) {
+        let mut labels: Vec<String> = campaign
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let result = match campaign.record(i) {
+                    Some(record) => format!("best {}", record.best_crossings),
+                    None if campaign.is_upgraded_solve(i) => "solved with upgrades".to_string(),
+                    None => "unsolved".to_string(),
+                };
+                format!("{}. {} ({result})", i + 1, entry.level.name)
+            })
+            .collect();
+        labels.extend(GENERATED_ROWS.map(str::to_string));
+        labels.push(match chosen.iter().count() {
+            0 => "Mutators: off".to_string(),
+            on => format!("Mutators: {on} on"),
+        });
+        labels.push(format!(
+            "Boat upgrades: {} on ({} stars)",
+            campaign.upgrades().iter().count(),
+            campaign.stars()
+        ));
+        labels.push(if self.from_pause { "Back" } else { "Quit" }.to_string());
+        (labels, self.selected)
+    }
+
+    pub fn handle(
+        &mut self,
+        input: &MenuInput,
+        view: Rect,
+        campaign: &Campaign,
+    ) -> Option<LevelChoice> {
+        let levels = campaign.levels.len();
+        let count = levels + GENERATED_ROWS.len() + 3;
+        let leave = if self.from_pause {
+            LevelChoice::Back
+        } else {
+            LevelChoice::Quit
+        };
+        if input.back && self.from_pause {
+            return Some(leave);
+        }
+        if !pause::navigate(&mut self.selected, count, input, view) {
+            return None;
+        }
+        match self.selected.checked_sub(levels) {
+            None => Some(LevelChoice::Play(self.selected)),
+            Some(0) => Some(LevelChoice::Daily),
+            Some(1) => Some(LevelChoice::Random),
+            Some(2) => Some(LevelChoice::Endless),
+            Some(3) => Some(LevelChoice::Mutators),
+            Some(4) => Some(LevelChoice::Upgrades),
+            Some(_) => Some(leave),
+        }
+    }
+}
+
+/// Turning mutators on and off for the levels started from here on, opened
+/// from the level select.
+pub struct MutatorMenu {
+    selected: usize,
+}
+
+impl MutatorMenu {
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
+
+    pub fn title(&self) -> &'static str {
+        "Mutators"
+    }
+
+    /// A row for each mutator saying whether it's on, then Back.
+    pub fn items(&self, chosen: Mutators) -> (Vec<String>, usize) {
+        let mut labels: Vec<String> = mutators::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, mutator)| {
+                let status = if chosen.is_on(i) { "on" } else { "off" };
+                format!("{}: {status}", mutator.name())
+            })
+            .collect();
+        labels.push("Back".to_string());
+        (labels, self.selected)
+    }
+
+    /// What the highlighted mutator does; None on Back.
+    pub fn description(&self) -> Option<&'static str> {
+        mutators::ALL.get(self.selected).map(|m| m.describe())
+    }
+
+    /// Toggle the picked mutator in `chosen`. Returns true once the menu
+    /// is closed.
+    pub fn handle(&mut self, input: &MenuInput, view: Rect, chosen: &mut Mutators) -> bool {
+        if input.back {
+            return true;
+        }
+        let count = mutators::ALL.len() + 1;
+        if !pause::navigate(&mut self.selected, count, input, view) {
+            return false;
+        }
+        if self.selected == mutators::ALL.len() {
+            return true;
+        }
+        chosen.toggle(self.selected);
+        false
+    }
+}
+
+/// What the player picked on the boat upgrades page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeChoice {
+    Toggle(Upgrade),
+    /// One the stars don't reach yet.
+    Locked(Upgrade),
+    Back,
+}
+
+/// Turning the unlocked boat upgrades on and off, opened from the level
+/// select. The picks are saved with the campaign's progress.
+pub struct UpgradeMenu {
+    selected: usize,
+}
+
+impl UpgradeMenu {
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
+
+    pub fn title(&self) -> &'static str {
+        "Boat upgrades"
+    }
+
+    /// A row for each upgrade saying whether it's on or what unlocks it,
+    /// then Back.
+    pub fn items(&self, campaign: &Campaign) -> (Vec<String>, usize) {
+        let on = campaign.upgrades();
+        let mut labels: Vec<String> = Upgrade::ALL
+            .iter()
+            .map(|&upgrade| {
+                let status = if upgrade.stars() > campaign.stars() {
+                    format!("locked, {} stars", upgrade.stars())
+                } else if on.is_on(upgrade) {
+                    "on".to_string()
+                } else {
+                    "off".to_string()
+                };
+                format!("{}: {status}", upgrade.name())
+            })
+            .collect();
+        labels.push("Back".to_string());
+        (labels, self.selected)
+    }
+
+    /// What the highlighted upgrade does; None on Back.
+    pub fn description(&self) -> Option<&'static str> {
+        Upgrade::ALL.get(self.selected).map(|u| u.describe())
+    }
+
+    pub fn handle(
+        &mut self,
+        input: &MenuInput,
+        view: Rect,
+        campaign: &Campaign,
+    ) -> Option<UpgradeChoice> {
+        if input.back {
+            return Some(UpgradeChoice::Back);
+        }
+        let count = Upgrade::ALL.len() + 1;
+        if !pause::navigate(&mut self.selected, count, input, view) {
+            return None;
+        }
+        Some(match Upgrade::ALL.get(self.selected) {
+            Some(&upgrade) if upgrade.stars() > campaign.stars() => UpgradeChoice::Locked(upgrade),
+            Some(&upgrade) => UpgradeChoice::Toggle(upgrade),
+            None => UpgradeChoice::Back,
+        })
+    }
+}
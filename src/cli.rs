@@ -0,0 +1,94 @@
+use crate::game::{Action, EntityId};
+use crate::level::LevelDef;
+use crate::solver::{self, PlanStep, Puzzle, PuzzleState};
+
+pub const USAGE: &str = "\
+usage: sanzu [options]
+
+  --level <path>     play this level file straight away
+  --windowed         open in a window, whatever the settings say
+  --fullscreen       open fullscreen, whatever the settings say
+  --seed <n>         seed the random stream, so animals wander the same way
+  --replay <path>    play back a recorded run, on --level if given
+  --ghost <path>     race a recorded run's ghost, such as a shared replay.ron
+  --solve <path>     print the shortest solution of a level file and exit
+  --help             show this message";
+
+/// Launch options from the command line. Anything left unset falls back
+/// to the settings file or the usual start at the level select.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Args {
+    pub level: Option<String>,
+    /// Overrides the window setting for this run only.
+    pub fullscreen: Option<bool>,
+    pub seed: Option<u64>,
+    pub replay: Option<String>,
+    pub ghost: Option<String>,
+    pub solve: Option<String>,
+    pub help: bool,
+}
+
+impl Args {
+    /// Read the arguments after the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} needs a value"));
+            match arg.as_str() {
+                "--level" => parsed.level = Some(value(&arg)?),
+                "--windowed" => parsed.fullscreen = Some(false),
+                "--fullscreen" => parsed.fullscreen = Some(true),
+                "--seed" => {
+                    let seed = value(&arg)?;
+                    let seed = seed
+                        .parse()
+                        .map_err(|_| format!("--seed wants a whole number, got {seed:?}"))?;
+                    parsed.seed = Some(seed);
+                }
+                "--replay" => parsed.replay = Some(value(&arg)?),
+                "--ghost" => parsed.ghost = Some(value(&arg)?),
+                "--solve" => parsed.solve = Some(value(&arg)?),
+                "--help" | "-h" => parsed.help = true,
+                _ => return Err(format!("unknown option {arg:?}")),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// The `--solve` report for the level file at `path`: the fewest
+/// crossings and the actions that make them.
+pub fn solve(path: &str) -> Result<String, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let level = LevelDef::parse(&source).map_err(|e| format!("{path}: {e}"))?;
+    let solution = solver::solve(&Puzzle::new(&level), PuzzleState::initial(&level))
+        .ok_or_else(|| format!("{}: no solution", level.name))?;
+    let steps = solver::plan_steps(&solution);
+    let mut lines = vec![format!(
+        "{}: {} crossings, {} actions",
+        level.name,
+        solution.len(),
+        steps.len()
+    )];
+    let name = |id: EntityId| &level.kind(level.entities[id.0].0).name;
+    for (i, step) in steps.iter().enumerate() {
+        let text = match *step {
+            PlanStep::Act(Action::PickUp(id)) => format!("pick up the {}", name(id)),
+            PlanStep::Act(Action::Drop(id)) => format!("drop the {}", name(id)),
+            PlanStep::Act(Action::LoadOntoBoat(id)) => {
+                format!("load the {} onto the boat", name(id))
+            }
+            PlanStep::Act(Action::UnloadFromBoat(id)) => format!("unload the {}", name(id)),
+            PlanStep::Act(Action::BoardBoat) => "board the boat".to_string(),
+            PlanStep::Act(Action::UnboardBoat) => "get off the boat".to_string(),
+            PlanStep::Act(Action::Talk) => "talk to the ferryman".to_string(),
+            PlanStep::Row { to } => format!("row to the {}", level.region_name(to)),
+            PlanStep::Walk { to } => {
+                format!("walk over the bridge to the {}", level.region_name(to))
+            }
+        };
+        lines.push(format!("{:>3}. {text}", i + 1));
+    }
+    Ok(lines.join("\n"))
+}
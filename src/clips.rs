@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use macroquad::logging::error;
+use macroquad::prelude::load_string;
+use serde::Deserialize;
+
+use crate::kinds::SpriteSet;
+
+const MANIFEST_PATH: &str = "assets/sprites/clips.ron";
+
+pub const IDLE: &str = "idle";
+pub const WALK: &str = "walk";
+pub const EAT: &str = "eat";
+pub const SLEEP: &str = "sleep";
+pub const ROW: &str = "row";
+
+/// Seconds a frame shows for when a clip doesn't say.
+const DEFAULT_DURATION: f32 = 0.12;
+
+/// A named run of frames: sprite files under `assets/sprites/`, without
+/// the `.png`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Clip {
+    pub frames: Vec<String>,
+    /// Seconds each frame shows for.
+    #[serde(default = "default_duration")]
+    pub duration: f32,
+    /// Whether it starts over at the end, rather than holding the last
+    /// frame.
+    #[serde(default)]
+    pub looping: bool,
+}
+
+fn default_duration() -> f32 {
+    DEFAULT_DURATION
+}
+
+impl Clip {
+    fn still(frame: String) -> Self {
+        Self {
+            frames: vec![frame],
+            duration: DEFAULT_DURATION,
+            looping: true,
+        }
+    }
+
+    /// Which frame shows `time` seconds in.
+    pub fn frame_at(&self, time: f32) -> usize {
+        let count = self.frames.len().max(1);
+        let frame = (time / self.duration.max(f32::EPSILON)) as usize;
+        if self.looping {
+            frame % count
+        } else {
+            frame.min(count - 1)
+        }
+    }
+}
+
+/// The clips of each sprite set, by set name and then clip name, from
+/// `assets/sprites/clips.ron`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Manifest {
+    sets: BTreeMap<String, BTreeMap<String, Clip>>,
+}
+
+impl Manifest {
+    /// Load the manifest; without one, every set gets the clips its files
+    /// imply.
+    pub async fn load() -> Self {
+        let parsed = match load_string(MANIFEST_PATH).await {
+            Ok(source) => ron::from_str(&source).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        parsed.unwrap_or_else(|e| {
+            error!("{MANIFEST_PATH}: {e}; animating from the sprite files alone");
+            Self::default()
+        })
+    }
+
+    /// `set`'s clips. Sets the manifest leaves out get an idle clip, and a
+    /// walk cycle when they're animated.
+    pub fn clips(&self, set: &SpriteSet) -> BTreeMap<String, Clip> {
+        if let Some(clips) = self.sets.get(&set.name) {
+            return clips.clone();
+        }
+        let name = &set.name;
+        if !set.animated {
+            return BTreeMap::from([(IDLE.to_string(), Clip::still(name.clone()))]);
+        }
+        let walk = Clip {
+            frames: vec![format!("{name}_walk1"), format!("{name}_walk2")],
+            duration: DEFAULT_DURATION,
+            looping: true,
+        };
+        BTreeMap::from([
+            (IDLE.to_string(), Clip::still(format!("{name}_idle"))),
+            (WALK.to_string(), walk),
+        ])
+    }
+}
+
+/// Which clip someone is playing, and for how long it's been on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Playback {
+    pub clip: &'static str,
+    pub time: f32,
+}
+
+impl Playback {
+    pub fn new() -> Self {
+        Self {
+            clip: IDLE,
+            time: 0.0,
+        }
+    }
+
+    /// Keep playing `clip` for another `dt`, starting it over if it's a
+    /// change.
+    pub fn play(&mut self, clip: &'static str, dt: f32) {
+        if self.clip == clip {
+            self.time += dt;
+        } else {
+            *self = Self { clip, time: 0.0 };
+        }
+    }
+}
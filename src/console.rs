@@ -0,0 +1,404 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::campaign::{Campaign, CampaignLevel};
+use crate::game::{
+    Action, EntityInstance, EntityLocation, GamePhase, GameState, LoseReason, PlayerLocation,
+};
+use crate::text::{self, TextStyle};
+use crate::world::GridPos;
+
+/// Output lines kept in the log.
+const LOG_LEN: usize = 10;
+/// Submitted lines kept for Up/Down recall.
+const HISTORY_LEN: usize = 32;
+
+const LINE_HEIGHT: f32 = 16.0;
+const FONT_SIZE: f32 = 16.0;
+const MARGIN: f32 = 10.0;
+
+const HELP: [&str; 7] = [
+    "tp <col> <row>      move the player to a land tile",
+    "give <kind>         have an animal of that kind follow the player",
+    "set-phase <phase>   playing, won or lost",
+    "load-level <level>  by number, file name or level name",
+    "restart             start the level over",
+    "clear               empty this log",
+    "help                this list",
+];
+
+/// A parsed console line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Teleport(GridPos),
+    Give(String),
+    SetPhase(PhaseArg),
+    LoadLevel(String),
+    Restart,
+    Clear,
+    Help,
+}
+
+/// Phases `set-phase` can switch to. Paused and the level select have
+/// menus of their own, so they're left to the pause key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhaseArg {
+    Playing,
+    Won,
+    Lost,
+}
+
+/// Something a command needs the caller to do after it changed the state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleEffect {
+    /// The state jumped outside the rules; snap the animations to it.
+    Changed,
+    /// Start the campaign level at this index.
+    LoadLevel(usize),
+}
+
+/// Read a console line. Errs with a message for the log.
+pub fn parse(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("type a command, or help for a list".to_string());
+    };
+    let args: Vec<&str> = words.collect();
+    let expect = |count: usize, usage: &str| {
+        if args.len() == count {
+            Ok(())
+        } else {
+            Err(format!("usage: {usage}"))
+        }
+    };
+    match name.to_ascii_lowercase().as_str() {
+        "tp" => {
+            expect(2, "tp <col> <row>")?;
+            let coord = |arg: &str| {
+                arg.parse::<i32>()
+                    .map_err(|_| format!("{arg:?} isn't a whole number"))
+            };
+            Ok(Command::Teleport(GridPos::new(
+                coord(args[0])?,
+                coord(args[1])?,
+            )))
+        }
+        "give" => {
+            expect(1, "give <kind>")?;
+            Ok(Command::Give(args[0].to_string()))
+        }
+        "set-phase" => {
+            expect(1, "set-phase playing|won|lost")?;
+            let phase = match args[0].to_ascii_lowercase().as_str() {
+                "playing" => PhaseArg::Playing,
+                "won" => PhaseArg::Won,
+                "lost" => PhaseArg::Lost,
+                other => return Err(format!("no phase {other:?}; try playing, won or lost")),
+            };
+            Ok(Command::SetPhase(phase))
+        }
+        "load-level" => {
+            if args.is_empty() {
+                return Err("usage: load-level <level>".to_string());
+            }
+            Ok(Command::LoadLevel(args.join(" ")))
+        }
+        "restart" => expect(0, "restart").map(|()| Command::Restart),
+        "clear" => expect(0, "clear").map(|()| Command::Clear),
+        "help" => expect(0, "help").map(|()| Command::Help),
+        other => Err(format!("unknown command {other:?}; try help")),
+    }
+}
+
+/// The developer console, opened with the backtick key. Commands change
+/// the state through the same checks as play: `give` is a `PickUp` the
+/// rules must accept, and nothing moves outside `Playing`.
+pub struct Console {
+    open: bool,
+    input: String,
+    log: VecDeque<String>,
+    history: Vec<String>,
+    /// Index into `history` while recalling with Up/Down.
+    recall: Option<usize>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            log: VecDeque::from(["help lists the commands".to_string()]),
+            history: Vec::new(),
+            recall: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        // Drop the backtick that opened it.
+        while get_char_pressed().is_some() {}
+    }
+
+    /// Read typing while open. Returns a line when Enter submits one.
+    pub fn update(&mut self) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+        if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::GraveAccent) {
+            self.open = false;
+            self.input.clear();
+            while get_char_pressed().is_some() {}
+            return None;
+        }
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                self.input.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.step_history(-1);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.step_history(1);
+        }
+        if !is_key_pressed(KeyCode::Enter) && !is_key_pressed(KeyCode::KpEnter) {
+            return None;
+        }
+        self.recall = None;
+        let line = std::mem::take(&mut self.input);
+        let line = line.trim().to_string();
+        if line.is_empty() {
+            return None;
+        }
+        if self.history.last() != Some(&line) {
+            if self.history.len() == HISTORY_LEN {
+                self.history.remove(0);
+            }
+            self.history.push(line.clone());
+        }
+        Some(line)
+    }
+
+    fn step_history(&mut self, delta: i32) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        let next = match (self.recall, delta < 0) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(0), true) => Some(0),
+            (Some(i), true) => Some(i - 1),
+            (Some(i), false) if i < last => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.recall = next;
+        self.input = next.map_or_else(String::new, |i| self.history[i].clone());
+    }
+
+    /// Parse and run `line`, logging what happened.
+    pub fn run(
+        &mut self,
+        line: &str,
+        state: &mut GameState,
+        campaign: &Campaign,
+    ) -> Option<ConsoleEffect> {
+        self.push(format!("> {line}"));
+        let result = parse(line).and_then(|command| self.execute(command, state, campaign));
+        match result {
+            Ok((message, effect)) => {
+                if !message.is_empty() {
+                    self.push(message);
+                }
+                effect
+            }
+            Err(e) => {
+                self.push(format!("error: {e}"));
+                None
+            }
+        }
+    }
+
+    fn execute(
+        &mut self,
+        command: Command,
+        state: &mut GameState,
+        campaign: &Campaign,
+    ) -> Result<(String, Option<ConsoleEffect>), String> {
+        match command {
+            Command::Teleport(pos) => {
+                teleport(state, pos)?;
+                Ok((
+                    format!("player at {}, {}", pos.col, pos.row),
+                    Some(ConsoleEffect::Changed),
+                ))
+            }
+            Command::Give(kind) => {
+                let name = give(state, &kind)?;
+                Ok((
+                    format!("the {name} follows the player"),
+                    Some(ConsoleEffect::Changed),
+                ))
+            }
+            Command::SetPhase(phase) => {
+                state.finish_walking();
+                match phase {
+                    PhaseArg::Playing => state.phase = GamePhase::Playing,
+                    PhaseArg::Won => state.win(),
+                    PhaseArg::Lost => {
+                        state.lose(state.check_eating_rules().unwrap_or(LoseReason::Nightfall))
+                    }
+                }
+                Ok((
+                    format!("phase {:?}", state.phase),
+                    Some(ConsoleEffect::Changed),
+                ))
+            }
+            Command::LoadLevel(query) => {
+                let index = find_level(campaign, &query)?;
+                let name = &campaign.levels()[index].level.name;
+                Ok((
+                    format!("loading {name}"),
+                    Some(ConsoleEffect::LoadLevel(index)),
+                ))
+            }
+            Command::Restart => {
+                state.reset();
+                Ok(("restarted".to_string(), Some(ConsoleEffect::Changed)))
+            }
+            Command::Clear => {
+                self.log.clear();
+                Ok((String::new(), None))
+            }
+            Command::Help => {
+                for line in HELP {
+                    self.push(line.to_string());
+                }
+                Ok((String::new(), None))
+            }
+        }
+    }
+
+    fn push(&mut self, line: String) {
+        if self.log.len() == LOG_LEN {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+    }
+
+    /// Draw in screen space across the top, when open.
+    pub fn draw(&self) {
+        if !self.open {
+            return;
+        }
+        set_default_camera();
+        let height = (self.log.len() + 1) as f32 * LINE_HEIGHT + MARGIN;
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        );
+        let log_style = TextStyle::new(FONT_SIZE, LIGHTGRAY);
+        let mut y = LINE_HEIGHT;
+        for line in &self.log {
+            text::draw(line, MARGIN, y, &log_style);
+            y += LINE_HEIGHT;
+        }
+        // A blinking caret after the typed text.
+        let caret = if get_time().fract() < 0.5 { "_" } else { "" };
+        let prompt = format!("> {}{caret}", self.input);
+        text::draw(&prompt, MARGIN, y, &TextStyle::new(FONT_SIZE, YELLOW));
+    }
+}
+
+/// Move the player to `pos`, which must be land on either bank.
+fn teleport(state: &mut GameState, pos: GridPos) -> Result<(), String> {
+    if state.phase != GamePhase::Playing {
+        return Err("the game isn't being played".to_string());
+    }
+    if !matches!(state.player.location, PlayerLocation::OnLand(_)) {
+        return Err("the player isn't on land".to_string());
+    }
+    if !state.level.is_walkable(pos) || state.level.region_of(pos).is_none() {
+        return Err(format!(
+            "{}, {} isn't open land on a bank",
+            pos.col, pos.row
+        ));
+    }
+    state.finish_walking();
+    state.player.move_queue.clear();
+    state.player.bridge_entry = None;
+    state.player.location = PlayerLocation::OnLand(pos);
+    Ok(())
+}
+
+/// Bring an animal of `kind` to the player and pick it up. Prefers one
+/// on the player's bank; if the rules refuse the pick-up, it goes back.
+/// Returns the kind's name.
+fn give(state: &mut GameState, kind: &str) -> Result<String, String> {
+    let level = &state.level;
+    let id = level
+        .kinds
+        .ids()
+        .find(|&id| level.kind(id).name.eq_ignore_ascii_case(kind))
+        .ok_or_else(|| format!("no kind {kind:?} in this level"))?;
+    let PlayerLocation::OnLand(pos) = state.player.location else {
+        return Err("the player isn't on land".to_string());
+    };
+    let region = level
+        .region_of(pos)
+        .ok_or("the player isn't on a bank".to_string())?;
+    let on_bank =
+        |e: &&EntityInstance| e.kind == id && matches!(e.location, EntityLocation::OnBank { .. });
+    let (entity, previous) = state
+        .entities
+        .iter()
+        .filter(on_bank)
+        .min_by_key(
+            |e| !matches!(e.location, EntityLocation::OnBank { region: r, .. } if r == region),
+        )
+        .map(|e| (e.id, e.location))
+        .ok_or_else(|| format!("no {kind} is waiting on a bank"))?;
+    let name = state.kind(entity).name.clone();
+
+    state.finish_walking();
+    state.set_entity_location(entity, EntityLocation::OnBank { region, pos });
+    if let Err(e) = state.apply(Action::PickUp(entity)) {
+        state.set_entity_location(entity, previous);
+        return Err(e.to_string());
+    }
+    Ok(name)
+}
+
+/// A campaign level by 1-based number, file name with or without its
+/// extension and number prefix, or level name, ignoring case.
+fn find_level(campaign: &Campaign, query: &str) -> Result<usize, String> {
+    let levels = campaign.levels();
+    if let Ok(number) = query.parse::<usize>() {
+        if (1..=levels.len()).contains(&number) {
+            return Ok(number - 1);
+        }
+        return Err(format!("the campaign has levels 1 to {}", levels.len()));
+    }
+    let matches = |entry: &CampaignLevel| {
+        let stem = entry.key.strip_suffix(".ron").unwrap_or(&entry.key);
+        let bare = stem.split_once('_').map_or(stem, |(_, rest)| rest);
+        [entry.key.as_str(), stem, bare, entry.level.name.as_str()]
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(query))
+    };
+    levels
+        .iter()
+        .position(matches)
+        .ok_or_else(|| format!("no level {query:?}"))
+}
@@ -0,0 +1,178 @@
+use macroquad::logging::error;
+use macroquad::prelude::{Vec2, load_string};
+use serde::Deserialize;
+
+use crate::anim::{AnimState, Puppet};
+use crate::game::GameState;
+use crate::tween::{self, Ease};
+use crate::world::{self, GridPos};
+
+const INTRO_PATH: &str = "assets/cutscenes/intro.ron";
+const ENDING_PATH: &str = "assets/cutscenes/ending.ron";
+
+/// One beat of a cutscene; each starts when the one before it is done.
+#[derive(Debug, Clone, Deserialize)]
+enum Step {
+    /// Swing the camera over `seconds` to centre on a tile, zoomed in by
+    /// `zoom`.
+    Pan {
+        to: GridPos,
+        #[serde(default = "unzoomed")]
+        zoom: f32,
+        seconds: f32,
+    },
+    /// Walk `who`, "farmer" or an entity kind, to a tile; done when they
+    /// get there.
+    Walk { who: String, to: GridPos },
+    /// A line of dialogue, shown for `seconds`.
+    Say {
+        who: String,
+        text: String,
+        seconds: f32,
+    },
+    /// Let the scene breathe.
+    Wait(f32),
+}
+
+fn unzoomed() -> f32 {
+    1.0
+}
+
+/// A cutscene file: its steps, played in order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl Script {
+    async fn load(path: &str) -> Option<Self> {
+        let parsed = match load_string(path).await {
+            Ok(source) => ron::from_str(&source).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        parsed
+            .inspect_err(|e| error!("{path}: {e}; skipping that cutscene"))
+            .ok()
+    }
+}
+
+/// The cutscenes the game plays: on starting up, and after the last level.
+pub struct Scripts {
+    pub intro: Option<Script>,
+    pub ending: Option<Script>,
+}
+
+impl Scripts {
+    pub async fn load() -> Self {
+        Self {
+            intro: Script::load(INTRO_PATH).await,
+            ending: Script::load(ENDING_PATH).await,
+        }
+    }
+}
+
+/// A script playing over a level. It moves its own copy of the
+/// animation, so the game underneath is left as it was.
+pub struct Cutscene {
+    steps: Vec<Step>,
+    index: usize,
+    /// Seconds into the current step.
+    elapsed: f32,
+    anim: AnimState,
+    /// Where the camera looks and how close, and where the current pan set
+    /// out from.
+    camera: (Vec2, f32),
+    pan_from: (Vec2, f32),
+}
+
+impl Cutscene {
+    /// Start `script` with everyone where `anim` has them, and the camera
+    /// on `focus`.
+    pub fn new(script: &Script, anim: &AnimState, focus: Vec2) -> Self {
+        let camera = (focus, 1.0);
+        Self {
+            steps: script.steps.clone(),
+            index: 0,
+            elapsed: 0.0,
+            anim: anim.clone(),
+            camera,
+            pan_from: camera,
+        }
+    }
+
+    pub fn anim(&self) -> &AnimState {
+        &self.anim
+    }
+
+    /// The point the camera should centre on and its zoom.
+    pub fn camera(&self) -> (Vec2, f32) {
+        self.camera
+    }
+
+    /// The line being said, as (speaker, text).
+    pub fn line(&self) -> Option<(&str, &str)> {
+        match self.steps.get(self.index)? {
+            Step::Say { who, text, .. } => Some((who, text)),
+            _ => None,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.index >= self.steps.len()
+    }
+
+    /// Jump past the rest of the scene.
+    pub fn skip(&mut self) {
+        self.index = self.steps.len();
+    }
+
+    pub fn update(&mut self, state: &GameState, dt: f32) {
+        let Some(step) = self.steps.get(self.index) else {
+            return;
+        };
+        self.elapsed += dt;
+        let done = match step {
+            Step::Pan { to, zoom, seconds } => {
+                let t = (self.elapsed / seconds.max(f32::EPSILON)).min(1.0);
+                let eased = Ease::SmoothStep.apply(t);
+                let target = Vec2::from(world::grid_to_iso(*to));
+                let (from, from_zoom) = self.pan_from;
+                self.camera = (
+                    from.lerp(target, eased),
+                    tween::lerp(from_zoom, *zoom, eased),
+                );
+                t >= 1.0
+            }
+            Step::Walk { who, to } => match puppet(state, who) {
+                Some(puppet) => {
+                    let target = world::grid_to_iso(*to);
+                    !self.anim.walk_scripted(puppet, target, dt)
+                }
+                None => {
+                    error!("cutscene: nobody called {who:?} to walk");
+                    true
+                }
+            },
+            Step::Say { seconds, .. } => self.elapsed >= *seconds,
+            Step::Wait(seconds) => self.elapsed >= *seconds,
+        };
+        self.anim.advance_clips(dt);
+        if done {
+            self.index += 1;
+            self.elapsed = 0.0;
+            self.pan_from = self.camera;
+        }
+    }
+}
+
+/// The farmer, or the first entity of the kind named `who`.
+fn puppet(state: &GameState, who: &str) -> Option<Puppet> {
+    if who.eq_ignore_ascii_case("farmer") {
+        return Some(Puppet::Player);
+    }
+    state
+        .entities
+        .iter()
+        .find(|entity| state.level.kind(entity.kind).name.eq_ignore_ascii_case(who))
+        .map(|entity| Puppet::Entity(entity.id))
+}
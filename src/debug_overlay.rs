@@ -0,0 +1,218 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::game::{Action, EntityId, GameState, RuleError};
+use crate::input::InputEvent;
+use crate::interaction;
+
+/// Lines kept in each of the event and action logs.
+const LOG_LEN: usize = 12;
+/// Frames shown in the frame time graph.
+const GRAPH_FRAMES: usize = 120;
+/// Frame time at the top of the graph; the marked line is a 60 Hz frame.
+const GRAPH_MS: f32 = 1000.0 / 30.0;
+const BUDGET_MS: f32 = 1000.0 / 60.0;
+/// Recent frames the FPS counter averages over.
+const FPS_FRAMES: usize = 30;
+
+const PANEL_WIDTH: f32 = 420.0;
+const LINE_HEIGHT: f32 = 15.0;
+const FONT_SIZE: f32 = 15.0;
+const MARGIN: f32 = 10.0;
+
+/// The F3 overlay: recent input events, the actions `Interact` resolved
+/// to, the state's key fields and a frame time graph, plus the FPS counter
+/// the settings can turn on. Records all the time, so the history is there
+/// the moment it's opened.
+pub struct DebugOverlay {
+    enabled: bool,
+    frame: u64,
+    events: VecDeque<String>,
+    actions: VecDeque<String>,
+    frame_times: VecDeque<f32>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            frame: 0,
+            events: VecDeque::new(),
+            actions: VecDeque::new(),
+            frame_times: VecDeque::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Start a new frame that took `dt` seconds.
+    pub fn begin_frame(&mut self, dt: f32) {
+        self.frame += 1;
+        push_capped(&mut self.frame_times, dt * 1000.0, GRAPH_FRAMES);
+    }
+
+    pub fn record_event(&mut self, event: InputEvent) {
+        if event != InputEvent::None {
+            let line = format!("{:>6} {event:?}", self.frame);
+            push_capped(&mut self.events, line, LOG_LEN);
+        }
+    }
+
+    /// Note what an interaction resolved to and whether the rules took it.
+    pub fn record_action(
+        &mut self,
+        state: &GameState,
+        action: Action,
+        result: Result<(), RuleError>,
+    ) {
+        let outcome = match result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => format!("refused: {e}"),
+        };
+        let line = format!("{:>6} {} -> {outcome}", self.frame, describe(state, action));
+        push_capped(&mut self.actions, line, LOG_LEN);
+    }
+
+    /// Draw in screen space down the right-hand side.
+    pub fn draw(&self, state: &GameState) {
+        if !self.enabled {
+            return;
+        }
+        set_default_camera();
+        let x = screen_width() - PANEL_WIDTH - MARGIN;
+        let mut lines: Vec<(String, Color)> = vec![("State (F3: hide)".to_string(), YELLOW)];
+        lines.extend(state_lines(state).into_iter().map(|line| (line, WHITE)));
+        lines.push(("Input events".to_string(), YELLOW));
+        lines.extend(self.events.iter().map(|line| (line.clone(), LIGHTGRAY)));
+        lines.push(("Resolved actions".to_string(), YELLOW));
+        lines.extend(self.actions.iter().map(|line| (line.clone(), LIGHTGRAY)));
+
+        let graph_height = 60.0;
+        let height = lines.len() as f32 * LINE_HEIGHT + graph_height + 24.0;
+        draw_rectangle(
+            x,
+            MARGIN,
+            PANEL_WIDTH,
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        );
+        let mut y = MARGIN + LINE_HEIGHT;
+        for (line, color) in &lines {
+            draw_text(line, x + 6.0, y, FONT_SIZE, *color);
+            y += LINE_HEIGHT;
+        }
+        self.draw_graph(Rect::new(x + 6.0, y, PANEL_WIDTH - 12.0, graph_height));
+    }
+
+    /// The frame rate, averaged over the last few frames, in the bottom
+    /// right corner. `idle` notes that low-power mode is holding it down.
+    pub fn draw_fps(&self, idle: bool) {
+        set_default_camera();
+        let recent = self.frame_times.iter().rev().take(FPS_FRAMES);
+        let (count, total) = recent.fold((0, 0.0), |(n, sum), &ms| (n + 1, sum + ms));
+        let fps = if total > 0.0 {
+            count as f32 * 1000.0 / total
+        } else {
+            0.0
+        };
+        let label = if idle {
+            format!("{fps:.0} fps (low power)")
+        } else {
+            format!("{fps:.0} fps")
+        };
+        let size = measure_text(&label, None, FONT_SIZE as u16, 1.0);
+        let x = screen_width() - size.width - MARGIN;
+        let y = screen_height() - MARGIN;
+        draw_rectangle(
+            x - 4.0,
+            y - size.offset_y - 4.0,
+            size.width + 8.0,
+            size.height + 8.0,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        draw_text(&label, x, y, FONT_SIZE, WHITE);
+    }
+
+    fn draw_graph(&self, area: Rect) {
+        draw_rectangle_lines(area.x, area.y, area.w, area.h, 1.0, GRAY);
+        let bar = area.w / GRAPH_FRAMES as f32;
+        for (i, &ms) in self.frame_times.iter().enumerate() {
+            let h = (ms / GRAPH_MS).min(1.0) * area.h;
+            let color = if ms > BUDGET_MS * 1.1 { ORANGE } else { GREEN };
+            draw_rectangle(area.x + i as f32 * bar, area.bottom() - h, bar, h, color);
+        }
+        let budget = area.bottom() - BUDGET_MS / GRAPH_MS * area.h;
+        draw_line(area.x, budget, area.right(), budget, 1.0, WHITE);
+        let latest = self.frame_times.back().copied().unwrap_or(0.0);
+        let label = format!("{latest:.1} ms (line = {BUDGET_MS:.1} ms)");
+        draw_text(&label, area.x + 4.0, area.y + 14.0, FONT_SIZE, WHITE);
+    }
+}
+
+fn push_capped<T>(log: &mut VecDeque<T>, item: T, cap: usize) {
+    if log.len() == cap {
+        log.pop_front();
+    }
+    log.push_back(item);
+}
+
+fn entity(state: &GameState, id: EntityId) -> String {
+    format!("#{} {}", id.0, state.kind(id).name)
+}
+
+fn describe(state: &GameState, action: Action) -> String {
+    match action {
+        Action::PickUp(id) => format!("PickUp({})", entity(state, id)),
+        Action::Drop(id) => format!("Drop({})", entity(state, id)),
+        Action::LoadOntoBoat(id) => format!("LoadOntoBoat({})", entity(state, id)),
+        Action::UnloadFromBoat(id) => format!("UnloadFromBoat({})", entity(state, id)),
+        Action::BoardBoat | Action::UnboardBoat | Action::Talk => format!("{action:?}"),
+    }
+}
+
+/// The fields that matter when working out why an interaction did what it
+/// did, plus what the interact key would do right now.
+fn state_lines(state: &GameState) -> Vec<String> {
+    let optional = |id: Option<EntityId>| id.map_or("-".to_string(), |id| entity(state, id));
+    let next = interaction::resolve_interaction(state, state.player.target)
+        .map_or("nothing".to_string(), |action| describe(state, action));
+    vec![
+        format!("phase {:?}", state.phase),
+        format!("player {:?}", state.player.location),
+        format!(
+            "boats {:?}",
+            state.boats.iter().map(|b| b.state).collect::<Vec<_>>()
+        ),
+        format!(
+            "follower {}  cargo {}",
+            optional(state.player.follower),
+            state
+                .boats
+                .iter()
+                .map(|boat| match boat.cargo.as_slice() {
+                    [] => "-".to_string(),
+                    cargo => cargo
+                        .iter()
+                        .map(|&id| entity(state, id))
+                        .collect::<Vec<_>>()
+                        .join("+"),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        format!(
+            "moves {}  crossings {}  queued steps {}",
+            state.move_count,
+            state.crossing_count,
+            state.player.move_queue.len()
+        ),
+        format!(
+            "walk {:?}  bridge {:?}",
+            state.player.walk, state.player.bridge_entry
+        ),
+        format!("interact would: {next}"),
+    ]
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use macroquad::logging::error;
+use macroquad::prelude::{Rect, load_string};
+use serde::Deserialize;
+
+use crate::game::{GamePhase, GameState, LoseReason};
+use crate::job::Job;
+use crate::level::LevelDef;
+use crate::pause::{self, MenuInput};
+use crate::solver;
+
+const TREE_PATH: &str = "assets/dialogue/ferryman.ron";
+
+/// What picking a choice does besides moving the conversation on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+enum Effect {
+    /// Work out the next step, for `cost` extra moves on the counter.
+    /// Fills in `{hint}` in the text that follows.
+    Hint { cost: u32 },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Choice {
+    text: String,
+    /// The node it leads to; None ends the conversation.
+    #[serde(default)]
+    goto: Option<String>,
+    #[serde(default)]
+    effect: Option<Effect>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Node {
+    text: String,
+    /// None means a lone "Goodbye".
+    #[serde(default)]
+    choices: Vec<Choice>,
+}
+
+/// A dialogue tree from a data file: nodes of text, each with choices
+/// leading to other nodes.
+#[derive(Debug, Clone, Deserialize)]
+struct Tree {
+    /// The node a conversation usually opens on.
+    start: String,
+    /// Where it opens instead after the level was just lost, by cause:
+    /// "eaten", "nightfall" or "scripted". `{eater}` and `{eaten}` in the
+    /// text name who ate whom.
+    #[serde(default)]
+    after_loss: BTreeMap<String, String>,
+    nodes: BTreeMap<String, Node>,
+}
+
+impl Tree {
+    /// Used when the file is missing, so the ferryman still answers.
+    fn fallback() -> Self {
+        let node = Node {
+            text: "The ferryman nods, and says nothing.".to_string(),
+            choices: Vec::new(),
+        };
+        Self {
+            start: "start".to_string(),
+            after_loss: BTreeMap::new(),
+            nodes: BTreeMap::from([("start".to_string(), node)]),
+        }
+    }
+}
+
+/// How picking a choice turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogueOutcome {
+    /// The conversation is over.
+    Closed,
+    /// A hint was bought, adding to the move counter.
+    PaidForHint,
+}
+
+/// An open conversation: the node it's at and the highlighted choice.
+struct Conversation {
+    node: String,
+    selected: usize,
+    /// What a bought hint says, once it's been worked out.
+    hint: Option<Job<Option<String>>>,
+    /// Who ate whom, for loss comments.
+    loss: Option<(String, String)>,
+}
+
+/// The NPC on the left bank: his dialogue tree, the loss he can comment
+/// on and the conversation, while there is one.
+pub struct Ferryman {
+    tree: Tree,
+    /// The latest loss on this level, until he's had his say about it.
+    last_loss: Option<LoseReason>,
+    level: Option<Arc<LevelDef>>,
+    conversation: Option<Conversation>,
+}
+
+impl Ferryman {
+    pub async fn load() -> Self {
+        let parsed = match load_string(TREE_PATH).await {
+            Ok(source) => ron::from_str(&source).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let tree = parsed.unwrap_or_else(|e| {
+            error!("{TREE_PATH}: {e}; the ferryman will have little to say");
+            Tree::fallback()
+        });
+        Self {
+            tree,
+            last_loss: None,
+            level: None,
+            conversation: None,
+        }
+    }
+
+    pub fn is_talking(&self) -> bool {
+        self.conversation.is_some()
+    }
+
+    /// Note losses to bring up later, forgetting them on a new level.
+    pub fn observe(&mut self, state: &GameState) {
+        if !self
+            .level
+            .as_ref()
+            .is_some_and(|level| Arc::ptr_eq(level, &state.level))
+        {
+            self.level = Some(Arc::clone(&state.level));
+            self.last_loss = None;
+            self.conversation = None;
+        }
+        if let GamePhase::Lost(reason) = state.phase {
+            self.last_loss = Some(reason);
+        }
+    }
+
+    /// Open a conversation, about the last loss if there was one.
+    pub fn talk(&mut self, state: &GameState) {
+        let (node, loss) = match self.last_loss.take() {
+            Some(reason) => {
+                let (cause, loss) = match reason {
+                    LoseReason::Eaten { eater, eaten } => {
+                        let name = |kind| state.level.kind(kind).name.clone();
+                        ("eaten", Some((name(eater), name(eaten))))
+                    }
+                    LoseReason::Nightfall => ("nightfall", None),
+                    LoseReason::BoatSank => ("sank", None),
+                    LoseReason::Caught => ("caught", None),
+                    LoseReason::OutOfCrossings => ("out_of_crossings", None),
+                    LoseReason::Scripted(_) => ("scripted", None),
+                };
+                let node = self.tree.after_loss.get(cause).cloned();
+                (node.unwrap_or_else(|| self.tree.start.clone()), loss)
+            }
+            None => (self.tree.start.clone(), None),
+        };
+        self.conversation = Some(Conversation {
+            node,
+            selected: 0,
+            hint: None,
+            loss,
+        });
+    }
+
+    /// What he's saying and the choices to answer with, and which one is
+    /// highlighted.
+    pub fn page(&self) -> Option<(String, Vec<&str>, usize)> {
+        let conversation = self.conversation.as_ref()?;
+        let Some(node) = self.tree.nodes.get(&conversation.node) else {
+            let text = format!("(no dialogue called {:?})", conversation.node);
+            return Some((text, vec!["Goodbye"], 0));
+        };
+        let mut text = node.text.clone();
+        if let Some(hint) = &conversation.hint {
+            let hint = match hint.get() {
+                Some(Some(hint)) => hint.as_str(),
+                Some(None) => "You're nearly there",
+                None => "hmm, let me think",
+            };
+            text = text.replace("{hint}", hint);
+        }
+        if let Some((eater, eaten)) = &conversation.loss {
+            text = text.replace("{eater}", eater).replace("{eaten}", eaten);
+        }
+        let mut choices: Vec<&str> = node.choices.iter().map(|c| c.text.as_str()).collect();
+        if choices.is_empty() {
+            choices.push("Goodbye");
+        }
+        Some((text, choices, conversation.selected))
+    }
+
+    /// Move through the choices with `input`, laid out in `choices`, and
+    /// act on the one picked.
+    pub fn handle(
+        &mut self,
+        input: &MenuInput,
+        choices: Rect,
+        state: &mut GameState,
+    ) -> Option<DialogueOutcome> {
+        let conversation = self.conversation.as_mut()?;
+        if input.back {
+            self.conversation = None;
+            return Some(DialogueOutcome::Closed);
+        }
+        let options = self
+            .tree
+            .nodes
+            .get(&conversation.node)
+            .map_or(&[][..], |node| node.choices.as_slice());
+        let count = options.len().max(1);
+        if !pause::navigate(&mut conversation.selected, count, input, choices) {
+            return None;
+        }
+        let Some(choice) = options.get(conversation.selected) else {
+            self.conversation = None;
+            return Some(DialogueOutcome::Closed);
+        };
+        let mut outcome = None;
+        if let Some(Effect::Hint { cost }) = choice.effect {
+            let asked = state.clone();
+            conversation.hint = Some(Job::spawn(move || solver::hint(&asked)));
+            state.move_count += cost;
+            outcome = Some(DialogueOutcome::PaidForHint);
+        }
+        match &choice.goto {
+            Some(node) => {
+                conversation.node = node.clone();
+                conversation.selected = 0;
+                outcome
+            }
+            None => {
+                self.conversation = None;
+                Some(DialogueOutcome::Closed)
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::anim::AnimState;
+use crate::game::{EntityId, EntityLocation, GamePhase, GameState, PlayerLocation};
+
+/// How close, in world pixels, an eater has to come before its prey reacts.
+const NOTICE_DISTANCE: f32 = 90.0;
+/// Seconds the farmer stands still before nodding off.
+const DOZE_AFTER: f32 = 8.0;
+
+/// A little icon in a bubble over someone's head.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emote {
+    /// An animal with something that eats it close by.
+    Panic,
+    /// Something that can't run, with an eater coming near.
+    Alarm,
+    /// The farmer, after standing about for a while.
+    Doze,
+}
+
+impl Emote {
+    /// What the bubble says.
+    pub fn icon(self) -> &'static str {
+        match self {
+            Emote::Panic => "!!",
+            Emote::Alarm => "!?",
+            Emote::Doze => "zzz",
+        }
+    }
+}
+
+/// How entity `id` feels, judged from where everyone stands on screen.
+pub fn entity_emote(state: &GameState, anim: &AnimState, id: EntityId) -> Option<Emote> {
+    if state.phase != GamePhase::Playing
+        || !matches!(state.entity_location(id), EntityLocation::OnBank { .. })
+    {
+        return None;
+    }
+    let kind = state.entities[id.0].kind;
+    let (x, y) = anim.entity_anim(id).pos;
+    let threatened = state.entities.iter().any(|eater| {
+        let (ex, ey) = anim.entity_anim(eater.id).pos;
+        eater.id != id
+            && matches!(eater.location, EntityLocation::OnBank { .. })
+            && state
+                .level
+                .eating_rules
+                .iter()
+                .any(|rule| rule.eater == eater.kind && rule.eaten == kind)
+            && (ex - x).hypot(ey - y) < NOTICE_DISTANCE
+    });
+    if !threatened {
+        None
+    } else if state.kind(id).alive {
+        Some(Emote::Panic)
+    } else {
+        Some(Emote::Alarm)
+    }
+}
+
+/// The farmer's, while they've been left standing on land.
+pub fn player_emote(state: &GameState, anim: &AnimState) -> Option<Emote> {
+    let idle = state.phase == GamePhase::Playing
+        && matches!(state.player.location, PlayerLocation::OnLand(_))
+        && anim.player_idle >= DOZE_AFTER;
+    idle.then_some(Emote::Doze)
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use macroquad::logging::error;
+use serde::{Deserialize, Serialize};
+
+use crate::generator;
+use crate::job::Job;
+use crate::level::LevelDef;
+use crate::stats::Score;
+use crate::storage;
+
+const BEST_PATH: &str = "endless.ron";
+/// What the multiplier grows by with each round cleared, and again for
+/// clearing it in the fewest crossings.
+const MULTIPLIER_STEP: f32 = 0.25;
+
+/// The best endless run so far.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BestRun {
+    pub score: u32,
+    /// Rounds it cleared.
+    pub rounds: u32,
+}
+
+impl BestRun {
+    fn load() -> Self {
+        let parsed = storage::read(BEST_PATH).and_then(|source| match source {
+            Some(source) => ron::from_str(&source).map_err(|e| format!("{BEST_PATH}: {e}")),
+            None => Ok(Self::default()),
+        });
+        parsed.unwrap_or_else(|e| {
+            error!("{e}; starting with no best endless run");
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        storage::write(BEST_PATH, &source)
+    }
+}
+
+/// Endless mode: generated puzzles one after another, each a little
+/// harder, until the first loss ends the run. Every round cleared adds its
+/// score times the multiplier, which grows each round and faster for
+/// rounds cleared in the fewest crossings.
+pub struct EndlessRun {
+    seed: u64,
+    /// Rounds cleared; the one being played is the next.
+    round: u32,
+    score: u32,
+    multiplier: f32,
+    /// Set once the run is lost.
+    over: bool,
+    /// Whether any round was played in assist mode; such a run sets no
+    /// best.
+    assisted: bool,
+    new_best: bool,
+    best: BestRun,
+}
+
+impl EndlessRun {
+    /// A fresh run, seeded by the clock.
+    pub fn new() -> Self {
+        Self {
+            seed: (miniquad::date::now() * 1000.0) as u64,
+            round: 0,
+            score: 0,
+            multiplier: 1.0,
+            over: false,
+            assisted: false,
+            new_best: false,
+            best: BestRun::load(),
+        }
+    }
+
+    /// Start drawing the round to play now.
+    pub fn level(&self) -> Job<Arc<LevelDef>> {
+        let (seed, round) = (self.seed, self.round);
+        Job::spawn(move || Arc::new(generator::endless(seed, round)))
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round + 1
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+    }
+
+    pub fn best(&self) -> BestRun {
+        self.best
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    pub fn is_new_best(&self) -> bool {
+        self.new_best
+    }
+
+    /// Add the score of the round just won, with or without assist, and
+    /// move on to the next.
+    pub fn clear_round(&mut self, round: Score, assisted: bool) {
+        self.assisted |= assisted;
+        self.score += (round.total() as f32 * self.multiplier).round() as u32;
+        self.multiplier += MULTIPLIER_STEP;
+        if round.crossings_bonus > 0 {
+            self.multiplier += MULTIPLIER_STEP;
+        }
+        self.round += 1;
+    }
+
+    /// End the run on a loss, keeping it if it beat the best without
+    /// assist.
+    pub fn finish(&mut self, assisted: bool) -> Result<(), String> {
+        if self.over {
+            return Ok(());
+        }
+        self.over = true;
+        self.assisted |= assisted;
+        if self.assisted || self.score <= self.best.score {
+            return Ok(());
+        }
+        self.new_best = true;
+        self.best = BestRun {
+            score: self.score,
+            rounds: self.round,
+        };
+        self.best.save()
+    }
+}
@@ -0,0 +1,41 @@
+use crate::game::{BoatId, EntityId, LoseReason, PlayerId};
+use crate::world::{GridPos, RegionId};
+
+/// Something that just happened in the game, queued by `GameState` for
+/// audio, effects, animation and stats to react to, so none of them has
+/// to work it out by comparing frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    /// A fresh run began: a new level or a restart.
+    Started,
+    /// A farmer finished a step.
+    Moved {
+        who: PlayerId,
+        from: GridPos,
+        to: GridPos,
+    },
+    /// A farmer picked up or called an entity.
+    PickedUp {
+        who: PlayerId,
+        entity: EntityId,
+    },
+    /// A farmer set down the entity they led on the bank, rather than
+    /// loading it into a boat.
+    Dropped {
+        who: PlayerId,
+        entity: EntityId,
+    },
+    CrossingStarted(BoatId),
+    /// Assist mode kept the boat in: leaving would have lost the run.
+    CrossingRefused(LoseReason),
+    /// A crossing counted: a boat trip ended, or a farmer walked over a
+    /// bridge.
+    Crossed,
+    /// A boat moored, at the end of a crossing or after drifting back.
+    Docked {
+        boat: BoatId,
+        region: RegionId,
+    },
+    Won,
+    Lost(LoseReason),
+}
@@ -0,0 +1,68 @@
+use crate::input::{HeldInput, InputEvent};
+
+/// Fixed simulation step used while frame-advancing.
+pub const TICK: f32 = crate::timestep::STEP;
+
+/// The input applied to a single frame-advance tick.
+pub struct TickInput {
+    pub event: InputEvent,
+    /// The player's and player two's.
+    pub held: (HeldInput, HeldInput),
+}
+
+/// Debug mode that freezes the simulation and steps it one fixed tick per
+/// key press. Gameplay input pressed while paused is queued for the next
+/// tick, so exact per-tick sequences can be entered by hand.
+pub struct FrameAdvance {
+    active: bool,
+    pending: InputEvent,
+    ticks: u64,
+}
+
+impl FrameAdvance {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            pending: InputEvent::None,
+            ticks: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+        self.pending = InputEvent::None;
+        self.ticks = 0;
+    }
+
+    /// Remember a gameplay event for the next tick. The latest one wins.
+    pub fn queue(&mut self, event: InputEvent) {
+        if event.is_gameplay() {
+            self.pending = event;
+        }
+    }
+
+    /// Consume the queued input for one tick. `held` is sampled from the
+    /// keyboard at the moment the tick is taken.
+    pub fn take_tick(&mut self, held: (HeldInput, HeldInput)) -> TickInput {
+        self.ticks += 1;
+        TickInput {
+            event: std::mem::replace(&mut self.pending, InputEvent::None),
+            held,
+        }
+    }
+
+    pub fn status_line(&self) -> String {
+        let next = match self.pending {
+            InputEvent::None => "-".to_string(),
+            event => format!("{event:?}"),
+        };
+        format!(
+            "FRAME ADVANCE  tick {}  next: {next}   F8: step   F7: resume",
+            self.ticks
+        )
+    }
+}
@@ -1,56 +1,92 @@
-use crate::world::{self, Bank, Direction, GridPos};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
 
-/// The three transportable entities.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Entity {
-    Wolf,
-    Sheep,
-    Cabbage,
-}
+use crate::events::GameEvent;
+use crate::inventory::{self, Inventory, Item};
+use crate::kinds::{EntityKind, KindId};
+use crate::level::LevelDef;
+use crate::mutators::Mutators;
+use crate::patrol::Patrol;
+use crate::rng::Rng;
+use crate::solver::Crossing;
+use crate::upgrades::Upgrades;
+use crate::world::{self, Direction, GridPos, RegionId};
 
-#[allow(dead_code)]
-impl Entity {
-    pub const ALL: [Entity; 3] = [Entity::Wolf, Entity::Sheep, Entity::Cabbage];
+/// Identifies one entity in a level: the index of its entry in
+/// `LevelDef::entities`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityId(pub usize);
 
-    pub fn name(self) -> &'static str {
-        match self {
-            Entity::Wolf => "wolf",
-            Entity::Sheep => "sheep",
-            Entity::Cabbage => "cabbage",
-        }
-    }
+/// Identifies one boat: the index of its entry in `LevelDef::boats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoatId(pub usize);
 
-    pub fn is_alive(self) -> bool {
-        matches!(self, Entity::Wolf | Entity::Sheep)
-    }
+/// One entity in play. A level may hold several of the same kind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityInstance {
+    pub id: EntityId,
+    pub kind: KindId,
+    pub location: EntityLocation,
+    /// Seconds until an idle animal next strolls to a neighbouring tile.
+    pub wander_timer: f32,
+    /// From 0, fed, to 1, famished. Only eaters on levels with `hunger`
+    /// get hungry.
+    pub hunger: f32,
+    /// Tied up with a rope until `crossing_count` reaches this.
+    pub tied_until: u32,
+    /// Seconds left busy with a fish.
+    pub distracted: f32,
 }
 
 /// Where an entity currently is.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EntityLocation {
-    OnBank { bank: Bank, pos: GridPos },
+    OnBank { region: RegionId, pos: GridPos },
     FollowingPlayer,
-    OnBoat,
+    OnBoat(BoatId),
 }
 
 /// Where the player currently is.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlayerLocation {
     OnLand(GridPos),
-    OnBoat,
+    OnBoat(BoatId),
 }
 
-/// The boat's state.
+/// A boat's state.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BoatState {
-    Docked(Bank),
-    Crossing { from: Bank, progress: f32 },
+    Docked(RegionId),
+    Crossing {
+        from: RegionId,
+        progress: f32,
+        /// Rows the current has pushed the boat down the river, less any
+        /// steering against it.
+        drift: f32,
+        /// Set while drifting back from a missed dock on `from`'s side to
+        /// the side the trip started on.
+        returning: bool,
+    },
+}
+
+/// A boat and what it carries, in loading order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boat {
+    pub state: BoatState,
+    pub cargo: Vec<EntityId>,
 }
 
 /// High-level game phase.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GamePhase {
+    /// Choosing a level; the current level sits frozen behind the list.
+    /// Shown at launch and from the pause menu.
+    Menu,
     Playing,
+    /// Frozen behind the pause menu; entered from `Playing`, or from `Menu`
+    /// when backing out of the level select.
+    Paused,
     Won,
     Lost(LoseReason),
 }
@@ -58,15 +94,48 @@ pub enum GamePhase {
 /// Why the player lost.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LoseReason {
-    WolfAteSheep,
-    SheepAteCabbage,
+    Eaten {
+        eater: KindId,
+        eaten: KindId,
+    },
+    /// Timed mode only: the day ran out.
+    Nightfall,
+    /// A boat loaded past what it can float went down mid-river.
+    BoatSank,
+    /// The level's patrolling wolf caught a farmer.
+    Caught,
+    /// The level's loss script fired; indexes its messages.
+    Scripted(u16),
+    /// Limited crossings: set out on one more than allowed.
+    OutOfCrossings,
 }
 
 impl LoseReason {
-    pub fn message(self) -> &'static str {
+    pub fn message(self, level: &LevelDef) -> String {
         match self {
-            LoseReason::WolfAteSheep => "The wolf ate the sheep!",
-            LoseReason::SheepAteCabbage => "The sheep ate the cabbage!",
+            LoseReason::Eaten { eater, eaten } => {
+                let (eater, eaten) = (&level.kind(eater).name, &level.kind(eaten).name);
+                format!("The {eater} ate the {eaten}!")
+            }
+            LoseReason::Nightfall => "Night fell before everyone got across!".to_string(),
+            LoseReason::BoatSank => "The boat sank under its load!".to_string(),
+            LoseReason::Caught => "The wild wolf caught you in the dark!".to_string(),
+            LoseReason::OutOfCrossings => "You ran out of crossings!".to_string(),
+            LoseReason::Scripted(index) => match &level.loss_script {
+                Some(script) => script.message(index),
+                None => String::new(),
+            },
+        }
+    }
+
+    /// Assist mode's warning, for a crossing that would lose this way.
+    pub fn warning(self, level: &LevelDef) -> String {
+        match self {
+            LoseReason::Eaten { eater, eaten } => {
+                let (eater, eaten) = (&level.kind(eater).name, &level.kind(eaten).name);
+                format!("Assist: the {eater} would eat the {eaten}! The boat stays put")
+            }
+            _ => "Assist: leaving now would lose the run! The boat stays put".to_string(),
         }
     }
 }
@@ -74,243 +143,1545 @@ impl LoseReason {
 /// All possible interaction actions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
-    PickUp(Entity),
-    Drop(Entity),
-    LoadOntoBoat(Entity),
-    UnloadFromBoat(Entity),
+    PickUp(EntityId),
+    Drop(EntityId),
+    LoadOntoBoat(EntityId),
+    UnloadFromBoat(EntityId),
     BoardBoat,
     UnboardBoat,
+    /// Strike up a conversation with the ferryman.
+    Talk,
+}
+
+/// Why `GameState::apply` refused an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleError {
+    /// The game is over or paused.
+    NotPlaying,
+    UnknownEntity(EntityId),
+    /// The action needs the player on land.
+    NotOnLand,
+    /// The action needs the player on the boat.
+    NotOnBoat,
+    /// The boat is out on the water.
+    BoatNotDocked,
+    /// The player isn't at or beside the dock the boat is moored at.
+    NotAtDock,
+    /// Already leading something; only one at a time.
+    AlreadyCarrying,
+    /// The entity isn't the one following the player.
+    NotCarrying(EntityId),
+    /// The entity is on the other bank, or too far away to call.
+    OutOfReach(EntityId),
+    /// The boat has no room for another passenger.
+    BoatFull,
+    /// The entity isn't the boat's cargo.
+    NotAboard(EntityId),
+    /// Player two in co-op stays on their bank.
+    KeepsToBank,
+    /// There's nobody close enough to talk to.
+    NobodyToTalkTo,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::NotPlaying => write!(f, "the game isn't being played"),
+            RuleError::UnknownEntity(id) => write!(f, "no entity {}", id.0),
+            RuleError::NotOnLand => write!(f, "the player isn't on land"),
+            RuleError::NotOnBoat => write!(f, "the player isn't on the boat"),
+            RuleError::BoatNotDocked => write!(f, "the boat is crossing"),
+            RuleError::NotAtDock => write!(f, "the player isn't at the boat's dock"),
+            RuleError::AlreadyCarrying => write!(f, "the player is already leading something"),
+            RuleError::NotCarrying(id) => write!(f, "entity {} isn't following the player", id.0),
+            RuleError::OutOfReach(id) => write!(f, "entity {} is out of reach", id.0),
+            RuleError::BoatFull => write!(f, "the boat is full"),
+            RuleError::NotAboard(id) => write!(f, "entity {} isn't on the boat", id.0),
+            RuleError::KeepsToBank => write!(f, "player two keeps to their bank"),
+            RuleError::NobodyToTalkTo => write!(f, "nobody is close enough to talk to"),
+        }
+    }
 }
 
 const CROSSING_DURATION: f32 = 2.0;
+/// Rows per second the player can steer the boat across the current.
+const STEER_SPEED: f32 = 1.5;
+/// How far from the dock's row, in rows, the boat may drift and still dock.
+pub const DOCK_TOLERANCE: f32 = 0.5;
+/// How far across an overloaded boat gets before it sinks.
+const SINKING_PROGRESS: f32 = 0.5;
+/// How far, in tiles, from a boat's dock a farmer can board or load it.
+const BOAT_REACH: i32 = 1;
+
+/// Range of seconds an idle animal waits between strolls.
+const WANDER_DELAY: (f32, f32) = (3.0, 8.0);
+/// Faster steps while an eater and its prey share a bank.
+const TENSE_DELAY: (f32, f32) = (1.2, 2.5);
+/// How close, in tiles, a stalking eater creeps to its prey.
+const STALK_DISTANCE: i32 = 2;
+
+/// How many times faster hunger wears off with a farmer around than it
+/// grows without one.
+const HUNGER_RECOVERY: f32 = 4.0;
+
+/// Seconds from dawn to nightfall in timed mode.
+pub const DAY_LENGTH: f32 = 150.0;
+
+/// Time multiplier applied to a crossing while the player holds Space.
+pub const HURRY_TIME_SCALE: f32 = 10.0;
+
+/// Seconds the player takes to walk one tile. Matches the held-key repeat
+/// rate, so holding a direction walks without pausing on each tile.
+pub const STEP_DURATION: f32 = 0.12;
+/// Steps that may wait behind the one being walked.
+const MAX_QUEUED_STEPS: usize = 2;
+/// World pixels a second a farmer glides in analog movement.
+const GLIDE_SPEED: f32 = 240.0;
+/// How far, in world pixels, a gliding farmer reaches to pick something up.
+pub const REACH_RADIUS: f32 = 40.0;
+
+/// A step between two neighbouring tiles, partway done.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Walk {
+    pub from: GridPos,
+    pub to: GridPos,
+    /// 0 when leaving `from`, 1 on arrival at `to`.
+    pub progress: f32,
+}
+
+/// Whether `farmer` may walk onto `pos`: open ground, not the ferryman,
+/// and within their home bank if they keep to one.
+fn can_step_onto(level: &LevelDef, farmer: &Farmer, pos: GridPos) -> bool {
+    // Nobody walks through the ferryman.
+    level.is_walkable(pos)
+        && level.ferryman() != Some(pos)
+        && farmer
+            .home
+            .is_none_or(|home| level.region_of(pos) == Some(home))
+}
+
+/// The steps a gliding farmer takes from `from` to reach `to`: none on
+/// the same tile, one to a neighbour, and two round a corner, through
+/// whichever tile beside both is open. None if they can't get there.
+fn glide_steps(
+    level: &LevelDef,
+    farmer: &Farmer,
+    from: GridPos,
+    to: GridPos,
+) -> Option<Vec<GridPos>> {
+    let (dc, dr) = (to.col - from.col, to.row - from.row);
+    match (dc.abs(), dr.abs()) {
+        (0, 0) => Some(Vec::new()),
+        (0, 1) | (1, 0) => can_step_onto(level, farmer, to).then(|| vec![to]),
+        (1, 1) if can_step_onto(level, farmer, to) => [
+            GridPos::new(from.col + dc, from.row),
+            GridPos::new(from.col, from.row + dr),
+        ]
+        .into_iter()
+        .find(|&corner| can_step_onto(level, farmer, corner))
+        .map(|corner| vec![corner, to]),
+        _ => None,
+    }
+}
+
+/// Which farmer an input or action belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerId {
+    One,
+    /// The co-op partner.
+    Two,
+}
+
+/// One farmer: where they are, who they lead, and the steps they're
+/// walking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Farmer {
+    pub location: PlayerLocation,
+    pub follower: Option<EntityId>,
+    /// The step being walked. `location` stays on the tile it started from
+    /// until the step completes.
+    pub walk: Option<Walk>,
+    /// Steps to take after `walk`, checked when each one starts.
+    pub move_queue: VecDeque<Direction>,
+    /// The region the farmer walked onto a bridge from, while on one.
+    pub bridge_entry: Option<RegionId>,
+    /// A region the farmer never leaves, and whose boats they can't board.
+    pub home: Option<RegionId>,
+    /// The entity picked out to interact with, ahead of the usual
+    /// priority, while it's still in reach.
+    pub target: Option<EntityId>,
+    /// Where on their tile they stand in analog movement, in world
+    /// coordinates. None stands in the middle.
+    pub free: Option<(f32, f32)>,
+}
+
+impl Farmer {
+    fn new(pos: GridPos, home: Option<RegionId>) -> Self {
+        Self {
+            location: PlayerLocation::OnLand(pos),
+            follower: None,
+            walk: None,
+            move_queue: VecDeque::new(),
+            bridge_entry: None,
+            home,
+            target: None,
+            free: None,
+        }
+    }
+
+    /// Where the farmer is on land in fractional (col, row), partway
+    /// through any step; None aboard a boat.
+    pub fn grid_position(&self) -> Option<(f32, f32)> {
+        let PlayerLocation::OnLand(pos) = self.location else {
+            return None;
+        };
+        let Some(walk) = self.walk else {
+            return Some((pos.col as f32, pos.row as f32));
+        };
+        let t = walk.progress;
+        let lerp = |a: i32, b: i32| a as f32 + (b - a) as f32 * t;
+        Some((
+            lerp(walk.from.col, walk.to.col),
+            lerp(walk.from.row, walk.to.row),
+        ))
+    }
+
+    /// The region the farmer stands on; None on a bridge or a boat.
+    pub fn region(&self, level: &LevelDef) -> Option<RegionId> {
+        match self.location {
+            PlayerLocation::OnLand(pos) => level.region_of(pos),
+            PlayerLocation::OnBoat(_) => None,
+        }
+    }
+}
 
 /// The full game state.
+#[derive(Debug, Clone)]
 pub struct GameState {
+    pub level: Arc<LevelDef>,
     pub phase: GamePhase,
-    pub player: PlayerLocation,
-    pub entities: [(Entity, EntityLocation); 3],
-    pub follower: Option<Entity>,
-    pub boat: BoatState,
-    pub boat_cargo: Option<Entity>,
+    pub player: Farmer,
+    /// Player two in co-op, keeping to the right bank to receive animals.
+    pub partner: Option<Farmer>,
+    /// Indexed by `EntityId`.
+    pub entities: Vec<EntityInstance>,
+    /// Indexed by `BoatId`. Only the player rows, so at most one is out
+    /// on the water.
+    pub boats: Vec<Boat>,
+    /// The wild wolf on night stealth levels.
+    pub patrol: Option<Patrol>,
+    /// Items still lying where the level put them.
+    pub pickups: Vec<(Item, GridPos)>,
+    /// What the farmers have picked up between them.
+    pub inventory: Inventory,
+    /// Shared by the boats, for the one crossing.
     pub crossing_timer: f32,
+    pub crossing_time_scale: f32,
     pub crossing_count: u32,
+    /// Every crossing counted so far, in order, with who rode along.
+    pub trips: Vec<Crossing>,
+    /// Tiles walked by the farmers.
+    pub move_count: u32,
+    /// Timed mode: the puzzle must be solved before night falls. Survives
+    /// `reset`.
+    pub timed: bool,
+    /// Co-op: a second farmer waits on the right bank. Survives `reset`.
+    pub coop: bool,
+    /// Analog movement: the player glides about the banks instead of
+    /// stepping tile by tile, and reaches by distance. Survives `reset`.
+    pub analog: bool,
+    /// Assist mode: a boat won't leave a bank that would lose the run.
+    /// Such runs set no records. Survives `reset`.
+    pub assist: bool,
+    /// Challenge modifiers. Survive `reset`.
+    pub mutators: Mutators,
+    /// The fewest crossings this run's puzzle takes, once whoever is
+    /// solving it in the background says; limited crossings counts from
+    /// it. Survives `reset`, but not a change of level.
+    pub min_crossings: Option<u32>,
+    /// Boat upgrades unlocked in the campaign. Survive `reset`.
+    pub upgrades: Upgrades,
+    /// Seconds since dawn; only advances in timed mode.
+    pub day_elapsed: f32,
+    /// Drives cosmetic behavior such as wandering. Survives `reset`, so
+    /// each replay of a level plays out a little differently.
+    pub rng: Rng,
+    /// What happened since the presentation last took them.
+    events: Vec<GameEvent>,
 }
 
 impl GameState {
-    pub fn new() -> Self {
+    pub fn new(level: Arc<LevelDef>) -> Self {
+        let entities = level
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(i, &(kind, pos))| EntityInstance {
+                id: EntityId(i),
+                kind,
+                location: EntityLocation::OnBank {
+                    region: level.region_of(pos).unwrap(),
+                    pos,
+                },
+                wander_timer: WANDER_DELAY.1,
+                hunger: 0.0,
+                tied_until: 0,
+                distracted: 0.0,
+            })
+            .collect();
+        // Every boat starts out on its river's left side.
+        let boats = level
+            .boats
+            .iter()
+            .map(|def| Boat {
+                state: BoatState::Docked(RegionId(def.river)),
+                cargo: Vec::new(),
+            })
+            .collect();
+
         Self {
             phase: GamePhase::Playing,
-            player: PlayerLocation::OnLand(world::PLAYER_START),
-            entities: [
-                (
-                    Entity::Wolf,
-                    EntityLocation::OnBank {
-                        bank: Bank::Left,
-                        pos: world::WOLF_START,
-                    },
-                ),
-                (
-                    Entity::Sheep,
-                    EntityLocation::OnBank {
-                        bank: Bank::Left,
-                        pos: world::SHEEP_START,
-                    },
-                ),
-                (
-                    Entity::Cabbage,
-                    EntityLocation::OnBank {
-                        bank: Bank::Left,
-                        pos: world::CABBAGE_START,
-                    },
-                ),
-            ],
-            follower: None,
-            boat: BoatState::Docked(Bank::Left),
-            boat_cargo: None,
+            player: Farmer::new(level.player_start, None),
+            partner: None,
+            entities,
+            boats,
+            patrol: level.patrol.as_ref().map(Patrol::new),
+            pickups: level.items.clone(),
+            inventory: Inventory::default(),
             crossing_timer: 0.0,
+            crossing_time_scale: 1.0,
             crossing_count: 0,
+            trips: Vec::new(),
+            move_count: 0,
+            timed: false,
+            coop: false,
+            analog: false,
+            assist: false,
+            mutators: Mutators::default(),
+            min_crossings: None,
+            upgrades: Upgrades::default(),
+            day_elapsed: 0.0,
+            rng: Rng::new(0x5a2d_u64),
+            events: vec![GameEvent::Started],
+            level,
         }
     }
 
     pub fn reset(&mut self) {
-        *self = Self::new();
+        let (timed, coop, analog, assist) = (self.timed, self.coop, self.analog, self.assist);
+        let (mutators, upgrades) = (self.mutators, self.upgrades);
+        let min_crossings = self.min_crossings;
+        let rng = self.rng.clone();
+        *self = Self::new(Arc::clone(&self.level));
+        self.timed = timed;
+        self.coop = coop;
+        self.analog = analog;
+        self.assist = assist;
+        self.mutators = mutators;
+        self.upgrades = upgrades;
+        self.min_crossings = min_crossings;
+        self.rng = rng;
+        if coop {
+            let start = self.partner_start();
+            let home = self.level.goal_region();
+            self.partner = Some(Farmer::new(start, Some(home)));
+        }
+        mutators.begin(self);
+    }
+
+    /// Beside the right bank's first dock, so player two doesn't block it.
+    fn partner_start(&self) -> GridPos {
+        let goal = self.level.goal_region();
+        let boat = self.level.boats_on(goal.0 - 1).next().unwrap_or(0);
+        let dock = self.level.dock(boat, goal);
+        Direction::ALL
+            .into_iter()
+            .map(|dir| dock.step(dir))
+            .find(|&pos| self.is_free_bank_tile(pos, goal))
+            .unwrap_or(dock)
+    }
+
+    /// This state as drawn `alpha` of the way on from `prev`, the state a
+    /// tick earlier: boats and the patroller glide between ticks rather
+    /// than jumping. Only for drawing.
+    pub fn blended(&self, prev: &GameState, alpha: f32) -> GameState {
+        let mut shown = self.clone();
+        if !Arc::ptr_eq(&self.level, &prev.level) {
+            return shown;
+        }
+        for (boat, was) in shown.boats.iter_mut().zip(&prev.boats) {
+            if let (
+                BoatState::Crossing {
+                    from,
+                    progress,
+                    drift,
+                    returning,
+                },
+                BoatState::Crossing {
+                    from: was_from,
+                    progress: was_progress,
+                    drift: was_drift,
+                    returning: was_returning,
+                },
+            ) = (&mut boat.state, was.state)
+                && (*from, *returning) == (was_from, was_returning)
+            {
+                *progress = was_progress + (*progress - was_progress) * alpha;
+                *drift = was_drift + (*drift - was_drift) * alpha;
+            }
+        }
+        if let (Some(patrol), Some(was)) = (&mut shown.patrol, &prev.patrol) {
+            patrol.blend_from(was, alpha);
+        }
+        shown
+    }
+
+    /// Hand over everything that happened since the last call.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// What happened since the events were last taken.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    pub fn win(&mut self) {
+        self.phase = GamePhase::Won;
+        self.emit(GameEvent::Won);
+    }
+
+    pub fn lose(&mut self, reason: LoseReason) {
+        self.phase = GamePhase::Lost(reason);
+        self.emit(GameEvent::Lost(reason));
+    }
+
+    pub fn farmer(&self, who: PlayerId) -> Option<&Farmer> {
+        match who {
+            PlayerId::One => Some(&self.player),
+            PlayerId::Two => self.partner.as_ref(),
+        }
+    }
+
+    fn farmer_mut(&mut self, who: PlayerId) -> Option<&mut Farmer> {
+        match who {
+            PlayerId::One => Some(&mut self.player),
+            PlayerId::Two => self.partner.as_mut(),
+        }
+    }
+
+    /// Pick out what `who`'s interact key acts on, or go back to the usual
+    /// priority with None.
+    pub fn set_target(&mut self, who: PlayerId, target: Option<EntityId>) {
+        if let Some(farmer) = self.farmer_mut(who) {
+            farmer.target = target;
+        }
+    }
+
+    /// Whether either farmer is leading `id`.
+    pub fn is_led(&self, id: EntityId) -> bool {
+        self.player.follower == Some(id)
+            || self
+                .partner
+                .as_ref()
+                .is_some_and(|p| p.follower == Some(id))
+    }
+
+    /// Regions a farmer stands on, so nothing there gets eaten.
+    pub fn is_guarded(&self, region: RegionId) -> bool {
+        std::iter::once(&self.player)
+            .chain(&self.partner)
+            .any(|farmer| farmer.region(&self.level) == Some(region))
+    }
+
+    /// Fog of war: whether `region` is out of the farmers' sight. They see
+    /// the bank they stand on and the one the player's boat is moored at.
+    /// Everything shows once the run is over.
+    pub fn hidden_by_fog(&self, region: RegionId) -> bool {
+        if !self.mutators.fogs() || self.phase != GamePhase::Playing || self.is_guarded(region) {
+            return false;
+        }
+        let moored = self.player_boat().map(|boat| self.boat(boat).state);
+        moored != Some(BoatState::Docked(region))
+    }
+
+    pub fn boat(&self, id: BoatId) -> &Boat {
+        &self.boats[id.0]
+    }
+
+    /// The boat the player is aboard, if any.
+    pub fn player_boat(&self) -> Option<BoatId> {
+        match self.player.location {
+            PlayerLocation::OnBoat(boat) => Some(boat),
+            PlayerLocation::OnLand(_) => None,
+        }
+    }
+
+    /// The boat out on the water, if one is.
+    pub fn crossing_boat(&self) -> Option<BoatId> {
+        self.boats
+            .iter()
+            .position(|boat| matches!(boat.state, BoatState::Crossing { .. }))
+            .map(BoatId)
+    }
+
+    /// The boat moored at the dock at `pos`, if there is one.
+    pub fn boat_at_dock(&self, pos: GridPos) -> Option<BoatId> {
+        let (boat, region) = self.level.dock_at(pos)?;
+        (self.boats[boat].state == BoatState::Docked(region)).then_some(BoatId(boat))
+    }
+
+    /// The moored boat a farmer at `pos` can reach: docked on the same
+    /// region, at most `BOAT_REACH` tiles away. The nearest wins, then the
+    /// first listed.
+    pub fn nearest_docked_boat(&self, pos: GridPos) -> Option<BoatId> {
+        let region = self.level.region_of(pos)?;
+        (0..self.boats.len())
+            .map(BoatId)
+            .filter(|&boat| self.docked_region(boat) == Some(region))
+            .filter_map(|boat| Some((manhattan(self.boat_dock(boat)?, pos), boat)))
+            .filter(|&(distance, _)| distance <= BOAT_REACH)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, boat)| boat)
+    }
+
+    /// The river `boat` plies.
+    pub fn river_of(&self, boat: BoatId) -> usize {
+        self.level.boats[boat.0].river
+    }
+
+    /// Who rides in `boat`, a bit for each `EntityId`: its cargo and any
+    /// farmer's follower sitting in it.
+    fn passengers(&self, boat: BoatId) -> u16 {
+        let farmers = [Some(&self.player), self.partner.as_ref()];
+        let followers = farmers
+            .into_iter()
+            .flatten()
+            .filter(|farmer| farmer.location == PlayerLocation::OnBoat(boat))
+            .filter_map(|farmer| farmer.follower);
+        self.boat(boat)
+            .cargo
+            .iter()
+            .copied()
+            .chain(followers)
+            .fold(0, |bits, id| bits | 1 << id.0)
+    }
+
+    /// The weight aboard `boat`: its cargo, and whoever follows the player
+    /// onto it.
+    pub fn boat_load(&self, boat: BoatId) -> f32 {
+        let follower = self
+            .player
+            .follower
+            .filter(|_| self.player_boat() == Some(boat));
+        self.boat(boat)
+            .cargo
+            .iter()
+            .chain(&follower)
+            .map(|&id| self.kind(id).weight)
+            .sum()
+    }
+
+    /// Whether `boat` has room for another passenger.
+    pub fn has_room(&self, boat: BoatId) -> bool {
+        let capacity = self.level.boats[boat.0].capacity + self.upgrades.extra_capacity();
+        self.boat(boat).cargo.len() < capacity
+    }
+
+    /// Whether `boat` is moored, and where.
+    pub fn docked_region(&self, boat: BoatId) -> Option<RegionId> {
+        match self.boat(boat).state {
+            BoatState::Docked(region) => Some(region),
+            BoatState::Crossing { .. } => None,
+        }
+    }
+
+    /// The dock `boat` is moored at, if it is.
+    pub fn boat_dock(&self, boat: BoatId) -> Option<GridPos> {
+        let region = self.docked_region(boat)?;
+        Some(self.level.dock(boat.0, region))
+    }
+
+    /// The boat carrying `id`, if one is.
+    pub fn boat_carrying(&self, id: EntityId) -> Option<BoatId> {
+        match self.entity_location(id) {
+            EntityLocation::OnBoat(boat) => Some(boat),
+            _ => None,
+        }
+    }
+
+    /// Start `level` from scratch, keeping the mode and the random stream.
+    pub fn change_level(&mut self, level: Arc<LevelDef>) {
+        self.level = level;
+        self.min_crossings = None;
+        self.reset();
+    }
+
+    /// How far through the day it is, from 0 at dawn to 1 at nightfall.
+    /// None outside timed mode.
+    pub fn day_progress(&self) -> Option<f32> {
+        self.timed.then(|| (self.day_elapsed / DAY_LENGTH).min(1.0))
+    }
+
+    /// Advance the sun in timed mode. Returns true once night has fallen.
+    pub fn advance_day(&mut self, dt: f32) -> bool {
+        if !self.timed {
+            return false;
+        }
+        self.day_elapsed += dt;
+        self.day_elapsed >= DAY_LENGTH
+    }
+
+    /// The kind of a specific entity.
+    pub fn kind_of(&self, id: EntityId) -> KindId {
+        self.entities[id.0].kind
+    }
+
+    /// The description of a specific entity's kind.
+    pub fn kind(&self, id: EntityId) -> &EntityKind {
+        self.level.kind(self.kind_of(id))
     }
 
     /// Get the location of a specific entity.
-    pub fn entity_location(&self, entity: Entity) -> EntityLocation {
+    pub fn entity_location(&self, id: EntityId) -> EntityLocation {
+        self.entities[id.0].location
+    }
+
+    /// Set the location of a specific entity.
+    pub fn set_entity_location(&mut self, id: EntityId, loc: EntityLocation) {
+        self.entities[id.0].location = loc;
+    }
+
+    /// Get all entities in a given region (not following player, not on a
+    /// boat).
+    pub fn entities_in(&self, region: RegionId) -> Vec<&EntityInstance> {
         self.entities
             .iter()
-            .find(|(e, _)| *e == entity)
-            .map(|(_, loc)| *loc)
-            .unwrap()
+            .filter(|e| {
+                // Exclude entities following a farmer.
+                !self.is_led(e.id)
+                    && matches!(e.location, EntityLocation::OnBank { region: r, .. } if r == region)
+            })
+            .collect()
     }
 
-    /// Set the location of a specific entity.
-    pub fn set_entity_location(&mut self, entity: Entity, loc: EntityLocation) {
-        for (e, l) in &mut self.entities {
-            if *e == entity {
-                *l = loc;
-                return;
+    /// Let idle animals stroll. An animal resting on a bank occasionally
+    /// steps to a neighbouring free tile of the same bank, so the puzzle
+    /// state never changes. Prey sharing a bank with its eater edges away
+    /// while the eater stalks closer, to telegraph the eating rules.
+    pub fn update_wandering(&mut self, dt: f32) {
+        let pairs = self.tense_pairs();
+        for i in 0..self.entities.len() {
+            let entity = self.entities[i];
+            let EntityLocation::OnBank { region, pos } = entity.location else {
+                continue;
+            };
+            if !self.level.kind(entity.kind).alive || self.is_led(entity.id) {
+                continue;
+            }
+
+            let timer = entity.wander_timer - dt;
+            if timer > 0.0 {
+                self.entities[i].wander_timer = timer;
+                continue;
+            }
+
+            let threats: Vec<GridPos> = pairs
+                .iter()
+                .filter(|&&(_, prey)| prey == entity.id)
+                .filter_map(|&(eater, _)| self.bank_pos(eater))
+                .collect();
+            let prey: Vec<GridPos> = pairs
+                .iter()
+                .filter(|&&(eater, _)| eater == entity.id)
+                .filter_map(|&(_, prey)| self.bank_pos(prey))
+                .collect();
+            let delay = if threats.is_empty() && prey.is_empty() {
+                WANDER_DELAY
+            } else {
+                TENSE_DELAY
+            };
+            self.entities[i].wander_timer = self.rng.range(delay.0, delay.1);
+
+            let options: Vec<GridPos> = Direction::ALL
+                .into_iter()
+                .map(|dir| pos.step(dir))
+                .filter(|&p| self.is_free_bank_tile(p, region))
+                .collect();
+            let nearest =
+                |p: GridPos, targets: &[GridPos]| targets.iter().map(|&t| manhattan(p, t)).min();
+
+            let to = if let Some(here) = nearest(pos, &threats) {
+                // Flee: the step that gains the most distance, if any does.
+                options
+                    .iter()
+                    .copied()
+                    .max_by_key(|&p| nearest(p, &threats))
+                    .filter(|&p| nearest(p, &threats) > Some(here))
+            } else if let Some(here) = nearest(pos, &prey) {
+                // Stalk: close in, but keep a tile between.
+                options
+                    .iter()
+                    .copied()
+                    .filter(|&p| nearest(p, &prey) >= Some(STALK_DISTANCE))
+                    .min_by_key(|&p| nearest(p, &prey))
+                    .filter(|&p| nearest(p, &prey) < Some(here))
+            } else if options.is_empty() {
+                None
+            } else {
+                Some(options[self.rng.index(options.len())])
+            };
+            if let Some(to) = to {
+                self.entities[i].location = EntityLocation::OnBank { region, pos: to };
             }
         }
     }
 
-    /// Get all entities on a given bank (not following player, not on boat).
-    pub fn entities_on_bank(&self, bank: Bank) -> Vec<Entity> {
-        self.entities
-            .iter()
-            .filter_map(|(e, loc)| {
-                // Exclude the entity currently following the player.
-                if self.follower == Some(*e) {
-                    return None;
+    /// Eater/prey pairs resting together on a bank. While the player stays
+    /// with them nothing happens, but they are one crossing from a loss.
+    pub fn tense_pairs(&self) -> Vec<(EntityId, EntityId)> {
+        let resting = |e: &&EntityInstance| {
+            !self.is_led(e.id) && matches!(e.location, EntityLocation::OnBank { .. })
+        };
+        let mut pairs = Vec::new();
+        for rule in &self.level.eating_rules {
+            for eater in self.entities.iter().filter(resting) {
+                for prey in self.entities.iter().filter(resting) {
+                    if eater.kind == rule.eater
+                        && prey.kind == rule.eaten
+                        && eater.id != prey.id
+                        && !self.is_restrained(eater.id)
+                        && self.region_of_entity(eater.id) == self.region_of_entity(prey.id)
+                    {
+                        pairs.push((eater.id, prey.id));
+                    }
                 }
-                match loc {
-                    EntityLocation::OnBank { bank: b, .. } if *b == bank => Some(*e),
-                    _ => None,
+            }
+        }
+        pairs
+    }
+
+    /// Whether a farmer is keeping an eye on `id`: it stands in a guarded
+    /// region, follows someone, or rides in the player's boat.
+    fn is_attended(&self, id: EntityId) -> bool {
+        match self.entity_location(id) {
+            EntityLocation::OnBank { region, .. } => self.is_guarded(region),
+            EntityLocation::FollowingPlayer => true,
+            EntityLocation::OnBoat(boat) => self.player_boat() == Some(boat),
+        }
+    }
+
+    /// Seconds an eater left alone takes to grow famished: the level's
+    /// own, as the mutators have it. None without hunger.
+    pub fn hunger_seconds(&self) -> Option<f32> {
+        self.mutators.hunger(self.level.hunger)
+    }
+
+    /// On levels with `hunger`, eaters left alone get hungrier and those
+    /// with a farmer calm down.
+    pub fn update_hunger(&mut self, dt: f32) {
+        let Some(seconds) = self.hunger_seconds() else {
+            return;
+        };
+        let level = Arc::clone(&self.level);
+        for i in 0..self.entities.len() {
+            let kind = self.entities[i].kind;
+            if !level.eating_rules.iter().any(|rule| rule.eater == kind) {
+                continue;
+            }
+            let change = if self.is_attended(EntityId(i)) {
+                -dt / seconds * HUNGER_RECOVERY
+            } else {
+                dt / seconds
+            };
+            let entity = &mut self.entities[i];
+            entity.hunger = (entity.hunger + change).clamp(0.0, 1.0);
+        }
+    }
+
+    /// A famished eater sharing a region with its prey eats it on the spot,
+    /// farmer or no farmer.
+    pub fn check_hunger(&self) -> Option<LoseReason> {
+        for region in self.level.regions() {
+            let here = self.entities_in(region);
+            for rule in &self.level.eating_rules {
+                let eats = here.iter().any(|eater| {
+                    eater.kind == rule.eater
+                        && eater.hunger >= 1.0
+                        && !self.is_restrained(eater.id)
+                        && here
+                            .iter()
+                            .any(|eaten| eaten.kind == rule.eaten && eaten.id != eater.id)
+                });
+                if eats {
+                    return Some(LoseReason::Eaten {
+                        eater: rule.eater,
+                        eaten: rule.eaten,
+                    });
                 }
-            })
-            .collect()
+            }
+        }
+        None
+    }
+
+    fn region_of_entity(&self, id: EntityId) -> Option<RegionId> {
+        match self.entity_location(id) {
+            EntityLocation::OnBank { region, .. } => Some(region),
+            _ => None,
+        }
+    }
+
+    fn bank_pos(&self, id: EntityId) -> Option<GridPos> {
+        match self.entity_location(id) {
+            EntityLocation::OnBank { pos, .. } => Some(pos),
+            _ => None,
+        }
+    }
+
+    /// Somewhere an idle animal may step to: open land in `region`, off
+    /// the docks.
+    fn is_free_bank_tile(&self, pos: GridPos, region: RegionId) -> bool {
+        self.level.is_land(pos)
+            && self.level.region_of(pos) == Some(region)
+            && self.level.dock_at(pos).is_none()
     }
 
-    /// Try to move the player in a direction. Returns true if successful.
+    /// Whether a farmer at `pos` is next to the ferryman.
+    pub fn can_talk(&self, pos: GridPos) -> bool {
+        self.level
+            .ferryman()
+            .is_some_and(|spot| world::is_adjacent(pos, spot))
+    }
+
+    /// Start walking a step in a direction, or queue it behind the step
+    /// under way. Returns true if the step was started or queued.
     pub fn try_move_player(&mut self, dir: Direction) -> bool {
-        let PlayerLocation::OnLand(pos) = self.player else {
+        self.try_move(PlayerId::One, dir)
+    }
+
+    /// `try_move_player` for either farmer.
+    pub fn try_move(&mut self, who: PlayerId, dir: Direction) -> bool {
+        let Some(farmer) = self.farmer_mut(who) else {
+            return false;
+        };
+        if farmer.walk.is_some() {
+            if farmer.move_queue.len() >= MAX_QUEUED_STEPS {
+                return false;
+            }
+            farmer.move_queue.push_back(dir);
+            return true;
+        }
+        self.start_step(who, dir)
+    }
+
+    fn start_step(&mut self, who: PlayerId, dir: Direction) -> bool {
+        let level = Arc::clone(&self.level);
+        let Some(farmer) = self.farmer_mut(who) else {
+            return false;
+        };
+        let PlayerLocation::OnLand(pos) = farmer.location else {
             return false;
         };
 
         let new_pos = pos.step(dir);
-        if !world::is_walkable(new_pos) {
+        if !can_step_onto(&level, farmer, new_pos) {
             return false;
         }
+        farmer.walk = Some(Walk {
+            from: pos,
+            to: new_pos,
+            progress: 0.0,
+        });
+        true
+    }
 
-        self.player = PlayerLocation::OnLand(new_pos);
+    /// Glide `who` over the bank in analog movement, `dir` being the way
+    /// along the grid, -1 to 1 for columns and for rows. Their tile
+    /// follows them a step at a time, and they slide along anything they
+    /// can't walk onto.
+    pub fn glide(&mut self, who: PlayerId, dir: (i8, i8), dt: f32) {
+        let level = Arc::clone(&self.level);
+        let Some(farmer) = self.farmer(who) else {
+            return;
+        };
+        let PlayerLocation::OnLand(pos) = farmer.location else {
+            return;
+        };
+        if !self.analog || dir == (0, 0) || farmer.walk.is_some() {
+            return;
+        }
+        let here = farmer.free.unwrap_or_else(|| world::grid_to_iso(pos));
+        let (half_w, half_h) = (world::TILE_WIDTH / 2.0, world::TILE_HEIGHT / 2.0);
+        let (col, row) = (dir.0 as f32, dir.1 as f32);
+        let (dx, dy) = ((col - row) * half_w, (col + row) * half_h);
+        let speed = GLIDE_SPEED * dt / dx.hypot(dy);
+        let (vx, vy) = (dx * speed, dy * speed);
+        // The whole way, or failing that along whichever edge is in the way.
+        let tries = [
+            (here.0 + vx, here.1 + vy),
+            (here.0 + vx, here.1),
+            (here.0, here.1 + vy),
+        ];
+        let Some((to, steps)) = tries.into_iter().filter(|&to| to != here).find_map(|to| {
+            let tile = world::iso_to_grid(to.0, to.1);
+            glide_steps(&level, farmer, pos, tile).map(|steps| (to, steps))
+        }) else {
+            return;
+        };
+        let mut from = pos;
+        for tile in steps {
+            if let Some(farmer) = self.farmer_mut(who) {
+                farmer.walk = Some(Walk {
+                    from,
+                    to: tile,
+                    progress: 1.0,
+                });
+            }
+            self.complete_step(who);
+            from = tile;
+        }
+        if let Some(farmer) = self.farmer_mut(who) {
+            farmer.free = Some(to);
+        }
+    }
 
-        // Move follower to the player's old position.
-        if let Some(entity) = self.follower {
-            self.set_entity_location(
-                entity,
-                EntityLocation::OnBank {
-                    bank: world::bank_of(new_pos).unwrap(),
-                    pos,
-                },
-            );
+    /// How far `at` is from a farmer on `pos` (standing at `free` on it),
+    /// in tiles, if it's within their reach: their tile or a neighbour,
+    /// or in analog movement anything within `REACH_RADIUS`.
+    pub fn reach(&self, pos: GridPos, free: Option<(f32, f32)>, at: GridPos) -> Option<f32> {
+        if self.analog {
+            let spot = free.unwrap_or_else(|| world::grid_to_iso(pos));
+            let tile = world::grid_to_iso(at);
+            let distance = (tile.0 - spot.0).hypot(tile.1 - spot.1);
+            return (distance <= REACH_RADIUS).then_some(distance / REACH_RADIUS);
         }
+        if at == pos {
+            Some(0.0)
+        } else {
+            world::is_adjacent(at, pos).then_some(1.0)
+        }
+    }
 
-        true
+    /// Advance the steps being walked. A farmer's tile changes when their
+    /// step completes; any time left over goes into the next queued step,
+    /// so a run of steps is one continuous walk.
+    pub fn update_walking(&mut self, dt: f32) {
+        for who in [PlayerId::One, PlayerId::Two] {
+            let mut left = dt / STEP_DURATION;
+            while let Some(walk) = self.farmer_mut(who).and_then(|f| f.walk.as_mut()) {
+                walk.progress += left;
+                if walk.progress < 1.0 {
+                    break;
+                }
+                left = walk.progress - 1.0;
+                self.complete_step(who);
+                while let Some(dir) = self.farmer_mut(who).and_then(|f| f.move_queue.pop_front()) {
+                    if self.start_step(who, dir) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Land every pending step at once, so an interaction acts where the
+    /// farmers were headed.
+    pub fn finish_walking(&mut self) {
+        let walking = |state: &Self| {
+            std::iter::once(&state.player)
+                .chain(&state.partner)
+                .any(|farmer| farmer.walk.is_some())
+        };
+        while walking(self) {
+            self.update_walking(STEP_DURATION);
+        }
+    }
+
+    fn complete_step(&mut self, who: PlayerId) {
+        let level = Arc::clone(&self.level);
+        let Some(farmer) = self.farmer_mut(who) else {
+            return;
+        };
+        let Some(Walk { from, to, .. }) = farmer.walk.take() else {
+            return;
+        };
+        farmer.location = PlayerLocation::OnLand(to);
+        farmer.free = None;
+
+        let from_region = level.region_of(from);
+        // Where a bridge crossing came from and went to.
+        let mut crossed = None;
+        let mut left_behind = None;
+        match (from_region, level.region_of(to)) {
+            // Onto a bridge: whatever the farmer had waits at the end.
+            (Some(region), None) => {
+                farmer.bridge_entry = Some(region);
+                left_behind = farmer.follower.take();
+            }
+            // Off a bridge: reaching the far side counts as a crossing.
+            (None, Some(region)) => {
+                crossed = farmer
+                    .bridge_entry
+                    .take()
+                    .filter(|&entry| entry != region)
+                    .map(|entry| (entry, region));
+            }
+            _ => {}
+        }
+        // The follower takes the farmer's old position.
+        let follower = left_behind.or(farmer.follower);
+
+        self.move_count += 1;
+        self.emit(GameEvent::Moved { who, from, to });
+        if let Some((entry, region)) = crossed {
+            self.crossing_count += 1;
+            let river = level
+                .rivers_of(entry)
+                .find(|&r| level.across(r, entry) == region);
+            self.trips.push(Crossing {
+                river: river.unwrap_or(0),
+                from: entry,
+                to: region,
+                boat: None,
+                passengers: 0,
+            });
+            self.emit(GameEvent::Crossed);
+        }
+        if let (Some(entity), Some(region)) = (follower, from_region) {
+            self.set_entity_location(entity, EntityLocation::OnBank { region, pos: from });
+        }
+    }
+
+    /// Whether the player stands on a bridge, leaving every region
+    /// unattended.
+    pub fn is_on_bridge(&self) -> bool {
+        match self.player.location {
+            PlayerLocation::OnLand(pos) => self.level.region_of(pos).is_none(),
+            PlayerLocation::OnBoat(_) => false,
+        }
+    }
+
+    /// Check that `action` is legal right now and carry it out. Nothing
+    /// changes when it isn't.
+    pub fn apply(&mut self, action: Action) -> Result<(), RuleError> {
+        self.apply_as(PlayerId::One, action)
+    }
+
+    /// `apply` for either farmer.
+    pub fn apply_as(&mut self, who: PlayerId, action: Action) -> Result<(), RuleError> {
+        self.check_action(who, action)?;
+        let was = self.farmer(who).map(|farmer| farmer.location);
+        self.execute_action(who, action);
+        // Into or out of a boat: back to the middle of the tile.
+        if let Some(farmer) = self.farmer_mut(who)
+            && Some(farmer.location) != was
+        {
+            farmer.free = None;
+        }
+        Ok(())
+    }
+
+    fn check_action(&self, who: PlayerId, action: Action) -> Result<(), RuleError> {
+        if self.phase != GamePhase::Playing {
+            return Err(RuleError::NotPlaying);
+        }
+        let Some(farmer) = self.farmer(who) else {
+            return Err(RuleError::NotPlaying);
+        };
+        let entity = match action {
+            Action::PickUp(id)
+            | Action::Drop(id)
+            | Action::LoadOntoBoat(id)
+            | Action::UnloadFromBoat(id) => Some(id),
+            Action::BoardBoat | Action::UnboardBoat | Action::Talk => None,
+        };
+        if let Some(id) = entity
+            && id.0 >= self.entities.len()
+        {
+            return Err(RuleError::UnknownEntity(id));
+        }
+        let reach = self.boat_within_reach(farmer);
+        let beside = |boat: BoatId| match farmer.location {
+            PlayerLocation::OnLand(pos) => self.nearest_docked_boat(pos) == Some(boat),
+            PlayerLocation::OnBoat(_) => false,
+        };
+
+        match action {
+            Action::PickUp(id) => {
+                let PlayerLocation::OnLand(pos) = farmer.location else {
+                    return Err(RuleError::NotOnLand);
+                };
+                if farmer.follower.is_some() {
+                    return Err(RuleError::AlreadyCarrying);
+                }
+                let reachable = match self.entity_location(id) {
+                    EntityLocation::OnBank { region, pos: at } => {
+                        self.level.region_of(pos) == Some(region)
+                            && self.reach(pos, farmer.free, at).is_some()
+                            && !self.is_led(id)
+                    }
+                    _ => false,
+                };
+                if !reachable {
+                    return Err(RuleError::OutOfReach(id));
+                }
+            }
+            Action::Drop(id) => {
+                if farmer.follower != Some(id) {
+                    return Err(RuleError::NotCarrying(id));
+                }
+                if !matches!(farmer.location, PlayerLocation::OnLand(_)) {
+                    return Err(RuleError::NotOnLand);
+                }
+            }
+            Action::LoadOntoBoat(id) => {
+                if farmer.follower != Some(id) {
+                    return Err(RuleError::NotCarrying(id));
+                }
+                if !self.has_room(reach?) {
+                    return Err(RuleError::BoatFull);
+                }
+            }
+            Action::UnloadFromBoat(id) => {
+                let boat = self.boat_carrying(id).ok_or(RuleError::NotAboard(id))?;
+                if self.docked_region(boat).is_none() {
+                    return Err(RuleError::BoatNotDocked);
+                }
+                // A farmer who keeps to a bank takes cargo off from the dock.
+                let ashore = farmer.home.is_some() && beside(boat);
+                if farmer.location != PlayerLocation::OnBoat(boat) && !ashore {
+                    return Err(RuleError::NotOnBoat);
+                }
+            }
+            Action::BoardBoat => {
+                if farmer.home.is_some() {
+                    return Err(RuleError::KeepsToBank);
+                }
+                if matches!(farmer.location, PlayerLocation::OnBoat(_)) {
+                    return Err(RuleError::NotOnLand);
+                }
+                reach?;
+            }
+            Action::UnboardBoat => {
+                if !matches!(farmer.location, PlayerLocation::OnBoat(_)) {
+                    return Err(RuleError::NotOnBoat);
+                }
+                reach?;
+            }
+            Action::Talk => {
+                let PlayerLocation::OnLand(pos) = farmer.location else {
+                    return Err(RuleError::NotOnLand);
+                };
+                if !self.can_talk(pos) {
+                    return Err(RuleError::NobodyToTalkTo);
+                }
+            }
+        }
+        Ok(())
     }
 
-    /// Execute an interaction action.
-    pub fn execute_action(&mut self, action: Action) {
+    /// The boat a farmer's boat actions work on: the one they're aboard,
+    /// or the nearest one moored within reach.
+    fn boat_within_reach(&self, farmer: &Farmer) -> Result<BoatId, RuleError> {
+        let pos = match farmer.location {
+            PlayerLocation::OnBoat(boat) => {
+                return match self.boat(boat).state {
+                    BoatState::Docked(_) => Ok(boat),
+                    BoatState::Crossing { .. } => Err(RuleError::BoatNotDocked),
+                };
+            }
+            PlayerLocation::OnLand(pos) => pos,
+        };
+        if let Some(boat) = self.nearest_docked_boat(pos) {
+            return Ok(boat);
+        }
+        // Standing at the dock of a boat that's out on the water.
+        match self.level.dock_at(pos) {
+            Some((boat, _)) if self.docked_region(BoatId(boat)).is_none() => {
+                Err(RuleError::BoatNotDocked)
+            }
+            _ => Err(RuleError::NotAtDock),
+        }
+    }
+
+    /// Carry out an action `check_action` has already allowed.
+    fn execute_action(&mut self, who: PlayerId, action: Action) {
+        let level = Arc::clone(&self.level);
+        // The boat the action works on, where it's moored, and its dock.
+        let moored = |boat: BoatId| Some((boat, self.docked_region(boat)?, self.boat_dock(boat)?));
+        let reach = self
+            .farmer(who)
+            .and_then(|f| self.boat_within_reach(f).ok());
+        let reach = reach.and_then(moored);
+        let unloaded = match action {
+            Action::UnloadFromBoat(entity) => self.boat_carrying(entity).and_then(moored),
+            _ => None,
+        };
+        let Some(farmer) = self.farmer_mut(who) else {
+            return;
+        };
+        // Where the action leaves an entity, once the farmer is done.
+        let mut placed = None;
         match action {
             Action::PickUp(entity) => {
-                self.follower = Some(entity);
-                self.set_entity_location(entity, EntityLocation::FollowingPlayer);
+                farmer.follower = Some(entity);
+                placed = Some((entity, EntityLocation::FollowingPlayer));
             }
             Action::Drop(entity) => {
-                self.follower = None;
-                if let PlayerLocation::OnLand(pos) = self.player {
-                    if let Some(bank) = world::bank_of(pos) {
-                        self.set_entity_location(
-                            entity,
-                            EntityLocation::OnBank { bank, pos },
-                        );
-                    }
+                farmer.follower = None;
+                if let PlayerLocation::OnLand(pos) = farmer.location
+                    && let Some(region) = level.region_of(pos)
+                {
+                    placed = Some((entity, EntityLocation::OnBank { region, pos }));
                 }
             }
             Action::LoadOntoBoat(entity) => {
-                self.follower = None;
-                self.boat_cargo = Some(entity);
-                self.set_entity_location(entity, EntityLocation::OnBoat);
+                farmer.follower = None;
+                if let Some((boat, _, _)) = reach {
+                    placed = Some((entity, EntityLocation::OnBoat(boat)));
+                }
             }
             Action::UnloadFromBoat(entity) => {
-                self.boat_cargo = None;
-                if let BoatState::Docked(bank) = self.boat {
-                    let dock = world::dock_for(bank);
-                    self.set_entity_location(
-                        entity,
-                        EntityLocation::OnBank { bank, pos: dock },
-                    );
+                if let Some((_, region, dock)) = unloaded {
+                    placed = Some((entity, EntityLocation::OnBank { region, pos: dock }));
                 }
             }
             Action::BoardBoat => {
                 // If carrying a follower, the follower conceptually comes along
                 // (stays FollowingPlayer, will be loaded next E press on boat).
-                self.player = PlayerLocation::OnBoat;
+                if let Some((boat, _, _)) = reach {
+                    farmer.location = PlayerLocation::OnBoat(boat);
+                }
             }
             Action::UnboardBoat => {
-                if let BoatState::Docked(bank) = self.boat {
-                    let dock = world::dock_for(bank);
-                    self.player = PlayerLocation::OnLand(dock);
+                if let Some((_, region, dock)) = reach {
+                    farmer.location = PlayerLocation::OnLand(dock);
                     // If we had a follower, place them at the dock.
-                    if let Some(entity) = self.follower {
-                        self.set_entity_location(
-                            entity,
-                            EntityLocation::OnBank { bank, pos: dock },
-                        );
-                        self.follower = None;
+                    if let Some(entity) = farmer.follower.take() {
+                        placed = Some((entity, EntityLocation::OnBank { region, pos: dock }));
                     }
                 }
             }
+            // The conversation itself is the caller's to hold.
+            Action::Talk => {}
+        }
+        match action {
+            Action::PickUp(entity) => self.emit(GameEvent::PickedUp { who, entity }),
+            Action::Drop(entity) => self.emit(GameEvent::Dropped { who, entity }),
+            _ => {}
+        }
+        if let (Some((boat, _, _)), Action::UnloadFromBoat(entity)) = (unloaded, action) {
+            self.boats[boat.0].cargo.retain(|&id| id != entity);
+        }
+        if let Some((entity, location)) = placed {
+            if let EntityLocation::OnBoat(boat) = location {
+                self.boats[boat.0].cargo.push(entity);
+            }
+            self.set_entity_location(entity, location);
         }
     }
 
-    /// Start a river crossing. Returns true if crossing started.
+    /// Start a river crossing. Returns true if crossing started; in assist
+    /// mode, not if casting off would leave a bank to be eaten.
     pub fn start_crossing(&mut self) -> bool {
-        if self.player != PlayerLocation::OnBoat {
+        let Some(boat) = self.player_boat() else {
             return false;
-        }
-        let BoatState::Docked(bank) = self.boat else {
+        };
+        let BoatState::Docked(region) = self.boat(boat).state else {
             return false;
         };
+        if self.assist
+            && let Some(reason) = self.would_lose_if_crossing()
+        {
+            self.emit(GameEvent::CrossingRefused(reason));
+            return false;
+        }
 
-        self.boat = BoatState::Crossing {
-            from: bank,
+        self.boats[boat.0].state = BoatState::Crossing {
+            from: region,
             progress: 0.0,
+            drift: 0.0,
+            returning: false,
         };
         self.crossing_timer = 0.0;
+        self.emit(GameEvent::CrossingStarted(boat));
         true
     }
 
-    /// Update crossing animation. Call each frame with delta time.
-    pub fn update_crossing(&mut self, dt: f32) {
-        if let BoatState::Crossing {
+    /// Update crossing animation. Call each frame with delta time;
+    /// `time_scale` above 1.0 fast-forwards the crossing. `steer` runs from
+    /// -1 (up the rows) to 1 (down the rows) and works against the level's
+    /// current.
+    pub fn update_crossing(&mut self, dt: f32, time_scale: f32, steer: f32) {
+        let Some(boat) = self.crossing_boat() else {
+            return;
+        };
+        let BoatState::Crossing {
             from,
-            ref mut progress,
-        } = self.boat
-        {
-            self.crossing_timer += dt;
-            *progress = (self.crossing_timer / CROSSING_DURATION).min(1.0);
+            drift,
+            returning,
+            ..
+        } = self.boat(boat).state
+        else {
+            return;
+        };
+        let destination = self.level.across(self.river_of(boat), from);
+        self.crossing_time_scale = time_scale;
+        let dt = dt * time_scale;
+        self.crossing_timer += dt;
+        let (def, load) = (&self.level.boats[boat.0], self.boat_load(boat));
+        let duration =
+            CROSSING_DURATION * self.level.weather.crossing_factor() * def.slowdown(load)
+                / self.upgrades.crossing_speed();
+        // An overloaded boat never makes it past halfway.
+        let furthest = if def.sinks_under(load) {
+            SINKING_PROGRESS
+        } else {
+            1.0
+        };
+        let progress = (self.crossing_timer / duration).min(furthest);
+        let drift = if returning {
+            drift
+        } else {
+            // The grid's edges hold the boat in.
+            let dock_row = self.level.dock(boat.0, destination).row as f32;
+            let current = self.level.current * self.upgrades.current_factor();
+            let push = current + steer.clamp(-1.0, 1.0) * STEER_SPEED;
+            (drift + push * dt).clamp(-dock_row, (self.level.rows - 1) as f32 - dock_row)
+        };
+        // Who's aboard now is who made the trip, if this is the end of it.
+        let trip = Crossing {
+            river: self.river_of(boat),
+            from,
+            to: destination,
+            boat: Some(boat),
+            passengers: self.passengers(boat),
+        };
+        let state = &mut self.boats[boat.0].state;
+        *state = BoatState::Crossing {
+            from,
+            progress,
+            drift,
+            returning,
+        };
+        if progress < 1.0 {
+            return;
+        }
+
+        self.crossing_time_scale = 1.0;
+        self.crossing_count += 1;
+        self.trips.push(trip);
+        self.events.push(GameEvent::Crossed);
+        if returning || drift.abs() <= DOCK_TOLERANCE {
+            *state = BoatState::Docked(destination);
+            self.events.push(GameEvent::Docked {
+                boat,
+                region: destination,
+            });
+        } else {
+            // Swept past the dock: the trip is wasted, and drifting back
+            // costs another crossing.
+            *state = BoatState::Crossing {
+                from: destination,
+                progress: 0.0,
+                drift,
+                returning: true,
+            };
+            self.crossing_timer = 0.0;
+        }
+    }
+
+    /// Pick up any item a farmer stands on, and let fish run out.
+    pub fn update_items(&mut self, dt: f32) {
+        for entity in &mut self.entities {
+            entity.distracted = (entity.distracted - dt).max(0.0);
+        }
+        let farmers = [Some(&self.player), self.partner.as_ref()];
+        let tiles: Vec<GridPos> = farmers
+            .into_iter()
+            .flatten()
+            .filter_map(|farmer| match farmer.location {
+                PlayerLocation::OnLand(pos) => Some(pos),
+                PlayerLocation::OnBoat(_) => None,
+            })
+            .collect();
+        let inventory = &mut self.inventory;
+        self.pickups.retain(|&(item, pos)| {
+            let found = tiles.contains(&pos);
+            if found {
+                inventory.add(item);
+            }
+            !found
+        });
+    }
+
+    /// Whether a rope or a fish keeps `id` from eating.
+    pub fn is_restrained(&self, id: EntityId) -> bool {
+        let entity = &self.entities[id.0];
+        self.crossing_count < entity.tied_until || entity.distracted > 0.0
+    }
 
-            if *progress >= 1.0 {
-                let destination = from.opposite();
-                self.boat = BoatState::Docked(destination);
-                self.crossing_count += 1;
+    /// The eater a rope or fish would go to: the one the player leads, one
+    /// in their boat, or one beside them on the bank.
+    pub fn item_target(&self) -> Option<EntityId> {
+        let is_eater = |id: EntityId| {
+            let kind = self.entities[id.0].kind;
+            self.level
+                .eating_rules
+                .iter()
+                .any(|rule| rule.eater == kind)
+        };
+        if let Some(id) = self.player.follower.filter(|&id| is_eater(id)) {
+            return Some(id);
+        }
+        match self.player.location {
+            PlayerLocation::OnBoat(boat) => self
+                .boat(boat)
+                .cargo
+                .iter()
+                .copied()
+                .find(|&id| is_eater(id)),
+            PlayerLocation::OnLand(pos) => {
+                let region = self.level.region_of(pos)?;
+                self.entities_in(region)
+                    .into_iter()
+                    .filter(|e| is_eater(e.id))
+                    .filter_map(|e| Some((e.id, manhattan(self.bank_pos(e.id)?, pos))))
+                    .filter(|&(_, dist)| dist <= 1)
+                    .min_by_key(|&(_, dist)| dist)
+                    .map(|(id, _)| id)
             }
         }
     }
 
+    /// Use one of the carried items. Returns false when there's none to
+    /// use, or nobody to use it on.
+    pub fn use_item(&mut self, item: Item) -> bool {
+        if self.inventory.count(item) == 0 {
+            return false;
+        }
+        match item {
+            Item::Lantern => self.inventory.lantern_lit = !self.inventory.lantern_lit,
+            Item::Rope | Item::Fish => {
+                let Some(id) = self.item_target() else {
+                    return false;
+                };
+                let crossing_count = self.crossing_count;
+                let entity = &mut self.entities[id.0];
+                if item == Item::Rope {
+                    entity.tied_until = crossing_count + inventory::ROPE_CROSSINGS;
+                } else {
+                    entity.distracted = inventory::FISH_SECONDS;
+                    entity.hunger = 0.0;
+                }
+            }
+        }
+        if item.is_consumed() {
+            self.inventory.take(item);
+        }
+        true
+    }
+
+    /// Walk the wild wolf along its beat.
+    pub fn update_patrol(&mut self, dt: f32) {
+        if let Some(patrol) = &mut self.patrol {
+            patrol.update(dt);
+        }
+    }
+
+    /// The wild wolf has any farmer it reaches on land; aboard a boat they
+    /// are out of its way.
+    pub fn check_caught(&self) -> Option<LoseReason> {
+        let patrol = self.patrol.as_ref()?;
+        let caught = [Some(&self.player), self.partner.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter_map(Farmer::grid_position)
+            .any(|pos| patrol.reaches(pos));
+        caught.then_some(LoseReason::Caught)
+    }
+
+    /// An overloaded boat makes it halfway across before it goes down.
+    pub fn check_sinking(&self) -> Option<LoseReason> {
+        let boat = self.crossing_boat()?;
+        let BoatState::Crossing { progress, .. } = self.boat(boat).state else {
+            return None;
+        };
+        let sinks = self.level.boats[boat.0].sinks_under(self.boat_load(boat));
+        (sinks && progress >= SINKING_PROGRESS).then_some(LoseReason::BoatSank)
+    }
+
+    /// What casting off now would lose the run to, without casting off.
+    /// None unless the player is aboard a docked boat.
+    pub fn would_lose_if_crossing(&self) -> Option<LoseReason> {
+        let boat = self.player_boat()?;
+        if !matches!(self.boat(boat).state, BoatState::Docked(_)) {
+            return None;
+        }
+        // Aboard, the farmer already watches no bank: the banks are as
+        // they'll be once the boat is off.
+        self.check_eating_rules()
+    }
+
+    /// The prey casting off now would leave with something that eats it
+    /// and nobody watching.
+    pub fn crossing_victims(&self) -> Vec<EntityId> {
+        if !matches!(
+            self.would_lose_if_crossing(),
+            Some(LoseReason::Eaten { .. })
+        ) {
+            return Vec::new();
+        }
+        self.tense_pairs()
+            .into_iter()
+            .map(|(_, prey)| prey)
+            .filter(|&prey| {
+                self.region_of_entity(prey)
+                    .is_some_and(|region| !self.is_guarded(region))
+            })
+            .collect()
+    }
+
     /// Check if any forbidden pair is left unattended.
     pub fn check_eating_rules(&self) -> Option<LoseReason> {
-        let player_bank = match self.player {
-            PlayerLocation::OnLand(pos) => world::bank_of(pos),
-            PlayerLocation::OnBoat => None,
-        };
+        let player_region = self.player.region(&self.level);
+        let player_label = player_region.map(|r| self.level.region_label(r));
 
-        for bank in [Bank::Left, Bank::Right] {
-            if player_bank == Some(bank) {
+        for region in self.level.regions() {
+            if self.is_guarded(region) {
                 continue;
             }
 
-            let entities_here = self.entities_on_bank(bank);
-            let has_wolf = entities_here.contains(&Entity::Wolf);
-            let has_sheep = entities_here.contains(&Entity::Sheep);
-            let has_cabbage = entities_here.contains(&Entity::Cabbage);
-
-            if has_wolf && has_sheep {
-                return Some(LoseReason::WolfAteSheep);
+            let entities_here = self.entities_in(region);
+            for rule in &self.level.eating_rules {
+                // Two different instances, so "wolves eat wolves" needs a pair.
+                let eats = entities_here.iter().any(|eater| {
+                    eater.kind == rule.eater
+                        && !self.is_restrained(eater.id)
+                        && entities_here
+                            .iter()
+                            .any(|eaten| eaten.kind == rule.eaten && eaten.id != eater.id)
+                });
+                if eats {
+                    return Some(LoseReason::Eaten {
+                        eater: rule.eater,
+                        eaten: rule.eaten,
+                    });
+                }
             }
-            if has_sheep && has_cabbage {
-                return Some(LoseReason::SheepAteCabbage);
+            if let Some(script) = &self.level.loss_script {
+                let names: Vec<&str> = entities_here
+                    .iter()
+                    .map(|e| self.level.kind(e.kind).name.as_str())
+                    .collect();
+                let label = self.level.region_label(region);
+                if let Some(index) = script.evaluate(&label, &names, player_label.as_deref()) {
+                    return Some(LoseReason::Scripted(index));
+                }
             }
         }
 
@@ -319,14 +1690,13 @@ impl GameState {
 
     /// Check if all entities are on the right bank.
     pub fn check_win(&self) -> bool {
-        self.entities.iter().all(|(_, loc)| {
-            matches!(
-                loc,
-                EntityLocation::OnBank {
-                    bank: Bank::Right,
-                    ..
-                }
-            )
-        })
+        let goal = self.level.goal_region();
+        self.entities
+            .iter()
+            .all(|e| matches!(e.location, EntityLocation::OnBank { region, .. } if region == goal))
     }
 }
+
+fn manhattan(a: GridPos, b: GridPos) -> i32 {
+    (a.col - b.col).abs() + (a.row - b.row).abs()
+}
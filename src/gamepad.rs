@@ -0,0 +1,87 @@
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks};
+use gilrs::{EventType, GamepadId, Gilrs};
+use macroquad::logging::error;
+use macroquad::time::get_time;
+
+use crate::input::Haptics;
+
+/// Gamepads read through gilrs, rumbling the one pressed last if it has
+/// motors.
+pub struct Gamepads {
+    gilrs: Gilrs,
+    active: Option<GamepadId>,
+    /// Rumbles still going, with when they end; dropping one stops it.
+    playing: Vec<(Effect, f64)>,
+}
+
+impl Gamepads {
+    /// None, after logging why, where the platform's gamepads can't be
+    /// read.
+    pub fn new() -> Option<Self> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Self {
+                gilrs,
+                active: None,
+                playing: Vec::new(),
+            }),
+            Err(e) => {
+                error!("{e}; gamepads won't rumble");
+                None
+            }
+        }
+    }
+}
+
+impl Haptics for Gamepads {
+    fn update(&mut self) {
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(..) | EventType::AxisChanged(..)
+                    if self.gilrs.gamepad(event.id).is_ff_supported() =>
+                {
+                    self.active = Some(event.id);
+                }
+                EventType::Disconnected if self.active == Some(event.id) => {
+                    self.active = None;
+                    self.playing.clear();
+                }
+                _ => {}
+            }
+        }
+        let now = get_time();
+        self.playing.retain(|&(_, end)| end > now);
+    }
+
+    fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    fn rumble(&mut self, strength: f32, seconds: f32) {
+        let Some(id) = self.active else {
+            return;
+        };
+        let length = Ticks::from_ms((seconds * 1000.0) as u32);
+        let magnitude = (strength.clamp(0.0, 1.0) * f32::from(u16::MAX)) as u16;
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong { magnitude },
+                scheduling: Replay {
+                    play_for: length,
+                    ..Default::default()
+                },
+                envelope: Default::default(),
+            })
+            .gamepads(&[id])
+            .repeat(Repeat::For(length))
+            .finish(&mut self.gilrs)
+            .and_then(|effect| effect.play().map(|()| effect));
+        match effect {
+            Ok(effect) => self.playing.push((effect, get_time() + f64::from(seconds))),
+            Err(e) => {
+                // Don't try again every pulse; pressing it again will.
+                error!("{e}; the gamepad won't rumble");
+                self.active = None;
+            }
+        }
+    }
+}
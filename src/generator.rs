@@ -0,0 +1,175 @@
+use crate::kinds::{KindId, KindRegistry};
+use crate::level::LevelDef;
+use crate::rng::Rng;
+use crate::solver;
+use crate::world::{GridPos, RegionId};
+
+/// Draws to try before giving up on a seed.
+const ATTEMPTS: usize = 500;
+
+/// How hard a generated puzzle is drawn.
+struct Difficulty {
+    /// Entities in the puzzle, fewest to most.
+    entities: (usize, usize),
+    /// Chance that a living kind eats any one other kind in the puzzle.
+    eat_chance: f32,
+}
+
+/// The daily and random puzzles'.
+const STANDARD: Difficulty = Difficulty {
+    entities: (3, 5),
+    eat_chance: 0.45,
+};
+
+/// An endless run's first round, and how much sharper each round after
+/// gets: an entity more every `ENDLESS_ROUNDS_PER_ENTITY` rounds, up to the
+/// standard most, and a likelier diet every round, up to `ENDLESS_EAT_MAX`.
+const ENDLESS_START: Difficulty = Difficulty {
+    entities: (3, 3),
+    eat_chance: 0.35,
+};
+const ENDLESS_ROUNDS_PER_ENTITY: u32 = 2;
+const ENDLESS_EAT_STEP: f32 = 0.04;
+const ENDLESS_EAT_MAX: f32 = 0.6;
+/// From this round on eaters left alone get hungry, famished in the first
+/// of these seconds and sooner each round after, down to the second.
+const ENDLESS_HUNGER_FROM: u32 = 3;
+const ENDLESS_HUNGER: (f32, f32) = (60.0, 25.0);
+const ENDLESS_HUNGER_STEP: f32 = 5.0;
+
+/// A random solvable variant of the classic layout for `seed`: a few
+/// entities drawn from the built-in kinds, with a random graph of who eats
+/// whom. Only puzzles the solver can finish, and that need more than just
+/// ferrying everyone over one by one, are kept. The same seed always gives
+/// the same puzzle. Falls back to the classic riddle if no draw worked.
+pub fn generate(seed: u64, name: String) -> LevelDef {
+    generate_at(seed, name, &STANDARD)
+}
+
+/// Round `round` of the endless run seeded by `seed`, counting from 0:
+/// more entities, more eaters and, a few rounds in, hungrier ones the
+/// further the run gets. The same seed and round always give the same
+/// puzzle.
+pub fn endless(seed: u64, round: u32) -> LevelDef {
+    let entities = (ENDLESS_START.entities.0 + (round / ENDLESS_ROUNDS_PER_ENTITY) as usize)
+        .min(STANDARD.entities.1);
+    let difficulty = Difficulty {
+        entities: (entities, entities),
+        eat_chance: (ENDLESS_START.eat_chance + round as f32 * ENDLESS_EAT_STEP)
+            .min(ENDLESS_EAT_MAX),
+    };
+    let name = format!("Endless #{} Round {}", seed % 10_000, round + 1);
+    let mut level = generate_at(seed.wrapping_add(u64::from(round)), name, &difficulty);
+    if let Some(later) = round.checked_sub(ENDLESS_HUNGER_FROM) {
+        let seconds = ENDLESS_HUNGER.0 - later as f32 * ENDLESS_HUNGER_STEP;
+        level.hunger = Some(seconds.max(ENDLESS_HUNGER.1));
+    }
+    level
+}
+
+fn generate_at(seed: u64, name: String, difficulty: &Difficulty) -> LevelDef {
+    let template = LevelDef::classic();
+    // Spread nearby seeds, like consecutive days, far apart.
+    let mut rng = Rng::new(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    for _ in 0..ATTEMPTS {
+        if let Some(level) = attempt(&template, &mut rng, &name, difficulty) {
+            return level;
+        }
+    }
+    template
+}
+
+/// Today's puzzle: seeded by the UTC date, so everyone gets the same one.
+pub fn daily() -> LevelDef {
+    let day = (miniquad::date::now() / 86_400.0).floor() as i64;
+    let (year, month, date) = civil_from_days(day);
+    let name = format!("Daily Puzzle {year}-{month:02}-{date:02}");
+    generate(day as u64, name)
+}
+
+/// A fresh puzzle each time, seeded by the clock.
+pub fn random() -> LevelDef {
+    let seed = (miniquad::date::now() * 1000.0) as u64;
+    generate(seed, format!("Random Puzzle #{}", seed % 10_000))
+}
+
+fn attempt(
+    template: &LevelDef,
+    rng: &mut Rng,
+    name: &str,
+    difficulty: &Difficulty,
+) -> Option<LevelDef> {
+    let base = KindRegistry::with_extra(Vec::new()).ok()?;
+    let all: Vec<KindId> = base.ids().collect();
+    let (fewest, most) = difficulty.entities;
+    let count = fewest + rng.index(most - fewest + 1);
+    let picks: Vec<KindId> = (0..count).map(|_| all[rng.index(all.len())]).collect();
+    let mut used: Vec<KindId> = picks.clone();
+    used.sort();
+    used.dedup();
+    if used.len() < 2 {
+        return None;
+    }
+
+    // A fresh diet for every kind; only the living eat, and only what's
+    // in this puzzle.
+    let kinds: Vec<_> = all
+        .iter()
+        .map(|&id| {
+            let mut kind = base.get(id).clone();
+            let eats = kind.alive && used.contains(&id);
+            kind.eats = used
+                .iter()
+                .filter(|&&other| eats && other != id && rng.next_f32() < difficulty.eat_chance)
+                .map(|&other| base.get(other).name.clone())
+                .collect();
+            kind
+        })
+        .collect();
+    let registry = KindRegistry::with_extra(kinds).ok()?;
+    if registry.eating_rules().ok()?.is_empty() {
+        return None;
+    }
+
+    let mut spots = open_tiles(template);
+    let entities = picks
+        .iter()
+        .map(|&kind| (kind, spots.swap_remove(rng.index(spots.len()))))
+        .collect();
+    let level = template
+        .with_entities(name.to_string(), registry, entities)
+        .ok()?;
+    let crossings = solver::min_crossings(&level)?;
+    // Ferrying one at a time with nobody eaten takes 2n - 1 crossings.
+    (crossings > 2 * count - 1).then_some(level)
+}
+
+/// Land on the left bank where an entity can start: not the dock or the
+/// player's tile.
+fn open_tiles(level: &LevelDef) -> Vec<GridPos> {
+    (0..level.rows)
+        .flat_map(|row| (0..level.cols).map(move |col| GridPos::new(col, row)))
+        .filter(|&pos| {
+            level.region_of(pos) == Some(RegionId(0))
+                && level.is_land(pos)
+                && level.dock_at(pos).is_none()
+                && pos != level.player_start
+        })
+        .collect()
+}
+
+/// Year, month and day for days since 1970-01-01, in the proleptic
+/// Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use crate::anim::AnimState;
+use crate::game::GameState;
+use crate::level::LevelDef;
+use crate::replay::{Frame, Replay};
+
+/// A saved run raced alongside the live one: its farmer and boat replay
+/// step for step with the player's, so both set out together.
+pub struct Ghost {
+    replay: Replay,
+    /// The ghost's own copy of the level, played by the replay's frames.
+    /// None until the player is on the run's level.
+    run: Option<(GameState, AnimState)>,
+    played: usize,
+    /// Seconds into the live run, as of the last sync.
+    live_time: f32,
+}
+
+impl Ghost {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            run: None,
+            played: 0,
+            live_time: 0.0,
+        }
+    }
+
+    /// The ghost's game, while the player is on its level.
+    pub fn run(&self) -> Option<(&GameState, &AnimState)> {
+        self.run.as_ref().map(|(state, anim)| (state, anim))
+    }
+
+    /// The saved run's time from start to win.
+    pub fn time(&self) -> f32 {
+        self.replay.duration()
+    }
+
+    pub fn live_time(&self) -> f32 {
+        self.live_time
+    }
+
+    /// Keep up with the live run: `progress` is the recorder's frames and
+    /// seconds so far, or None while it isn't recording. A live run that
+    /// started over starts the ghost over too. `advance` steps the ghost's
+    /// game by one recorded frame.
+    pub fn sync(
+        &mut self,
+        live: &GameState,
+        progress: Option<(usize, f32)>,
+        mut advance: impl FnMut(&mut GameState, &mut AnimState, &Frame),
+    ) {
+        if live.level.name != self.replay.level {
+            self.run = None;
+            return;
+        }
+        let Some((frames, seconds)) = progress else {
+            return;
+        };
+        self.live_time = seconds;
+        let stale = match &self.run {
+            Some((state, _)) => !Arc::ptr_eq(&state.level, &live.level) || frames < self.played,
+            None => true,
+        };
+        if stale {
+            self.run = Some(self.start(Arc::clone(&live.level)));
+            self.played = 0;
+        }
+        let Some((state, anim)) = &mut self.run else {
+            return;
+        };
+        let end = frames.min(self.replay.frames.len());
+        for frame in &self.replay.frames[self.played.min(end)..end] {
+            advance(state, anim, frame);
+        }
+        self.played = self.played.max(end);
+    }
+
+    fn start(&self, level: Arc<LevelDef>) -> (GameState, AnimState) {
+        let mut state = GameState::new(level);
+        self.replay.prepare(&mut state);
+        // The partner, in co-op, only appears on reset.
+        state.reset();
+        let anim = AnimState::new(&state);
+        (state, anim)
+    }
+}
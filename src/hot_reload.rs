@@ -0,0 +1,100 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+
+use macroquad::logging::error;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+const ASSETS_DIR: &str = "assets";
+/// Seconds without further changes before a batch is reloaded. Editors
+/// often save in several writes, and this waits for the last one.
+const SETTLE_TIME: f32 = 0.25;
+
+/// Asset files that changed on disk since the last batch.
+#[derive(Debug, Default)]
+pub struct Changes {
+    /// File names of changed level files.
+    pub levels: BTreeSet<String>,
+    /// Whether any sprite image changed.
+    pub sprites: bool,
+}
+
+impl Changes {
+    fn is_empty(&self) -> bool {
+        self.levels.is_empty() && !self.sprites
+    }
+}
+
+/// Watches `assets/` for edits to level files and sprites.
+pub struct AssetWatcher {
+    // Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    pending: Changes,
+    /// Seconds since the last change arrived.
+    quiet: f32,
+}
+
+impl AssetWatcher {
+    /// None, after logging why, if the platform can't watch the directory.
+    pub fn new() -> Option<Self> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+            watcher.watch(Path::new(ASSETS_DIR), RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        match watcher {
+            Ok(watcher) => Some(Self {
+                _watcher: watcher,
+                events,
+                pending: Changes::default(),
+                quiet: 0.0,
+            }),
+            Err(e) => {
+                error!("{ASSETS_DIR}: {e}; assets won't reload when changed");
+                None
+            }
+        }
+    }
+
+    /// Collect changes, `dt` seconds after the last call. Returns them as a
+    /// batch once none have arrived for `SETTLE_TIME`.
+    pub fn poll(&mut self, dt: f32) -> Option<Changes> {
+        for event in self.events.try_iter() {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    error!("watching {ASSETS_DIR}: {e}");
+                    continue;
+                }
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            for path in &event.paths {
+                let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                match path.extension().and_then(|ext| ext.to_str()) {
+                    // The clip manifest lives with the sprites it animates.
+                    Some("ron") if name == "clips.ron" => self.pending.sprites = true,
+                    // So do the themes that pick them.
+                    Some("ron") if path.parent().is_some_and(|dir| dir.ends_with("themes")) => {
+                        self.pending.sprites = true;
+                    }
+                    Some("ron") => {
+                        self.pending.levels.insert(name.to_string());
+                    }
+                    Some("png") => self.pending.sprites = true,
+                    _ => continue,
+                }
+                self.quiet = 0.0;
+            }
+        }
+        if self.pending.is_empty() {
+            return None;
+        }
+        self.quiet += dt;
+        (self.quiet >= SETTLE_TIME).then(|| std::mem::take(&mut self.pending))
+    }
+}
@@ -0,0 +1,71 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long to wait on a slow or unreachable server.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// POST `body` to a plain `http://host[:port]/path` URL and return the
+/// response body. Speaks HTTP/1.0, so the reply comes back whole rather
+/// than chunked. There's no TLS; put a local proxy in front of an https
+/// server.
+pub fn post(url: &str, content_type: &str, body: &str) -> Result<String, String> {
+    let (host, path) = split_url(url)?;
+    let addr = host
+        .to_socket_addrs()
+        .map_err(|e| format!("{host}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("{host}: no address"))?;
+    let mut stream =
+        TcpStream::connect_timeout(&addr, TIMEOUT).map_err(|e| format!("{host}: {e}"))?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+
+    let request = format!(
+        "POST {path} HTTP/1.0\r\nHost: {host}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("{host}: {e}"))?;
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|e| format!("{host}: {e}"))?;
+    let response = String::from_utf8_lossy(&response);
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| format!("{host}: malformed response"))?;
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .ok_or_else(|| format!("{host}: malformed response"))?;
+    if !status.starts_with('2') {
+        return Err(format!("{host}: HTTP {status}"));
+    }
+    Ok(body.to_string())
+}
+
+/// `host:port` for connecting, and the path to ask for.
+fn split_url(url: &str) -> Result<(String, &str), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("{url}: only http:// URLs are supported"))?;
+    let (host, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err(format!("{url}: no host"));
+    }
+    let host = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{host}:80")
+    };
+    Ok((host, path))
+}
@@ -0,0 +1,253 @@
+use macroquad::prelude::{Rect, Vec2, vec2};
+
+use crate::settings::HudSettings;
+use crate::world;
+
+/// Edge or corner of the visible playfield a HUD element hangs from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// The individually placeable pieces of the HUD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudElement {
+    Controls,
+    CrossingCounter,
+    ActionHint,
+    CrossPrompt,
+    Toast,
+    SolverHint,
+    DayClock,
+    SpeedrunTimer,
+    /// The live run's time against a ghost's.
+    GhostTimer,
+    /// The narration log, growing up from its anchor.
+    Narration,
+    /// The items the farmers carry and their number keys.
+    Inventory,
+    /// An endless run's round, score and multiplier.
+    Score,
+}
+
+impl HudElement {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Placement of a single element. `offset` is measured inward from the
+/// anchor, so the same offset works for every corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementLayout {
+    pub visible: bool,
+    pub anchor: Anchor,
+    pub offset: Vec2,
+}
+
+impl ElementLayout {
+    const fn at(anchor: Anchor, x: f32, y: f32) -> Self {
+        Self {
+            visible: true,
+            anchor,
+            offset: Vec2::new(x, y),
+        }
+    }
+}
+
+/// Named starting points for the HUD layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudPreset {
+    Default,
+    /// Only the prompts needed to play.
+    Minimal,
+    /// Everything, pulled in from the edges and out of the top corners to
+    /// leave room for stream overlays.
+    Streamer,
+}
+
+impl HudPreset {
+    pub fn name(self) -> &'static str {
+        match self {
+            HudPreset::Default => "Default",
+            HudPreset::Minimal => "Minimal",
+            HudPreset::Streamer => "Streamer",
+        }
+    }
+
+    pub fn next(self) -> HudPreset {
+        match self {
+            HudPreset::Default => HudPreset::Minimal,
+            HudPreset::Minimal => HudPreset::Streamer,
+            HudPreset::Streamer => HudPreset::Default,
+        }
+    }
+}
+
+/// Anchored layout for every HUD element plus a margin kept clear around
+/// the edge of the view.
+#[derive(Debug, Clone)]
+pub struct HudLayout {
+    pub preset: HudPreset,
+    pub margin: f32,
+    /// How big the HUD is drawn against the default; 0 sizes it to fit
+    /// the view.
+    pub scale: f32,
+    /// The share of the view's width and height kept clear at each edge,
+    /// for screens that crop the picture.
+    pub safe_area: f32,
+    elements: [ElementLayout; 12],
+}
+
+impl HudLayout {
+    pub fn from_preset(preset: HudPreset) -> Self {
+        let mut layout = Self {
+            preset,
+            margin: 6.0,
+            scale: 0.0,
+            safe_area: 0.0,
+            elements: [
+                ElementLayout::at(Anchor::TopLeft, 4.0, 12.0),
+                ElementLayout::at(Anchor::TopRight, 18.0, 12.0),
+                ElementLayout::at(Anchor::BottomCenter, 0.0, 14.0),
+                ElementLayout::at(Anchor::BottomCenter, 0.0, 36.0),
+                ElementLayout::at(Anchor::TopCenter, 0.0, 40.0),
+                ElementLayout::at(Anchor::TopCenter, 0.0, 66.0),
+                ElementLayout::at(Anchor::TopCenter, 0.0, 14.0),
+                ElementLayout::at(Anchor::TopRight, 18.0, 34.0),
+                ElementLayout::at(Anchor::TopRight, 18.0, 76.0),
+                ElementLayout::at(Anchor::BottomLeft, 4.0, 60.0),
+                ElementLayout::at(Anchor::TopLeft, 4.0, 50.0),
+                ElementLayout::at(Anchor::TopRight, 18.0, 98.0),
+            ],
+        };
+
+        match preset {
+            HudPreset::Default => {}
+            HudPreset::Minimal => {
+                layout.element_mut(HudElement::Controls).visible = false;
+                layout.element_mut(HudElement::CrossingCounter).visible = false;
+            }
+            HudPreset::Streamer => {
+                layout.margin = 40.0;
+                *layout.element_mut(HudElement::Controls) =
+                    ElementLayout::at(Anchor::BottomLeft, 0.0, 18.0);
+                *layout.element_mut(HudElement::CrossingCounter) =
+                    ElementLayout::at(Anchor::BottomRight, 0.0, 18.0);
+                *layout.element_mut(HudElement::SpeedrunTimer) =
+                    ElementLayout::at(Anchor::BottomRight, 0.0, 40.0);
+                *layout.element_mut(HudElement::GhostTimer) =
+                    ElementLayout::at(Anchor::BottomRight, 0.0, 82.0);
+                *layout.element_mut(HudElement::Score) =
+                    ElementLayout::at(Anchor::BottomRight, 0.0, 104.0);
+                layout.element_mut(HudElement::Narration).offset.y = 44.0;
+                layout.element_mut(HudElement::ActionHint).offset.y = 40.0;
+                layout.element_mut(HudElement::CrossPrompt).offset.y = 62.0;
+            }
+        }
+        layout
+    }
+
+    /// Switch to `preset`'s placements, keeping the scale and safe area.
+    pub fn set_preset(&mut self, preset: HudPreset) {
+        *self = Self {
+            scale: self.scale,
+            safe_area: self.safe_area,
+            ..Self::from_preset(preset)
+        };
+    }
+
+    pub fn apply(&mut self, settings: &HudSettings) {
+        self.scale = settings.scale;
+        self.safe_area = settings.safe_area;
+    }
+
+    /// The HUD's scale in `view`: the setting, or when that's automatic,
+    /// whatever fits the default layout, made for the classic view, across
+    /// it.
+    pub fn scale_in(&self, view: Rect) -> f32 {
+        if self.scale > 0.0 {
+            self.scale
+        } else {
+            (view.w / world::WORLD_WIDTH).min(1.0)
+        }
+    }
+
+    /// The space the HUD is laid out in over `view`: its top-left corner,
+    /// with the size divided by the scale. Drawn stretched over `view`, a
+    /// bigger scale makes everything in it bigger.
+    pub fn frame(&self, view: Rect) -> Rect {
+        let scale = self.scale_in(view);
+        Rect::new(view.x, view.y, view.w / scale, view.h / scale)
+    }
+
+    pub fn element(&self, element: HudElement) -> &ElementLayout {
+        &self.elements[element.index()]
+    }
+
+    pub fn element_mut(&mut self, element: HudElement) -> &mut ElementLayout {
+        &mut self.elements[element.index()]
+    }
+
+    /// Where to start drawing a `width`-wide line of text for `element`
+    /// inside `view`, clear of the safe area: the left end of its
+    /// baseline. None when hidden.
+    pub fn place(&self, element: HudElement, view: Rect, width: f32) -> Option<Vec2> {
+        let layout = self.element(element);
+        if !layout.visible {
+            return None;
+        }
+
+        let safe = vec2(view.w, view.h) * self.safe_area;
+        let inset = safe + vec2(self.margin, self.margin) + layout.offset;
+        let x = match layout.anchor {
+            Anchor::TopLeft | Anchor::BottomLeft => view.x + inset.x,
+            Anchor::TopCenter | Anchor::BottomCenter => {
+                view.x + view.w / 2.0 - width / 2.0 + layout.offset.x
+            }
+            Anchor::TopRight | Anchor::BottomRight => view.right() - inset.x - width,
+        };
+        let y = match layout.anchor {
+            Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => view.y + inset.y,
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => {
+                view.bottom() - inset.y
+            }
+        };
+        Some(vec2(x, y))
+    }
+}
+
+const TOAST_DURATION: f32 = 1.5;
+
+/// A single short-lived status message shown at the toast anchor.
+pub struct Toast {
+    message: Option<(String, f32)>,
+}
+
+impl Toast {
+    pub fn new() -> Self {
+        Self { message: None }
+    }
+
+    pub fn show(&mut self, text: impl Into<String>) {
+        self.message = Some((text.into(), TOAST_DURATION));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if let Some((_, timer)) = &mut self.message {
+            *timer -= dt;
+            if *timer <= 0.0 {
+                self.message = None;
+            }
+        }
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_ref().map(|(text, _)| text.as_str())
+    }
+}
@@ -1,5 +1,14 @@
+use std::collections::VecDeque;
+
 use macroquad::prelude::*;
 
+use crate::events::GameEvent;
+use crate::game::{BoatState, GamePhase, GameState};
+use crate::interaction::{ClickFinish, ClickPlan};
+use crate::inventory::Item;
+use crate::pause::MenuInput;
+use crate::settings::KeyBindings;
+use crate::touch::{TouchButton, TouchPad, TouchPoint};
 use crate::world::Direction;
 
 /// Events produced by the input system.
@@ -7,73 +16,1154 @@ use crate::world::Direction;
 pub enum InputEvent {
     Move(Direction),
     Interact,
+    /// Tab, or holding interact: pick the next thing in reach.
+    CycleTarget,
     CrossRiver,
     Restart,
+    /// A number key: use that item.
+    UseItem(Item),
+    ToggleBadges,
+    ToggleNarration,
+    ToggleProfiler,
+    ToggleDebugOverlay,
+    OpenConsole,
+    SaveState,
+    LoadState,
+    NextSaveSlot,
+    /// After a loss: back to before the crossing that lost it.
+    Rewind,
+    ToggleFrameAdvance,
+    CycleHudPreset,
+    ToggleHint,
+    /// Spectator mode: the movement keys pan the camera and the player
+    /// walks with the arrows.
+    ToggleSpectator,
+    ToggleMute,
+    /// F1: the level's rules.
+    ShowRules,
+    VolumeDown,
+    VolumeUp,
+    Pause,
     None,
 }
 
+impl InputEvent {
+    /// Whether the game itself acts on this, rather than the menus,
+    /// toggles and debug tools around it.
+    pub fn is_gameplay(self) -> bool {
+        matches!(
+            self,
+            InputEvent::Move(_)
+                | InputEvent::Interact
+                | InputEvent::CycleTarget
+                | InputEvent::CrossRiver
+                | InputEvent::Restart
+                | InputEvent::UseItem(_)
+        )
+    }
+}
+
+/// What a player holds down over a tick, rather than presses on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HeldInput {
+    /// Hurrying a crossing along.
+    pub hurry: bool,
+    /// Steering against the current: -1 for up, 1 for down.
+    pub steer: f32,
+    /// The way to glide in analog movement, -1 to 1 for columns and for
+    /// rows.
+    pub glide: (i8, i8),
+}
+
+/// What the player last played with, for prompts to name its controls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    /// The on-screen controls.
+    Touch,
+}
+
+/// Who the keyboard is talking to. Contexts stack over the game, and each
+/// one claims the events it consumes; the game only sees what nothing
+/// over it claimed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputContext {
+    Gameplay,
+    /// The pause menu, level select, settings and conversations, which
+    /// read the keys themselves. Only the debug tools get past them.
+    Menu,
+    /// Typing into the console.
+    Console,
+    /// Any key skips it.
+    Cutscene,
+}
+
+impl InputContext {
+    /// Whether this context keeps `event` from anything beneath it.
+    fn consumes(self, event: InputEvent) -> bool {
+        match self {
+            InputContext::Gameplay | InputContext::Console | InputContext::Cutscene => true,
+            InputContext::Menu => !matches!(
+                event,
+                InputEvent::ToggleProfiler | InputEvent::ToggleDebugOverlay
+            ),
+        }
+    }
+}
+
+/// Gameplay actions the player can rebind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Interact,
+    CrossRiver,
+    Restart,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Interact,
+        Action::CrossRiver,
+        Action::Restart,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Interact => "Interact",
+            Action::CrossRiver => "Cross river",
+            Action::Restart => "Restart",
+        }
+    }
+}
+
+/// Keys bound to each action, resolved from the names in the settings.
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    keys: [Vec<KeyCode>; Action::ALL.len()],
+    /// Co-op: player two's keys are theirs, whatever else they're bound to.
+    coop: bool,
+}
+
+impl KeyMap {
+    /// Unknown key names are skipped with a warning. An action left with no
+    /// usable key gets its default keys back, so it can't become unreachable.
+    pub fn from_bindings(bindings: &KeyBindings) -> Self {
+        let defaults = KeyBindings::default();
+        let parse = |names: &[String]| -> Vec<KeyCode> {
+            names
+                .iter()
+                .filter_map(|name| key_from_name(name))
+                .collect()
+        };
+        let keys = Action::ALL.map(|action| {
+            for name in bindings.keys(action) {
+                if key_from_name(name).is_none() {
+                    warn!("unknown key {name:?} bound to {}", action.label());
+                }
+            }
+            let parsed = parse(bindings.keys(action));
+            if parsed.is_empty() {
+                parse(defaults.keys(action))
+            } else {
+                parsed
+            }
+        });
+        Self { keys, coop: false }
+    }
+
+    /// Name of the first key bound to `action`, for prompts.
+    pub fn primary_name(&self, action: Action) -> &'static str {
+        self.keys(action)
+            .first()
+            .and_then(|&key| key_name(key))
+            .unwrap_or("?")
+    }
+
+    fn keys(&self, action: Action) -> &[KeyCode] {
+        &self.keys[action as usize]
+    }
+
+    fn live_keys(&self, action: Action) -> impl Iterator<Item = KeyCode> + '_ {
+        self.keys(action)
+            .iter()
+            .copied()
+            .filter(|&key| !(self.coop && is_partner_key(key)))
+    }
+
+    fn pressed(&self, action: Action) -> bool {
+        self.live_keys(action).any(is_key_pressed)
+    }
+
+    fn down(&self, action: Action) -> bool {
+        self.live_keys(action).any(is_key_down)
+    }
+}
+
+/// Player two's movement keys in co-op.
+const PARTNER_MOVE_KEYS: [(KeyCode, Direction); 4] = [
+    (KeyCode::Up, Direction::Up),
+    (KeyCode::Down, Direction::Down),
+    (KeyCode::Left, Direction::Left),
+    (KeyCode::Right, Direction::Right),
+];
+const PARTNER_INTERACT: KeyCode = KeyCode::Enter;
+/// Crossing and hurrying, for player two in a versus race.
+const PARTNER_CROSS: KeyCode = KeyCode::RightShift;
+/// How prompts name `PARTNER_INTERACT` and `PARTNER_CROSS`.
+pub const PARTNER_INTERACT_NAME: &str = "Enter";
+pub const PARTNER_CROSS_NAME: &str = "RShift";
+
+/// A number key for each item, in `Item::ALL` order.
+const ITEM_KEYS: [KeyCode; Item::ALL.len()] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+
+fn is_partner_key(key: KeyCode) -> bool {
+    key == PARTNER_INTERACT
+        || key == PARTNER_CROSS
+        || PARTNER_MOVE_KEYS.iter().any(|&(k, _)| k == key)
+}
+
+fn is_arrow(key: KeyCode) -> bool {
+    PARTNER_MOVE_KEYS.iter().any(|&(k, _)| k == key)
+}
+
+fn partner_direction() -> Option<Direction> {
+    PARTNER_MOVE_KEYS
+        .iter()
+        .find(|&&(key, _)| is_key_down(key))
+        .map(|&(_, dir)| dir)
+}
+
+/// The sum of the held directions, each way -1 to 1.
+fn glide_from(held: [(bool, Direction); 4]) -> (i8, i8) {
+    held.into_iter()
+        .filter(|&(down, _)| down)
+        .fold((0, 0), |(col, row), (_, dir)| {
+            let (dc, dr) = dir.delta();
+            (col + dc as i8, row + dr as i8)
+        })
+}
+
+/// Keys with fixed jobs outside the rebindable actions.
+const RESERVED_KEYS: [KeyCode; 27] = [
+    KeyCode::Escape,
+    KeyCode::GraveAccent,
+    KeyCode::Tab,
+    KeyCode::B,
+    KeyCode::N,
+    KeyCode::H,
+    KeyCode::M,
+    KeyCode::U,
+    KeyCode::LeftBracket,
+    KeyCode::RightBracket,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Equal,
+    KeyCode::Minus,
+];
+
+pub fn is_reserved(key: KeyCode) -> bool {
+    RESERVED_KEYS.contains(&key)
+}
+
+/// Names used for keys in the settings file and on screen.
+const KEY_NAMES: [(KeyCode, &str); 63] = [
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::LeftShift, "LShift"),
+    (KeyCode::RightShift, "RShift"),
+    (KeyCode::LeftControl, "LCtrl"),
+    (KeyCode::RightControl, "RCtrl"),
+    (KeyCode::LeftAlt, "LAlt"),
+    (KeyCode::RightAlt, "RAlt"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Apostrophe, "Apostrophe"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Backslash, "Backslash"),
+    (KeyCode::Kp2, "Num2"),
+    (KeyCode::Kp4, "Num4"),
+    (KeyCode::Kp5, "Num5"),
+    (KeyCode::Kp6, "Num6"),
+    (KeyCode::Kp8, "Num8"),
+    (KeyCode::KpEnter, "NumEnter"),
+    (KeyCode::Escape, "Escape"),
+];
+
+/// Whether any key, mouse button or touch went down this frame.
+pub fn any_pressed() -> bool {
+    get_last_key_pressed().is_some()
+        || [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+            .into_iter()
+            .any(is_mouse_button_pressed)
+        || touches()
+            .iter()
+            .any(|touch| touch.phase == TouchPhase::Started)
+}
+
+/// Case-insensitive, so hand-edited files don't have to match exactly.
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|&(key, _)| key)
+}
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|&&(k, _)| k == key).map(|&(_, n)| n)
+}
+
+/// Camera controls read this frame.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CameraInput {
+    /// Positive zooms in, negative zooms out.
+    pub zoom_steps: f32,
+    /// Mouse movement in screen pixels while the pan button is held.
+    pub drag: Option<Vec2>,
+    /// Held spectator panning, -1 to 1 on each axis.
+    pub pan: Vec2,
+    /// Set in spectator mode: the camera stays where it's put rather
+    /// than following the player.
+    pub spectating: bool,
+    pub reset: bool,
+}
+
+/// Seconds interact is held before it starts cycling targets.
+const HOLD_TIME: f32 = 0.4;
+/// Seconds between targets while it stays held.
+const HOLD_CYCLE: f32 = 0.5;
+
+/// Seconds before the next step is due that a fresh direction press is
+/// kept for it rather than dropped.
+const MOVE_BUFFER: f32 = 0.1;
+/// Seconds a buffered interact waits for an animation to let it through.
+const INTERACT_BUFFER: f32 = 1.0;
+/// Most events kept waiting at once.
+const BUFFER_LEN: usize = 2;
+
 const INITIAL_MOVE_DELAY: f32 = 0.20;
 const REPEAT_MOVE_DELAY: f32 = 0.12;
 
+/// Held-key repeat for one player's movement: a step on the press, then
+/// a steady stream after a pause.
+struct MoveRepeat {
+    cooldown: f32,
+    first_press: bool,
+}
+
+impl MoveRepeat {
+    fn new() -> Self {
+        Self {
+            cooldown: 0.0,
+            first_press: true,
+        }
+    }
+
+    /// Forget the held key, as if it were let go.
+    fn reset(&mut self) {
+        self.cooldown = 0.0;
+        self.first_press = true;
+    }
+
+    /// The step to take this frame while `direction` is held.
+    fn update(&mut self, direction: Option<Direction>, dt: f32) -> Option<Direction> {
+        let Some(direction) = direction else {
+            self.reset();
+            return None;
+        };
+        self.cooldown -= dt;
+        if self.cooldown > 0.0 {
+            return None;
+        }
+        self.cooldown = if self.first_press {
+            self.first_press = false;
+            INITIAL_MOVE_DELAY
+        } else {
+            REPEAT_MOVE_DELAY
+        };
+        Some(direction)
+    }
+}
+
+/// The fixed keys for toggles, tools and volume, wherever the game is.
+fn read_toggles() -> InputEvent {
+    if is_key_pressed(KeyCode::B) {
+        return InputEvent::ToggleBadges;
+    }
+    if is_key_pressed(KeyCode::N) {
+        return InputEvent::ToggleNarration;
+    }
+    if is_key_pressed(KeyCode::F2) {
+        return InputEvent::ToggleProfiler;
+    }
+    if is_key_pressed(KeyCode::F3) {
+        return InputEvent::ToggleDebugOverlay;
+    }
+    if is_key_pressed(KeyCode::GraveAccent) {
+        return InputEvent::OpenConsole;
+    }
+    if is_key_pressed(KeyCode::F5) {
+        return InputEvent::SaveState;
+    }
+    if is_key_pressed(KeyCode::F9) {
+        return InputEvent::LoadState;
+    }
+    if is_key_pressed(KeyCode::F6) {
+        return InputEvent::NextSaveSlot;
+    }
+    if is_key_pressed(KeyCode::U) {
+        return InputEvent::Rewind;
+    }
+    if is_key_pressed(KeyCode::F7) {
+        return InputEvent::ToggleFrameAdvance;
+    }
+    if is_key_pressed(KeyCode::F4) {
+        return InputEvent::CycleHudPreset;
+    }
+    if is_key_pressed(KeyCode::H) {
+        return InputEvent::ToggleHint;
+    }
+    if is_key_pressed(KeyCode::F10) {
+        return InputEvent::ToggleSpectator;
+    }
+    if is_key_pressed(KeyCode::M) {
+        return InputEvent::ToggleMute;
+    }
+    if is_key_pressed(KeyCode::F1) {
+        return InputEvent::ShowRules;
+    }
+    if is_key_pressed(KeyCode::LeftBracket) {
+        return InputEvent::VolumeDown;
+    }
+    if is_key_pressed(KeyCode::RightBracket) {
+        return InputEvent::VolumeUp;
+    }
+    InputEvent::None
+}
+
+/// An event that came too early to take, and how much longer it keeps.
+struct Buffered {
+    event: InputEvent,
+    left: f32,
+}
+
 /// Tracks input state for movement cooldowns.
 pub struct InputState {
-    move_cooldown: f32,
-    first_press: bool,
+    keys: KeyMap,
+    moves: MoveRepeat,
+    /// Player two's, in co-op.
+    partner_moves: MoveRepeat,
+    last_drag_pos: Option<Vec2>,
+    /// Events left over from a mouse click, played back one per step.
+    planned: VecDeque<InputEvent>,
+    planned_cooldown: f32,
+    last_pointer: Option<Vec2>,
+    touch: TouchPad,
+    /// Set while an animation has the player busy: gameplay keys do
+    /// nothing, and a clicked route waits.
+    locked: bool,
+    /// Spectator mode: the movement keys pan the camera instead, and the
+    /// arrows walk the player.
+    spectating: bool,
+    /// Bottom first; the game's own context is always at the bottom.
+    contexts: Vec<InputContext>,
+    /// How many things the interact key could act on.
+    choices: usize,
+    /// Seconds interact has been held, with several things to choose from.
+    interact_held: Option<f32>,
+    /// Presses waiting for the moment they can be taken: a step pressed
+    /// just before the last one's cooldown ran out, or an interact
+    /// pressed while locked.
+    buffer: VecDeque<Buffered>,
+    /// Analog movement: held directions glide rather than step.
+    analog: bool,
+    /// Reversed controls: the player's direction keys go the other way.
+    /// Clicked routes still go where they were clicked.
+    reversed: bool,
+    device: InputDevice,
 }
 
 impl InputState {
-    pub fn new() -> Self {
+    pub fn new(keys: KeyMap) -> Self {
         Self {
-            move_cooldown: 0.0,
-            first_press: true,
+            keys,
+            moves: MoveRepeat::new(),
+            partner_moves: MoveRepeat::new(),
+            last_drag_pos: None,
+            planned: VecDeque::new(),
+            planned_cooldown: 0.0,
+            last_pointer: None,
+            touch: TouchPad::default(),
+            locked: false,
+            spectating: false,
+            contexts: vec![InputContext::Gameplay],
+            choices: 0,
+            interact_held: None,
+            buffer: VecDeque::new(),
+            analog: false,
+            reversed: false,
+            device: InputDevice::Keyboard,
+        }
+    }
+
+    /// Drop any presses waiting to be taken, when the game they were
+    /// meant for has moved on.
+    pub fn clear_buffer(&mut self) {
+        self.buffer.clear();
+    }
+
+    fn buffer(&mut self, event: InputEvent, keep: f32) {
+        // A newer press of the same kind replaces the older one.
+        let same =
+            |b: &Buffered| std::mem::discriminant(&b.event) == std::mem::discriminant(&event);
+        self.buffer.retain(|b| !same(b));
+        if self.buffer.len() == BUFFER_LEN {
+            self.buffer.pop_front();
         }
+        self.buffer.push_back(Buffered { event, left: keep });
+    }
+
+    /// Take the oldest waiting event `wanted` matches.
+    fn take_buffered(&mut self, wanted: impl Fn(InputEvent) -> bool) -> Option<InputEvent> {
+        let i = self.buffer.iter().position(|b| wanted(b.event))?;
+        self.buffer.remove(i).map(|b| b.event)
+    }
+
+    /// Say how many things are in reach to interact with. With more than
+    /// one, interact acts on release, and holding it cycles through them.
+    pub fn set_choices(&mut self, choices: usize) {
+        self.choices = choices;
+    }
+
+    /// Replace the context stack, bottom first, under the game's own.
+    pub fn set_contexts(&mut self, contexts: impl IntoIterator<Item = InputContext>) {
+        self.contexts.truncate(1);
+        self.contexts.extend(contexts);
+    }
+
+    /// Put `context` over the others until the next `set_contexts`.
+    pub fn push_context(&mut self, context: InputContext) {
+        self.contexts.push(context);
+    }
+
+    /// Whether the game has the keyboard, with nothing stacked over it.
+    fn has_keyboard(&self) -> bool {
+        self.contexts.last() == Some(&InputContext::Gameplay)
+    }
+
+    /// Whether a context over the game takes `event`.
+    fn claimed(&self, event: InputEvent) -> bool {
+        self.contexts
+            .iter()
+            .rev()
+            .take_while(|&&context| context != InputContext::Gameplay)
+            .any(|context| context.consumes(event))
+    }
+
+    /// Hold back the player's gameplay input, or let it through again.
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        self.spectating
     }
 
-    /// Poll input this frame. Returns the highest-priority event.
+    /// Switch spectator mode on or off.
+    pub fn set_spectating(&mut self, spectating: bool) {
+        self.spectating = spectating;
+        self.moves = MoveRepeat::new();
+    }
+
+    /// Switch reversed controls on or off.
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    /// Switch analog movement on or off.
+    pub fn set_analog(&mut self, analog: bool) {
+        self.analog = analog;
+        self.moves = MoveRepeat::new();
+        self.partner_moves = MoveRepeat::new();
+    }
+
+    pub fn key_map(&self) -> &KeyMap {
+        &self.keys
+    }
+
+    pub fn set_key_map(&mut self, keys: KeyMap) {
+        self.keys = KeyMap {
+            coop: self.keys.coop,
+            ..keys
+        };
+    }
+
+    /// Hand the arrow keys, Enter and RShift to player two, or back.
+    pub fn set_coop(&mut self, coop: bool) {
+        self.keys.coop = coop;
+    }
+
+    pub fn touch_pad(&self) -> &TouchPad {
+        &self.touch
+    }
+
+    /// Read this frame's touches against the on-screen controls, and note
+    /// whether the keyboard or the screen was last used. Call before
+    /// `poll`. `to_hud` maps screen pixels into the HUD space of `view`.
+    pub fn update_touch(&mut self, to_hud: impl Fn(Vec2) -> Vec2, view: Rect) {
+        let points: Vec<TouchPoint> = touches()
+            .iter()
+            .filter(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .map(|t| TouchPoint {
+                position: to_hud(t.position),
+                started: t.phase == TouchPhase::Started,
+            })
+            .collect();
+        self.touch.update(&points, view);
+        // Touches also arrive as mouse clicks, so only keys count as the
+        // keyboard.
+        if points.iter().any(|point| point.started) {
+            self.device = InputDevice::Touch;
+        } else if get_last_key_pressed().is_some() {
+            self.device = InputDevice::Keyboard;
+        }
+    }
+
+    /// The device the player last used.
+    pub fn device(&self) -> InputDevice {
+        self.device
+    }
+
+    /// Poll input this frame. Returns the highest-priority event, or None
+    /// if a context over the game claimed it.
     pub fn poll(&mut self, dt: f32) -> InputEvent {
+        if !self.has_keyboard() {
+            self.buffer.clear();
+            let toggle = read_toggles();
+            return if self.claimed(toggle) {
+                InputEvent::None
+            } else {
+                toggle
+            };
+        }
+
+        for waiting in &mut self.buffer {
+            waiting.left -= dt;
+        }
+        self.buffer.retain(|waiting| waiting.left > 0.0);
+
+        // Any gameplay key takes over from a clicked route.
+        let pressed =
+            self.keys.pressed(Action::Interact) || self.touch.is_pressed(TouchButton::Interact);
+        let interact = self.read_interact(pressed, dt);
+        let cross =
+            self.keys.pressed(Action::CrossRiver) || self.touch.is_pressed(TouchButton::Cross);
+        if self.read_direction().is_some() || pressed || cross || self.keys.pressed(Action::Restart)
+        {
+            self.planned.clear();
+        }
+
         // Single-press actions take priority.
-        if is_key_pressed(KeyCode::E) {
-            return InputEvent::Interact;
+        if is_key_pressed(KeyCode::Escape) {
+            self.planned.clear();
+            self.buffer.clear();
+            return InputEvent::Pause;
+        }
+        match interact {
+            Some(event) if !self.locked => return event,
+            // Busy: go as soon as the animation's over.
+            Some(InputEvent::Interact) => self.buffer(InputEvent::Interact, INTERACT_BUFFER),
+            _ => {}
+        }
+        if is_key_pressed(KeyCode::Tab) && !self.locked {
+            return InputEvent::CycleTarget;
         }
-        if is_key_pressed(KeyCode::Space) {
+        if cross && !self.locked {
             return InputEvent::CrossRiver;
         }
-        if is_key_pressed(KeyCode::R) {
+        if self.keys.pressed(Action::Restart) {
+            self.buffer.clear();
             return InputEvent::Restart;
         }
+        if !self.locked
+            && let Some(i) = ITEM_KEYS.iter().position(|&key| is_key_pressed(key))
+        {
+            return InputEvent::UseItem(Item::ALL[i]);
+        }
+        match read_toggles() {
+            InputEvent::None => {}
+            InputEvent::OpenConsole => {
+                self.planned.clear();
+                return InputEvent::OpenConsole;
+            }
+            toggle => return toggle,
+        }
 
-        // Movement with held-key repeat.
-        if let Some(direction) = self.read_direction() {
-            self.move_cooldown -= dt;
-            if self.move_cooldown <= 0.0 {
-                let delay = if self.first_press {
-                    self.first_press = false;
-                    INITIAL_MOVE_DELAY
-                } else {
-                    REPEAT_MOVE_DELAY
-                };
-                self.move_cooldown = delay;
-                return InputEvent::Move(direction);
+        if self.locked {
+            return InputEvent::None;
+        }
+        if let Some(event) = self.take_buffered(|event| event == InputEvent::Interact) {
+            return event;
+        }
+        if !self.planned.is_empty() {
+            self.planned_cooldown -= dt;
+            if self.planned_cooldown <= 0.0 {
+                self.planned_cooldown = REPEAT_MOVE_DELAY;
+                return self.planned.pop_front().unwrap();
             }
+            return InputEvent::None;
+        }
+
+        // Held directions glide instead; see `held`.
+        if self.analog {
+            return InputEvent::None;
+        }
+
+        // A fresh press just before the next step is due waits for it.
+        if let Some(direction) = self.pressed_direction()
+            && self.moves.cooldown > 0.0
+            && self.moves.cooldown <= MOVE_BUFFER
+        {
+            self.buffer(InputEvent::Move(direction), MOVE_BUFFER);
+        }
+        let is_move = |event| matches!(event, InputEvent::Move(_));
+
+        // Movement with held-key repeat, a waiting step going first.
+        let held = self.read_direction();
+        if held.is_none() {
+            // Nothing held to wait on: a waiting step goes now.
+            self.moves.reset();
+            return self.take_buffered(is_move).unwrap_or(InputEvent::None);
+        }
+        match self.moves.update(held, dt) {
+            Some(direction) => self
+                .take_buffered(is_move)
+                .unwrap_or(InputEvent::Move(direction)),
+            None => InputEvent::None,
+        }
+    }
+
+    /// Player two's input this frame: a step, an interaction or a
+    /// crossing. Always `None` unless player two's keys are handed over.
+    pub fn poll_partner(&mut self, dt: f32) -> InputEvent {
+        if !self.keys.coop || !self.has_keyboard() {
+            return InputEvent::None;
+        }
+        if is_key_pressed(PARTNER_INTERACT) {
+            return InputEvent::Interact;
+        }
+        if is_key_pressed(PARTNER_CROSS) {
+            return InputEvent::CrossRiver;
+        }
+        if self.analog {
+            return InputEvent::None;
+        }
+        match self.partner_moves.update(partner_direction(), dt) {
+            Some(direction) => InputEvent::Move(direction),
+            None => InputEvent::None,
+        }
+    }
+
+    /// `held` for player two.
+    pub fn partner_held(&self) -> HeldInput {
+        let steer = match partner_direction() {
+            Some(Direction::Up) => -1.0,
+            Some(Direction::Down) => 1.0,
+            _ => 0.0,
+        };
+        let glide = if self.analog && self.keys.coop && self.has_keyboard() {
+            glide_from(PARTNER_MOVE_KEYS.map(|(key, dir)| (is_key_down(key), dir)))
+        } else {
+            (0, 0)
+        };
+        HeldInput {
+            hurry: is_key_down(PARTNER_CROSS),
+            steer,
+            glide,
+        }
+    }
+
+    /// Screen position of a left click this frame. Taps on the touch
+    /// controls also arrive as clicks, so those are left out.
+    pub fn clicked(&self) -> Option<Vec2> {
+        if self.touch.consumed_tap() || !self.has_keyboard() {
+            return None;
+        }
+        is_mouse_button_pressed(MouseButton::Left).then(|| Vec2::from(mouse_position()))
+    }
+
+    /// Replace any route in progress with the steps of a click.
+    pub fn follow_plan(&mut self, plan: &ClickPlan) {
+        self.planned.clear();
+        self.planned_cooldown = 0.0;
+        if plan.interact_first {
+            self.planned.push_back(InputEvent::Interact);
+        }
+        self.planned
+            .extend(plan.path.iter().map(|&dir| InputEvent::Move(dir)));
+        match plan.finish {
+            Some(ClickFinish::Interact) => self.planned.push_back(InputEvent::Interact),
+            Some(ClickFinish::CrossRiver) => self.planned.push_back(InputEvent::CrossRiver),
+            None => {}
+        }
+    }
+
+    /// Held steering for a crossing boat: -1 for up, 1 for down.
+    pub fn steer_held(&self) -> f32 {
+        match self.read_direction() {
+            Some(Direction::Up) => -1.0,
+            Some(Direction::Down) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Everything the player holds down this frame.
+    pub fn held(&self) -> HeldInput {
+        HeldInput {
+            hurry: self.hurry_held(),
+            steer: self.steer_held(),
+            glide: self.glide_held(),
+        }
+    }
+
+    /// The way the player glides in analog movement: any mix of the held
+    /// movement keys. Nothing while the player's held back.
+    fn glide_held(&self) -> (i8, i8) {
+        if !self.analog || self.locked || !self.has_keyboard() {
+            return (0, 0);
+        }
+        let glide = if self.spectating {
+            glide_from(PARTNER_MOVE_KEYS.map(|(key, dir)| (is_key_down(key), dir)))
+        } else {
+            glide_from([
+                (self.keys.down(Action::MoveUp), self.turned(Direction::Up)),
+                (
+                    self.keys.down(Action::MoveDown),
+                    self.turned(Direction::Down),
+                ),
+                (
+                    self.keys.down(Action::MoveLeft),
+                    self.turned(Direction::Left),
+                ),
+                (
+                    self.keys.down(Action::MoveRight),
+                    self.turned(Direction::Right),
+                ),
+            ])
+        };
+        if glide != (0, 0) {
+            return glide;
+        }
+        match self.touch.direction().map(|dir| self.turned(dir).delta()) {
+            Some((dc, dr)) => (dc as i8, dr as i8),
+            None => (0, 0),
+        }
+    }
+
+    /// Whether the player is holding the hurry key to fast-forward a crossing.
+    pub fn hurry_held(&self) -> bool {
+        self.keys.down(Action::CrossRiver) || self.touch.is_held(TouchButton::Cross)
+    }
+
+    /// Zoom with the mouse wheel or +/-, pan by dragging with the right
+    /// mouse button (or the movement keys, spectating), and reset with 0.
+    pub fn camera_input(&mut self) -> CameraInput {
+        if !self.has_keyboard() {
+            self.last_drag_pos = None;
+            return CameraInput::default();
+        }
+        let mut zoom_steps = mouse_wheel().1.signum();
+        if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+            zoom_steps += 1.0;
+        }
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+            zoom_steps -= 1.0;
+        }
+
+        let drag = if is_mouse_button_down(MouseButton::Right) {
+            let pos = Vec2::from(mouse_position());
+            let delta = self.last_drag_pos.map(|last| pos - last);
+            self.last_drag_pos = Some(pos);
+            delta
         } else {
-            self.move_cooldown = 0.0;
-            self.first_press = true;
+            self.last_drag_pos = None;
+            None
+        };
+
+        let mut pan = Vec2::ZERO;
+        if self.spectating {
+            let held = |action| {
+                self.keys
+                    .live_keys(action)
+                    .any(|key| !is_arrow(key) && is_key_down(key))
+            };
+            pan.x = held(Action::MoveRight) as i32 as f32 - held(Action::MoveLeft) as i32 as f32;
+            pan.y = held(Action::MoveDown) as i32 as f32 - held(Action::MoveUp) as i32 as f32;
+        }
+
+        CameraInput {
+            zoom_steps,
+            drag,
+            pan,
+            spectating: self.spectating,
+            reset: is_key_pressed(KeyCode::Key0),
+        }
+    }
+
+    /// Menu navigation. The pointer is only reported when the mouse has
+    /// moved or clicked, so it does not fight the keyboard selection.
+    /// `to_hud` maps screen pixels into the menu's coordinate space.
+    pub fn menu_input(&mut self, to_hud: impl Fn(Vec2) -> Vec2) -> MenuInput {
+        let screen = Vec2::from(mouse_position());
+        let click = is_mouse_button_pressed(MouseButton::Left);
+        let moved = self.last_pointer != Some(screen);
+        self.last_pointer = Some(screen);
+
+        let keys = &self.keys;
+        MenuInput {
+            up: is_key_pressed(KeyCode::Up) || keys.pressed(Action::MoveUp),
+            down: is_key_pressed(KeyCode::Down) || keys.pressed(Action::MoveDown),
+            left: is_key_pressed(KeyCode::Left) || keys.pressed(Action::MoveLeft),
+            right: is_key_pressed(KeyCode::Right) || keys.pressed(Action::MoveRight),
+            confirm: is_key_pressed(KeyCode::Enter)
+                || keys.pressed(Action::Interact)
+                || keys.pressed(Action::CrossRiver),
+            back: is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Backspace),
+            pointer: (moved || click).then(|| to_hud(screen)),
+            click,
+            key: get_last_key_pressed(),
         }
+    }
 
-        InputEvent::None
+    /// Whether the frame-advance step key was pressed this frame.
+    pub fn frame_advance_pressed(&self) -> bool {
+        is_key_pressed(KeyCode::F8)
+    }
+
+    /// The interact key, `pressed` this frame: straight away with one
+    /// thing in reach. With more, it acts when let go, and cycles targets
+    /// every so often while held.
+    fn read_interact(&mut self, pressed: bool, dt: f32) -> Option<InputEvent> {
+        if self.choices < 2 {
+            self.interact_held = None;
+            return pressed.then_some(InputEvent::Interact);
+        }
+        let down = self.keys.down(Action::Interact) || self.touch.is_held(TouchButton::Interact);
+        match &mut self.interact_held {
+            None => {
+                if pressed {
+                    self.interact_held = Some(0.0);
+                }
+                None
+            }
+            Some(_) if !down => {
+                self.interact_held = None;
+                Some(InputEvent::Interact)
+            }
+            Some(held) => {
+                let cycles = |t: f32| ((t - HOLD_TIME) / HOLD_CYCLE).floor() as i32;
+                let before = *held;
+                *held += dt;
+                (*held >= HOLD_TIME && cycles(*held) > cycles(before).max(-1))
+                    .then_some(InputEvent::CycleTarget)
+            }
+        }
+    }
+
+    /// A direction key that went down this frame.
+    fn pressed_direction(&self) -> Option<Direction> {
+        if self.spectating {
+            return PARTNER_MOVE_KEYS
+                .iter()
+                .find(|&&(key, _)| is_key_pressed(key))
+                .map(|&(_, dir)| dir);
+        }
+        [
+            (Action::MoveUp, Direction::Up),
+            (Action::MoveDown, Direction::Down),
+            (Action::MoveLeft, Direction::Left),
+            (Action::MoveRight, Direction::Right),
+        ]
+        .into_iter()
+        .find(|&(action, _)| self.keys.pressed(action))
+        .map(|(_, dir)| self.turned(dir))
     }
 
     fn read_direction(&self) -> Option<Direction> {
-        if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
+        if self.spectating {
+            return partner_direction().or_else(|| self.touch.direction());
+        }
+        let held = if self.keys.down(Action::MoveUp) {
             Some(Direction::Up)
-        } else if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
+        } else if self.keys.down(Action::MoveDown) {
             Some(Direction::Down)
-        } else if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
+        } else if self.keys.down(Action::MoveLeft) {
             Some(Direction::Left)
-        } else if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
+        } else if self.keys.down(Action::MoveRight) {
             Some(Direction::Right)
         } else {
-            None
+            self.touch.direction()
+        };
+        held.map(|dir| self.turned(dir))
+    }
+
+    /// `dir` as the player means it, with reversed controls on or off.
+    fn turned(&self, dir: Direction) -> Direction {
+        if self.reversed { dir.opposite() } else { dir }
+    }
+}
+
+// --- Rumble ---
+
+/// Rumble on a moored boat coming in.
+const DOCK_RUMBLE: (f32, f32) = (0.5, 0.2);
+/// Rumble on losing.
+const LOSS_RUMBLE: (f32, f32) = (1.0, 0.6);
+/// The pulse while the player could cast off, and how often it comes.
+const CROSS_PULSE: (f32, f32) = (0.15, 0.08);
+const CROSS_PULSE_EVERY: f32 = 1.5;
+
+/// A gamepad's rumble motors, as the platform offers them.
+pub trait Haptics {
+    /// Catch up on gamepads plugged in, pulled out or used. Called once a
+    /// frame, before the rest.
+    fn update(&mut self) {}
+    /// Whether a gamepad is in use to rumble.
+    fn is_active(&self) -> bool;
+    /// Run the motors at `strength`, 0 to 1, for `seconds`.
+    fn rumble(&mut self, strength: f32, seconds: f32);
+}
+
+/// Where there's no gamepad to rumble: builds without the rumble feature,
+/// the browser, and platforms gilrs can't read gamepads on.
+struct NoGamepad;
+
+impl Haptics for NoGamepad {
+    fn is_active(&self) -> bool {
+        false
+    }
+
+    fn rumble(&mut self, _strength: f32, _seconds: f32) {}
+}
+
+/// Rumbles the gamepad for what happens in the game: a boat docking, a
+/// loss, and a gentle pulse while the crossing button would cast off.
+pub struct Rumble {
+    haptics: Box<dyn Haptics>,
+    /// Seconds to the next crossing pulse, while one is due.
+    pulse_in: Option<f32>,
+}
+
+impl Rumble {
+    pub fn new() -> Self {
+        #[cfg(all(feature = "rumble", not(target_arch = "wasm32")))]
+        let haptics: Box<dyn Haptics> = match crate::gamepad::Gamepads::new() {
+            Some(gamepads) => Box::new(gamepads),
+            None => Box::new(NoGamepad),
+        };
+        #[cfg(not(all(feature = "rumble", not(target_arch = "wasm32"))))]
+        let haptics: Box<dyn Haptics> = Box::new(NoGamepad);
+        Self {
+            haptics,
+            pulse_in: None,
+        }
+    }
+
+    /// Call once a frame with the game's events.
+    pub fn observe(&mut self, state: &GameState, events: &[GameEvent], dt: f32) {
+        self.haptics.update();
+        if !self.haptics.is_active() {
+            self.pulse_in = None;
+            return;
+        }
+        for event in events {
+            let (strength, seconds) = match event {
+                GameEvent::Docked { .. } => DOCK_RUMBLE,
+                GameEvent::Lost(_) => LOSS_RUMBLE,
+                _ => continue,
+            };
+            self.haptics.rumble(strength, seconds);
+        }
+        let can_cross = state.phase == GamePhase::Playing
+            && state
+                .player_boat()
+                .is_some_and(|boat| matches!(state.boat(boat).state, BoatState::Docked(_)));
+        if !can_cross {
+            self.pulse_in = None;
+            return;
+        }
+        let left = self.pulse_in.get_or_insert(0.0);
+        *left -= dt;
+        if *left <= 0.0 {
+            *left = CROSS_PULSE_EVERY;
+            self.haptics.rumble(CROSS_PULSE.0, CROSS_PULSE.1);
         }
     }
 }
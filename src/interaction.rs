@@ -1,34 +1,94 @@
-use crate::game::{Action, BoatState, Entity, EntityLocation, GameState, PlayerLocation};
-use crate::world::{self, Bank, GridPos};
+use crate::game::{
+    Action, BoatId, BoatState, EntityId, EntityLocation, Farmer, GameState, PlayerId,
+    PlayerLocation,
+};
+use crate::world::{self, Direction, GridPos, RegionId};
 
-/// Determine what pressing E does in the current game state.
-/// Returns None if no valid interaction is available.
-pub fn resolve_interaction(state: &GameState) -> Option<Action> {
-    match state.player {
-        PlayerLocation::OnBoat => resolve_on_boat(state),
-        PlayerLocation::OnLand(pos) => resolve_on_land(state, pos),
+/// Determine what pressing E does in the current game state, acting on
+/// `preferred` ahead of the usual priority when it's one of the
+/// `target_choices`. Returns None if no valid interaction is available.
+pub fn resolve_interaction(state: &GameState, preferred: Option<EntityId>) -> Option<Action> {
+    let farmer = state.farmer(PlayerId::One)?;
+    resolve_with(state, farmer, preferred)
+}
+
+/// What either farmer's interact key does, with the target they picked.
+pub fn resolve_for(state: &GameState, who: PlayerId) -> Option<Action> {
+    let farmer = state.farmer(who)?;
+    resolve_with(state, farmer, farmer.target)
+}
+
+fn resolve_with(state: &GameState, farmer: &Farmer, preferred: Option<EntityId>) -> Option<Action> {
+    match farmer.location {
+        PlayerLocation::OnBoat(boat) => resolve_on_boat(state, farmer, boat, preferred),
+        PlayerLocation::OnLand(pos) => resolve_on_land(state, farmer, pos, preferred),
+    }
+}
+
+/// The entities the interact key could act on where `who` stands, most
+/// likely first: what's within reach on the bank, or the cargo of a
+/// moored boat they're on. Empty where there's nothing to choose from.
+pub fn target_choices(state: &GameState, who: PlayerId) -> Vec<EntityId> {
+    let Some(farmer) = state.farmer(who) else {
+        return Vec::new();
+    };
+    if farmer.follower.is_some() {
+        return Vec::new();
+    }
+    match farmer.location {
+        PlayerLocation::OnBoat(boat) if state.docked_region(boat).is_some() => {
+            state.boat(boat).cargo.iter().rev().copied().collect()
+        }
+        PlayerLocation::OnBoat(_) => Vec::new(),
+        PlayerLocation::OnLand(pos) => match state.level.region_of(pos) {
+            Some(_) if state.boat_at_dock(pos).is_some() => Vec::new(),
+            Some(region) => nearby_entities(state, pos, farmer.free, region),
+            None => Vec::new(),
+        },
     }
 }
 
-/// When the player is on the boat.
-fn resolve_on_boat(state: &GameState) -> Option<Action> {
-    let BoatState::Docked(_bank) = state.boat else {
+/// Pick the next of `who`'s `target_choices` after the one the interact
+/// key would act on now.
+pub fn cycle_target(state: &mut GameState, who: PlayerId) {
+    let choices = target_choices(state, who);
+    if choices.len() < 2 {
+        return;
+    }
+    let current = state
+        .farmer(who)
+        .and_then(|farmer| farmer.target)
+        .and_then(|target| choices.iter().position(|&id| id == target))
+        .unwrap_or(0);
+    state.set_target(who, Some(choices[(current + 1) % choices.len()]));
+}
+
+/// When the player is on a boat.
+fn resolve_on_boat(
+    state: &GameState,
+    farmer: &Farmer,
+    boat: BoatId,
+    preferred: Option<EntityId>,
+) -> Option<Action> {
+    let BoatState::Docked(_region) = state.boat(boat).state else {
         // No interactions while crossing.
         return None;
     };
-
-    // Priority 1: If player has a follower and boat cargo is empty, load it.
-    if let Some(entity) = state.follower {
-        if state.boat_cargo.is_none() {
-            return Some(Action::LoadOntoBoat(entity));
-        }
+    // Priority 1: If player has a follower and the boat has room, load it.
+    if let Some(entity) = farmer.follower
+        && state.has_room(boat)
+    {
+        return Some(Action::LoadOntoBoat(entity));
     }
 
-    // Priority 2: If boat has cargo and player has no follower, unload it.
-    if let Some(entity) = state.boat_cargo {
-        if state.follower.is_none() {
-            return Some(Action::UnloadFromBoat(entity));
-        }
+    // Priority 2: If boat has cargo and player has no follower, unload the
+    // one picked, or the last loaded.
+    let cargo = &state.boat(boat).cargo;
+    let picked = preferred.filter(|id| cargo.contains(id));
+    if let Some(entity) = picked.or(cargo.last().copied())
+        && farmer.follower.is_none()
+    {
+        return Some(Action::UnloadFromBoat(entity));
     }
 
     // Priority 3: Get off the boat.
@@ -36,96 +96,303 @@ fn resolve_on_boat(state: &GameState) -> Option<Action> {
 }
 
 /// When the player is on land.
-fn resolve_on_land(state: &GameState, pos: GridPos) -> Option<Action> {
-    let bank = world::bank_of(pos)?;
-
-    let at_dock = world::is_dock_position(pos, bank)
-        && state.boat == BoatState::Docked(bank);
-
-    // Priority 1: If at dock with the boat, board it.
-    if at_dock {
-        // If carrying a follower, load it onto the boat instead of boarding
-        // (if boat cargo is empty). This feels more natural: you walk to the dock
-        // with a follower, press E to load, then press E again to board.
-        if let Some(entity) = state.follower {
-            if state.boat_cargo.is_none() {
-                return Some(Action::LoadOntoBoat(entity));
-            } else {
-                return None;
-            }
-        }
-        return Some(Action::BoardBoat);
+fn resolve_on_land(
+    state: &GameState,
+    farmer: &Farmer,
+    pos: GridPos,
+    preferred: Option<EntityId>,
+) -> Option<Action> {
+    let region = state.level.region_of(pos)?;
+
+    // Priority 1: If at a dock with its boat, use the boat.
+    if let Some(boat) = state.boat_at_dock(pos) {
+        return use_boat(state, farmer, boat);
     }
 
-    // Priority 2: If carrying a follower, drop it.
-    if let Some(entity) = state.follower {
-        return Some(Action::Drop(entity));
+    // Otherwise the nearest boat moored beside us, if any.
+    let boat = state.nearest_docked_boat(pos);
+
+    // Priority 2: If carrying a follower, load it onto a boat beside us,
+    // or drop it.
+    if let Some(entity) = farmer.follower {
+        let load = boat.and_then(|boat| use_boat(state, farmer, boat));
+        return load.or(Some(Action::Drop(entity)));
     }
 
-    // Priority 3: If near a free entity on the same bank, pick it up.
-    if let Some(entity) = find_nearby_entity(state, pos, bank) {
+    // Priority 3: If near a free entity on the same bank, pick it up: the
+    // one picked, or the first in line.
+    let nearby = nearby_entities(state, pos, farmer.free, region);
+    let picked = preferred.filter(|id| nearby.contains(id));
+    if let Some(entity) = picked.or(nearby.first().copied()) {
         return Some(Action::PickUp(entity));
     }
 
+    // Priority 4: Board a boat moored beside us.
+    if let Some(boat) = boat {
+        return use_boat(state, farmer, boat);
+    }
+
+    // Priority 5: Otherwise, chat with the ferryman if he's beside you.
+    if state.can_talk(pos) {
+        return Some(Action::Talk);
+    }
+
     None
 }
 
-/// Find an entity on the same bank at or adjacent to the player.
-/// Priority order: same tile first, then adjacent. Within each, Sheep > Wolf > Cabbage.
-fn find_nearby_entity(state: &GameState, player_pos: GridPos, bank: Bank) -> Option<Entity> {
-    let priority = [Entity::Sheep, Entity::Wolf, Entity::Cabbage];
+/// What a farmer on land does with a boat in reach.
+fn use_boat(state: &GameState, farmer: &Farmer, boat: BoatId) -> Option<Action> {
+    // If carrying a follower, load it onto the boat instead of boarding
+    // (if the boat has room). This feels more natural: you walk to the dock
+    // with a follower, press E to load, then press E again to board.
+    if let Some(entity) = farmer.follower {
+        return state.has_room(boat).then_some(Action::LoadOntoBoat(entity));
+    }
+    // Player two never boards, but takes cargo off from the dock.
+    if farmer.home.is_some() {
+        return state
+            .boat(boat)
+            .cargo
+            .last()
+            .copied()
+            .map(Action::UnloadFromBoat);
+    }
+    Some(Action::BoardBoat)
+}
 
-    // Same tile first.
-    for &entity in &priority {
-        if state.follower == Some(entity) {
-            continue;
-        }
-        if let EntityLocation::OnBank { bank: b, pos } = state.entity_location(entity) {
-            if b == bank && pos == player_pos {
-                return Some(entity);
+/// The free entities on the same bank at or adjacent to the player, in
+/// the order they'd be picked up: same tile first, then adjacent. Within
+/// each, the kind with the highest `priority`, then level order.
+fn nearby_entities(
+    state: &GameState,
+    player_pos: GridPos,
+    free: Option<(f32, f32)>,
+    region: RegionId,
+) -> Vec<EntityId> {
+    let mut found: Vec<(f32, EntityId)> = state
+        .entities
+        .iter()
+        .filter_map(|e| match e.location {
+            EntityLocation::OnBank { region: r, pos } if !state.is_led(e.id) && r == region => {
+                Some((state.reach(player_pos, free, pos)?, e.id))
             }
-        }
+            _ => None,
+        })
+        .collect();
+    // Nearest first, then by priority. Stable, so ties keep level order.
+    found.sort_by(|(a, x), (b, y)| {
+        a.total_cmp(b)
+            .then_with(|| state.kind(*y).priority.cmp(&state.kind(*x).priority))
+    });
+    found.into_iter().map(|(_, id)| id).collect()
+}
+
+/// The entity the interact key would act on, if any.
+pub fn interaction_target(state: &GameState) -> Option<EntityId> {
+    match resolve_for(state, PlayerId::One)? {
+        Action::PickUp(id)
+        | Action::Drop(id)
+        | Action::LoadOntoBoat(id)
+        | Action::UnloadFromBoat(id) => Some(id),
+        Action::BoardBoat | Action::UnboardBoat | Action::Talk => None,
     }
+}
+
+/// Return a human-readable hint for what the interact key will do.
+pub fn describe_available_action(state: &GameState) -> Option<String> {
+    describe_for(state, PlayerId::One)
+}
 
-    // Adjacent tiles.
-    for &entity in &priority {
-        if state.follower == Some(entity) {
-            continue;
+/// `describe_available_action` for either farmer.
+pub fn describe_for(state: &GameState, who: PlayerId) -> Option<String> {
+    let farmer = state.farmer(who)?;
+    let boat = match farmer.location {
+        PlayerLocation::OnBoat(boat) => Some(boat),
+        PlayerLocation::OnLand(pos) => state.nearest_docked_boat(pos),
+    };
+    let readout = |extra| {
+        boat.and_then(|boat| load_readout(state, boat, extra))
+            .unwrap_or_default()
+    };
+    resolve_for(state, who).map(|action| match action {
+        Action::PickUp(id) => match state.kind(id) {
+            kind if kind.alive => format!("Call {}", kind.name),
+            kind => format!("Pick up {}", kind.name),
+        },
+        Action::Drop(id) => match state.kind(id) {
+            kind if kind.alive => format!("Send {} away", kind.name),
+            kind => format!("Put down {}", kind.name),
+        },
+        Action::LoadOntoBoat(id) => {
+            // A follower already counts once the player is aboard.
+            let extra = matches!(farmer.location, PlayerLocation::OnLand(_)).then_some(id);
+            format!("Load {} onto boat{}", state.kind(id).name, readout(extra))
         }
-        if let EntityLocation::OnBank { bank: b, pos } = state.entity_location(entity) {
-            if b == bank && world::is_adjacent(player_pos, pos) {
-                return Some(entity);
-            }
+        Action::UnloadFromBoat(id) => format!("Unload {}", state.kind(id).name),
+        Action::BoardBoat => format!("Board boat{}", readout(farmer.follower)),
+        Action::UnboardBoat => "Get off boat".to_string(),
+        Action::Talk => "Talk to the ferryman".to_string(),
+    })
+}
+
+/// What a weighed boat would carry once `extra` is aboard, against its
+/// limit: " (5/4, slow)". None for boats that aren't weighed.
+fn load_readout(state: &GameState, boat: BoatId, extra: Option<EntityId>) -> Option<String> {
+    let def = &state.level.boats[boat.0];
+    let max = def.max_load?;
+    let load = state.boat_load(boat) + extra.map_or(0.0, |id| state.kind(id).weight);
+    let note = if def.sinks_under(load) {
+        ", will sink"
+    } else if load > max {
+        ", slow"
+    } else {
+        ""
+    };
+    Some(format!(" ({load}/{max}{note})"))
+}
+
+/// What the player clicked on in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickTarget {
+    Entity(EntityId),
+    Boat(BoatId),
+    Tile(GridPos),
+}
+
+/// What to do once a click's walk is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickFinish {
+    Interact,
+    CrossRiver,
+}
+
+/// How to carry out a click: optionally interact first (to get off the
+/// boat or put something down), walk `path`, then optionally finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickPlan {
+    pub interact_first: bool,
+    pub path: Vec<Direction>,
+    pub finish: Option<ClickFinish>,
+}
+
+impl ClickPlan {
+    fn finish(finish: ClickFinish) -> Self {
+        Self {
+            interact_first: false,
+            path: Vec::new(),
+            finish: Some(finish),
         }
     }
+}
 
-    None
+/// Turn a click into moves and interactions.
+/// Returns None if the click does nothing from here.
+pub fn plan_click(state: &GameState, target: ClickTarget) -> Option<ClickPlan> {
+    match state.player.location {
+        PlayerLocation::OnBoat(boat) => plan_click_on_boat(state, boat, target),
+        PlayerLocation::OnLand(pos) => plan_click_on_land(state, pos, target),
+    }
 }
 
-/// Return a human-readable hint for what E will do.
-pub fn describe_available_action(state: &GameState) -> Option<&'static str> {
-    resolve_interaction(state).map(|action| match action {
-        Action::PickUp(e) => match e {
-            Entity::Wolf => "[E] Call wolf",
-            Entity::Sheep => "[E] Call sheep",
-            Entity::Cabbage => "[E] Pick up cabbage",
-        },
-        Action::Drop(e) => match e {
-            Entity::Wolf => "[E] Send wolf away",
-            Entity::Sheep => "[E] Send sheep away",
-            Entity::Cabbage => "[E] Put down cabbage",
-        },
-        Action::LoadOntoBoat(e) => match e {
-            Entity::Wolf => "[E] Load wolf onto boat",
-            Entity::Sheep => "[E] Load sheep onto boat",
-            Entity::Cabbage => "[E] Load cabbage onto boat",
-        },
-        Action::UnloadFromBoat(e) => match e {
-            Entity::Wolf => "[E] Unload wolf",
-            Entity::Sheep => "[E] Unload sheep",
-            Entity::Cabbage => "[E] Unload cabbage",
+fn plan_click_on_boat(state: &GameState, boat: BoatId, target: ClickTarget) -> Option<ClickPlan> {
+    let BoatState::Docked(region) = state.boat(boat).state else {
+        return None;
+    };
+
+    let tile = match target {
+        ClickTarget::Boat(clicked) if clicked == boat => {
+            return Some(ClickPlan::finish(ClickFinish::CrossRiver));
+        }
+        ClickTarget::Boat(_) => return None,
+        ClickTarget::Entity(entity) if state.boat(boat).cargo.contains(&entity) => {
+            return Some(ClickPlan::finish(ClickFinish::Interact));
+        }
+        ClickTarget::Entity(entity) => match state.entity_location(entity) {
+            EntityLocation::OnBank { pos, .. } => pos,
+            _ => return None,
         },
-        Action::BoardBoat => "[E] Board boat",
-        Action::UnboardBoat => "[E] Get off boat",
+        ClickTarget::Tile(pos) => pos,
+    };
+
+    // Clicking land: pending loading/unloading comes first, otherwise step
+    // off at the dock and walk to the tile.
+    if resolve_on_boat(state, &state.player, boat, None) != Some(Action::UnboardBoat) {
+        return Some(ClickPlan::finish(ClickFinish::Interact));
+    }
+    let dock = state.level.dock(boat.0, region);
+    let path = path_on_bank(state, dock, region, |p| p == tile)?;
+    Some(ClickPlan {
+        interact_first: true,
+        path,
+        finish: None,
+    })
+}
+
+fn plan_click_on_land(state: &GameState, pos: GridPos, target: ClickTarget) -> Option<ClickPlan> {
+    let region = state.level.region_of(pos)?;
+
+    match target {
+        ClickTarget::Boat(boat) => {
+            if state.boat(boat).state != BoatState::Docked(region) {
+                return None;
+            }
+            let dock = state.level.dock(boat.0, region);
+            let path = path_on_bank(state, pos, region, |p| p == dock)?;
+            Some(ClickPlan {
+                interact_first: false,
+                path,
+                finish: Some(ClickFinish::Interact),
+            })
+        }
+        ClickTarget::Entity(entity) if state.player.follower == Some(entity) => {
+            Some(ClickPlan::finish(ClickFinish::Interact))
+        }
+        ClickTarget::Entity(entity) => {
+            let EntityLocation::OnBank { region: r, .. } = state.entity_location(entity) else {
+                return None;
+            };
+            if r != region {
+                return None;
+            }
+            // Walk until E would pick this one rather than a neighbour, and
+            // put down whatever we are carrying first.
+            let path = path_on_bank(state, pos, region, |p| {
+                nearby_entities(state, p, None, region).first() == Some(&entity)
+            })?;
+            let drop_first = matches!(
+                resolve_on_land(state, &state.player, pos, None),
+                Some(Action::Drop(_))
+            );
+            Some(ClickPlan {
+                interact_first: drop_first,
+                path,
+                finish: Some(ClickFinish::Interact),
+            })
+        }
+        ClickTarget::Tile(tile) => {
+            if state.level.region_of(tile) != Some(region) {
+                return None;
+            }
+            let path = path_on_bank(state, pos, region, |p| p == tile)?;
+            Some(ClickPlan {
+                interact_first: false,
+                path,
+                finish: None,
+            })
+        }
+    }
+}
+
+fn path_on_bank(
+    state: &GameState,
+    from: GridPos,
+    region: RegionId,
+    is_goal: impl Fn(GridPos) -> bool,
+) -> Option<Vec<Direction>> {
+    let ferryman = state.level.ferryman();
+    world::grid_path(from, is_goal, |p| {
+        state.level.is_walkable(p)
+            && state.level.region_of(p) == Some(region)
+            && Some(p) != ferryman
     })
 }
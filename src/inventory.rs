@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// Crossings a rope holds an eater for: the trip away and the one back.
+pub const ROPE_CROSSINGS: u32 = 2;
+/// Seconds a fish keeps an eater busy.
+pub const FISH_SECONDS: f32 = 20.0;
+
+/// A small tool the farmer can carry. Found lying on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Item {
+    /// Ties up the eater beside the farmer until the boat is there and
+    /// back.
+    Rope,
+    /// Lit, it pushes back the fog around the farmer.
+    Lantern,
+    /// Fed to the eater beside the farmer, it keeps it busy for a while.
+    Fish,
+}
+
+impl Item {
+    /// In number-key order.
+    pub const ALL: [Item; 3] = [Item::Rope, Item::Lantern, Item::Fish];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Item::Rope => "rope",
+            Item::Lantern => "lantern",
+            Item::Fish => "fish",
+        }
+    }
+
+    /// Lanterns are kept when used; the rest are used up.
+    pub fn is_consumed(self) -> bool {
+        self != Item::Lantern
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// What the farmers carry between them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inventory {
+    counts: [u32; Item::ALL.len()],
+    pub lantern_lit: bool,
+}
+
+impl Inventory {
+    pub fn count(&self, item: Item) -> u32 {
+        self.counts[item.index()]
+    }
+
+    pub fn add(&mut self, item: Item) {
+        self.counts[item.index()] += 1;
+    }
+
+    /// Use one up, if there's one to use.
+    pub fn take(&mut self, item: Item) -> bool {
+        let count = &mut self.counts[item.index()];
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|&count| count == 0)
+    }
+}
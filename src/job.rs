@@ -0,0 +1,48 @@
+use std::cell::OnceCell;
+use std::sync::mpsc::{self, Receiver};
+
+/// Work done away from the render loop, like a search of a big puzzle,
+/// whose answer is picked up once it's ready. On desktop it runs on a
+/// thread of its own; the browser has none, so there it runs right away.
+pub struct Job<T> {
+    reply: Receiver<T>,
+    answer: OnceCell<T>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    pub fn spawn(work: impl FnOnce() -> T + Send + 'static) -> Self {
+        let (send, reply) = mpsc::channel();
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(move || {
+            // Nobody is waiting if the job was dropped.
+            let _ = send.send(work());
+        });
+        #[cfg(target_arch = "wasm32")]
+        let _ = send.send(work());
+        Self {
+            reply,
+            answer: OnceCell::new(),
+        }
+    }
+}
+
+impl<T> Job<T> {
+    /// The answer, once the work is done.
+    pub fn get(&self) -> Option<&T> {
+        if let Ok(answer) = self.reply.try_recv() {
+            let _ = self.answer.set(answer);
+        }
+        self.answer.get()
+    }
+
+    /// The answer, waiting for it if the work isn't done yet. None only if
+    /// the work panicked.
+    pub fn wait(&self) -> Option<&T> {
+        if self.answer.get().is_none()
+            && let Ok(answer) = self.reply.recv()
+        {
+            let _ = self.answer.set(answer);
+        }
+        self.answer.get()
+    }
+}
@@ -0,0 +1,139 @@
+use serde::Deserialize;
+
+const BUILTIN_SOURCE: &str = include_str!("../assets/kinds.ron");
+
+/// Identifies a kind within a level: its index in the level's registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KindId(pub u16);
+
+/// The shape drawn over a kind when shape badges are on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Badge {
+    Triangle,
+    Circle,
+    Square,
+    #[default]
+    Diamond,
+}
+
+/// The sprite files for a kind, named after `name` under `assets/sprites/`.
+/// `assets/sprites/clips.ron` says how they animate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct SpriteSet {
+    pub name: String,
+    /// Sets the clip manifest leaves out: animated ones have `_idle`,
+    /// `_walk1` and `_walk2` frames, the rest a single `<name>.png`.
+    #[serde(default)]
+    pub animated: bool,
+}
+
+/// Everything the game knows about one kind of transportable entity.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EntityKind {
+    pub name: String,
+    /// Defaults to the name plus "s".
+    #[serde(default)]
+    plural: Option<String>,
+    pub sprites: SpriteSet,
+    /// Living kinds wander, follow on foot and are "called" rather than
+    /// carried.
+    #[serde(default)]
+    pub alive: bool,
+    /// Walking speed relative to the usual pace.
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// Names of the kinds this one eats when left alone with them.
+    #[serde(default)]
+    pub eats: Vec<String>,
+    #[serde(default)]
+    pub badge: Badge,
+    /// When several entities are in reach, the highest priority is picked
+    /// up first.
+    #[serde(default)]
+    pub priority: i32,
+    /// How heavily it loads a boat; see `BoatDef::max_load`.
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// Which built-in voice plays on pickup, if any.
+    #[serde(default)]
+    pub sound: Option<String>,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+impl EntityKind {
+    pub fn plural(&self) -> String {
+        match &self.plural {
+            Some(plural) => plural.clone(),
+            None => format!("{}s", self.name),
+        }
+    }
+}
+
+/// A predator/prey pair: left alone together on a bank, `eater` eats `eaten`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EatingRule {
+    pub eater: KindId,
+    pub eaten: KindId,
+}
+
+/// The kinds a level can use: the built-ins, then any the level adds.
+#[derive(Debug, Clone)]
+pub struct KindRegistry {
+    kinds: Vec<EntityKind>,
+}
+
+impl KindRegistry {
+    /// The built-in kinds, with `extra` added on; an extra kind with a
+    /// built-in name replaces it.
+    pub fn with_extra(extra: Vec<EntityKind>) -> Result<Self, String> {
+        let mut kinds: Vec<EntityKind> =
+            ron::from_str(BUILTIN_SOURCE).expect("built-in kinds are valid");
+        for kind in extra {
+            match kinds.iter_mut().find(|k| k.name == kind.name) {
+                Some(existing) => *existing = kind,
+                None => kinds.push(kind),
+            }
+        }
+        let registry = Self { kinds };
+        registry.eating_rules()?;
+        Ok(registry)
+    }
+
+    pub fn get(&self, id: KindId) -> &EntityKind {
+        &self.kinds[id.0 as usize]
+    }
+
+    pub fn find(&self, name: &str) -> Option<KindId> {
+        let index = self.kinds.iter().position(|k| k.name == name)?;
+        Some(KindId(index as u16))
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = KindId> + '_ {
+        (0..self.kinds.len()).map(|i| KindId(i as u16))
+    }
+
+    /// Every kind's `eats` list as rules, in registry order. Errs on a name
+    /// that isn't a kind.
+    pub fn eating_rules(&self) -> Result<Vec<EatingRule>, String> {
+        let mut rules = Vec::new();
+        for eater in self.ids() {
+            for name in &self.get(eater).eats {
+                let Some(eaten) = self.find(name) else {
+                    return Err(format!(
+                        "the {} eats \"{name}\", which is not a kind",
+                        self.get(eater).name
+                    ));
+                };
+                rules.push(EatingRule { eater, eaten });
+            }
+        }
+        Ok(rules)
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::{Receiver, Sender};
+
+use macroquad::logging::error;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GamePhase, GameState};
+use crate::settings::LeaderboardSettings;
+use crate::storage;
+
+/// The last scores seen for each level, plus any not yet sent.
+const CACHE_PATH: &str = "leaderboard.ron";
+/// Rows shown on the win screen.
+pub const TOP: usize = 10;
+
+/// One finished run on the board.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub player: String,
+    /// Seconds from the start to the win.
+    pub time: f32,
+    pub crossings: u32,
+}
+
+/// What's posted to the endpoint, as RON. The server answers with the
+/// level's best scores, fastest first, as a RON list of `Score`s.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Submission {
+    level: String,
+    score: Score,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Cache {
+    tops: BTreeMap<String, Vec<Score>>,
+    /// Wins made offline, sent again on the next start.
+    pending: Vec<Submission>,
+}
+
+impl Cache {
+    fn load() -> Self {
+        let parsed = storage::read(CACHE_PATH).and_then(|source| match source {
+            Some(source) => ron::from_str(&source).map_err(|e| format!("{CACHE_PATH}: {e}")),
+            None => Ok(Self::default()),
+        });
+        parsed.unwrap_or_else(|e| {
+            error!("{e}; starting with no cached scores");
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        storage::write(CACHE_PATH, &source)
+    }
+
+    /// Slot a score into the cached board, for showing while offline.
+    fn insert(&mut self, submission: &Submission) {
+        let top = self.tops.entry(submission.level.clone()).or_default();
+        if top.contains(&submission.score) {
+            return;
+        }
+        top.push(submission.score.clone());
+        top.sort_by(|a, b| a.time.total_cmp(&b.time));
+        top.truncate(TOP);
+    }
+}
+
+/// How fresh the board on the win screen is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardStatus {
+    Sending,
+    Online,
+    /// The server couldn't be reached; the cached board is shown, with
+    /// this win slotted in.
+    Offline,
+}
+
+/// The server's answer to one submission.
+type Reply = (Submission, Result<Vec<Score>, String>);
+
+/// Sends each campaign win to the leaderboard endpoint on a background
+/// thread and keeps the boards it sends back. Off unless the settings name
+/// an endpoint and the game was built with the `leaderboard` feature.
+pub struct Leaderboard {
+    player: String,
+    jobs: Option<Sender<Submission>>,
+    replies: Option<Receiver<Reply>>,
+    cache: Cache,
+    /// The level of the latest win and how its board is doing.
+    shown: Option<(String, BoardStatus)>,
+    /// Whether the current win has been sent, so it goes only once.
+    submitted: bool,
+}
+
+impl Leaderboard {
+    pub fn new(settings: &LeaderboardSettings) -> Self {
+        let mut board = Self {
+            player: settings.player.clone(),
+            jobs: None,
+            replies: None,
+            cache: Cache::load(),
+            shown: None,
+            submitted: false,
+        };
+        if !settings.endpoint.is_empty() {
+            board.connect(settings.endpoint.clone());
+        }
+        board
+    }
+
+    #[cfg(all(feature = "leaderboard", not(target_arch = "wasm32")))]
+    fn connect(&mut self, endpoint: String) {
+        let (jobs, queue) = std::sync::mpsc::channel::<Submission>();
+        let (replies, answers) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for submission in queue {
+                let reply = ron::to_string(&submission)
+                    .map_err(|e| e.to_string())
+                    .and_then(|body| crate::http::post(&endpoint, "application/ron", &body))
+                    .and_then(|body| ron::from_str(&body).map_err(|e| e.to_string()));
+                if replies.send((submission, reply)).is_err() {
+                    return;
+                }
+            }
+        });
+        for submission in &self.cache.pending {
+            let _ = jobs.send(submission.clone());
+        }
+        self.jobs = Some(jobs);
+        self.replies = Some(answers);
+    }
+
+    /// Builds without the HTTP client leave the leaderboard off.
+    #[cfg(not(all(feature = "leaderboard", not(target_arch = "wasm32"))))]
+    fn connect(&mut self, _endpoint: String) {
+        error!("built without the leaderboard feature; scores stay offline");
+    }
+
+    /// The board for `level`, best first, if a win there has been sent.
+    pub fn board(&self, level: &str) -> Option<(&[Score], BoardStatus)> {
+        let (shown, status) = self.shown.as_ref().filter(|(shown, _)| shown == level)?;
+        let top = self.cache.tops.get(shown).map_or(&[][..], Vec::as_slice);
+        Some((top, *status))
+    }
+
+    /// Send a win once `solve`, its time, is known, and take in any
+    /// answers. `solve` is None for runs that don't count. Errs if the
+    /// cache couldn't be saved.
+    pub fn observe(&mut self, state: &GameState, solve: Option<f32>) -> Result<(), String> {
+        let mut changed = false;
+        while let Some(Ok((submission, reply))) = self.replies.as_ref().map(Receiver::try_recv) {
+            changed |= self.receive(submission, reply);
+        }
+
+        if state.phase != GamePhase::Won {
+            self.submitted = false;
+        } else if let Some(time) = solve
+            && let Some(jobs) = &self.jobs
+            && !self.submitted
+        {
+            self.submitted = true;
+            let submission = Submission {
+                level: state.level.name.clone(),
+                score: Score {
+                    player: self.player.clone(),
+                    time,
+                    crossings: state.crossing_count,
+                },
+            };
+            self.shown = Some((submission.level.clone(), BoardStatus::Sending));
+            // Kept until the server has it.
+            self.cache.pending.push(submission.clone());
+            changed = true;
+            let _ = jobs.send(submission);
+        }
+
+        if changed { self.cache.save() } else { Ok(()) }
+    }
+
+    /// File an answer; true if the cache changed.
+    fn receive(&mut self, submission: Submission, reply: Result<Vec<Score>, String>) -> bool {
+        let status = match reply {
+            Ok(mut top) => {
+                top.truncate(TOP);
+                self.cache.tops.insert(submission.level.clone(), top);
+                self.cache.pending.retain(|pending| *pending != submission);
+                BoardStatus::Online
+            }
+            Err(e) => {
+                error!("leaderboard: {e}");
+                self.cache.insert(&submission);
+                BoardStatus::Offline
+            }
+        };
+        if let Some((level, shown)) = &mut self.shown
+            && *level == submission.level
+        {
+            *shown = status;
+        }
+        true
+    }
+}
@@ -0,0 +1,717 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::inventory::Item;
+use crate::kinds::{EatingRule, EntityKind, KindId, KindRegistry};
+use crate::patrol::PatrolRoute;
+use crate::script::LossScript;
+use crate::world::{self, GridPos, RegionId, Tile};
+
+/// The solver tracks entities in a bitmask; more than this would also make
+/// its search impractically large.
+pub const MAX_ENTITIES: usize = 16;
+/// The solver packs each entity's region into four bits, so a level has at
+/// most sixteen regions.
+pub const MAX_RIVERS: usize = 15;
+/// The solver keeps one bit per boat for which side it's on.
+pub const MAX_BOATS: usize = 16;
+
+pub const LEVELS_DIR: &str = "assets/levels";
+
+/// The built-in layout, used when no level file can be loaded.
+pub const CLASSIC_PATH: &str = "assets/levels/02_classic.ron";
+const CLASSIC_SOURCE: &str = include_str!("../assets/levels/02_classic.ron");
+
+/// A level file as written: entities name their kind, and new kinds may
+/// be declared alongside.
+#[derive(Deserialize)]
+struct LevelFile {
+    name: String,
+    cols: i32,
+    rows: i32,
+    /// A single river, as most levels have; `rivers` lists several.
+    #[serde(default, deserialize_with = "present")]
+    river: Option<(i32, i32)>,
+    #[serde(default, deserialize_with = "present")]
+    left_dock: Option<GridPos>,
+    #[serde(default, deserialize_with = "present")]
+    right_dock: Option<GridPos>,
+    #[serde(default)]
+    rivers: Vec<River>,
+    /// Without a list every river gets one boat for one passenger, moored
+    /// at the river's docks.
+    #[serde(default)]
+    boats: Vec<BoatFile>,
+    player_start: GridPos,
+    entities: Vec<(String, GridPos)>,
+    #[serde(default)]
+    kinds: Vec<EntityKind>,
+    /// One string per row; see `Tile::from_char`. Without a map the river
+    /// columns are water and the rest is grass.
+    #[serde(default)]
+    tiles: Vec<String>,
+    #[serde(default)]
+    loss_script: Option<LossScript>,
+    #[serde(default)]
+    par: Option<u32>,
+    #[serde(default)]
+    hunger: Option<f32>,
+    #[serde(default, deserialize_with = "present")]
+    patrol: Option<PatrolRoute>,
+    #[serde(default)]
+    items: Vec<(Item, GridPos)>,
+    #[serde(default)]
+    current: f32,
+    #[serde(default)]
+    weather: Weather,
+    #[serde(default)]
+    biome: Biome,
+}
+
+/// A boat as written. Its docks default to its river's.
+#[derive(Deserialize)]
+struct BoatFile {
+    river: usize,
+    #[serde(default, deserialize_with = "present")]
+    docks: Option<(GridPos, GridPos)>,
+    #[serde(default = "default_capacity")]
+    capacity: usize,
+    #[serde(default, deserialize_with = "present")]
+    max_load: Option<f32>,
+}
+
+fn default_capacity() -> usize {
+    1
+}
+
+/// Reads a field that's optional by being left out, rather than by
+/// `Some(..)`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// The season or country a level is set in. It picks the tile colors,
+/// trees and water, and leaves the puzzle alone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum Biome {
+    #[default]
+    Summer,
+    Autumn,
+    Winter,
+    Swamp,
+}
+
+/// A level's weather. Everything defaults to off, so clear skies need no
+/// entry in the level file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Weather {
+    /// Rain intensity, 0 to 1.
+    pub rain: f32,
+    /// How thick the fog is beyond the player's surroundings, 0 to 1.
+    pub fog: f32,
+    /// Wind strength, -1 to 1; negative blows toward the left of the
+    /// screen. Slants the rain and whips up the waves.
+    pub wind: f32,
+    /// Extra time crossings take, as a fraction of the usual crossing.
+    pub crossing_delay: f32,
+    /// How dark it is, 0 to 1. Night levels see only as far as the
+    /// farmers' lights reach.
+    pub darkness: f32,
+}
+
+impl Weather {
+    pub fn validate(&self) -> Result<(), String> {
+        let in_range = |value: f32, min: f32| (min..=1.0).contains(&value);
+        let levels = [self.rain, self.fog, self.darkness];
+        if !levels.iter().all(|&value| in_range(value, 0.0)) || !in_range(self.wind, -1.0) {
+            return Err("rain, fog and darkness must be 0 to 1, wind -1 to 1".to_string());
+        }
+        if !(self.crossing_delay >= 0.0 && self.crossing_delay.is_finite()) {
+            return Err(format!(
+                "crossing delay {} must be zero or more",
+                self.crossing_delay
+            ));
+        }
+        Ok(())
+    }
+
+    /// How much longer than usual a crossing takes.
+    pub fn crossing_factor(&self) -> f32 {
+        1.0 + self.crossing_delay
+    }
+}
+
+/// A band of water columns with a dock on either side. River `i` of a
+/// level joins region `i` to region `i + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct River {
+    /// First and last water column, inclusive.
+    pub cols: (i32, i32),
+    /// The docks on its left and right side.
+    pub docks: (GridPos, GridPos),
+}
+
+impl River {
+    fn contains(&self, col: i32) -> bool {
+        (self.cols.0..=self.cols.1).contains(&col)
+    }
+}
+
+/// A boat plying one river between a pair of docks. Boats start moored on
+/// their river's left side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoatDef {
+    pub river: usize,
+    /// The docks on its river's left and right side.
+    pub docks: (GridPos, GridPos),
+    /// How many passengers it takes besides the farmer.
+    pub capacity: usize,
+    /// The weight it carries at full speed, if it's weighed at all. More
+    /// slows it down, and more than `SINKING_LOAD` times as much sinks it.
+    pub max_load: Option<f32>,
+}
+
+/// How far past its `max_load` a boat can be loaded before it sinks.
+pub const SINKING_LOAD: f32 = 1.5;
+
+impl BoatDef {
+    /// How much slower than usual it crosses carrying `load`.
+    pub fn slowdown(&self, load: f32) -> f32 {
+        self.max_load.map_or(1.0, |max| (load / max).max(1.0))
+    }
+
+    /// Whether `load` is too much for it to stay afloat.
+    pub fn sinks_under(&self, load: f32) -> bool {
+        self.max_load.is_some_and(|max| load > max * SINKING_LOAD)
+    }
+}
+
+/// A puzzle layout, loaded from a RON file under `assets/levels/`.
+///
+/// Rivers are bands of columns splitting the grid into regions: the left
+/// bank, any islands, and the right bank everything must reach. Everything
+/// else (docks, starts, rules) is placed on that grid.
+#[derive(Debug, Clone)]
+pub struct LevelDef {
+    pub name: String,
+    pub cols: i32,
+    pub rows: i32,
+    /// Left to right, never empty.
+    pub rivers: Vec<River>,
+    /// Indexed by `BoatId`; every river has at least one.
+    pub boats: Vec<BoatDef>,
+    pub player_start: GridPos,
+    /// The built-in kinds plus any the level declares.
+    pub kinds: KindRegistry,
+    /// Kind and starting tile of each entity. Kinds may repeat.
+    pub entities: Vec<(KindId, GridPos)>,
+    /// Who eats whom, from the kinds' `eats` lists. Checked in order; the
+    /// first matching pair decides the loss.
+    pub eating_rules: Vec<EatingRule>,
+    /// Row-major, `cols * rows` long.
+    tiles: Vec<Tile>,
+    /// Extra losing condition checked after `eating_rules`. Needs the
+    /// `scripting` feature.
+    pub loss_script: Option<LossScript>,
+    /// Player moves a tidy solution takes, shown in the HUD.
+    pub par: Option<u32>,
+    /// Hard mode: seconds an eater left alone takes to grow famished, at
+    /// which point it eats its prey whoever is watching.
+    pub hunger: Option<f32>,
+    /// Night stealth: a wild wolf walking this beat catches any farmer it
+    /// runs into.
+    pub patrol: Option<PatrolRoute>,
+    /// Tools lying on the map for the farmer to pick up.
+    pub items: Vec<(Item, GridPos)>,
+    /// Rows per second the river pushes a crossing boat down the grid; the
+    /// player steers against it to make the dock.
+    pub current: f32,
+    pub weather: Weather,
+    pub biome: Biome,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelError {
+    Parse(String),
+    Invalid(String),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Parse(msg) => write!(f, "could not parse level: {msg}"),
+            LevelError::Invalid(msg) => write!(f, "invalid level: {msg}"),
+        }
+    }
+}
+
+impl LevelDef {
+    /// Parse and validate a level from RON source.
+    pub fn parse(source: &str) -> Result<Self, LevelError> {
+        let file: LevelFile =
+            ron::from_str(source).map_err(|e| LevelError::Parse(e.to_string()))?;
+        let level = Self::resolve(file).map_err(LevelError::Invalid)?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    /// Look up each entity's kind by name and work out the eating rules.
+    fn resolve(file: LevelFile) -> Result<Self, String> {
+        let kinds = KindRegistry::with_extra(file.kinds)?;
+        let eating_rules = kinds.eating_rules()?;
+        let entities = file
+            .entities
+            .into_iter()
+            .map(|(name, pos)| match kinds.find(&name) {
+                Some(kind) => Ok((kind, pos)),
+                None => Err(format!("unknown entity kind \"{name}\"")),
+            })
+            .collect::<Result<_, _>>()?;
+        let rivers = match (file.river, file.left_dock, file.right_dock) {
+            (Some(cols), Some(left), Some(right)) if file.rivers.is_empty() => vec![River {
+                cols,
+                docks: (left, right),
+            }],
+            (None, None, None) if !file.rivers.is_empty() => file.rivers,
+            _ => return Err("give either `river` and both docks, or `rivers`".to_string()),
+        };
+        let boats = if file.boats.is_empty() {
+            rivers
+                .iter()
+                .enumerate()
+                .map(|(i, river)| BoatDef {
+                    river: i,
+                    docks: river.docks,
+                    capacity: 1,
+                    max_load: None,
+                })
+                .collect()
+        } else {
+            file.boats
+                .into_iter()
+                .map(|boat| match rivers.get(boat.river) {
+                    Some(river) => Ok(BoatDef {
+                        river: boat.river,
+                        docks: boat.docks.unwrap_or(river.docks),
+                        capacity: boat.capacity,
+                        max_load: boat.max_load,
+                    }),
+                    None => Err(format!(
+                        "a boat is on river {}, which doesn't exist",
+                        boat.river
+                    )),
+                })
+                .collect::<Result<_, _>>()?
+        };
+        let tiles = if file.tiles.is_empty() {
+            (0..file.rows)
+                .flat_map(|_| 0..file.cols)
+                .map(|col| {
+                    if rivers.iter().any(|river| river.contains(col)) {
+                        Tile::Water
+                    } else {
+                        Tile::Grass
+                    }
+                })
+                .collect()
+        } else {
+            parse_tile_map(&file.tiles, file.cols, file.rows)?
+        };
+        Ok(Self {
+            name: file.name,
+            cols: file.cols,
+            rows: file.rows,
+            rivers,
+            boats,
+            player_start: file.player_start,
+            kinds,
+            entities,
+            eating_rules,
+            tiles,
+            loss_script: file.loss_script,
+            par: file.par,
+            hunger: file.hunger,
+            patrol: file.patrol,
+            items: file.items,
+            current: file.current,
+            weather: file.weather,
+            biome: file.biome,
+        })
+    }
+
+    pub fn kind(&self, id: KindId) -> &EntityKind {
+        self.kinds.get(id)
+    }
+
+    /// The classic riddle, compiled into the binary.
+    pub fn classic() -> Self {
+        Self::parse(CLASSIC_SOURCE).expect("built-in classic level is valid")
+    }
+
+    /// This layout with other kinds and entities, as the generator builds.
+    /// Checked like a level file; par and the loss script are dropped.
+    pub fn with_entities(
+        &self,
+        name: String,
+        kinds: KindRegistry,
+        entities: Vec<(KindId, GridPos)>,
+    ) -> Result<Self, LevelError> {
+        let eating_rules = kinds.eating_rules().map_err(LevelError::Invalid)?;
+        let level = Self {
+            name,
+            kinds,
+            entities,
+            eating_rules,
+            loss_script: None,
+            par: None,
+            ..self.clone()
+        };
+        level.validate()?;
+        Ok(level)
+    }
+
+    fn validate(&self) -> Result<(), LevelError> {
+        let invalid = |msg: String| Err(LevelError::Invalid(msg));
+
+        if self.cols <= 0 || self.rows <= 0 {
+            return invalid(format!(
+                "grid must be non-empty, got {}x{}",
+                self.cols, self.rows
+            ));
+        }
+        if self.rivers.is_empty() || self.rivers.len() > MAX_RIVERS {
+            return invalid(format!(
+                "a level needs 1 to {MAX_RIVERS} rivers, got {}",
+                self.rivers.len()
+            ));
+        }
+        // Each river leaves land on both sides, so rivers never touch.
+        let mut land_from = 0;
+        for &River {
+            cols: (min, max), ..
+        } in &self.rivers
+        {
+            if min <= land_from || max >= self.cols - 1 || min > max {
+                return invalid(format!(
+                    "river columns {min}..={max} must leave land on both sides"
+                ));
+            }
+            land_from = max + 1;
+        }
+        if !(self.current >= 0.0 && self.current.is_finite()) {
+            return invalid(format!("current {} must be zero or more", self.current));
+        }
+        if let Some(seconds) = self.hunger
+            && !(seconds > 0.0 && seconds.is_finite())
+        {
+            return invalid(format!("hunger {seconds} must be more than zero seconds"));
+        }
+        self.weather
+            .validate()
+            .map_err(|e| LevelError::Invalid(format!("weather: {e}")))?;
+        if let Some(route) = &self.patrol {
+            self.validate_patrol(route).map_err(LevelError::Invalid)?;
+        }
+        for &(item, pos) in &self.items {
+            if self.region_of(pos).is_none() || !self.is_land(pos) {
+                return invalid(format!("the {} at {pos} is off land", item.name()));
+            }
+        }
+        for (i, river) in self.rivers.iter().enumerate() {
+            for col in river.cols.0..=river.cols.1 {
+                for row in 0..self.rows {
+                    let pos = GridPos::new(col, row);
+                    if !matches!(self.tile(pos), Tile::Water | Tile::Bridge) {
+                        return invalid(format!("river tile {pos} is not water or bridge"));
+                    }
+                }
+            }
+            if !self.boats.iter().any(|boat| boat.river == i) {
+                return invalid(format!("river {i} has no boat"));
+            }
+        }
+        if self.boats.len() > MAX_BOATS {
+            return invalid(format!(
+                "a level has at most {MAX_BOATS} boats, got {}",
+                self.boats.len()
+            ));
+        }
+        for (i, boat) in self.boats.iter().enumerate() {
+            if boat.capacity == 0 || boat.capacity > MAX_ENTITIES {
+                return invalid(format!(
+                    "boat {i} must take 1 to {MAX_ENTITIES} passengers, not {}",
+                    boat.capacity
+                ));
+            }
+            if let Some(max) = boat.max_load
+                && !(max > 0.0 && max.is_finite())
+            {
+                return invalid(format!("boat {i}'s max load {max} must be more than zero"));
+            }
+            for region in [RegionId(boat.river), RegionId(boat.river + 1)] {
+                let dock = self.dock(i, region);
+                let name = self.region_name(region);
+                if self.region_of(dock) != Some(region) || !self.in_bounds(dock) {
+                    return invalid(format!("dock {dock} is not on the {name}"));
+                }
+                if !self.is_land(dock) {
+                    return invalid(format!("the {name}'s dock {dock} is blocked"));
+                }
+                if self.dock_at(dock).is_some_and(|(other, _)| other != i) {
+                    return invalid(format!("two boats share the dock at {dock}"));
+                }
+            }
+        }
+        if self.region_of(self.player_start).is_none() || !self.is_land(self.player_start) {
+            return invalid(format!("player start {} is not on land", self.player_start));
+        }
+        if self.entities.is_empty() || self.entities.len() > MAX_ENTITIES {
+            return invalid(format!(
+                "a level needs 1 to {MAX_ENTITIES} entities, got {}",
+                self.entities.len()
+            ));
+        }
+        for &(kind, pos) in &self.entities {
+            if !self.is_land(pos) {
+                let name = &self.kind(kind).name;
+                return invalid(format!("the {name} starts off land at {pos}"));
+            }
+        }
+        // Everyone has to be able to walk to a boat.
+        let starts = self.entities.iter().map(|&(_, pos)| pos);
+        for pos in starts.chain([self.player_start]) {
+            let Some(region) = self.region_of(pos) else {
+                continue;
+            };
+            let is_dock = |p: GridPos| self.dock_at(p).is_some_and(|(_, r)| r == region);
+            if world::grid_path(pos, is_dock, |p| self.is_land(p)).is_none() {
+                let name = self.region_name(region);
+                return invalid(format!("{pos} is walled off from the {name}'s docks"));
+            }
+        }
+        Ok(())
+    }
+
+    /// A patrol keeps to the land of one region, and never starts on the
+    /// player.
+    fn validate_patrol(&self, route: &PatrolRoute) -> Result<(), String> {
+        if !(route.step > 0.0 && route.step.is_finite()) {
+            return Err(format!("patrol step {} must be more than zero", route.step));
+        }
+        let tiles = route.tiles()?;
+        let region = self.region_of(tiles[0]);
+        for &pos in &tiles {
+            if region.is_none() || self.region_of(pos) != region || !self.is_land(pos) {
+                return Err(format!("patrol tile {pos} is off its bank's land"));
+            }
+        }
+        if tiles[0] == self.player_start {
+            return Err("the patrol starts on the player".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn in_bounds(&self, pos: GridPos) -> bool {
+        pos.row >= 0 && pos.row < self.rows && pos.col >= 0 && pos.col < self.cols
+    }
+
+    /// What covers a tile. Off the grid is water.
+    pub fn tile(&self, pos: GridPos) -> Tile {
+        if !self.in_bounds(pos) {
+            return Tile::Water;
+        }
+        self.tiles[(pos.row * self.cols + pos.col) as usize]
+    }
+
+    /// Check if the player can walk onto a grid position.
+    pub fn is_walkable(&self, pos: GridPos) -> bool {
+        self.tile(pos).is_walkable()
+    }
+
+    /// Check if an animal or item can stand on a grid position.
+    pub fn is_land(&self, pos: GridPos) -> bool {
+        self.tile(pos).is_land()
+    }
+
+    /// Whether the player can walk from one of `river`'s docks to the other
+    /// without the boat.
+    pub fn has_bridge(&self, river: usize) -> bool {
+        let River { cols, docks } = self.rivers[river];
+        let sides = [Some(RegionId(river)), Some(RegionId(river + 1))];
+        let walkable = |p: GridPos| {
+            self.is_walkable(p)
+                && ((cols.0..=cols.1).contains(&p.col) || sides.contains(&self.region_of(p)))
+        };
+        world::grid_path(docks.0, |p| p == docks.1, walkable).is_some()
+    }
+
+    pub fn trees(&self) -> impl Iterator<Item = GridPos> + '_ {
+        (0..self.rows)
+            .flat_map(move |row| (0..self.cols).map(move |col| GridPos::new(col, row)))
+            .filter(|&pos| self.tile(pos) == Tile::Tree)
+    }
+
+    /// Determine which region a position is on, if any. River columns,
+    /// bridges included, are on none.
+    pub fn region_of(&self, pos: GridPos) -> Option<RegionId> {
+        if pos.col < 0 || pos.col >= self.cols {
+            return None;
+        }
+        if self.rivers.iter().any(|river| river.contains(pos.col)) {
+            return None;
+        }
+        Some(RegionId(
+            self.rivers.iter().filter(|r| r.cols.1 < pos.col).count(),
+        ))
+    }
+
+    /// The far bank, where everything has to end up.
+    pub fn goal_region(&self) -> RegionId {
+        RegionId(self.rivers.len())
+    }
+
+    /// Every region, left to right.
+    pub fn regions(&self) -> impl Iterator<Item = RegionId> + use<> {
+        (0..=self.rivers.len()).map(RegionId)
+    }
+
+    /// The rivers bordering `region`: one for a bank, two for an island.
+    pub fn rivers_of(&self, region: RegionId) -> impl Iterator<Item = usize> + use<> {
+        let count = self.rivers.len();
+        (region.0.saturating_sub(1)..=region.0).filter(move |&i| i < count)
+    }
+
+    /// The region on the other side of `river` from `region`.
+    pub fn across(&self, river: usize, region: RegionId) -> RegionId {
+        if region.0 == river {
+            RegionId(river + 1)
+        } else {
+            RegionId(river)
+        }
+    }
+
+    /// A short name for a region, as loss scripts see it: "left", "right",
+    /// "island", or "island 2" and so on when there are several.
+    pub fn region_label(&self, region: RegionId) -> String {
+        match region.0 {
+            0 => "left".to_string(),
+            r if r == self.rivers.len() => "right".to_string(),
+            _ if self.rivers.len() == 2 => "island".to_string(),
+            r => format!("island {r}"),
+        }
+    }
+
+    /// How the game speaks of a region: "left bank", "island".
+    pub fn region_name(&self, region: RegionId) -> String {
+        match region.0 {
+            0 => "left bank".to_string(),
+            r if r == self.rivers.len() => "right bank".to_string(),
+            _ => self.region_label(region),
+        }
+    }
+
+    /// The land column touching `river` on `region`'s side.
+    pub fn shore_col(&self, river: usize, region: RegionId) -> i32 {
+        let cols = self.rivers[river].cols;
+        if region.0 == river {
+            cols.0 - 1
+        } else {
+            cols.1 + 1
+        }
+    }
+
+    /// Whether a position is on land right by a river.
+    pub fn is_shore(&self, pos: GridPos) -> bool {
+        let Some(region) = self.region_of(pos) else {
+            return false;
+        };
+        self.rivers_of(region)
+            .any(|river| pos.col == self.shore_col(river, region))
+    }
+
+    /// Where the ferryman stands: on the left bank's shore, off the docks,
+    /// in the row furthest from the first boat's dock that nobody starts
+    /// on. None if the whole shore is taken.
+    pub fn ferryman(&self) -> Option<GridPos> {
+        let col = self.shore_col(0, RegionId(0));
+        let dock = self.dock(self.boats_on(0).next()?, RegionId(0));
+        let taken = |pos: GridPos| {
+            self.dock_at(pos).is_some()
+                || pos == self.player_start
+                || self.entities.iter().any(|&(_, start)| start == pos)
+        };
+        (0..self.rows)
+            .map(|row| GridPos::new(col, row))
+            .filter(|&pos| self.is_walkable(pos) && !taken(pos))
+            .max_by_key(|pos| (pos.row - dock.row).abs())
+    }
+
+    /// The column of `river` touching `region`.
+    pub fn river_edge_col(&self, river: usize, region: RegionId) -> i32 {
+        let cols = self.rivers[river].cols;
+        if region.0 == river { cols.0 } else { cols.1 }
+    }
+
+    /// Boat `boat`'s dock on `region`'s side of its river.
+    pub fn dock(&self, boat: usize, region: RegionId) -> GridPos {
+        let BoatDef { river, docks, .. } = self.boats[boat];
+        if region.0 == river { docks.0 } else { docks.1 }
+    }
+
+    /// The boat and region of the dock at `pos`, if there is one.
+    pub fn dock_at(&self, pos: GridPos) -> Option<(usize, RegionId)> {
+        self.boats.iter().enumerate().find_map(|(i, boat)| {
+            if boat.docks.0 == pos {
+                Some((i, RegionId(boat.river)))
+            } else if boat.docks.1 == pos {
+                Some((i, RegionId(boat.river + 1)))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The boats plying `river`.
+    pub fn boats_on(&self, river: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.boats.len()).filter(move |&i| self.boats[i].river == river)
+    }
+
+    /// Bounding box of the whole grid in isometric world coordinates, as
+    /// (min_x, min_y, max_x, max_y) over the outer tile corners.
+    pub fn iso_bounds(&self) -> (f32, f32, f32, f32) {
+        let (left, _) = world::grid_to_iso(GridPos::new(0, self.rows - 1));
+        let (right, _) = world::grid_to_iso(GridPos::new(self.cols - 1, 0));
+        let (_, top) = world::grid_to_iso(GridPos::new(0, 0));
+        let (_, bottom) = world::grid_to_iso(GridPos::new(self.cols - 1, self.rows - 1));
+        (
+            left - world::TILE_WIDTH / 2.0,
+            top - world::TILE_HEIGHT / 2.0,
+            right + world::TILE_WIDTH / 2.0,
+            bottom + world::TILE_HEIGHT / 2.0,
+        )
+    }
+}
+
+fn parse_tile_map(map: &[String], cols: i32, rows: i32) -> Result<Vec<Tile>, String> {
+    if map.len() != rows as usize {
+        return Err(format!("tile map has {} rows, expected {rows}", map.len()));
+    }
+    let mut tiles = Vec::with_capacity((cols * rows) as usize);
+    for (row, line) in map.iter().enumerate() {
+        if line.chars().count() != cols as usize {
+            return Err(format!("tile map row {row} is not {cols} tiles wide"));
+        }
+        for c in line.chars() {
+            let tile =
+                Tile::from_char(c).ok_or_else(|| format!("unknown tile '{c}' in row {row}"))?;
+            tiles.push(tile);
+        }
+    }
+    Ok(tiles)
+}
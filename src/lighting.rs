@@ -0,0 +1,268 @@
+use macroquad::logging::error;
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation, PipelineParams};
+use macroquad::prelude::*;
+
+use crate::anim::AnimState;
+use crate::game::GameState;
+use crate::render;
+use crate::tween::Ease;
+use crate::world::{self, GridPos, Tile};
+
+/// What a level with no light of its own looks like at full darkness.
+const NIGHT: Color = Color::new(0.18, 0.20, 0.38, 1.0);
+/// How dark the day/night cycle gets on its own by nightfall, and when in
+/// the day it starts to.
+const DUSK_DARKNESS: f32 = 0.5;
+const DUSK_FROM: f32 = 0.7;
+
+/// Light radii in world units.
+const LANTERN_RADIUS: f32 = 150.0;
+const LANTERN_COLOR: Color = Color::new(1.0, 0.82, 0.55, 1.0);
+/// What the farmers see by with the lantern out.
+const GLOW_RADIUS: f32 = 55.0;
+const GLOW_COLOR: Color = Color::new(0.45, 0.42, 0.40, 1.0);
+const FIREFLY_RADIUS: f32 = 14.0;
+const FIREFLY_COLOR: Color = Color::new(0.75, 0.95, 0.35, 1.0);
+
+/// Fireflies come out once it's at least this dark.
+const FIREFLY_DARKNESS: f32 = 0.3;
+const FIREFLY_COUNT: usize = 14;
+const FIREFLY_SPEED: f32 = 12.0;
+/// Seconds one firefly keeps glowing before it drifts off and another
+/// takes its place.
+const FIREFLY_LIFE: (f32, f32) = (4.0, 9.0);
+
+const VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+    color = color0 / 255.0;
+}"#;
+
+/// A light drawn as a rectangle: brightest in the middle, gone at the
+/// inscribed circle's edge. Added onto what's already in the light map.
+const LIGHT_FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+void main() {
+    float falloff = clamp(1.0 - length(uv - 0.5) * 2.0, 0.0, 1.0);
+    gl_FragColor = vec4(color.rgb * color.a * falloff * falloff, 1.0);
+}"#;
+
+/// The finished light map, multiplied into the picture.
+const MULTIPLY_FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = vec4(texture2D(Texture, uv).rgb, 1.0);
+}"#;
+
+struct Firefly {
+    pos: Vec2,
+    heading: f32,
+    age: f32,
+    life: f32,
+}
+
+/// Darkness and the lights that push it back. With it dark enough, the
+/// world is drawn as usual and then multiplied by a light map: the
+/// ambient light everywhere, plus the farmers' lantern and glow and any
+/// fireflies about. Off in daylight, and if the platform won't take the
+/// shaders.
+pub struct Lighting {
+    /// None if the platform rejected the shaders.
+    materials: Option<(Material, Material)>,
+    target: Option<RenderTarget>,
+    darkness: f32,
+    fireflies: Vec<Firefly>,
+}
+
+impl Lighting {
+    pub fn new() -> Self {
+        Self {
+            materials: load(),
+            target: None,
+            darkness: 0.0,
+            fireflies: Vec::new(),
+        }
+    }
+
+    /// Follow the level and the sun. Fireflies gather at dusk and drift
+    /// over the grass.
+    pub fn update(&mut self, state: &GameState, dt: f32) {
+        self.darkness = darkness(state);
+        let wanted = if self.darkness >= FIREFLY_DARKNESS {
+            FIREFLY_COUNT
+        } else {
+            0
+        };
+        for fly in &mut self.fireflies {
+            fly.age += dt;
+            fly.heading += rand::gen_range(-2.0, 2.0) * dt;
+            fly.pos += Vec2::from_angle(fly.heading) * FIREFLY_SPEED * dt;
+        }
+        self.fireflies.retain(|fly| fly.age < fly.life);
+        if self.fireflies.len() < wanted {
+            let grass: Vec<GridPos> = (0..state.level.rows)
+                .flat_map(|row| (0..state.level.cols).map(move |col| GridPos::new(col, row)))
+                .filter(|&pos| state.level.tile(pos) == Tile::Grass)
+                .collect();
+            if !grass.is_empty() {
+                let (x, y) = world::grid_to_iso(grass[rand::gen_range(0, grass.len())]);
+                self.fireflies.push(Firefly {
+                    pos: vec2(x, y - rand::gen_range(4.0, 20.0)),
+                    heading: rand::gen_range(0.0, std::f32::consts::TAU),
+                    age: 0.0,
+                    life: rand::gen_range(FIREFLY_LIFE.0, FIREFLY_LIFE.1),
+                });
+            }
+        }
+    }
+
+    /// The target to draw the light map into this frame, with the world
+    /// camera; None while there's nothing to darken.
+    pub fn target(&mut self) -> Option<RenderTarget> {
+        if self.darkness <= 0.0 || self.materials.is_none() {
+            self.target = None;
+            return None;
+        }
+        // The light map is smooth; a quarter of the pixels is plenty.
+        let (width, height) = (
+            (screen_width() as u32 / 2).max(1),
+            (screen_height() as u32 / 2).max(1),
+        );
+        let stale = self.target.as_ref().is_none_or(|target| {
+            let texture = &target.texture;
+            texture.width() as u32 != width || texture.height() as u32 != height
+        });
+        if stale {
+            let target = render_target(width, height);
+            target.texture.set_filter(FilterMode::Linear);
+            self.target = Some(target);
+        }
+        self.target.clone()
+    }
+
+    /// Fill the light map. Call with the world camera pointed at the
+    /// target from `target`.
+    pub fn draw_lights(&self, state: &GameState, anim: &AnimState) {
+        let Some((light, _)) = &self.materials else {
+            return;
+        };
+        let ambient = Color::from_vec(WHITE.to_vec().lerp(NIGHT.to_vec(), self.darkness));
+        clear_background(ambient);
+        gl_use_material(light);
+        let player = render::camera_focus(state, anim);
+        if state.inventory.lantern_lit {
+            // A lantern flickers a little.
+            let flicker = 1.0 + (get_time() as f32 * 13.0).sin() * 0.02;
+            draw_light(player, LANTERN_RADIUS * flicker, LANTERN_COLOR, 1.0);
+        } else {
+            draw_light(player, GLOW_RADIUS, GLOW_COLOR, 1.0);
+        }
+        if let Some(partner) = &anim.partner {
+            draw_light(Vec2::from(partner.pos), GLOW_RADIUS, GLOW_COLOR, 1.0);
+        }
+        for fly in &self.fireflies {
+            // Fade in and out at either end of its life, blinking between.
+            let fade = Ease::SmoothStep.apply((fly.age.min(fly.life - fly.age)).min(1.0));
+            let blink = 0.6 + 0.4 * (fly.age * 5.0 + fly.life).sin();
+            draw_light(fly.pos, FIREFLY_RADIUS, FIREFLY_COLOR, fade * blink);
+        }
+        gl_use_default_material();
+    }
+
+    /// Multiply the light map into the picture over `view`. Call with the
+    /// HUD camera set, after the world and before the HUD.
+    pub fn apply(&self, view: Rect) {
+        let (Some((_, multiply)), Some(target)) = (&self.materials, &self.target) else {
+            return;
+        };
+        gl_use_material(multiply);
+        draw_texture_ex(
+            &target.texture,
+            view.x,
+            view.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(view.size()),
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    }
+}
+
+/// How dark it is, 0 to 1: the level's own darkness, or the cycle's dusk
+/// if that's further along.
+fn darkness(state: &GameState) -> f32 {
+    let dusk = state.day_progress().map_or(0.0, |progress| {
+        DUSK_DARKNESS * Ease::SmoothStep.apply((progress - DUSK_FROM) / (1.0 - DUSK_FROM))
+    });
+    state.level.weather.darkness.max(dusk)
+}
+
+fn draw_light(at: Vec2, radius: f32, color: Color, strength: f32) {
+    let color = Color {
+        a: strength,
+        ..color
+    };
+    draw_rectangle(
+        at.x - radius,
+        at.y - radius,
+        radius * 2.0,
+        radius * 2.0,
+        color,
+    );
+}
+
+fn load() -> Option<(Material, Material)> {
+    let material = |fragment, color_blend| {
+        let params = MaterialParams {
+            pipeline_params: PipelineParams {
+                color_blend: Some(color_blend),
+                // Leave the target's alpha as it is.
+                alpha_blend: Some(BlendState::new(
+                    Equation::Add,
+                    BlendFactor::Zero,
+                    BlendFactor::One,
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let shader = ShaderSource::Glsl {
+            vertex: VERTEX,
+            fragment,
+        };
+        load_material(shader, params)
+    };
+    let add = BlendState::new(Equation::Add, BlendFactor::One, BlendFactor::One);
+    let multiply = BlendState::new(
+        Equation::Add,
+        BlendFactor::Value(BlendValue::DestinationColor),
+        BlendFactor::Zero,
+    );
+    material(LIGHT_FRAGMENT, add)
+        .and_then(|light| Ok((light, material(MULTIPLY_FRAGMENT, multiply)?)))
+        .inspect_err(|e| error!("lighting shader: {e}; lighting is off"))
+        .ok()
+}
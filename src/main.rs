@@ -1,86 +1,188 @@
+// Without rendering, only the solver's slice of the game logic is used.
+#![cfg_attr(not(feature = "render"), allow(dead_code))]
+
+#[cfg(feature = "render")]
 use macroquad::prelude::*;
 
-mod anim;
+mod cli;
+mod events;
 mod game;
+mod inventory;
+mod kinds;
+mod level;
+mod mutators;
+mod patrol;
+mod rng;
+mod script;
+mod solver;
+mod upgrades;
+mod world;
+
+// The game itself, which needs a window.
+#[cfg(feature = "render")]
+mod ambience;
+#[cfg(feature = "render")]
+mod anim;
+#[cfg(feature = "render")]
+mod app;
+#[cfg(feature = "render")]
+mod audio;
+#[cfg(feature = "render")]
+mod camera;
+#[cfg(feature = "render")]
+mod campaign;
+#[cfg(feature = "render")]
+mod clips;
+#[cfg(feature = "render")]
+mod console;
+#[cfg(feature = "render")]
+mod cutscene;
+#[cfg(feature = "render")]
+mod debug_overlay;
+#[cfg(feature = "render")]
+mod dialogue;
+#[cfg(feature = "render")]
+mod emote;
+#[cfg(feature = "render")]
+mod endless;
+#[cfg(feature = "render")]
+mod frame_advance;
+#[cfg(all(feature = "rumble", not(target_arch = "wasm32")))]
+mod gamepad;
+#[cfg(feature = "render")]
+mod generator;
+#[cfg(feature = "render")]
+mod ghost;
+#[cfg(all(feature = "render", not(target_arch = "wasm32")))]
+mod hot_reload;
+#[cfg(all(feature = "leaderboard", not(target_arch = "wasm32")))]
+mod http;
+#[cfg(feature = "render")]
+mod hud;
+#[cfg(feature = "render")]
 mod input;
+#[cfg(feature = "render")]
 mod interaction;
+#[cfg(feature = "render")]
+mod job;
+#[cfg(feature = "render")]
+mod leaderboard;
+#[cfg(feature = "render")]
+mod lighting;
+#[cfg(feature = "render")]
+mod narrator;
+#[cfg(feature = "render")]
+mod outline;
+#[cfg(feature = "render")]
+mod pacing;
+#[cfg(feature = "render")]
+mod particles;
+#[cfg(feature = "render")]
+mod pause;
+#[cfg(feature = "render")]
+mod postfx;
+#[cfg(feature = "render")]
+mod profiler;
+#[cfg(feature = "render")]
+mod race;
+#[cfg(feature = "render")]
 mod render;
-mod world;
+#[cfg(feature = "render")]
+mod replay;
+#[cfg(feature = "render")]
+mod savestate;
+#[cfg(feature = "render")]
+mod screens;
+#[cfg(feature = "render")]
+mod settings;
+#[cfg(all(feature = "tts", not(target_arch = "wasm32")))]
+mod speech;
+#[cfg(feature = "render")]
+mod speedrun;
+#[cfg(feature = "render")]
+mod stats;
+#[cfg(feature = "render")]
+mod storage;
+#[cfg(feature = "render")]
+mod text;
+#[cfg(feature = "render")]
+mod theme;
+#[cfg(feature = "render")]
+mod timestep;
+#[cfg(feature = "render")]
+mod touch;
+#[cfg(feature = "render")]
+mod tween;
+#[cfg(feature = "render")]
+mod undo;
+#[cfg(feature = "render")]
+mod water;
+#[cfg(feature = "render")]
+mod weather;
+#[cfg(feature = "render")]
+mod wipe;
 
-fn window_conf() -> Conf {
+/// The smallest window the game keeps to; a window dragged smaller is
+/// grown back.
+const MIN_WINDOW_SIZE: (f32, f32) = (640.0, 360.0);
+
+#[cfg(feature = "render")]
+fn window_conf(args: &cli::Args) -> Conf {
+    let window = settings::Settings::load().window;
+    let (min_width, min_height) = MIN_WINDOW_SIZE;
     Conf {
         window_title: "River Crossing".to_string(),
-        window_width: 1920,
-        window_height: 1080,
+        window_width: (window.width as f32).max(min_width) as i32,
+        window_height: (window.height as f32).max(min_height) as i32,
+        window_resizable: true,
+        fullscreen: args.fullscreen.unwrap_or(window.fullscreen),
+        platform: miniquad::conf::Platform {
+            swap_interval: Some(if window.vsync { 1 } else { 0 }),
+            ..Default::default()
+        },
         ..Default::default()
     }
 }
 
-
-#[macroquad::main(window_conf)]
-async fn main() {
-    // #[cfg(target_arch = "wasm32")]
-    // console_error_panic_hook::set_once();
-    let atlas = render::SpriteAtlas::load().await;
-    let mut state = game::GameState::new();
-    let mut anim = anim::AnimState::new();
-    let mut input_state = input::InputState::new();
-
-    loop {
-        let dt = get_frame_time();
-        let time = get_time() as f32;
-
-        // --- INPUT ---
-        let event = input_state.poll(dt);
-
-        // --- UPDATE ---
-        match state.phase {
-            game::GamePhase::Playing => {
-                match event {
-                    input::InputEvent::Move(dir) => {
-                        state.try_move_player(dir);
-                    }
-                    input::InputEvent::Interact => {
-                        if let Some(action) = interaction::resolve_interaction(&state) {
-                            state.execute_action(action);
-                            if state.check_win() {
-                                state.phase = game::GamePhase::Won;
-                            }
-                        }
-                    }
-                    input::InputEvent::CrossRiver => {
-                        if state.start_crossing() {
-                            if let Some(reason) = state.check_eating_rules() {
-                                state.phase = game::GamePhase::Lost(reason);
-                            }
-                        }
-                    }
-                    input::InputEvent::Restart => {
-                        state.reset();
-                        anim.reset();
-                    }
-                    input::InputEvent::None => {}
-                }
-
-                state.update_crossing(dt);
-                anim.update(&state, dt);
-            }
-            game::GamePhase::Won | game::GamePhase::Lost(_) => {
-                if event == input::InputEvent::Restart {
-                    state.reset();
-                    anim.reset();
-                }
+fn main() {
+    let args = match cli::Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    // The solver needs no window.
+    if let Some(path) = &args.solve {
+        match cli::solve(path) {
+            Ok(report) => println!("{report}"),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
             }
         }
+        return;
+    }
+    #[cfg(feature = "render")]
+    macroquad::Window::from_config(window_conf(&args), run(args));
+    #[cfg(not(feature = "render"))]
+    {
+        eprintln!("built without the render feature; only --solve is available");
+        std::process::exit(2);
+    }
+}
 
-        // --- RENDER ---
-        clear_background(Color::new(0.05, 0.06, 0.12, 1.0));
-        render::setup_camera();
-        render::draw_world(&state, &atlas, &anim, time);
-        render::draw_hud(&state);
-
-        set_default_camera();
-
-        next_frame().await
+#[cfg(feature = "render")]
+async fn run(args: cli::Args) {
+    // #[cfg(target_arch = "wasm32")]
+    // console_error_panic_hook::set_once();
+    text::load_font().await;
+    let mut app = app::App::load(args).await;
+    while app.frame().await {
+        next_frame().await;
     }
 }
@@ -0,0 +1,227 @@
+use std::fmt::Debug;
+
+use crate::game::{GameState, LoseReason};
+
+/// Crossings the limited-crossings mutator allows beyond the fewest the
+/// level can be solved in.
+const SPARE_CROSSINGS: u32 = 2;
+/// Seconds to famished that faster hunger gives a level with no hunger of
+/// its own, and how much quicker it makes a level's own.
+const HUNGER_SECONDS: f32 = 40.0;
+const HUNGER_SPEEDUP: f32 = 2.0;
+
+/// A challenge modifier, picked before starting a level. Each hooks in
+/// where it changes the game; the defaults leave the game as it is.
+pub trait Mutator: Debug + Sync {
+    /// Its name in the menu and the HUD.
+    fn name(&self) -> &'static str;
+    /// What it does, in a line.
+    fn describe(&self) -> &'static str;
+    /// Set up a run that has just been reset.
+    fn begin(&self, _state: &mut GameState) {}
+    /// Seconds an unattended eater takes to grow famished, given the
+    /// level's own.
+    fn hunger(&self, seconds: Option<f32>) -> Option<f32> {
+        seconds
+    }
+    /// A further way to lose, checked every tick of play.
+    fn check(&self, _state: &GameState) -> Option<LoseReason> {
+        None
+    }
+    /// How the run stands against it, for the HUD.
+    fn status(&self, _state: &GameState) -> Option<String> {
+        None
+    }
+    fn reverses_controls(&self) -> bool {
+        false
+    }
+    /// Only the banks a farmer can see are drawn.
+    fn fogs(&self) -> bool {
+        false
+    }
+    fn allows_undo(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+struct LimitedCrossings;
+
+impl LimitedCrossings {
+    /// The most crossings the run may make; None until the fewest are
+    /// known.
+    fn limit(state: &GameState) -> Option<u32> {
+        state.min_crossings.map(|min| min + SPARE_CROSSINGS)
+    }
+}
+
+impl Mutator for LimitedCrossings {
+    fn name(&self) -> &'static str {
+        "Limited crossings"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Only two crossings to spare over the best solution"
+    }
+
+    fn check(&self, state: &GameState) -> Option<LoseReason> {
+        let limit = Self::limit(state)?;
+        // Lost on setting out on one too many, not on finishing the last.
+        let setting_out = state.crossing_boat().is_some() || state.is_on_bridge();
+        (setting_out && state.crossing_count >= limit).then_some(LoseReason::OutOfCrossings)
+    }
+
+    fn status(&self, state: &GameState) -> Option<String> {
+        let limit = Self::limit(state)?;
+        let left = limit.saturating_sub(state.crossing_count);
+        Some(format!("Crossings left: {left}"))
+    }
+}
+
+#[derive(Debug)]
+struct FogOfWar;
+
+impl Mutator for FogOfWar {
+    fn name(&self) -> &'static str {
+        "Fog of war"
+    }
+
+    fn describe(&self) -> &'static str {
+        "You only see the bank you're on"
+    }
+
+    fn fogs(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+struct FasterHunger;
+
+impl Mutator for FasterHunger {
+    fn name(&self) -> &'static str {
+        "Faster hunger"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Eaters left alone go famished twice as fast, on every level"
+    }
+
+    fn hunger(&self, seconds: Option<f32>) -> Option<f32> {
+        Some(seconds.map_or(HUNGER_SECONDS, |seconds| seconds / HUNGER_SPEEDUP))
+    }
+}
+
+#[derive(Debug)]
+struct ReversedControls;
+
+impl Mutator for ReversedControls {
+    fn name(&self) -> &'static str {
+        "Reversed controls"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Every direction key goes the other way"
+    }
+
+    fn reverses_controls(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+struct NoUndo;
+
+impl Mutator for NoUndo {
+    fn name(&self) -> &'static str {
+        "No undo"
+    }
+
+    fn describe(&self) -> &'static str {
+        "No rewinding a loss and no loading savestates"
+    }
+
+    fn allows_undo(&self) -> bool {
+        false
+    }
+}
+
+/// Every mutator, in menu order.
+pub const ALL: [&dyn Mutator; 5] = [
+    &LimitedCrossings,
+    &FogOfWar,
+    &FasterHunger,
+    &ReversedControls,
+    &NoUndo,
+];
+
+/// The mutators a run is played under: one bit for each of `ALL`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Mutators(u8);
+
+impl Mutators {
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether `ALL[index]` is on.
+    pub fn is_on(self, index: usize) -> bool {
+        self.0 & (1 << index) != 0
+    }
+
+    pub fn toggle(&mut self, index: usize) {
+        self.0 ^= 1 << index;
+    }
+
+    /// The ones that are on.
+    pub fn iter(self) -> impl Iterator<Item = &'static dyn Mutator> {
+        (0..ALL.len())
+            .filter(move |&i| self.is_on(i))
+            .map(|i| ALL[i])
+    }
+
+    /// The ones named in `names`; names it doesn't know are left out.
+    pub fn from_names(names: &[String]) -> Self {
+        let mut mutators = Self::default();
+        for (i, mutator) in ALL.iter().enumerate() {
+            if names.iter().any(|name| name == mutator.name()) {
+                mutators.toggle(i);
+            }
+        }
+        mutators
+    }
+
+    pub fn names(self) -> Vec<String> {
+        self.iter().map(|m| m.name().to_string()).collect()
+    }
+
+    pub fn begin(self, state: &mut GameState) {
+        for mutator in self.iter() {
+            mutator.begin(state);
+        }
+    }
+
+    pub fn hunger(self, seconds: Option<f32>) -> Option<f32> {
+        self.iter().fold(seconds, |seconds, m| m.hunger(seconds))
+    }
+
+    pub fn check(self, state: &GameState) -> Option<LoseReason> {
+        self.iter().find_map(|m| m.check(state))
+    }
+
+    pub fn statuses(self, state: &GameState) -> Vec<String> {
+        self.iter().filter_map(|m| m.status(state)).collect()
+    }
+
+    pub fn reverses_controls(self) -> bool {
+        self.iter().any(|m| m.reverses_controls())
+    }
+
+    pub fn fogs(self) -> bool {
+        self.iter().any(|m| m.fogs())
+    }
+
+    pub fn allows_undo(self) -> bool {
+        self.iter().all(|m| m.allows_undo())
+    }
+}
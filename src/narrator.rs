@@ -0,0 +1,388 @@
+use std::collections::VecDeque;
+
+use crate::game::{
+    BoatState, EntityId, EntityInstance, EntityLocation, GamePhase, GameState, PlayerLocation,
+};
+use crate::inventory::Item;
+use crate::kinds::EntityKind;
+use crate::level::LevelDef;
+use crate::world::RegionId;
+
+/// Announcements kept for the log panel.
+const LOG_LEN: usize = 5;
+
+/// The facts the narrator talks about. Compared frame to frame; anything
+/// purely cosmetic (crossing progress, grid position) is left out so that
+/// only meaningful changes are announced.
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    phase: GamePhase,
+    on_boat: bool,
+    player_region: Option<RegionId>,
+    follower: Option<EntityId>,
+    /// Indexed by `BoatId`.
+    boats: Vec<BoatSnapshot>,
+    /// Every boat's cargo, by `BoatId`.
+    cargo: Vec<Vec<EntityId>>,
+    /// How hungry each entity is: 0 until half hungry, 1, then 2 once
+    /// famished.
+    hunger: Vec<u8>,
+    /// How many of each item the farmers carry, in `Item::ALL` order.
+    items: Vec<u32>,
+    lantern_lit: bool,
+    /// Tied up or busy with a fish, by entity.
+    restrained: Vec<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BoatSnapshot {
+    Docked(RegionId),
+    Crossing(RegionId),
+}
+
+impl Snapshot {
+    fn capture(state: &GameState) -> Self {
+        let boats = state.boats.iter().map(|boat| match boat.state {
+            BoatState::Docked(region) => BoatSnapshot::Docked(region),
+            BoatState::Crossing { from, .. } => BoatSnapshot::Crossing(from),
+        });
+        Self {
+            phase: state.phase,
+            on_boat: state.player_boat().is_some(),
+            player_region: state.player.region(&state.level),
+            follower: state.player.follower,
+            boats: boats.collect(),
+            cargo: state.boats.iter().map(|boat| boat.cargo.clone()).collect(),
+            hunger: state
+                .entities
+                .iter()
+                .map(|e| (e.hunger >= 0.5) as u8 + (e.hunger >= 1.0) as u8)
+                .collect(),
+            items: Item::ALL
+                .iter()
+                .map(|&item| state.inventory.count(item))
+                .collect(),
+            lantern_lit: state.inventory.lantern_lit,
+            restrained: state
+                .entities
+                .iter()
+                .map(|e| state.is_restrained(e.id))
+                .collect(),
+        }
+    }
+}
+
+/// Announces state changes as plain sentences for screen readers.
+/// Output goes to the sink (stdout, or speech with the `tts` feature), and
+/// the latest lines are kept for the on-screen log.
+pub struct Narrator {
+    enabled: bool,
+    last: Option<Snapshot>,
+    sink: Box<dyn FnMut(&str)>,
+    /// A description of the whole scene, redone whenever something changes.
+    scene: String,
+    log: VecDeque<String>,
+}
+
+impl Narrator {
+    /// Print to stdout, and speak too in builds with text-to-speech.
+    pub fn new() -> Self {
+        #[cfg(all(feature = "tts", not(target_arch = "wasm32")))]
+        let sink = crate::speech::sink();
+        #[cfg(not(all(feature = "tts", not(target_arch = "wasm32"))))]
+        let sink = |line: &str| println!("{line}");
+        Self::with_sink(sink)
+    }
+
+    /// Create a narrator that hands every announcement to `sink`.
+    pub fn with_sink(sink: impl FnMut(&str) + 'static) -> Self {
+        Self {
+            enabled: false,
+            last: None,
+            sink: Box::new(sink),
+            scene: String::new(),
+            log: VecDeque::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle(&mut self, state: &GameState) {
+        self.set_enabled(!self.enabled, state);
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool, state: &GameState) {
+        if enabled == self.enabled {
+            return;
+        }
+        self.enabled = enabled;
+        if self.enabled {
+            self.say(&format!("Narration on. Level: {}.", state.level.name));
+            self.scene = describe_scene(state);
+            let summary = self.scene.clone();
+            self.say(&summary);
+        } else {
+            self.say("Narration off.");
+            self.log.clear();
+        }
+        self.last = Some(Snapshot::capture(state));
+    }
+
+    /// The current scene description, while narration is on.
+    pub fn scene(&self) -> Option<&str> {
+        self.enabled.then_some(self.scene.as_str())
+    }
+
+    /// Recent announcements, oldest first.
+    pub fn log(&self) -> impl Iterator<Item = &str> {
+        self.log.iter().map(String::as_str)
+    }
+
+    /// Compare against the previous frame and announce what changed.
+    pub fn observe(&mut self, state: &GameState) {
+        let now = Snapshot::capture(state);
+        let Some(before) = self.last.replace(now.clone()) else {
+            return;
+        };
+        if !self.enabled || before == now {
+            return;
+        }
+
+        self.scene = describe_scene(state);
+        for line in describe_changes(&before, &now, state) {
+            self.say(&line);
+        }
+    }
+
+    fn say(&mut self, line: &str) {
+        (self.sink)(line);
+        if self.log.len() == LOG_LEN {
+            self.log.pop_front();
+        }
+        self.log.push_back(line.to_string());
+    }
+}
+
+fn describe_changes(before: &Snapshot, now: &Snapshot, state: &GameState) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if before.phase != now.phase {
+        match now.phase {
+            GamePhase::Menu => {
+                lines.push("Level select.".to_string());
+                return lines;
+            }
+            GamePhase::Paused => {
+                lines.push("Paused.".to_string());
+                return lines;
+            }
+            GamePhase::Playing if before.phase == GamePhase::Paused => {
+                lines.push("Resumed.".to_string());
+                return lines;
+            }
+            GamePhase::Playing => {
+                lines.push(format!("New game: {}.", state.level.name));
+                lines.push(describe_scene(state));
+                return lines;
+            }
+            GamePhase::Won => lines.push("Everything is across. You win!".to_string()),
+            GamePhase::Lost(reason) => {
+                lines.push(format!("{} You lose.", reason.message(&state.level)))
+            }
+        }
+    }
+
+    match (before.follower, now.follower) {
+        (None, Some(e)) => lines.push(pick_up_line(state.kind(e))),
+        (Some(e), None) if now.cargo.iter().any(|cargo| cargo.contains(&e)) => {
+            let name = &state.kind(e).name;
+            lines.push(format!("You loaded the {name} onto the boat."));
+        }
+        (Some(e), None) => {
+            if let EntityLocation::OnBank { region, .. } = state.entity_location(e) {
+                lines.push(format!(
+                    "You left the {} on the {}.",
+                    state.kind(e).name,
+                    state.level.region_name(region)
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    for (was, is) in before.cargo.iter().zip(&now.cargo) {
+        for &e in was {
+            if !is.contains(&e) && now.follower != Some(e) {
+                let name = &state.kind(e).name;
+                lines.push(format!("You unloaded the {name} from the boat."));
+            }
+        }
+    }
+
+    match (before.on_boat, now.on_boat) {
+        (false, true) => lines.push("You boarded the boat.".to_string()),
+        (true, false) => {
+            if let Some(region) = now.player_region {
+                let name = state.level.region_name(region);
+                lines.push(format!("You stepped off onto the {name}."));
+            }
+        }
+        _ => {}
+    }
+
+    let name = |region: RegionId| state.level.region_name(region);
+    for (&was, &is) in before.boats.iter().zip(&now.boats) {
+        match (was, is) {
+            (BoatSnapshot::Docked(_), BoatSnapshot::Crossing(from)) => {
+                lines.push(format!("The boat sets off from the {}.", name(from)));
+            }
+            (BoatSnapshot::Crossing(a), BoatSnapshot::Crossing(b)) if a != b => {
+                lines.push(format!(
+                    "The current swept the boat past the dock; it drifts back to the {}.",
+                    name(a)
+                ));
+            }
+            (BoatSnapshot::Crossing(_), BoatSnapshot::Docked(region)) => {
+                lines.push(format!(
+                    "Boat docked at the {}; {}",
+                    name(region),
+                    describe_banks(state)
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for (i, (&was, &is)) in before.hunger.iter().zip(&now.hunger).enumerate() {
+        let name = &state.kind(EntityId(i)).name;
+        match is {
+            1 if was == 0 => lines.push(format!("The {name} is getting hungry.")),
+            2 if was < 2 => lines.push(format!("The {name} is famished!")),
+            _ => {}
+        }
+    }
+
+    for (&item, (&was, &is)) in Item::ALL.iter().zip(before.items.iter().zip(&now.items)) {
+        if is > was {
+            lines.push(format!("You found a {}.", item.name()));
+        }
+    }
+    if before.lantern_lit != now.lantern_lit {
+        let line = if now.lantern_lit {
+            "You lit the lantern."
+        } else {
+            "You put out the lantern."
+        };
+        lines.push(line.to_string());
+    }
+    for (i, (&was, &is)) in before.restrained.iter().zip(&now.restrained).enumerate() {
+        let entity = &state.entities[i];
+        let name = &state.kind(entity.id).name;
+        match (was, is) {
+            (false, true) if state.crossing_count < entity.tied_until => {
+                lines.push(format!("You tied up the {name}."));
+            }
+            (false, true) => lines.push(format!("The {name} is busy with a fish.")),
+            (true, false) => lines.push(format!("The {name} is loose again.")),
+            _ => {}
+        }
+    }
+
+    lines
+}
+
+fn pick_up_line(kind: &EntityKind) -> String {
+    if kind.alive {
+        format!("The {} is following you.", kind.name)
+    } else {
+        format!("You picked up the {}.", kind.name)
+    }
+}
+
+/// A full description of the scene, used when narration starts or the game
+/// restarts.
+fn describe_scene(state: &GameState) -> String {
+    let level = &state.level;
+    let player = match state.player.location {
+        PlayerLocation::OnLand(pos) => match level.region_of(pos) {
+            Some(region) => format!("You are on the {}.", level.region_name(region)),
+            None => "You are by the river.".to_string(),
+        },
+        PlayerLocation::OnBoat(_) => "You are on the boat.".to_string(),
+    };
+    let mut text = player;
+    for boat in &state.boats {
+        let line = match boat.state {
+            BoatState::Docked(region) => {
+                format!(" A boat is docked at the {}.", level.region_name(region))
+            }
+            BoatState::Crossing { .. } => " A boat is crossing.".to_string(),
+        };
+        text.push_str(&line);
+    }
+    text.push_str(&format!(" {}", capitalize(&describe_banks(state))));
+    if let Some(e) = state.player.follower {
+        text.push_str(&format!(" The {} is with you.", state.kind(e).name));
+    }
+    for &e in state.boats.iter().flat_map(|boat| &boat.cargo) {
+        text.push_str(&format!(" The {} is on a boat.", state.kind(e).name));
+    }
+    text
+}
+
+/// "the wolf and the cabbage are on the left bank; the sheep is on the right bank."
+fn describe_banks(state: &GameState) -> String {
+    let parts: Vec<String> = state
+        .level
+        .regions()
+        .filter_map(|region| {
+            let here = state.entities_in(region);
+            if here.is_empty() {
+                return None;
+            }
+            let verb = if here.len() == 1 { "is" } else { "are" };
+            Some(format!(
+                "{} {verb} on the {}",
+                list_entities(&state.level, &here),
+                state.level.region_name(region)
+            ))
+        })
+        .collect();
+
+    if parts.is_empty() {
+        "nothing is left on any bank.".to_string()
+    } else {
+        format!("{}.", parts.join("; "))
+    }
+}
+
+/// Names grouped by kind: "the wolf and 2 sheep".
+fn list_entities(level: &LevelDef, entities: &[&EntityInstance]) -> String {
+    let names: Vec<String> = level
+        .kinds
+        .ids()
+        .filter_map(|kind| {
+            let count = entities.iter().filter(|e| e.kind == kind).count();
+            match count {
+                0 => None,
+                1 => Some(format!("the {}", level.kind(kind).name)),
+                n => Some(format!("{n} {}", level.kind(kind).plural())),
+            }
+        })
+        .collect();
+    match names.as_slice() {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,135 @@
+use std::cell::OnceCell;
+
+use macroquad::logging::error;
+use macroquad::prelude::*;
+
+/// Outline thickness, in sprite texels.
+const THICKNESS: f32 = 1.0;
+
+const VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+}"#;
+
+/// Lights the transparent texels next to opaque ones. Anything outside
+/// `bounds` counts as transparent, so neighbors in the atlas don't bleed in.
+const FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 texel;
+uniform vec4 bounds;
+uniform vec4 color;
+uniform float time;
+
+float alpha_at(vec2 at) {
+    if (at.x < bounds.x || at.y < bounds.y || at.x > bounds.z || at.y > bounds.w) {
+        return 0.0;
+    }
+    return texture2D(Texture, at).a;
+}
+
+void main() {
+    if (alpha_at(uv) > 0.5) {
+        discard;
+    }
+    float near = 0.0;
+    for (int dx = -1; dx <= 1; dx++) {
+        for (int dy = -1; dy <= 1; dy++) {
+            near = max(near, alpha_at(uv + vec2(float(dx), float(dy)) * texel));
+        }
+    }
+    if (near < 0.5) {
+        discard;
+    }
+    // A glint running around the sprite on top of a slower pulse.
+    float pulse = 0.75 + 0.25 * sin(time * 5.0);
+    float glint = 0.5 + 0.5 * sin(time * 8.0 - (uv.x + uv.y) / texel.x * 0.3);
+    gl_FragColor = vec4(color.rgb + vec3(0.35) * glint, color.a * pulse);
+}"#;
+
+thread_local! {
+    /// The outline shader, compiled on first use. None if the platform
+    /// rejected it.
+    static MATERIAL: OnceCell<Option<Material>> = const { OnceCell::new() };
+}
+
+fn load() -> Option<Material> {
+    let params = MaterialParams {
+        uniforms: vec![
+            UniformDesc::new("texel", UniformType::Float2),
+            UniformDesc::new("bounds", UniformType::Float4),
+            UniformDesc::new("color", UniformType::Float4),
+            UniformDesc::new("time", UniformType::Float1),
+        ],
+        ..Default::default()
+    };
+    let shader = ShaderSource::Glsl {
+        vertex: VERTEX,
+        fragment: FRAGMENT,
+    };
+    load_material(shader, params)
+        .inspect_err(|e| error!("outline shader: {e}; highlights are off"))
+        .ok()
+}
+
+/// Draw an animated `color` outline around the part of `texture` in
+/// `source`, for a sprite drawn at `dest`. Only the ring outside the
+/// sprite is drawn, so this can go before or after the sprite itself.
+pub fn draw_outline(
+    texture: &Texture2D,
+    source: Rect,
+    dest: Rect,
+    flip_x: bool,
+    color: Color,
+    time: f32,
+) {
+    let Some(material) = MATERIAL.with(|cell| cell.get_or_init(load).clone()) else {
+        return;
+    };
+    let size = texture.size();
+    let scale = dest.w / source.w;
+    let pad = THICKNESS * scale;
+
+    gl_use_material(&material);
+    material.set_uniform("texel", THICKNESS / size);
+    let bounds = vec4(
+        source.x / size.x,
+        source.y / size.y,
+        source.right() / size.x,
+        source.bottom() / size.y,
+    );
+    material.set_uniform("bounds", bounds);
+    material.set_uniform("color", vec4(color.r, color.g, color.b, color.a));
+    material.set_uniform("time", time);
+    draw_texture_ex(
+        texture,
+        dest.x - pad,
+        dest.y - pad,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(vec2(dest.w + pad * 2.0, dest.h + pad * 2.0)),
+            source: Some(Rect::new(
+                source.x - THICKNESS,
+                source.y - THICKNESS,
+                source.w + THICKNESS * 2.0,
+                source.h + THICKNESS * 2.0,
+            )),
+            flip_x,
+            ..Default::default()
+        },
+    );
+    gl_use_default_material();
+}
@@ -0,0 +1,65 @@
+use macroquad::prelude::*;
+
+use crate::settings::WindowSettings;
+
+/// Frame rates the settings screen cycles through; 0 is uncapped.
+pub const FPS_CAPS: [u32; 5] = [0, 30, 60, 120, 144];
+/// Seconds without any input, and with nothing moving on screen, before
+/// low-power mode counts the window as left alone. Macroquad doesn't say
+/// when the window loses focus, so this stands in for it.
+const IDLE_SECONDS: f32 = 10.0;
+/// The frame rate a window left alone drops to in low-power mode.
+const IDLE_FPS: u32 = 10;
+
+/// Keeps frames from coming faster than the settings allow, sleeping off
+/// whatever is left of each frame's budget instead of drawing again.
+pub struct FramePacer {
+    frame_start: f64,
+    idle: f32,
+}
+
+impl FramePacer {
+    pub fn new() -> Self {
+        Self {
+            frame_start: get_time(),
+            idle: 0.0,
+        }
+    }
+
+    /// Whether low-power mode has slowed the game down.
+    pub fn is_idle(&self, window: &WindowSettings) -> bool {
+        window.low_power && self.idle >= IDLE_SECONDS
+    }
+
+    /// Call at the top of each frame: waits until the last one has had its
+    /// full budget. `animating` keeps the game awake without input, say
+    /// through a cutscene. In the browser, the page paces frames itself.
+    pub fn pace(&mut self, window: &WindowSettings, animating: bool, dt: f32) {
+        let active = animating
+            || !get_keys_down().is_empty()
+            || mouse_delta_position() != Vec2::ZERO
+            || mouse_wheel() != (0.0, 0.0)
+            || !touches().is_empty();
+        self.idle = if active { 0.0 } else { self.idle + dt };
+        let fps = if self.is_idle(window) {
+            IDLE_FPS
+        } else {
+            window.fps_cap
+        };
+        if fps > 0 {
+            sleep_until(self.frame_start + 1.0 / fps as f64);
+        }
+        self.frame_start = get_time();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn sleep_until(time: f64) {
+    let left = time - get_time();
+    if left > 0.0 {
+        std::thread::sleep(std::time::Duration::from_secs_f64(left));
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn sleep_until(_time: f64) {}
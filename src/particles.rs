@@ -0,0 +1,346 @@
+use macroquad::prelude::*;
+
+use crate::anim::AnimState;
+use crate::events::GameEvent;
+use crate::game::{GameState, PlayerId, PlayerLocation};
+
+const POOL_SIZE: usize = 512;
+/// Seconds between ripples behind the boat at normal crossing speed.
+const RIPPLE_INTERVAL: f32 = 0.18;
+/// Seconds between pairs of wake ripples peeling off the stern.
+const WAKE_INTERVAL: f32 = 0.12;
+/// How far behind the boat's centre the wake starts.
+const STERN_OFFSET: f32 = 18.0;
+/// Sideways speed of the wake, spreading it into a V.
+const WAKE_SPREAD_SPEED: f32 = 14.0;
+/// A streak is as long as the distance its particle covers in this time.
+const STREAK_SECONDS: f32 = 0.03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Circle,
+    Square,
+    /// A flat ellipse outline lying on the water.
+    Ring,
+    /// A short line trailing behind the particle's motion.
+    Streak,
+}
+
+/// Where particles sort among the sprites standing in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// Flat on the ground or the water, under everything standing on it.
+    Ground,
+    /// By how far down the screen they are, like any sprite.
+    Sorted,
+    /// Up in the air, over everything.
+    Air,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Particle {
+    pos: Vec2,
+    vel: Vec2,
+    /// Seconds left; dead at zero or below.
+    life: f32,
+    max_life: f32,
+    size: f32,
+    /// Change in size per second.
+    growth: f32,
+    gravity: f32,
+    color: Color,
+    shape: Shape,
+    layer: Layer,
+}
+
+impl Particle {
+    const DEAD: Particle = Particle {
+        pos: Vec2::ZERO,
+        vel: Vec2::ZERO,
+        life: 0.0,
+        max_life: 1.0,
+        size: 0.0,
+        growth: 0.0,
+        gravity: 0.0,
+        color: WHITE,
+        shape: Shape::Circle,
+        layer: Layer::Air,
+    };
+
+    fn is_alive(&self) -> bool {
+        self.life > 0.0
+    }
+
+    /// Where it sorts in the world's render queue.
+    pub fn depth(&self) -> f32 {
+        match self.layer {
+            Layer::Ground => f32::NEG_INFINITY,
+            Layer::Sorted => self.pos.y,
+            Layer::Air => f32::INFINITY,
+        }
+    }
+
+    /// Draw it, fading out over its lifetime. Call with the world camera
+    /// set.
+    pub fn draw(&self) {
+        let fade = (self.life / self.max_life).clamp(0.0, 1.0);
+        let color = Color::new(
+            self.color.r,
+            self.color.g,
+            self.color.b,
+            self.color.a * fade,
+        );
+        let pos = self.pos;
+        match self.shape {
+            Shape::Circle => draw_circle(pos.x, pos.y, self.size, color),
+            Shape::Square => {
+                let half = self.size / 2.0;
+                draw_rectangle(pos.x - half, pos.y - half, self.size, self.size, color);
+            }
+            Shape::Ring => {
+                draw_ellipse_lines(pos.x, pos.y, self.size, self.size * 0.4, 0.0, 1.0, color)
+            }
+            Shape::Streak => {
+                let tail = pos - self.vel * STREAK_SECONDS;
+                draw_line(pos.x, pos.y, tail.x, tail.y, self.size, color);
+            }
+        }
+    }
+}
+
+/// How a burst of particles starts out. Each particle gets a random velocity
+/// within `spread` of `velocity` and a random color from `colors`.
+#[derive(Debug, Clone, Copy)]
+pub struct Emitter {
+    pub count: usize,
+    pub velocity: Vec2,
+    pub spread: Vec2,
+    pub life: f32,
+    pub size: f32,
+    pub growth: f32,
+    pub gravity: f32,
+    pub colors: &'static [Color],
+    pub shape: Shape,
+    pub layer: Layer,
+}
+
+/// A fixed-size pool of particles. Emitting reuses dead slots and, when
+/// the pool is full, overwrites the oldest particles.
+pub struct ParticlePool {
+    particles: Vec<Particle>,
+    next: usize,
+}
+
+impl ParticlePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            particles: vec![Particle::DEAD; capacity],
+            next: 0,
+        }
+    }
+
+    pub fn emit(&mut self, emitter: &Emitter, at: Vec2) {
+        for _ in 0..emitter.count {
+            let jitter = vec2(
+                rand::gen_range(-1.0, 1.0) * emitter.spread.x,
+                rand::gen_range(-1.0, 1.0) * emitter.spread.y,
+            );
+            let color = emitter.colors[rand::gen_range(0, emitter.colors.len())];
+            self.particles[self.next] = Particle {
+                pos: at,
+                vel: emitter.velocity + jitter,
+                life: emitter.life,
+                max_life: emitter.life,
+                size: emitter.size,
+                growth: emitter.growth,
+                gravity: emitter.gravity,
+                color,
+                shape: emitter.shape,
+                layer: emitter.layer,
+            };
+            self.next = (self.next + 1) % self.particles.len();
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for p in self.particles.iter_mut().filter(|p| p.is_alive()) {
+            p.vel.y += p.gravity * dt;
+            p.pos += p.vel * dt;
+            p.size = (p.size + p.growth * dt).max(0.0);
+            p.life -= dt;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.fill(Particle::DEAD);
+    }
+
+    pub fn live(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter().filter(|p| p.is_alive())
+    }
+
+    /// Draw every live particle. Call with the world camera set.
+    pub fn draw(&self) {
+        self.live().for_each(Particle::draw);
+    }
+}
+
+const DUST: Emitter = Emitter {
+    count: 5,
+    velocity: vec2(0.0, -10.0),
+    spread: vec2(18.0, 6.0),
+    life: 0.4,
+    size: 2.5,
+    growth: 3.0,
+    gravity: 20.0,
+    colors: &[
+        Color::new(0.72, 0.64, 0.48, 0.8),
+        Color::new(0.62, 0.55, 0.40, 0.8),
+    ],
+    shape: Shape::Circle,
+    layer: Layer::Sorted,
+};
+
+const RIPPLE: Emitter = Emitter {
+    count: 1,
+    velocity: Vec2::ZERO,
+    spread: vec2(2.0, 1.0),
+    life: 0.9,
+    size: 4.0,
+    growth: 16.0,
+    gravity: 0.0,
+    colors: &[Color::new(0.85, 0.93, 1.0, 0.7)],
+    shape: Shape::Ring,
+    layer: Layer::Ground,
+};
+
+/// A small ring sent off to one side of the stern; `update` fills in the
+/// velocity.
+const WAKE: Emitter = Emitter {
+    count: 1,
+    velocity: Vec2::ZERO,
+    spread: vec2(1.5, 1.5),
+    life: 1.4,
+    size: 2.0,
+    growth: 6.0,
+    gravity: 0.0,
+    colors: &[
+        Color::new(0.80, 0.90, 1.0, 0.55),
+        Color::new(0.92, 0.97, 1.0, 0.45),
+    ],
+    shape: Shape::Ring,
+    layer: Layer::Ground,
+};
+
+const CONFETTI: Emitter = Emitter {
+    count: 120,
+    velocity: vec2(0.0, -120.0),
+    spread: vec2(140.0, 80.0),
+    life: 2.2,
+    size: 3.5,
+    growth: 0.0,
+    gravity: 140.0,
+    colors: &[RED, GOLD, GREEN, SKYBLUE, PINK, ORANGE],
+    shape: Shape::Square,
+    layer: Layer::Air,
+};
+
+/// Cosmetic particle effects, sorted in among the world's sprites: dust
+/// from footsteps, ripples and a wake behind the boat, confetti on a win.
+/// Never touches game state.
+pub struct Effects {
+    pool: ParticlePool,
+    ripple_timer: f32,
+    wake_timer: f32,
+    /// Where the boat was last frame, to tell which way it's heading.
+    last_boat: Option<Vec2>,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self {
+            pool: ParticlePool::new(POOL_SIZE),
+            ripple_timer: 0.0,
+            wake_timer: 0.0,
+            last_boat: None,
+        }
+    }
+
+    /// Spawn effects for this frame's `events` and the crossing, then
+    /// advance every particle by `dt`. `boat_pos` is the world position of
+    /// the boat the player is in, or would be.
+    pub fn update(
+        &mut self,
+        state: &GameState,
+        events: &[GameEvent],
+        anim: &AnimState,
+        boat_pos: Vec2,
+        dt: f32,
+    ) {
+        self.spawn(state, events, anim, boat_pos, dt);
+        let heading = self.last_boat.replace(boat_pos).map(|last| boat_pos - last);
+        if let (Some(_), Some(heading)) = (state.crossing_boat(), heading) {
+            self.spawn_wake(boat_pos, heading, dt * state.crossing_time_scale);
+        }
+        self.pool.update(dt);
+    }
+
+    fn spawn(
+        &mut self,
+        state: &GameState,
+        events: &[GameEvent],
+        anim: &AnimState,
+        boat_pos: Vec2,
+        dt: f32,
+    ) {
+        for event in events {
+            match event {
+                GameEvent::Moved {
+                    who: PlayerId::One, ..
+                } => self.pool.emit(&DUST, Vec2::from(anim.player_pos)),
+                GameEvent::Won => {
+                    let at = match state.player.location {
+                        PlayerLocation::OnLand(_) => Vec2::from(anim.player_pos),
+                        PlayerLocation::OnBoat(_) => boat_pos,
+                    };
+                    self.pool.emit(&CONFETTI, at);
+                }
+                // A restart sweeps away whatever was still in the air.
+                GameEvent::Started => self.pool.clear(),
+                _ => {}
+            }
+        }
+
+        if state.crossing_boat().is_some() {
+            self.ripple_timer -= dt * state.crossing_time_scale;
+            if self.ripple_timer <= 0.0 {
+                self.ripple_timer = RIPPLE_INTERVAL;
+                self.pool.emit(&RIPPLE, boat_pos + vec2(0.0, 2.0));
+            }
+        }
+    }
+
+    /// A V of rings fanning out from the stern as the boat is rowed along
+    /// `heading`, its movement this frame.
+    fn spawn_wake(&mut self, boat_pos: Vec2, heading: Vec2, dt: f32) {
+        let Some(forward) = heading.try_normalize() else {
+            return;
+        };
+        self.wake_timer -= dt;
+        if self.wake_timer > 0.0 {
+            return;
+        }
+        self.wake_timer = WAKE_INTERVAL;
+        let stern = boat_pos - forward * STERN_OFFSET + vec2(0.0, 2.0);
+        let side = forward.perp();
+        for sign in [-1.0, 1.0] {
+            let velocity = side * sign * WAKE_SPREAD_SPEED - forward * 4.0;
+            self.pool.emit(&Emitter { velocity, ..WAKE }, stern);
+        }
+    }
+
+    /// The live particles, for the world's render queue to sort in.
+    pub fn particles(&self) -> impl Iterator<Item = &Particle> {
+        self.pool.live()
+    }
+}
@@ -0,0 +1,124 @@
+use serde::Deserialize;
+
+use crate::world::GridPos;
+
+/// How close, in tiles, a patroller gets to a farmer before it has them.
+pub const CATCH_DISTANCE: f32 = 0.6;
+
+/// A wild animal's beat, as a level writes it: corners joined by straight
+/// legs, walked to the end and back again.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PatrolRoute {
+    pub corners: Vec<GridPos>,
+    /// Seconds the patroller takes per tile.
+    #[serde(default = "default_step")]
+    pub step: f32,
+}
+
+fn default_step() -> f32 {
+    0.45
+}
+
+impl PatrolRoute {
+    /// Every tile along the route, in walking order.
+    pub fn tiles(&self) -> Result<Vec<GridPos>, String> {
+        let Some(&first) = self.corners.first() else {
+            return Err("a patrol needs at least one corner".to_string());
+        };
+        let mut tiles = vec![first];
+        for pair in self.corners.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if from.col != to.col && from.row != to.row {
+                return Err(format!("patrol leg {from} to {to} is not straight"));
+            }
+            let (dc, dr) = ((to.col - from.col).signum(), (to.row - from.row).signum());
+            let mut pos = from;
+            while pos != to {
+                pos = GridPos::new(pos.col + dc, pos.row + dr);
+                tiles.push(pos);
+            }
+        }
+        Ok(tiles)
+    }
+}
+
+/// A patroller partway along its beat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patrol {
+    tiles: Vec<GridPos>,
+    step: f32,
+    /// The tile being left.
+    at: usize,
+    /// Walking toward the end of the route, or back to its start.
+    outbound: bool,
+    /// 0 when leaving `tiles[at]`, 1 on arrival at the next.
+    pub progress: f32,
+    /// Seconds spent walking, for the walk cycle.
+    pub elapsed: f32,
+}
+
+impl Patrol {
+    /// Start at the route's first corner. The route must be valid.
+    pub fn new(route: &PatrolRoute) -> Self {
+        Self {
+            tiles: route.tiles().expect("patrol route was validated"),
+            step: route.step,
+            at: 0,
+            outbound: true,
+            progress: 0.0,
+            elapsed: 0.0,
+        }
+    }
+
+    fn next(&self) -> usize {
+        let last = self.tiles.len() - 1;
+        // Turn around at either end.
+        match (self.outbound, self.at) {
+            _ if last == 0 => 0,
+            (true, at) if at == last => at - 1,
+            (false, 0) => 1,
+            (true, at) => at + 1,
+            (false, at) => at - 1,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+        self.progress += dt / self.step;
+        while self.progress >= 1.0 {
+            self.progress -= 1.0;
+            let next = self.next();
+            self.outbound = next > self.at;
+            self.at = next;
+        }
+    }
+
+    /// Move back toward `prev`, a tick earlier, so only `alpha` of the
+    /// tick's walk is shown. A tick that reached a new tile is left as is.
+    pub fn blend_from(&mut self, prev: &Patrol, alpha: f32) {
+        if (prev.at, prev.outbound) == (self.at, self.outbound) {
+            self.progress = prev.progress + (self.progress - prev.progress) * alpha;
+        }
+    }
+
+    /// The tile being left and the one being walked to.
+    pub fn leg(&self) -> (GridPos, GridPos) {
+        (self.tiles[self.at], self.tiles[self.next()])
+    }
+
+    /// Where the patroller is, in fractional (col, row).
+    pub fn position(&self) -> (f32, f32) {
+        let (from, to) = self.leg();
+        let t = self.progress;
+        (
+            from.col as f32 + (to.col - from.col) as f32 * t,
+            from.row as f32 + (to.row - from.row) as f32 * t,
+        )
+    }
+
+    /// Whether someone at fractional `(col, row)` is within reach.
+    pub fn reaches(&self, (col, row): (f32, f32)) -> bool {
+        let (c, r) = self.position();
+        (c - col).hypot(r - row) < CATCH_DISTANCE
+    }
+}
@@ -0,0 +1,180 @@
+use macroquad::prelude::{KeyCode, Rect, Vec2};
+
+/// What a menu choice asks the game to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuCommand {
+    Resume,
+    Restart,
+    Quit,
+    LevelSelect,
+    StartRace,
+    ToggleSound,
+    ToggleBadges,
+    ToggleNarration,
+    ToggleHints,
+    CycleHudPreset,
+    ToggleTimedMode,
+    ToggleCoop,
+    ToggleAnalog,
+    ToggleSpeedrun,
+    ToggleAssist,
+    OpenDisplaySettings,
+    ShowRules,
+}
+
+/// Navigation read this frame while the menu is open.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MenuInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+    /// Pointer position in HUD coordinates.
+    pub pointer: Option<Vec2>,
+    pub click: bool,
+    /// Any key pressed this frame, for capturing a new binding.
+    pub key: Option<KeyCode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Main,
+    Settings,
+}
+
+/// An entry on a page: its label and what picking it does. `None` opens
+/// the settings page; on the settings page it returns to the main page.
+type Item = (&'static str, Option<MenuCommand>);
+
+const MAIN_ITEMS: [Item; 7] = [
+    ("Resume", Some(MenuCommand::Resume)),
+    ("Restart", Some(MenuCommand::Restart)),
+    ("Rules", Some(MenuCommand::ShowRules)),
+    ("Levels", Some(MenuCommand::LevelSelect)),
+    ("Versus race", Some(MenuCommand::StartRace)),
+    ("Settings", None),
+    ("Quit", Some(MenuCommand::Quit)),
+];
+
+const SETTINGS_ITEMS: [Item; 12] = [
+    ("Display & sound", Some(MenuCommand::OpenDisplaySettings)),
+    ("Sound on/off", Some(MenuCommand::ToggleSound)),
+    ("Shape badges", Some(MenuCommand::ToggleBadges)),
+    ("Narration", Some(MenuCommand::ToggleNarration)),
+    ("Solver hints", Some(MenuCommand::ToggleHints)),
+    ("HUD layout", Some(MenuCommand::CycleHudPreset)),
+    ("Timed mode", Some(MenuCommand::ToggleTimedMode)),
+    ("Two-player co-op", Some(MenuCommand::ToggleCoop)),
+    ("Analog movement", Some(MenuCommand::ToggleAnalog)),
+    ("Speedrun mode", Some(MenuCommand::ToggleSpeedrun)),
+    (
+        "Assist: no fatal crossings",
+        Some(MenuCommand::ToggleAssist),
+    ),
+    ("Back", None),
+];
+
+const ITEM_WIDTH: f32 = 320.0;
+const ITEM_HEIGHT: f32 = 30.0;
+const ITEM_GAP: f32 = 8.0;
+
+/// The pause menu: a main page and a settings page of quick toggles.
+pub struct PauseMenu {
+    page: Page,
+    selected: usize,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        Self {
+            page: Page::Main,
+            selected: 0,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.page {
+            Page::Main => "Paused",
+            Page::Settings => "Settings",
+        }
+    }
+
+    fn page_items(&self) -> &'static [Item] {
+        match self.page {
+            Page::Main => &MAIN_ITEMS,
+            Page::Settings => &SETTINGS_ITEMS,
+        }
+    }
+
+    /// Labels of the current page, with the selected index.
+    pub fn items(&self) -> (Vec<&'static str>, usize) {
+        let labels = self.page_items().iter().map(|(label, _)| *label).collect();
+        (labels, self.selected)
+    }
+
+    pub fn handle(&mut self, input: &MenuInput, view: Rect) -> Option<MenuCommand> {
+        let count = self.page_items().len();
+
+        if input.back {
+            return match self.page {
+                Page::Main => Some(MenuCommand::Resume),
+                Page::Settings => {
+                    self.switch(Page::Main);
+                    None
+                }
+            };
+        }
+        if !navigate(&mut self.selected, count, input, view) {
+            return None;
+        }
+        match self.page_items()[self.selected].1 {
+            Some(command) => Some(command),
+            None => {
+                let target = match self.page {
+                    Page::Main => Page::Settings,
+                    Page::Settings => Page::Main,
+                };
+                self.switch(target);
+                None
+            }
+        }
+    }
+
+    fn switch(&mut self, page: Page) {
+        self.page = page;
+        self.selected = 0;
+    }
+}
+
+/// Move `selected` with up/down and the pointer. Returns whether the
+/// selected row was confirmed this frame.
+pub fn navigate(selected: &mut usize, count: usize, input: &MenuInput, view: Rect) -> bool {
+    if input.up {
+        *selected = (*selected + count - 1) % count;
+    }
+    if input.down {
+        *selected = (*selected + 1) % count;
+    }
+    let mut confirm = input.confirm;
+    if let Some(pointer) = input.pointer
+        && let Some(hovered) = (0..count).find(|&i| item_rect(i, count, view).contains(pointer))
+    {
+        *selected = hovered;
+        confirm |= input.click;
+    }
+    confirm
+}
+
+/// Where item `index` of `count` sits, centered in `view`.
+pub fn item_rect(index: usize, count: usize, view: Rect) -> Rect {
+    let total = count as f32 * ITEM_HEIGHT + (count as f32 - 1.0) * ITEM_GAP;
+    let top = view.y + (view.h - total) / 2.0 + 20.0;
+    Rect::new(
+        view.x + (view.w - ITEM_WIDTH) / 2.0,
+        top + index as f32 * (ITEM_HEIGHT + ITEM_GAP),
+        ITEM_WIDTH,
+        ITEM_HEIGHT,
+    )
+}
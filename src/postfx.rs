@@ -0,0 +1,208 @@
+use macroquad::logging::error;
+use macroquad::prelude::*;
+
+use crate::game::GamePhase;
+use crate::settings::EffectSettings;
+
+/// Side of one chunky pixel, in screen pixels, when pixelating.
+const PIXEL_SIZE: f32 = 4.0;
+/// How quickly the grade eases toward the one for the current phase.
+const GRADE_RATE: f32 = 1.5;
+
+const VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+}"#;
+
+/// The effects run in a fixed order: pixelate, then grade, then vignette.
+/// Each one is a no-op at its neutral uniform values.
+const FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 resolution;
+uniform float pixel;
+uniform float saturation;
+uniform vec3 grade;
+uniform float vignette;
+
+void main() {
+    vec2 at = uv;
+    if (pixel > 0.0) {
+        vec2 block = pixel / resolution;
+        at = (floor(uv / block) + 0.5) * block;
+    }
+    vec3 color = texture2D(Texture, at).rgb;
+
+    float luma = dot(color, vec3(0.299, 0.587, 0.114));
+    color = mix(vec3(luma), color, saturation) * grade;
+
+    float edge = smoothstep(0.85, 0.35, length(uv - 0.5));
+    color *= mix(1.0, edge, vignette);
+
+    gl_FragColor = vec4(color, 1.0);
+}"#;
+
+/// A color grade: how saturated the picture is and a tint multiplied in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Grade {
+    saturation: f32,
+    tint: Vec3,
+}
+
+impl Grade {
+    const NEUTRAL: Self = Self {
+        saturation: 1.0,
+        tint: Vec3::ONE,
+    };
+
+    fn for_phase(phase: GamePhase) -> Self {
+        match phase {
+            GamePhase::Won => Self {
+                saturation: 1.25,
+                tint: vec3(1.08, 1.0, 0.85),
+            },
+            GamePhase::Lost(_) => Self {
+                saturation: 0.35,
+                tint: vec3(0.95, 0.82, 0.85),
+            },
+            GamePhase::Menu | GamePhase::Playing | GamePhase::Paused => Self::NEUTRAL,
+        }
+    }
+
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            saturation: self.saturation + (other.saturation - self.saturation) * t,
+            tint: self.tint.lerp(other.tint, t),
+        }
+    }
+}
+
+/// Full-screen effects over the world pass. When any is on, the world
+/// is drawn into an offscreen target and `apply` draws that back through
+/// the effect shader; with all of them off, or if the shader won't
+/// compile, the world goes straight to the screen as before.
+pub struct PostFx {
+    options: EffectSettings,
+    /// None if the platform rejected the shader.
+    material: Option<Material>,
+    target: Option<RenderTarget>,
+    grade: Grade,
+}
+
+impl PostFx {
+    pub fn new(options: &EffectSettings) -> Self {
+        Self {
+            options: options.clone(),
+            material: load(),
+            target: None,
+            grade: Grade::NEUTRAL,
+        }
+    }
+
+    pub fn set_options(&mut self, options: &EffectSettings) {
+        self.options = options.clone();
+    }
+
+    fn is_active(&self) -> bool {
+        let options = &self.options;
+        let any = options.vignette || options.color_grading || options.pixelate;
+        any && self.material.is_some()
+    }
+
+    /// Ease the grade toward the one for `phase`.
+    pub fn update(&mut self, phase: GamePhase, dt: f32) {
+        let t = 1.0 - (-GRADE_RATE * dt).exp();
+        self.grade = self.grade.lerp(Grade::for_phase(phase), t);
+    }
+
+    /// Where the world should be drawn this frame: a screen-sized target
+    /// while any effect is on, otherwise None for the screen itself.
+    pub fn target(&mut self) -> Option<RenderTarget> {
+        if !self.is_active() {
+            self.target = None;
+            return None;
+        }
+        let (width, height) = (screen_width() as u32, screen_height() as u32);
+        let stale = self.target.as_ref().is_none_or(|target| {
+            let texture = &target.texture;
+            texture.width() as u32 != width || texture.height() as u32 != height
+        });
+        if stale {
+            let target = render_target(width.max(1), height.max(1));
+            target.texture.set_filter(FilterMode::Nearest);
+            self.target = Some(target);
+        }
+        self.target.clone()
+    }
+
+    /// Draw the world from the target over `view` with the enabled effects.
+    /// Call with the HUD camera set, after the world and before the HUD.
+    pub fn apply(&self, view: Rect) {
+        let (Some(material), Some(target)) = (&self.material, &self.target) else {
+            return;
+        };
+        if !self.is_active() {
+            return;
+        }
+        let options = &self.options;
+        let texture = &target.texture;
+        let grade = if options.color_grading {
+            self.grade
+        } else {
+            Grade::NEUTRAL
+        };
+
+        gl_use_material(material);
+        material.set_uniform("resolution", vec2(texture.width(), texture.height()));
+        let pixel = if options.pixelate { PIXEL_SIZE } else { 0.0 };
+        material.set_uniform("pixel", pixel);
+        material.set_uniform("saturation", grade.saturation);
+        material.set_uniform("grade", grade.tint);
+        let vignette = if options.vignette { 1.0 } else { 0.0 };
+        material.set_uniform("vignette", vignette);
+        draw_texture_ex(
+            texture,
+            view.x,
+            view.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(view.size()),
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    }
+}
+
+fn load() -> Option<Material> {
+    let params = MaterialParams {
+        uniforms: vec![
+            UniformDesc::new("resolution", UniformType::Float2),
+            UniformDesc::new("pixel", UniformType::Float1),
+            UniformDesc::new("saturation", UniformType::Float1),
+            UniformDesc::new("grade", UniformType::Float3),
+            UniformDesc::new("vignette", UniformType::Float1),
+        ],
+        ..Default::default()
+    };
+    let shader = ShaderSource::Glsl {
+        vertex: VERTEX,
+        fragment: FRAGMENT,
+    };
+    load_material(shader, params)
+        .inspect_err(|e| error!("post-processing shader: {e}; effects are off"))
+        .ok()
+}
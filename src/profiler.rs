@@ -0,0 +1,158 @@
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+
+/// One timed region of a frame.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    name: &'static str,
+    depth: usize,
+    start: f64,
+    end: f64,
+}
+
+/// Records nested timing scopes for the current frame and keeps the last
+/// completed frame around for the flame-graph overlay.
+struct Profiler {
+    enabled: bool,
+    frame_start: f64,
+    spans: Vec<Span>,
+    open: Vec<usize>,
+    last_frame: Vec<Span>,
+    last_frame_start: f64,
+    last_frame_end: f64,
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = const {
+        RefCell::new(Profiler {
+            enabled: false,
+            frame_start: 0.0,
+            spans: Vec::new(),
+            open: Vec::new(),
+            last_frame: Vec::new(),
+            last_frame_start: 0.0,
+            last_frame_end: 0.0,
+        })
+    };
+}
+
+/// Guard returned by [`scope`]; the span ends when it is dropped.
+pub struct ScopeGuard {
+    index: Option<usize>,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let Some(index) = self.index else {
+            return;
+        };
+        PROFILER.with_borrow_mut(|p| {
+            if let Some(span) = p.spans.get_mut(index) {
+                span.end = get_time();
+            }
+            p.open.pop();
+        });
+    }
+}
+
+/// Time the enclosing block: `let _span = profiler::scope("render::tiles");`
+/// Does nothing while the overlay is hidden.
+pub fn scope(name: &'static str) -> ScopeGuard {
+    let index = PROFILER.with_borrow_mut(|p| {
+        if !p.enabled {
+            return None;
+        }
+        let now = get_time();
+        let index = p.spans.len();
+        p.spans.push(Span {
+            name,
+            depth: p.open.len(),
+            start: now,
+            end: now,
+        });
+        p.open.push(index);
+        Some(index)
+    });
+    ScopeGuard { index }
+}
+
+/// Mark the start of a new frame, publishing the previous one to the overlay.
+pub fn begin_frame() {
+    PROFILER.with_borrow_mut(|p| {
+        let now = get_time();
+        if p.enabled {
+            p.last_frame = std::mem::take(&mut p.spans);
+            p.last_frame_start = p.frame_start;
+            p.last_frame_end = now;
+        }
+        p.spans.clear();
+        p.open.clear();
+        p.frame_start = now;
+    });
+}
+
+pub fn toggle() {
+    PROFILER.with_borrow_mut(|p| {
+        p.enabled = !p.enabled;
+        p.last_frame.clear();
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Overlay
+// ---------------------------------------------------------------------------
+
+const ROW_HEIGHT: f32 = 18.0;
+const MARGIN: f32 = 10.0;
+/// The graph's full width covers this many milliseconds, so a 60 Hz frame
+/// budget fills it exactly.
+const GRAPH_MS: f64 = 1000.0 / 60.0;
+
+/// Draw the last completed frame as a flame graph in screen space.
+pub fn draw_overlay() {
+    PROFILER.with_borrow(|p| {
+        if !p.enabled {
+            return;
+        }
+        set_default_camera();
+
+        let width = screen_width() - MARGIN * 2.0;
+        let rows = p.last_frame.iter().map(|s| s.depth + 1).max().unwrap_or(0);
+        let height = ROW_HEIGHT * rows as f32 + 28.0;
+        let top = screen_height() - height - MARGIN;
+
+        draw_rectangle(MARGIN, top, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+        let frame_ms = (p.last_frame_end - p.last_frame_start) * 1000.0;
+        draw_text(
+            &format!("Frame {frame_ms:.2} ms  (bar = {GRAPH_MS:.1} ms)  F2: hide"),
+            MARGIN + 6.0,
+            top + 18.0,
+            18.0,
+            WHITE,
+        );
+
+        let ms_to_px = width as f64 / GRAPH_MS;
+        for span in &p.last_frame {
+            let x = MARGIN + ((span.start - p.last_frame_start) * 1000.0 * ms_to_px) as f32;
+            let w = (((span.end - span.start) * 1000.0 * ms_to_px) as f32).max(1.0);
+            let y = top + 26.0 + span.depth as f32 * ROW_HEIGHT;
+            draw_rectangle(x, y, w, ROW_HEIGHT - 2.0, span_color(span.name));
+
+            let label = format!("{} {:.2}ms", span.name, (span.end - span.start) * 1000.0);
+            let dims = measure_text(&label, None, 14, 1.0);
+            if dims.width + 4.0 < w {
+                draw_text(&label, x + 2.0, y + ROW_HEIGHT - 6.0, 14.0, BLACK);
+            }
+        }
+    });
+}
+
+/// Stable per-name color so a stage keeps its color across frames.
+fn span_color(name: &str) -> Color {
+    let hash = name
+        .bytes()
+        .fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+    let hue = (hash % 360) as f32 / 360.0;
+    macroquad::color::hsl_to_rgb(hue, 0.55, 0.65)
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use macroquad::prelude::Rect;
+
+use crate::anim::AnimState;
+use crate::camera::CameraRig;
+use crate::game::{GamePhase, GameState};
+use crate::input::{CameraInput, InputEvent};
+use crate::level::LevelDef;
+use crate::render;
+
+/// Seconds a racer's failed attempt stays on screen before it restarts.
+const RETRY_DELAY: f32 = 1.5;
+
+/// One side of a race: its own game, animation and camera.
+pub struct Racer {
+    pub state: GameState,
+    pub anim: AnimState,
+    pub camera: CameraRig,
+    /// Input from a frame with no tick due, waiting for the next one.
+    pub pending: InputEvent,
+    /// Counts down to a restart after a loss.
+    retry_in: Option<f32>,
+}
+
+impl Racer {
+    fn new(level: Arc<LevelDef>, viewport: Rect, analog: bool) -> Self {
+        let mut state = GameState::new(level);
+        state.analog = analog;
+        Self {
+            anim: AnimState::new(&state),
+            camera: CameraRig::new(render::view_rect_in(viewport)),
+            state,
+            pending: InputEvent::None,
+            retry_in: None,
+        }
+    }
+}
+
+/// Versus mode: two players solve the same level side by side, each in
+/// their own game, and the first to get everyone across wins.
+pub struct Race {
+    pub racers: [Racer; 2],
+    /// Seconds since the start, until someone wins.
+    pub elapsed: f32,
+    /// Index of the racer who finished first.
+    pub winner: Option<usize>,
+}
+
+impl Race {
+    /// A race on `level`, in analog movement or on the grid.
+    pub fn new(level: Arc<LevelDef>, analog: bool) -> Self {
+        let viewports = render::split_viewports();
+        Self {
+            racers: viewports.map(|viewport| Racer::new(Arc::clone(&level), viewport, analog)),
+            elapsed: 0.0,
+            winner: None,
+        }
+    }
+
+    /// Start both sides over.
+    pub fn restart(&mut self) {
+        let state = &self.racers[0].state;
+        *self = Self::new(Arc::clone(&state.level), state.analog);
+    }
+
+    /// Call once both racers have stepped: settles the winner, retries
+    /// losses and moves the cameras.
+    pub fn update(&mut self, dt: f32) {
+        if self.winner.is_none() {
+            self.elapsed += dt;
+            self.winner = self
+                .racers
+                .iter()
+                .position(|racer| racer.state.phase == GamePhase::Won);
+        }
+        let viewports = render::split_viewports();
+        for (racer, viewport) in self.racers.iter_mut().zip(viewports) {
+            if let GamePhase::Lost(_) = racer.state.phase {
+                let left = racer.retry_in.get_or_insert(RETRY_DELAY);
+                *left -= dt;
+                if *left <= 0.0 {
+                    racer.state.reset();
+                    racer.anim.reset(&racer.state);
+                }
+            } else {
+                racer.retry_in = None;
+            }
+            // The race is over for both once one side wins.
+            if self.winner.is_some() && racer.state.phase == GamePhase::Playing {
+                racer.state.phase = GamePhase::Paused;
+            }
+            let focus = render::camera_focus(&racer.state, &racer.anim);
+            let base = render::view_rect_in(viewport);
+            let level = &racer.state.level;
+            let input = CameraInput::default();
+            racer.camera.update(&input, level, focus, base, dt);
+        }
+    }
+}
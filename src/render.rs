@@ -1,245 +1,2470 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
 use macroquad::prelude::*;
 
+use crate::ambience::Ambience;
 use crate::anim::AnimState;
-use crate::game::{BoatState, Entity, EntityLocation, GamePhase, GameState, PlayerLocation};
-use crate::interaction;
-use crate::world::{self, Bank, GridPos};
+use crate::camera::{self, CameraFx, CameraRig, CrossingCamera};
+use crate::campaign::{Campaign, LevelSelect, MutatorMenu, UpgradeMenu};
+use crate::clips::{self, Clip, Playback};
+use crate::cutscene::Cutscene;
+use crate::dialogue::Ferryman;
+use crate::emote::{self, Emote};
+use crate::endless::EndlessRun;
+use crate::game::{
+    self, BoatId, BoatState, EntityId, EntityInstance, EntityLocation, GamePhase, GameState,
+    LoseReason, PlayerId, PlayerLocation,
+};
+use crate::ghost::Ghost;
+use crate::hud::{HudElement, HudLayout};
+use crate::input::{self, Action, InputDevice, KeyMap};
+use crate::interaction::{self, ClickTarget};
+use crate::inventory::{Inventory, Item};
+use crate::kinds::{Badge, EntityKind, KindId, SpriteSet};
+use crate::leaderboard::{BoardStatus, Leaderboard};
+use crate::level::{Biome, LevelDef};
+use crate::mutators::Mutators;
+use crate::narrator::Narrator;
+use crate::outline;
+use crate::particles::{Effects, Particle};
+use crate::patrol::Patrol;
+use crate::pause::{self, PauseMenu};
+use crate::profiler;
+use crate::race::Race;
+use crate::settings::{Settings, SettingsScreen};
+use crate::solver;
+use crate::speedrun::{self, Speedrun};
+use crate::stats::{Score, Stats};
+use crate::text::{self, TextStyle};
+use crate::theme::Theme;
+use crate::touch::{TouchButton, TouchPad};
+use crate::tween::{self, Ease};
+use crate::water;
+use crate::world::{self, GridPos, RegionId, Tile};
 
 // ---------------------------------------------------------------------------
 // Sprite atlas
 // ---------------------------------------------------------------------------
 
-pub struct SpriteAtlas {
-    pub player: [Texture2D; 3], // idle, walk1, walk2
-    pub wolf: [Texture2D; 3],
-    pub sheep: [Texture2D; 3],
-    pub cabbage: Texture2D,
-    pub boat: Texture2D,
-    pub tree: Texture2D,
-    pub highlight: Texture2D,
+/// Where one sprite sits inside the packed atlas texture, in pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct Sprite {
+    pub rect: Rect,
+    /// Texels per world unit, from the theme.
+    density: f32,
+}
+
+impl Sprite {
+    /// Size in world units.
+    pub fn width(&self) -> f32 {
+        self.rect.w / self.density
+    }
+
+    pub fn height(&self) -> f32 {
+        self.rect.h / self.density
+    }
+}
+
+/// A clip with its frames found in the atlas.
+struct SpriteClip {
+    clip: Clip,
+    frames: Vec<Sprite>,
 }
 
-async fn load_sprite(path: &str) -> Texture2D {
-    let tex = load_texture(path).await.unwrap();
-    tex.set_filter(FilterMode::Nearest);
-    tex
+/// Every sprite packed into one texture, so the world draws from a single
+/// texture and batches instead of switching per sprite.
+pub struct SpriteAtlas {
+    pub texture: Texture2D,
+    /// In `Biome` order.
+    trees: [Sprite; 4],
+    pub highlight: Sprite,
+    /// Each sprite set's clips, by set name and then clip name.
+    clips: HashMap<String, HashMap<String, SpriteClip>>,
+    /// Stands in for a sprite set that wasn't loaded.
+    placeholder: Sprite,
 }
 
+/// The tree each biome grows, in `Biome` order.
+const TREE_SPRITES: [&str; 4] = ["tree", "tree_autumn", "tree_winter", "tree_swamp"];
+const HIGHLIGHT_SPRITE: &str = "highlight";
+
+/// The farmer's animations, which the ferryman and ghosts borrow too.
+const PLAYER_SET: &str = "player";
+const BOAT_SET: &str = "boat";
+
 impl SpriteAtlas {
-    pub async fn load() -> Self {
+    /// Load every sprite, including the frames of each clip of the
+    /// player, the boat and each set in `sets`, fetching and decoding the
+    /// files concurrently, and pack them into one texture. `on_progress` is
+    /// called once per frame with the fraction of files finished so the
+    /// caller can draw a loading screen. A file that can't be loaded is
+    /// logged and drawn as a placeholder, so a missing asset never stops
+    /// the game. Each sprite is drawn from the file `theme` (one of
+    /// `theme::THEMES`, or a pack of the player's) gives it.
+    pub async fn load(sets: &[SpriteSet], theme: &str, mut on_progress: impl FnMut(f32)) -> Self {
+        let manifest = clips::Manifest::load().await;
+        let theme = Theme::load(theme).await;
+        let fixed_sets = [
+            SpriteSet {
+                name: PLAYER_SET.to_string(),
+                animated: true,
+            },
+            SpriteSet {
+                name: BOAT_SET.to_string(),
+                animated: false,
+            },
+        ];
+        let set_clips: Vec<(String, BTreeMap<String, Clip>)> = fixed_sets
+            .iter()
+            .chain(sets)
+            .map(|set| (set.name.clone(), manifest.clips(set)))
+            .collect();
+
+        let frame_path = |frame: &str| theme.path(frame);
+        let mut paths: Vec<String> = TREE_SPRITES.map(frame_path).to_vec();
+        paths.push(frame_path(HIGHLIGHT_SPRITE));
+        for (_, clips) in &set_clips {
+            for frame in clips.values().flat_map(|clip| &clip.frames) {
+                let path = frame_path(frame);
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        let path_refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let mut images: Vec<Image> = load_images(&path_refs, &mut on_progress)
+            .await
+            .into_iter()
+            .map(|loaded| {
+                loaded.unwrap_or_else(|e| {
+                    error!("{e}; using a placeholder");
+                    placeholder_image()
+                })
+            })
+            .collect();
+        images.push(placeholder_image());
+        let (packed, rects) = pack_images(&images);
+        let texture = Texture2D::from_image(&packed);
+        texture.set_filter(FilterMode::Nearest);
+        // The placeholder is drawn at the game's own resolution.
+        let density = theme.density();
+        let sprites: Vec<Sprite> = rects
+            .into_iter()
+            .enumerate()
+            .map(|(i, rect)| Sprite {
+                rect,
+                density: if i < paths.len() { density } else { 1.0 },
+            })
+            .collect();
+        let find = |path: &str| sprites[paths.iter().position(|p| p == path).unwrap()];
+
+        let clips = set_clips
+            .into_iter()
+            .map(|(name, clips)| {
+                let clips = clips
+                    .into_iter()
+                    .map(|(clip_name, clip)| {
+                        let frames = clip.frames.iter().map(|f| find(&frame_path(f))).collect();
+                        (clip_name, SpriteClip { clip, frames })
+                    })
+                    .collect();
+                (name, clips)
+            })
+            .collect();
+        Self {
+            trees: TREE_SPRITES.map(|name| find(&frame_path(name))),
+            highlight: find(&frame_path(HIGHLIGHT_SPRITE)),
+            clips,
+            placeholder: sprites[paths.len()],
+            texture,
+        }
+    }
+
+    /// The frame of `set` that `playback` is at. A clip the set doesn't
+    /// have plays its idle clip instead.
+    fn frame(&self, set: &str, playback: &Playback) -> Sprite {
+        let Some(clips) = self.clips.get(set) else {
+            return self.placeholder;
+        };
+        let clip = clips.get(playback.clip).or_else(|| clips.get(clips::IDLE));
+        clip.and_then(|clip| clip.frames.get(clip.clip.frame_at(playback.time)))
+            .copied()
+            .unwrap_or(self.placeholder)
+    }
+
+    fn tree(&self, biome: Biome) -> Sprite {
+        self.trees[biome as usize]
+    }
+
+    /// `set` standing still: the first frame of its idle clip.
+    fn still(&self, set: &str) -> Sprite {
+        self.frame(set, &Playback::new())
+    }
+
+    fn kind_still(&self, kind: &EntityKind) -> Sprite {
+        self.still(&kind.sprites.name)
+    }
+}
+
+/// Transparent pixels left around each sprite so neighbours never bleed
+/// into one another when scaled.
+const ATLAS_PADDING: u16 = 1;
+const ATLAS_MIN_WIDTH: u16 = 256;
+
+/// Shelf-pack `images` into one image, tallest first. Returns the packed
+/// image and each input's rect within it, in input order.
+fn pack_images(images: &[Image]) -> (Image, Vec<Rect>) {
+    let pad = ATLAS_PADDING;
+    let widest = images.iter().map(|i| i.width).max().unwrap_or(0);
+    let atlas_w = ATLAS_MIN_WIDTH.max(widest + 2 * pad);
+
+    let mut order: Vec<usize> = (0..images.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(images[i].height));
+
+    let mut origins = vec![(0u16, 0u16); images.len()];
+    let (mut x, mut y, mut shelf_h) = (pad, pad, 0u16);
+    for &i in &order {
+        let image = &images[i];
+        if x + image.width + pad > atlas_w {
+            x = pad;
+            y += shelf_h + pad;
+            shelf_h = 0;
+        }
+        origins[i] = (x, y);
+        x += image.width + pad;
+        shelf_h = shelf_h.max(image.height);
+    }
+    let atlas_h = y + shelf_h + pad;
+
+    let mut packed = Image::gen_image_color(atlas_w, atlas_h, Color::new(0.0, 0.0, 0.0, 0.0));
+    let stride = atlas_w as usize * 4;
+    for (image, &(ox, oy)) in images.iter().zip(&origins) {
+        let row_len = image.width as usize * 4;
+        for row in 0..image.height as usize {
+            let src = row * row_len;
+            let dst = (oy as usize + row) * stride + ox as usize * 4;
+            packed.bytes[dst..dst + row_len].copy_from_slice(&image.bytes[src..src + row_len]);
+        }
+    }
+
+    let rects = images
+        .iter()
+        .zip(&origins)
+        .map(|(image, &(ox, oy))| {
+            let (w, h) = (image.width as f32, image.height as f32);
+            Rect::new(ox as f32, oy as f32, w, h)
+        })
+        .collect();
+    (packed, rects)
+}
+
+const PLACEHOLDER_SIZE: u16 = 16;
+
+/// A magenta and black checkerboard, loud enough to notice in play.
+fn placeholder_image() -> Image {
+    let mut image = Image::gen_image_color(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, MAGENTA);
+    for y in 0..PLACEHOLDER_SIZE as u32 {
+        for x in 0..PLACEHOLDER_SIZE as u32 {
+            if (x / 4 + y / 4).is_multiple_of(2) {
+                image.set_pixel(x, y, BLACK);
+            }
+        }
+    }
+    image
+}
+
+fn decode_png(path: &str, bytes: &[u8]) -> Result<Image, String> {
+    Image::from_file_with_format(bytes, Some(ImageFormat::Png))
+        .map_err(|e| format!("failed to decode {path}: {e}"))
+}
+
+/// Native: read and decode each file on its own thread.
+#[cfg(not(target_arch = "wasm32"))]
+async fn load_images(
+    paths: &[&str],
+    on_progress: &mut impl FnMut(f32),
+) -> Vec<Result<Image, String>> {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let done = Arc::new(AtomicUsize::new(0));
+    let workers: Vec<_> = paths
+        .iter()
+        .map(|&path| {
+            let path = path.to_string();
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                let image = std::fs::read(&path)
+                    .map_err(|e| format!("failed to read {path}: {e}"))
+                    .and_then(|bytes| decode_png(&path, &bytes));
+                done.fetch_add(1, Ordering::Release);
+                image
+            })
+        })
+        .collect();
+
+    loop {
+        let finished = done.load(Ordering::Acquire);
+        on_progress(finished as f32 / paths.len() as f32);
+        if finished == paths.len() {
+            break;
+        }
+        next_frame().await;
+    }
+
+    workers
+        .into_iter()
+        .map(|worker| worker.join().unwrap())
+        .collect()
+}
+
+/// Web: issue every fetch up front and decode responses as they arrive.
+#[cfg(target_arch = "wasm32")]
+async fn load_images(
+    paths: &[&str],
+    on_progress: &mut impl FnMut(f32),
+) -> Vec<Result<Image, String>> {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let responses: Vec<Rc<RefCell<Option<miniquad::fs::Response>>>> = paths
+        .iter()
+        .map(|&path| {
+            let slot = Rc::new(RefCell::new(None));
+            let sink = Rc::clone(&slot);
+            miniquad::fs::load_file(path, move |response| {
+                *sink.borrow_mut() = Some(response);
+            });
+            slot
+        })
+        .collect();
+
+    loop {
+        let finished = responses.iter().filter(|r| r.borrow().is_some()).count();
+        on_progress(finished as f32 / paths.len() as f32);
+        if finished == paths.len() {
+            break;
+        }
+        next_frame().await;
+    }
+
+    paths
+        .iter()
+        .zip(responses)
+        .map(|(path, slot)| {
+            let response = slot.borrow_mut().take().unwrap();
+            response
+                .map_err(|e| format!("failed to fetch {path}: {e:?}"))
+                .and_then(|bytes| decode_png(path, &bytes))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Display options
+// ---------------------------------------------------------------------------
+
+/// Player-toggleable presentation settings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    /// Draw a geometric badge above each entity so they can be told apart
+    /// without relying on sprite colors.
+    pub shape_badges: bool,
+    /// A palette that doesn't lean on green against red, bolder outlines,
+    /// and shapes marking docks, danger and results.
+    pub high_contrast: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Camera
+// ---------------------------------------------------------------------------
+
+/// The whole window, in screen pixels.
+pub fn full_viewport() -> Rect {
+    Rect::new(0.0, 0.0, screen_width(), screen_height())
+}
+
+/// The window's left and right halves, for versus play.
+pub fn split_viewports() -> [Rect; 2] {
+    let half = screen_width() / 2.0;
+    [
+        Rect::new(0.0, 0.0, half, screen_height()),
+        Rect::new(half, 0.0, screen_width() - half, screen_height()),
+    ]
+}
+
+/// The classic framing of the whole level at the current aspect ratio.
+/// The HUD is always laid out in this space, whatever the world camera does.
+pub fn view_rect() -> Rect {
+    view_rect_in(full_viewport())
+}
+
+/// `view_rect` for a view drawn into `viewport` rather than the window.
+/// The classic view is never cropped: a wider viewport adds room at both
+/// sides, a narrower one above and below, leaving bars of background
+/// around the level.
+pub fn view_rect_in(viewport: Rect) -> Rect {
+    let aspect = viewport.w / viewport.h.max(1.0);
+    let (w, h) = if aspect >= world::WORLD_WIDTH / world::WORLD_HEIGHT {
+        (world::WORLD_HEIGHT * aspect, world::WORLD_HEIGHT)
+    } else {
+        (world::WORLD_WIDTH, world::WORLD_WIDTH / aspect)
+    };
+    Rect {
+        x: (world::WORLD_WIDTH - w) / 2.0,
+        y: (world::WORLD_HEIGHT - h) / 2.0,
+        w,
+        h,
+    }
+}
+
+/// Point the world camera at the rig's view, drawing into `target` if
+/// given rather than the screen.
+pub fn setup_camera(
+    rig: &CameraRig,
+    crossing: &CrossingCamera,
+    fx: &CameraFx,
+    target: Option<RenderTarget>,
+) {
+    let overview = rig.view(view_rect());
+    let view = lerp_rect(overview, crossing.close_up(overview), crossing.blend());
+    let (view, roll) = fx.apply(view);
+    let mut camera = camera_for(view);
+    camera.rotation = roll;
+    if target.is_some() {
+        // Render targets want the camera's own y-up orientation.
+        camera.zoom.y = -camera.zoom.y;
+        camera.render_target = target;
+    }
+    set_camera(&camera);
+}
+
+pub fn setup_hud_camera() {
+    set_camera(&camera_for(view_rect()));
+}
+
+/// A HUD camera for `layout`'s frame, blowing the HUD up by its scale.
+pub fn setup_layout_camera(layout: &HudLayout) {
+    set_camera(&camera_for(layout.frame(view_rect())));
+}
+
+/// Point the world camera at the rig's view, drawn into `viewport`.
+pub fn setup_camera_in(rig: &CameraRig, viewport: Rect) {
+    let mut camera = camera_for(rig.view(view_rect_in(viewport)));
+    camera.viewport = Some(pixel_rect(viewport));
+    set_camera(&camera);
+}
+
+/// A HUD camera for `viewport`, laid out in `view_rect_in(viewport)`.
+pub fn setup_hud_camera_in(viewport: Rect) {
+    let mut camera = camera_for(view_rect_in(viewport));
+    camera.viewport = Some(pixel_rect(viewport));
+    set_camera(&camera);
+}
+
+/// The view `t` of the way from `from` to `to`.
+fn lerp_rect(from: Rect, to: Rect, t: f32) -> Rect {
+    if t <= 0.0 {
+        return from;
+    }
+    Rect::new(
+        tween::lerp(from.x, to.x, t),
+        tween::lerp(from.y, to.y, t),
+        tween::lerp(from.w, to.w, t),
+        tween::lerp(from.h, to.h, t),
+    )
+}
+
+/// A viewport as the whole pixels a camera takes. Macroquad counts rows
+/// from the bottom of the window.
+fn pixel_rect(viewport: Rect) -> (i32, i32, i32, i32) {
+    let bottom = screen_height() - viewport.bottom();
+    (
+        viewport.x as i32,
+        bottom as i32,
+        viewport.w as i32,
+        viewport.h as i32,
+    )
+}
+
+fn camera_for(view: Rect) -> Camera2D {
+    let mut camera = Camera2D::from_display_rect(view);
+    camera.zoom.y = -camera.zoom.y;
+    camera
+}
+
+/// Convert a screen pixel position to world coordinates under the camera.
+pub fn screen_to_world(rig: &CameraRig, screen: Vec2) -> Vec2 {
+    camera_for(rig.view(view_rect())).screen_to_world(screen)
+}
+
+/// Convert a screen pixel position to HUD coordinates.
+pub fn screen_to_hud(screen: Vec2) -> Vec2 {
+    camera_for(view_rect()).screen_to_world(screen)
+}
+
+/// The point the camera should keep in view: the player, or the boat while
+/// the player is aboard. Something being eaten takes it over.
+pub fn camera_focus(state: &GameState, anim: &AnimState) -> Vec2 {
+    if let Some(scene) = &anim.loss {
+        return Vec2::from(anim.entity_anim(scene.eater).pos);
+    }
+    match state.player.location {
+        PlayerLocation::OnLand(_) => Vec2::from(anim.player_pos),
+        PlayerLocation::OnBoat(_) => boat_world_pos(state),
+    }
+}
+
+/// Where the player's boat is drawn, in world coordinates; the first boat
+/// while the player is ashore.
+pub fn boat_world_pos(state: &GameState) -> Vec2 {
+    let boat = state.player_boat().unwrap_or(BoatId(0));
+    Vec2::from(boat_screen_pos(state, boat))
+}
+
+/// Where the crossing boat is drawn, if one is crossing.
+pub fn crossing_boat_pos(state: &GameState) -> Option<Vec2> {
+    state
+        .crossing_boat()
+        .map(|boat| Vec2::from(boat_screen_pos(state, boat)))
+}
+
+// ---------------------------------------------------------------------------
+// Loading screen
+// ---------------------------------------------------------------------------
+
+pub fn draw_loading_screen(progress: f32) {
+    set_default_camera();
+    clear_background(Color::new(0.05, 0.06, 0.12, 1.0));
+
+    let bar_w = screen_width() * 0.4;
+    let bar_h = 16.0;
+    let x = (screen_width() - bar_w) / 2.0;
+    let y = screen_height() / 2.0;
+
+    let style = TextStyle::new(32.0, WHITE);
+    text::draw_centered("Loading...", screen_width() / 2.0, y - 20.0, &style);
+    draw_rectangle(x, y, bar_w, bar_h, Color::new(0.15, 0.17, 0.25, 1.0));
+    draw_rectangle(x, y, bar_w * progress.clamp(0.0, 1.0), bar_h, SKYBLUE);
+    draw_rectangle_lines(x, y, bar_w, bar_h, 2.0, WHITE);
+}
+
+// ---------------------------------------------------------------------------
+// Drawing
+// ---------------------------------------------------------------------------
+
+/// The level and everything in it. `effects`' particles are sorted in
+/// among the sprites; `ambience`'s grass and fish go on the ground under
+/// them.
+pub fn draw_world(
+    state: &GameState,
+    atlas: &SpriteAtlas,
+    anim: &AnimState,
+    options: &RenderOptions,
+    effects: Option<&Effects>,
+    ambience: Option<&Ambience>,
+    time: f32,
+) {
+    let _span = profiler::scope("render::world");
+    WORLD_TINT.set(world_tint(state));
+    let target = if state.phase == GamePhase::Playing {
+        interaction::interaction_target(state)
+    } else {
+        None
+    };
+    let palette = Palette::for_options(options, state.level.biome);
+    draw_water(&state.level, palette, time);
+    // Land drawn over the reflections trims them to the water.
+    draw_reflections(state, atlas, anim, time);
+    draw_land(&state.level, palette);
+    if let Some(ambience) = ambience {
+        ambience.draw_ground(&state.level, WORLD_TINT.get());
+    }
+    draw_shadows(state, atlas, anim);
+    draw_fog(state, time);
+    let fatal = fatal_region(state, anim);
+    if let Some(region) = fatal {
+        dim_ground_outside(&state.level, region);
+    }
+
+    let mut queue = RenderQueue::default();
+    queue_trees(&mut queue, &state.level);
+    queue_dock_markers(&mut queue, state, options);
+    queue_boats(&mut queue, state, anim);
+    queue_entities(&mut queue, state, atlas, anim);
+    queue_danger_markers(&mut queue, state, atlas, anim);
+    queue_hunger_bars(&mut queue, state, atlas, anim);
+    queue_items(&mut queue, state, atlas, anim);
+    if let Some(effects) = effects {
+        queue.particles(effects);
+    }
+    queue.draw(state, atlas, options, target, fatal, time);
+    if let Some(region) = fatal {
+        draw_loss_markers(state, region, time);
+    }
+    WORLD_TINT.set(WHITE);
+}
+
+// ---------------------------------------------------------------------------
+// Shadows
+// ---------------------------------------------------------------------------
+
+/// How dark a shadow is under someone standing on the ground.
+const SHADOW_ALPHA: f32 = 0.3;
+/// A shadow's width as a share of the sprite's, and its height as a share
+/// of its width.
+const SHADOW_WIDTH: f32 = 0.7;
+const SHADOW_ASPECT: f32 = 0.35;
+/// How high a hop goes before the shadow under it is half its size.
+const SHADOW_HALF_LIFT: f32 = 20.0;
+
+/// Soft shadows under everyone on the ground and under the boats, so they
+/// sit on the tiles instead of floating over them. Drawn under the whole
+/// render queue, so one sprite's shadow never falls on another.
+fn draw_shadows(state: &GameState, atlas: &SpriteAtlas, anim: &AnimState) {
+    for entity in &state.entities {
+        // Those sitting aboard are the boat's shadow.
+        let ea = anim.entity_anim(entity.id);
+        if is_aboard(state, entity.id) && ea.hop.is_none() || in_fog(state, entity.id) {
+            continue;
+        }
+        let left = match &anim.loss {
+            Some(scene) if scene.prey == entity.id => scene.prey_left(),
+            _ => 1.0,
+        };
+        let width = atlas.kind_still(state.level.kind(entity.kind)).width() * 2.0 * left;
+        draw_shadow(ea.pos, ea.hop.map_or(0.0, |hop| hop.lift()), width);
+    }
+
+    let farmer = atlas.still(PLAYER_SET).width() * 2.0;
+    if matches!(state.player.location, PlayerLocation::OnLand(_)) || anim.player_hop.is_some() {
+        let lift = anim.player_hop.map_or(0.0, |hop| hop.lift());
+        draw_shadow(anim.player_pos, lift, farmer);
+    }
+    if let Some(partner) = &anim.partner {
+        draw_shadow(partner.pos, 0.0, farmer);
+    }
+    if let Some(spot) = state.level.ferryman() {
+        draw_shadow(world::grid_to_iso(spot), 0.0, farmer);
+    }
+    if let Some(patrol) = &state.patrol {
+        let wolf = atlas.still(PATROLLER_SET).width() * 2.0;
+        draw_shadow(patroller_pos(patrol).0, 0.0, wolf);
+    }
+
+    let boat = atlas.still(BOAT_SET).width() * 2.5;
+    for i in 0..state.boats.len() {
+        draw_shadow(boat_screen_pos(state, BoatId(i)), 0.0, boat);
+    }
+}
+
+/// A shadow on the ground under a sprite `width` wide whose feet are at
+/// (`x`, `y`), `lift` above it. It shrinks and fades the higher they go.
+fn draw_shadow((x, y): (f32, f32), lift: f32, width: f32) {
+    if width <= 0.0 {
+        return;
+    }
+    let shrink = 1.0 / (1.0 + lift / SHADOW_HALF_LIFT);
+    let radius = width * SHADOW_WIDTH * shrink / 2.0;
+    let ground = y + lift;
+    // Rings, each smaller and stacked on the last, soften the edge.
+    const RINGS: usize = 3;
+    let color = Color::new(0.0, 0.0, 0.0, SHADOW_ALPHA * shrink / RINGS as f32);
+    for ring in 0..RINGS {
+        let r = radius * (1.0 - ring as f32 * 0.22);
+        draw_ellipse(x, ground, r, r * SHADOW_ASPECT, 0.0, color);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Loss forensics
+// ---------------------------------------------------------------------------
+
+/// Laid over the ground away from where a loss happened.
+const FORENSICS_SHADE: Color = Color::new(0.0, 0.0, 0.04, 0.55);
+const FORENSICS_MARK: Color = Color::new(1.0, 0.25, 0.2, 1.0);
+
+/// The bank something was eaten on, once the game is lost to it.
+fn fatal_region(state: &GameState, anim: &AnimState) -> Option<RegionId> {
+    if !matches!(state.phase, GamePhase::Lost(LoseReason::Eaten { .. })) {
+        return None;
+    }
+    let scene = anim.loss.as_ref()?;
+    match state.entity_location(scene.prey) {
+        EntityLocation::OnBank { region, .. } => Some(region),
+        _ => None,
+    }
+}
+
+/// `light` as it falls outside the bank a loss happened on.
+fn dimmed_light(light: Color) -> Color {
+    let keep = 1.0 - FORENSICS_SHADE.a;
+    Color::new(light.r * keep, light.g * keep, light.b * keep, light.a)
+}
+
+/// Shade every tile, water and all, that isn't on `region`.
+fn dim_ground_outside(level: &LevelDef, region: RegionId) {
+    for (pos, _) in tiles(level) {
+        if level.region_of(pos) != Some(region) {
+            let [top, right, bottom, left] = tile_corners(pos);
+            draw_triangle(top, right, bottom, FORENSICS_SHADE);
+            draw_triangle(top, left, bottom, FORENSICS_SHADE);
+        }
+    }
+}
+
+/// The top, right, bottom and left corners of `pos`'s diamond.
+fn tile_corners(pos: GridPos) -> [Vec2; 4] {
+    let (cx, cy) = world::grid_to_iso(pos);
+    let (hw, hh) = (world::TILE_WIDTH / 2.0, world::TILE_HEIGHT / 2.0);
+    [
+        vec2(cx, cy - hh),
+        vec2(cx + hw, cy),
+        vec2(cx, cy + hh),
+        vec2(cx - hw, cy),
+    ]
+}
+
+/// Around the bank a loss happened on: its outline, rings on the pair
+/// left alone there, and a line saying so above it.
+fn draw_loss_markers(state: &GameState, region: RegionId, time: f32) {
+    let level = &state.level;
+    let pulse = 0.6 + 0.4 * (time * 5.0).sin();
+    let mark = Color {
+        a: pulse,
+        ..FORENSICS_MARK
+    };
+    let mut top = f32::MAX;
+    let mut sides = (f32::MAX, f32::MIN);
+    for (pos, _) in tiles(level).filter(|&(pos, _)| level.region_of(pos) == Some(region)) {
+        let [t, r, b, l] = tile_corners(pos);
+        top = top.min(t.y);
+        sides = (sides.0.min(l.x), sides.1.max(r.x));
+        // Only the edges the bank ends at.
+        let edges = [
+            (GridPos::new(pos.col, pos.row - 1), t, r),
+            (GridPos::new(pos.col + 1, pos.row), r, b),
+            (GridPos::new(pos.col, pos.row + 1), b, l),
+            (GridPos::new(pos.col - 1, pos.row), l, t),
+        ];
+        for (next, a, b) in edges {
+            if level.region_of(next) != Some(region) {
+                draw_line(a.x, a.y, b.x, b.y, 2.0, mark);
+            }
+        }
+    }
+
+    let GamePhase::Lost(LoseReason::Eaten { eater, eaten }) = state.phase else {
+        return;
+    };
+    for entity in state.entities_in(region) {
+        if let EntityLocation::OnBank { pos, .. } = entity.location
+            && (entity.kind == eater || entity.kind == eaten)
+        {
+            let (x, y) = world::grid_to_iso(pos);
+            let r = 11.0 + pulse * 2.0;
+            draw_ellipse_lines(x, y, r, r * 0.5, 0.0, 2.0, mark);
+        }
+    }
+    let line = format!("Nobody was watching the {}", level.region_name(region));
+    let style = TextStyle::new(14.0, FORENSICS_MARK).shadow(BLACK);
+    text::draw_centered(&line, (sides.0 + sides.1) / 2.0, top - 8.0, &style);
+}
+
+// ---------------------------------------------------------------------------
+// Fog of war
+// ---------------------------------------------------------------------------
+
+const FOG_COLOR: Color = Color::new(0.72, 0.75, 0.80, 0.88);
+
+/// Fog of war: whether `id` stands on a bank the farmers can't see.
+fn in_fog(state: &GameState, id: EntityId) -> bool {
+    matches!(
+        state.entity_location(id),
+        EntityLocation::OnBank { region, .. } if state.hidden_by_fog(region)
+    )
+}
+
+/// Cover the banks out of sight, rolling a little so it reads as fog.
+fn draw_fog(state: &GameState, time: f32) {
+    let level = &state.level;
+    for (pos, _) in tiles(level) {
+        let Some(region) = level.region_of(pos) else {
+            continue;
+        };
+        if !state.hidden_by_fog(region) {
+            continue;
+        }
+        let roll = ((pos.col + pos.row) as f32 * 0.7 + time * 0.8).sin();
+        let color = tinted(Color {
+            a: FOG_COLOR.a + 0.06 * roll,
+            ..FOG_COLOR
+        });
+        let [top, right, bottom, left] = tile_corners(pos);
+        draw_triangle(top, right, bottom, color);
+        draw_triangle(top, left, bottom, color);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Day/night tint
+// ---------------------------------------------------------------------------
+
+thread_local! {
+    /// Light color multiplied into everything drawn in the world pass.
+    static WORLD_TINT: Cell<Color> = const { Cell::new(WHITE) };
+}
+
+/// Sky light through the day: (day progress, tint), from dawn to nightfall.
+const DAYLIGHT: [(f32, Color); 5] = [
+    (0.0, Color::new(1.0, 1.0, 1.0, 1.0)),
+    (0.55, Color::new(1.0, 1.0, 1.0, 1.0)),
+    (0.75, Color::new(1.0, 0.78, 0.58, 1.0)),
+    (0.9, Color::new(0.58, 0.52, 0.78, 1.0)),
+    (1.0, Color::new(0.28, 0.30, 0.52, 1.0)),
+];
+
+/// The light the world is drawn in at this point in the day.
+pub fn world_tint(state: &GameState) -> Color {
+    state.day_progress().map_or(WHITE, daylight_tint)
+}
+
+fn daylight_tint(progress: f32) -> Color {
+    let next = DAYLIGHT
+        .iter()
+        .position(|&(at, _)| at >= progress)
+        .unwrap_or(DAYLIGHT.len() - 1)
+        .max(1);
+    let (t0, c0) = DAYLIGHT[next - 1];
+    let (t1, c1) = DAYLIGHT[next];
+    let t = ((progress - t0) / (t1 - t0)).clamp(0.0, 1.0);
+    Color::new(
+        c0.r + (c1.r - c0.r) * t,
+        c0.g + (c1.g - c0.g) * t,
+        c0.b + (c1.b - c0.b) * t,
+        1.0,
+    )
+}
+
+/// Apply the current world tint to a color.
+fn tinted(color: Color) -> Color {
+    let tint = WORLD_TINT.get();
+    Color::new(
+        color.r * tint.r,
+        color.g * tint.g,
+        color.b * tint.b,
+        color.a,
+    )
+}
+
+fn queue_trees(queue: &mut RenderQueue, level: &LevelDef) {
+    for pos in level.trees() {
+        let (x, y) = world::grid_to_iso(pos);
+        queue.push(y, Drawable::Tree, (x, y), 2.5);
+    }
+}
+
+/// Derived information the HUD shows alongside the game state.
+#[derive(Debug, Clone, Default)]
+pub struct HudInfo {
+    pub hint: Option<String>,
+    pub min_crossings: Option<usize>,
+    /// The shortest solution, filled in on winning for the win screen to
+    /// set the player's crossings against.
+    pub solution: Option<Vec<solver::Crossing>>,
+    pub keys: KeyLabels,
+    /// Seconds until the won level moves on.
+    pub advance_in: Option<f32>,
+    /// Whether a win goes back to the level select: the campaign's last
+    /// level, or a generated puzzle.
+    pub last_level: bool,
+    /// How much of the way the win or lose banner has left to slide in
+    /// from the left; 0 once it's in place.
+    pub banner_out: f32,
+    /// Whether there's a crossing to rewind to from a loss.
+    pub can_rewind: bool,
+    /// The attempt's points so far.
+    pub score: Score,
+}
+
+/// Key names shown in prompts, following the player's bindings.
+#[derive(Debug, Clone, Default)]
+pub struct KeyLabels {
+    pub moves: String,
+    pub interact: String,
+    pub cross: String,
+    pub restart: String,
+    /// What the player last used: prompts show its controls.
+    pub device: InputDevice,
+}
+
+impl KeyLabels {
+    pub fn new(keys: &KeyMap) -> Self {
+        let moves = [
+            Action::MoveUp,
+            Action::MoveLeft,
+            Action::MoveDown,
+            Action::MoveRight,
+        ]
+        .map(|action| keys.primary_name(action));
+        // "WASD" reads better than "W/A/S/D", but "Up/Left/..." needs slashes.
+        let separator = if moves.iter().all(|name| name.len() == 1) {
+            ""
+        } else {
+            "/"
+        };
         Self {
-            player: [
-                load_sprite("assets/sprites/player_idle.png").await,
-                load_sprite("assets/sprites/player_walk1.png").await,
-                load_sprite("assets/sprites/player_walk2.png").await,
-            ],
-            wolf: [
-                load_sprite("assets/sprites/wolf_idle.png").await,
-                load_sprite("assets/sprites/wolf_walk1.png").await,
-                load_sprite("assets/sprites/wolf_walk2.png").await,
-            ],
-            sheep: [
-                load_sprite("assets/sprites/sheep_idle.png").await,
-                load_sprite("assets/sprites/sheep_walk1.png").await,
-                load_sprite("assets/sprites/sheep_walk2.png").await,
-            ],
-            cabbage: load_sprite("assets/sprites/cabbage.png").await,
-            boat: load_sprite("assets/sprites/boat.png").await,
-            tree: load_sprite("assets/sprites/tree.png").await,
-            highlight: load_sprite("assets/sprites/highlight.png").await,
+            moves: moves.join(separator),
+            interact: keys.primary_name(Action::Interact).to_uppercase(),
+            cross: keys.primary_name(Action::CrossRiver).to_uppercase(),
+            restart: keys.primary_name(Action::Restart).to_uppercase(),
+            device: InputDevice::Keyboard,
+        }
+    }
+
+    fn glyph(&self, key: &str, touch: Glyph) -> Glyph {
+        match self.device {
+            InputDevice::Keyboard => Glyph::Key(key.to_string()),
+            InputDevice::Touch => touch,
+        }
+    }
+
+    fn moves_glyph(&self) -> Glyph {
+        self.glyph(&self.moves, Glyph::Pad)
+    }
+
+    fn interact_glyph(&self) -> Glyph {
+        self.glyph(&self.interact, Glyph::Touch(TouchButton::Interact))
+    }
+
+    fn cross_glyph(&self) -> Glyph {
+        self.glyph(&self.cross, Glyph::Touch(TouchButton::Cross))
+    }
+}
+
+/// A control named in a prompt, drawn as the player would find it.
+#[derive(Debug, Clone, PartialEq)]
+enum Glyph {
+    /// A key cap with the key's name on it.
+    Key(String),
+    /// One of the on-screen buttons.
+    Touch(TouchButton),
+    /// The on-screen d-pad.
+    Pad,
+}
+
+impl Glyph {
+    fn width(&self, size: f32) -> f32 {
+        match self {
+            Glyph::Key(name) => (text::measure(name, size * 0.8) + size * 0.6).max(size),
+            Glyph::Touch(_) | Glyph::Pad => size,
+        }
+    }
+
+    /// Draw with its left edge at `x`, sitting on the text baseline `y`.
+    fn draw(&self, x: f32, y: f32, size: f32, color: Color) {
+        let w = self.width(size);
+        let top = y - size * 0.85;
+        let c = vec2(x + w / 2.0, top + size / 2.0);
+        let fill = Color::new(0.0, 0.0, 0.0, 0.45);
+        match self {
+            Glyph::Key(name) => {
+                draw_rectangle(x, top, w, size, fill);
+                draw_rectangle_lines(x, top, w, size, 2.0, color);
+                let style = TextStyle::new(size * 0.8, color);
+                text::draw_centered(name, c.x, top + size * 0.75, &style);
+            }
+            Glyph::Touch(button) => {
+                draw_circle(c.x, c.y, size / 2.0, fill);
+                draw_circle_lines(c.x, c.y, size / 2.0, 2.0, color);
+                let style = TextStyle::new(size * 0.5, color);
+                text::draw_centered(touch_label(*button), c.x, c.y + size * 0.17, &style);
+            }
+            Glyph::Pad => {
+                let (long, thick) = (size * 0.8, size * 0.28);
+                draw_rectangle(c.x - long / 2.0, c.y - thick / 2.0, long, thick, color);
+                draw_rectangle(c.x - thick / 2.0, c.y - long / 2.0, thick, long, color);
+            }
+        }
+    }
+}
+
+/// A piece of a prompt line.
+enum Prompt {
+    Glyph(Glyph),
+    Text(String),
+}
+
+impl Prompt {
+    fn text(text: impl Into<String>) -> Self {
+        Prompt::Text(text.into())
+    }
+
+    fn width(&self, size: f32) -> f32 {
+        match self {
+            Prompt::Glyph(glyph) => glyph.width(size),
+            Prompt::Text(text) => text::measure(text, size),
+        }
+    }
+}
+
+/// `draw_hud_text` for a line of text and control glyphs.
+fn draw_hud_prompt(
+    layout: &HudLayout,
+    element: HudElement,
+    view: Rect,
+    parts: &[Prompt],
+    font_size: f32,
+    color: Color,
+) {
+    let width = parts.iter().map(|part| part.width(font_size)).sum();
+    let Some(pos) = layout.place(element, view, width) else {
+        return;
+    };
+    let mut x = pos.x;
+    for part in parts {
+        match part {
+            Prompt::Glyph(glyph) => glyph.draw(x, pos.y, font_size, color),
+            Prompt::Text(text) => text::draw(text, x, pos.y, &hud_style(font_size, color)),
+        }
+        x += part.width(font_size);
+    }
+}
+
+/// Widest a solver hint gets before it wraps.
+const HINT_WIDTH: f32 = 420.0;
+
+/// Draws in `layout`'s frame; see `setup_layout_camera`.
+pub fn draw_hud(state: &GameState, info: &HudInfo, layout: &HudLayout, options: &RenderOptions) {
+    let _span = profiler::scope("render::hud");
+    let view = layout.frame(view_rect());
+
+    if state.phase == GamePhase::Playing {
+        let keys = &info.keys;
+        let hint = interaction::describe_available_action(state).map(|hint| {
+            vec![
+                Prompt::Glyph(keys.interact_glyph()),
+                Prompt::text(format!(" {hint}")),
+            ]
+        });
+        let partner_hint = interaction::describe_for(state, PlayerId::Two).map(|hint| {
+            vec![
+                Prompt::text("P2 "),
+                Prompt::Glyph(Glyph::Key(input::PARTNER_INTERACT_NAME.to_string())),
+                Prompt::text(format!(" {hint}")),
+            ]
+        });
+        let parts = match (hint, partner_hint) {
+            (Some(mut hint), Some(partner)) => {
+                hint.push(Prompt::text("    "));
+                hint.extend(partner);
+                Some(hint)
+            }
+            (hint, partner) => hint.or(partner),
+        };
+        if let Some(parts) = parts {
+            draw_hud_prompt(layout, HudElement::ActionHint, view, &parts, 22.0, WHITE);
+        }
+
+        if let Some(boat) = state.player_boat() {
+            let prompt = match state.boat(boat).state {
+                BoatState::Docked(_) => {
+                    vec![
+                        Prompt::Glyph(keys.cross_glyph()),
+                        Prompt::text(" Cross river"),
+                    ]
+                }
+                BoatState::Crossing { returning, .. } if returning => {
+                    vec![Prompt::text("Drifting back...")]
+                }
+                BoatState::Crossing { .. } if state.level.current > 0.0 => vec![
+                    Prompt::Glyph(keys.moves_glyph()),
+                    Prompt::text(" Steer against the current; hold "),
+                    Prompt::Glyph(keys.cross_glyph()),
+                    Prompt::text(" to hurry"),
+                ],
+                BoatState::Crossing { .. } => vec![
+                    Prompt::text("Hold "),
+                    Prompt::Glyph(keys.cross_glyph()),
+                    Prompt::text(" to hurry"),
+                ],
+            };
+            // Red when casting off would lose the run.
+            let color = if state.would_lose_if_crossing().is_some() {
+                Color::new(1.0, 0.3, 0.25, 1.0)
+            } else {
+                YELLOW
+            };
+            draw_hud_prompt(layout, HudElement::CrossPrompt, view, &prompt, 20.0, color);
+        }
+
+        let moves_text = match state.level.par {
+            Some(par) => format!("Moves: {} (par {par})", state.move_count),
+            None => format!("Moves: {}", state.move_count),
+        };
+        let crossings_text = match info.min_crossings {
+            Some(min) => format!("Crossings: {} (min {min})", state.crossing_count),
+            None => format!("Crossings: {}", state.crossing_count),
+        };
+        let mut count_text = format!(
+            "{moves_text}   {crossings_text}   Score: {}",
+            info.score.total()
+        );
+        if state.assist {
+            count_text += "   [Assist]";
+        }
+        if !state.upgrades.is_empty() {
+            count_text += "   [Upgrades on: no records]";
+        }
+        draw_hud_text(
+            layout,
+            HudElement::CrossingCounter,
+            view,
+            &count_text,
+            20.0,
+            WHITE,
+        );
+        // The mutators in play, and how the run stands against them.
+        if !state.mutators.is_empty()
+            && let Some(pos) = layout.place(
+                HudElement::CrossingCounter,
+                view,
+                text::measure(&count_text, 20.0),
+            )
+        {
+            let names: Vec<&str> = state.mutators.iter().map(|m| m.name()).collect();
+            let mut line = names.join(", ");
+            for status in state.mutators.statuses(state) {
+                line += &format!("   {status}");
+            }
+            text::draw(&line, pos.x, pos.y + 18.0, &hud_style(16.0, ORANGE));
+        }
+
+        if !state.inventory.is_empty() {
+            let text = inventory_text(&state.inventory);
+            draw_hud_text(layout, HudElement::Inventory, view, &text, 18.0, WHITE);
+        }
+
+        if let Some(progress) = state.day_progress() {
+            draw_day_clock(layout, view, progress);
+        }
+
+        if let Some(hint) = &info.hint {
+            // Long hints wrap rather than run off the screen.
+            let line = format!("Hint: {hint}");
+            let style = hud_style(18.0, SKYBLUE);
+            let width = text::measure(&line, style.size).min(HINT_WIDTH);
+            if let Some(pos) = layout.place(HudElement::SolverHint, view, width) {
+                text::draw_paragraph(&line, pos.x, pos.y, width, &style);
+            }
+        }
+
+        let controls = format!(
+            "{}/Click: Move   {}: Interact   Tab: Target   1-3: Items   H: Hint   {}: Restart   Esc: Pause   Wheel/RMB: Zoom/Pan",
+            keys.moves, keys.interact, keys.restart
+        );
+        let width = text::measure(&controls, 16.0);
+        if let Some(pos) = layout.place(HudElement::Controls, view, width) {
+            text::draw(&controls, pos.x, pos.y, &hud_style(16.0, GRAY));
+            text::draw(
+                "B: Badges  N: Narrate  M/[/]: Sound  F2: Profile  F3: Debug  `: Console  F4: HUD  F5/F9: Save/Load  F6: Slot  F7: Step  F10: Spectate  F11: Fullscreen",
+                pos.x,
+                pos.y + 14.0,
+                &hud_style(13.0, GRAY),
+            );
+        }
+    }
+
+    // The banner slides in from the left.
+    let dx = -info.banner_out * view.w;
+    let (cx, cy) = (view.center().x + dx, view.center().y);
+    match state.phase {
+        GamePhase::Won => {
+            let headline = "All items across! You win!";
+            let style = draw_result_banner(true, headline, view, dx, options);
+            text::draw_centered(headline, cx, cy - 5.0, &style);
+            if let Some(optimal) = info.min_crossings {
+                let stars = solver::star_rating(state.crossing_count, optimal);
+                draw_star_rating(stars, cx, cy - 34.0);
+            }
+            let next = if info.last_level {
+                "Level select"
+            } else {
+                "Next level"
+            };
+            let prompt = match info.advance_in {
+                Some(left) => format!(
+                    "[{}] Play again   [{}] {next} ({:.0})",
+                    info.keys.restart,
+                    info.keys.interact,
+                    left.ceil()
+                ),
+                None => format!("[{}] Play again", info.keys.restart),
+            };
+            text::draw_centered(&prompt, cx, cy + 25.0, &hud_style(20.0, WHITE));
+        }
+        GamePhase::Lost(reason) => {
+            // Scripted losses can say a lot; keep the last line where a
+            // one-line message would sit.
+            let message = reason.message(&state.level);
+            let lines = text::wrap(&message, view.w - 60.0, banner_style(RED).size);
+            let style = draw_result_banner(false, &lines[lines.len() - 1], view, dx, options);
+            let step = text::line_height(style.size);
+            let last = cy - 5.0;
+            for (i, line) in lines.iter().rev().enumerate() {
+                text::draw_centered(line, cx, last - i as f32 * step, &style);
+            }
+            let mut prompt = format!("[{}] Try again", info.keys.restart);
+            if info.can_rewind {
+                prompt.push_str("   [U] Rewind to before that crossing");
+            }
+            text::draw_centered(&prompt, cx, cy + 25.0, &hud_style(20.0, WHITE));
+        }
+        GamePhase::Menu | GamePhase::Playing | GamePhase::Paused => {}
+    }
+}
+
+/// Sun (or moon, near the end) and the time left before nightfall.
+fn draw_day_clock(layout: &HudLayout, view: Rect, progress: f32) {
+    let remaining = ((1.0 - progress) * game::DAY_LENGTH).ceil() as u32;
+    let text = format!("Nightfall in {}:{:02}", remaining / 60, remaining % 60);
+    let icon_space = 20.0;
+    let width = text::measure(&text, 18.0) + icon_space;
+    let Some(pos) = layout.place(HudElement::DayClock, view, width) else {
+        return;
+    };
+
+    let (cx, cy, r) = (pos.x + 7.0, pos.y - 5.0, 7.0);
+    if progress < 0.75 {
+        draw_circle(cx, cy, r, GOLD);
+    } else {
+        draw_circle(cx, cy, r, Color::new(0.85, 0.88, 1.0, 1.0));
+        let sky = Color::new(0.05, 0.06, 0.12, 1.0);
+        draw_circle(cx + 3.0, cy - 2.0, r * 0.8, sky);
+    }
+    let color = if progress < 0.75 { WHITE } else { ORANGE };
+    text::draw(&text, pos.x + icon_space, pos.y, &hud_style(18.0, color));
+}
+
+const STAR_RADIUS: f32 = 9.0;
+const STAR_SPACING: f32 = 24.0;
+
+/// Three stars centered on `cx`, the first `earned` of them filled.
+fn draw_star_rating(earned: u8, cx: f32, cy: f32) {
+    for i in 0..3u8 {
+        let x = cx + (i as f32 - 1.0) * STAR_SPACING;
+        let color = if i < earned {
+            GOLD
+        } else {
+            Color::new(0.3, 0.3, 0.3, 1.0)
+        };
+        draw_star(vec2(x, cy), STAR_RADIUS, color);
+    }
+}
+
+/// A filled five-pointed star, point up.
+fn draw_star(center: Vec2, radius: f32, color: Color) {
+    let point = |i: usize| {
+        let r = if i.is_multiple_of(2) {
+            radius
+        } else {
+            radius * 0.45
+        };
+        let angle = -std::f32::consts::FRAC_PI_2 + i as f32 * std::f32::consts::PI / 5.0;
+        center + vec2(angle.cos(), angle.sin()) * r
+    };
+    for i in 0..10 {
+        draw_triangle(center, point(i), point(i + 1), color);
+    }
+}
+
+/// A ghost's farmer and boat, faint over the live run.
+pub fn draw_ghost(ghost: &Ghost, atlas: &SpriteAtlas) {
+    let Some((state, anim)) = ghost.run() else {
+        return;
+    };
+    for (i, boat_anim) in anim.boats.iter().enumerate() {
+        let (bx, by) = boat_screen_pos(state, BoatId(i));
+        let boat = atlas.frame(BOAT_SET, &boat_anim.clip);
+        draw_sprite_tinted(atlas, boat, bx, by, 2.5, false, GHOST_TINT);
+    }
+    let (x, y, sprite, flip) = match state.player.location {
+        PlayerLocation::OnBoat(boat) => {
+            let (bx, by) = boat_screen_pos(state, boat);
+            (bx + 6.0, by - 10.0, atlas.still(PLAYER_SET), false)
+        }
+        PlayerLocation::OnLand(_) => {
+            let (x, y) = anim.player_pos;
+            let sprite = atlas.frame(PLAYER_SET, &anim.player_clip);
+            (x, y, sprite, !anim.player_facing_right)
+        }
+    };
+    draw_sprite_tinted(atlas, sprite, x, y, 2.0, flip, GHOST_TINT);
+}
+
+/// The live run's time beside the ghost's, and the gap once either has
+/// finished.
+pub fn draw_ghost_timer(ghost: &Ghost, state: &GameState, layout: &HudLayout) {
+    if ghost.run().is_none() || state.phase == GamePhase::Menu {
+        return;
+    }
+    let (live, best) = (ghost.live_time(), ghost.time());
+    let mut text = format!(
+        "You {}   Ghost {}",
+        speedrun::format_time(live),
+        speedrun::format_time(best)
+    );
+    if state.phase == GamePhase::Won || live > best {
+        let delta = live - best;
+        let sign = if delta < 0.0 { '-' } else { '+' };
+        text += &format!("   {sign}{:.2}", delta.abs());
+    }
+    let (view, color) = (layout.frame(view_rect()), Color::new(0.8, 0.9, 1.0, 1.0));
+    draw_hud_text(layout, HudElement::GhostTimer, view, &text, 20.0, color);
+}
+
+/// An endless run's round, score and multiplier, and once it's lost, how
+/// it went against the best.
+pub fn draw_endless_score(run: &EndlessRun, state: &GameState, layout: &HudLayout) {
+    if state.phase == GamePhase::Menu {
+        return;
+    }
+    let text = format!(
+        "Endless round {}   Score {}   x{:.2}",
+        run.round(),
+        run.score(),
+        run.multiplier()
+    );
+    let view = layout.frame(view_rect());
+    draw_hud_text(layout, HudElement::Score, view, &text, 20.0, GOLD);
+
+    let best = run.best();
+    let (line, color) = if !run.is_over() {
+        (format!("Best {}", best.score), GRAY)
+    } else if run.is_new_best() {
+        (format!("New best: {} rounds cleared!", best.rounds), GOLD)
+    } else {
+        (
+            format!("Run over   Best {} ({} rounds)", best.score, best.rounds),
+            ORANGE,
+        )
+    };
+    if let Some(pos) = layout.place(HudElement::Score, view, text::measure(&text, 20.0)) {
+        text::draw(&line, pos.x, pos.y + 18.0, &hud_style(16.0, color));
+    }
+}
+
+/// Both sides of a versus race, each in its half of the window with its
+/// own camera, and who's winning.
+pub fn draw_race(
+    race: &Race,
+    atlas: &SpriteAtlas,
+    keys: &KeyLabels,
+    options: &RenderOptions,
+    time: f32,
+) {
+    let _span = profiler::scope("render::race");
+    set_default_camera();
+    clear_background(Color::new(0.05, 0.06, 0.12, 1.0));
+    let controls = [
+        format!("{}, {}, {}", keys.moves, keys.interact, keys.cross),
+        format!(
+            "Arrows, {}, {}",
+            input::PARTNER_INTERACT_NAME,
+            input::PARTNER_CROSS_NAME
+        ),
+    ];
+    for (i, (racer, viewport)) in race.racers.iter().zip(split_viewports()).enumerate() {
+        setup_camera_in(&racer.camera, viewport);
+        draw_world(&racer.state, atlas, &racer.anim, options, None, None, time);
+
+        setup_hud_camera_in(viewport);
+        let view = view_rect_in(viewport);
+        let style = hud_style(22.0, WHITE);
+        let heading = format!("Player {}  ({})", i + 1, controls[i]);
+        text::draw(&heading, view.x + 16.0, view.y + 32.0, &style);
+        let crossings = format!("Crossings: {}", racer.state.crossing_count);
+        text::draw(&crossings, view.x + 16.0, view.y + 58.0, &style);
+
+        let center = view.center();
+        if let GamePhase::Lost(reason) = racer.state.phase {
+            let line = format!("{} Trying again...", reason.message(&racer.state.level));
+            text::draw_centered(&line, center.x, center.y, &banner_style(RED));
+        }
+        if let Some(winner) = race.winner {
+            let (line, color) = if winner == i {
+                (format!("Player {} wins!", i + 1), GREEN)
+            } else {
+                ("Too slow!".to_string(), GRAY)
+            };
+            text::draw_centered(&line, center.x, center.y, &banner_style(color));
+        }
+    }
+
+    set_default_camera();
+    let middle = screen_width() / 2.0;
+    draw_line(middle, 0.0, middle, screen_height(), 3.0, BLACK);
+    let status = match race.winner {
+        Some(_) => format!(
+            "{}   [{}] Race again   [Esc] Leave",
+            speedrun::format_time(race.elapsed),
+            keys.restart
+        ),
+        None => speedrun::format_time(race.elapsed),
+    };
+    let style = TextStyle::new(26.0, WHITE).outline(BLACK);
+    text::draw_centered(&status, middle, screen_height() - 24.0, &style);
+}
+
+/// Elapsed time, the latest split against the personal best, and the best
+/// itself (or a new-best note once the level is won).
+pub fn draw_speedrun_timer(run: &Speedrun, state: &GameState, layout: &HudLayout) {
+    if !run.is_enabled() || state.phase == GamePhase::Menu {
+        return;
+    }
+    let mut text = format!("Time {}", speedrun::format_time(run.elapsed()));
+    if state.assist {
+        text += "   Assist: not recorded";
+    } else if !state.upgrades.is_empty() {
+        text += "   Upgrades: not recorded";
+    } else if run.is_tainted() {
+        text += "   Loaded/rewound: not recorded";
+    }
+    if let Some(delta) = run.split_delta(&state.level) {
+        let sign = if delta < 0.0 { '-' } else { '+' };
+        text += &format!("   Split {} {sign}{:.2}", run.splits().len(), delta.abs());
+    }
+    let view = layout.frame(view_rect());
+    draw_hud_text(layout, HudElement::SpeedrunTimer, view, &text, 20.0, WHITE);
+
+    let best = if state.phase == GamePhase::Won && run.is_new_best() {
+        Some(("New personal best!".to_string(), GOLD))
+    } else {
+        run.best(&state.level)
+            .map(|best| (format!("PB {}", speedrun::format_time(best.time)), GRAY))
+    };
+    if let Some((line, color)) = best
+        && let Some(pos) = layout.place(HudElement::SpeedrunTimer, view, text::measure(&text, 20.0))
+    {
+        text::draw(&line, pos.x, pos.y + 18.0, &hud_style(16.0, color));
+    }
+}
+
+/// How far below the middle of the HUD the panels under the win and lose
+/// banners start.
+const RESULTS_PANEL_GAP: f32 = 60.0;
+
+/// Width of one trip in the win screen's diagram, at most, and the height
+/// of the passengers drawn in it.
+const TRIP_CELL: f32 = 30.0;
+const TRIP_ICON: f32 = 14.0;
+const TRIP_LABEL_WIDTH: f32 = 40.0;
+
+/// Over the win banner: the player's crossings in order, each trip's
+/// passengers over an arrow the way it went, with the shortest solution
+/// under them to compare. Trips where the player's run parted from it are
+/// drawn in orange.
+pub fn draw_solution_diagram(
+    state: &GameState,
+    info: &HudInfo,
+    atlas: &SpriteAtlas,
+    layout: &HudLayout,
+) {
+    if state.phase != GamePhase::Won || state.trips.is_empty() {
+        return;
+    }
+    let best = info.solution.as_deref();
+    let mut rows = vec![("You", state.trips.as_slice())];
+    rows.extend(best.map(|best| ("Best", best)));
+    let longest = rows.iter().map(|(_, trips)| trips.len()).max().unwrap_or(0);
+
+    let view = layout.frame(view_rect());
+    let cell = TRIP_CELL.min((view.w - TRIP_LABEL_WIDTH - 40.0) / longest as f32);
+    let width = TRIP_LABEL_WIDTH + cell * longest as f32;
+    let row_height = TRIP_ICON + 14.0;
+    let height = row_height * rows.len() as f32;
+    // Above the banner, sliding in with it.
+    let x = view.center().x - info.banner_out * view.w - width / 2.0;
+    let top = view.center().y - 58.0 - height;
+    let panel = Color::new(0.0, 0.0, 0.0, 0.7);
+    draw_rectangle(x - 8.0, top - 6.0, width + 16.0, height + 8.0, panel);
+    for (row, (label, trips)) in rows.iter().enumerate() {
+        let y = top + row as f32 * row_height;
+        text::draw(label, x, y + TRIP_ICON, &TextStyle::new(14.0, LIGHTGRAY));
+        for (i, &trip) in trips.iter().enumerate() {
+            let parted = row == 0 && best.is_some_and(|best| best.get(i) != Some(&trip));
+            let cx = x + TRIP_LABEL_WIDTH + cell * (i as f32 + 0.5);
+            draw_trip(state, atlas, trip, vec2(cx, y), cell, parted);
+        }
+    }
+}
+
+/// One trip in the diagram, centered on `top.x`: who rode, or the farmer
+/// on their own, over an arrow toward where they went.
+fn draw_trip(
+    state: &GameState,
+    atlas: &SpriteAtlas,
+    trip: solver::Crossing,
+    top: Vec2,
+    cell: f32,
+    parted: bool,
+) {
+    let riders: Vec<Sprite> = trip
+        .passengers()
+        .map(|id| atlas.kind_still(state.kind(id)))
+        .collect();
+    let riders = if riders.is_empty() {
+        vec![atlas.still(PLAYER_SET)]
+    } else {
+        riders
+    };
+    let size = |sprite: Sprite| {
+        let scale = TRIP_ICON / sprite.height().max(1.0);
+        vec2(sprite.width() * scale, TRIP_ICON)
+    };
+    // Side by side, overlapping when they'd spill out of the cell.
+    let widest = riders.iter().map(|&s| size(s).x).fold(0.0, f32::max);
+    let step = widest.min(cell * 0.8 / riders.len() as f32);
+    let mut x = top.x - step * (riders.len() - 1) as f32 / 2.0;
+    for &sprite in &riders {
+        let size = size(sprite);
+        let rect = Rect::new(x - size.x / 2.0, top.y, size.x, size.y);
+        draw_sprite_in(atlas, sprite, rect, false, WHITE);
+        x += step;
+    }
+
+    let color = if parted { ORANGE } else { WHITE };
+    let y = top.y + TRIP_ICON + 6.0;
+    let reach = cell * 0.35;
+    // Regions count up from the left bank.
+    let toward = if trip.to.0 > trip.from.0 { 1.0 } else { -1.0 };
+    let tip = vec2(top.x + reach * toward, y);
+    draw_line(top.x - reach * toward, y, tip.x, tip.y, 1.5, color);
+    draw_triangle(
+        tip + vec2(2.0 * toward, 0.0),
+        tip + vec2(-3.0 * toward, -3.0),
+        tip + vec2(-3.0 * toward, 3.0),
+        color,
+    );
+}
+
+/// Session and lifetime results, and what a win scored, under the win or
+/// lose banner.
+pub fn draw_stats_summary(stats: &Stats, state: &GameState, layout: &HudLayout) {
+    let (session, lifetime) = (&stats.session, &stats.lifetime);
+    let mut lines = Vec::new();
+    if let Some(seconds) = stats.last_solve() {
+        lines.push(format!("This solve: {seconds:.1}s"));
+    }
+    if let Some(score) = stats.last_score() {
+        let mut total = format!("Score: {}", score.total());
+        if let Some(best) = lifetime.best_scores.get(&state.level.name) {
+            total += &format!(" (best {best})");
+        }
+        lines.push(total);
+        let mut parts = vec![format!("{} across", score.delivered)];
+        if score.crossings_bonus > 0 {
+            parts.push(format!("+{} fewest crossings", score.crossings_bonus));
+        }
+        if score.time_bonus > 0 {
+            parts.push(format!("+{} time", score.time_bonus));
+        }
+        if score.drop_penalty > 0 {
+            parts.push(format!("-{} drops", score.drop_penalty));
+        }
+        lines.push(parts.join("  "));
+    }
+    lines.push(format!(
+        "Session: {} won, {} lost",
+        session.games_won, session.games_lost
+    ));
+    lines.push(format!(
+        "Lifetime: {} won, {} lost, {} crossings",
+        lifetime.games_won, lifetime.games_lost, lifetime.total_crossings
+    ));
+    if let Some(seconds) = lifetime.fastest_solve {
+        lines.push(format!("Fastest solve: {seconds:.1}s"));
+    }
+    if let Some((cause, count)) = lifetime.worst_loss() {
+        lines.push(format!("Most losses: {cause} ({count})"));
+    }
+
+    let line_height = 20.0;
+    let center = layout.frame(view_rect()).center();
+    let top = center.y + RESULTS_PANEL_GAP;
+    draw_rectangle(
+        center.x - 200.0,
+        top,
+        400.0,
+        lines.len() as f32 * line_height + 16.0,
+        Color::new(0.0, 0.0, 0.0, 0.7),
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let y = top + 22.0 + i as f32 * line_height;
+        text::draw_centered(line, center.x, y, &TextStyle::new(18.0, LIGHTGRAY));
+    }
+}
+
+/// The top scores for the level just won, in a panel right of the stats.
+/// Nothing unless this win went to the leaderboard.
+pub fn draw_leaderboard(board: &Leaderboard, state: &GameState, layout: &HudLayout) {
+    let Some((top, status)) = board.board(&state.level.name) else {
+        return;
+    };
+    let heading = match status {
+        BoardStatus::Sending => "Leaderboard (sending...)",
+        BoardStatus::Online => "Leaderboard",
+        BoardStatus::Offline => "Leaderboard (offline)",
+    };
+    let mut lines = vec![heading.to_string()];
+    for (i, score) in top.iter().enumerate() {
+        let player: String = score.player.chars().take(10).collect();
+        lines.push(format!(
+            "{:>2}. {player:<10} {} {}x",
+            i + 1,
+            speedrun::format_time(score.time),
+            score.crossings
+        ));
+    }
+    if top.is_empty() {
+        lines.push("No scores yet".to_string());
+    }
+
+    let line_height = 18.0;
+    let center = layout.frame(view_rect()).center();
+    let (left, top) = (center.x + 210.0, center.y + RESULTS_PANEL_GAP);
+    draw_rectangle(
+        left,
+        top,
+        220.0,
+        lines.len() as f32 * line_height + 16.0,
+        Color::new(0.0, 0.0, 0.0, 0.7),
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let y = top + 22.0 + i as f32 * line_height;
+        let color = if i == 0 { WHITE } else { LIGHTGRAY };
+        text::draw(line, left + 10.0, y, &TextStyle::new(16.0, color));
+    }
+}
+
+const NARRATION_WIDTH: f32 = 340.0;
+
+/// The narrator's scene description over its latest announcements, in a
+/// panel growing up from the narration anchor. Nothing while it's off.
+pub fn draw_narration(narrator: &Narrator, layout: &HudLayout) {
+    let Some(scene) = narrator.scene() else {
+        return;
+    };
+    let view = layout.frame(view_rect());
+    let Some(pos) = layout.place(HudElement::Narration, view, NARRATION_WIDTH) else {
+        return;
+    };
+    let scene_style = TextStyle::new(15.0, WHITE);
+    let log_style = TextStyle::new(13.0, LIGHTGRAY);
+    let wrap = |line: &str, style: &TextStyle| text::wrap(line, NARRATION_WIDTH, style.size);
+    let mut lines: Vec<(String, TextStyle)> = wrap(scene, &scene_style)
+        .into_iter()
+        .map(|line| (line, scene_style))
+        .collect();
+    for entry in narrator.log() {
+        let wrapped = wrap(entry, &log_style);
+        lines.extend(wrapped.into_iter().map(|line| (line, log_style)));
+    }
+
+    let step = |style: &TextStyle| text::line_height(style.size);
+    let height: f32 = lines.iter().map(|(_, style)| step(style)).sum();
+    let top = pos.y - height;
+    draw_rectangle(
+        pos.x - 8.0,
+        top - 4.0,
+        NARRATION_WIDTH + 16.0,
+        height + 12.0,
+        Color::new(0.0, 0.0, 0.0, 0.65),
+    );
+    let mut y = top;
+    for (line, style) in &lines {
+        y += step(style);
+        text::draw(line, pos.x, y - style.size * 0.25, style);
+    }
+}
+
+/// Height of the bars a cutscene frames the screen with.
+const LETTERBOX: f32 = 36.0;
+
+/// Letterbox bars, the line being said, and how to skip.
+pub fn draw_cutscene(scene: &Cutscene) {
+    let view = view_rect();
+    let bar = Color::new(0.0, 0.0, 0.0, 0.85);
+    draw_rectangle(view.x, view.y, view.w, LETTERBOX, bar);
+    draw_rectangle(view.x, view.bottom() - LETTERBOX, view.w, LETTERBOX, bar);
+    let hint = "Any key to skip";
+    let x = view.right() - text::measure(hint, 14.0) - 10.0;
+    text::draw(hint, x, view.y + 22.0, &hud_style(14.0, GRAY));
+
+    let Some((who, line)) = scene.line() else {
+        return;
+    };
+    let size = 18.0;
+    let width = view.w - 120.0;
+    let lines = text::wrap(line, width - 24.0, size);
+    let step = text::line_height(size);
+    let height = 34.0 + lines.len() as f32 * step;
+    let (x, y) = (view.x + 60.0, view.bottom() - LETTERBOX - height - 10.0);
+    draw_rectangle(x, y, width, height, Color::new(0.05, 0.06, 0.12, 0.9));
+    draw_rectangle_lines(x, y, width, height, 2.0, Color::new(0.85, 0.75, 0.5, 1.0));
+    text::draw(who, x + 12.0, y + 20.0, &hud_style(16.0, GOLD));
+    for (i, text_line) in lines.iter().enumerate() {
+        let baseline = y + 22.0 + (i + 1) as f32 * step;
+        text::draw(text_line, x + 12.0, baseline, &hud_style(size, WHITE));
+    }
+}
+
+/// Where the choices of a conversation with the ferryman go, for
+/// `pause::item_rect`: the lower part of `view`.
+pub fn dialogue_choices(view: Rect) -> Rect {
+    let top = view.y + view.h * 0.55;
+    Rect::new(view.x, top, view.w, view.bottom() - top)
+}
+
+/// The ferryman's line in a box, and the answers to pick from beneath it.
+pub fn draw_dialogue(ferryman: &Ferryman) {
+    let Some((line, choices, selected)) = ferryman.page() else {
+        return;
+    };
+    let view = view_rect();
+    let area = dialogue_choices(view);
+    let first = pause::item_rect(0, choices.len(), area);
+
+    let size = 18.0;
+    let width = view.w - 120.0;
+    let lines = text::wrap(&line, width - 24.0, size);
+    let step = text::line_height(size);
+    let height = 34.0 + lines.len() as f32 * step;
+    let (x, y) = (view.x + 60.0, first.y - height - 14.0);
+    draw_rectangle(x, y, width, height, Color::new(0.05, 0.06, 0.12, 0.9));
+    draw_rectangle_lines(x, y, width, height, 2.0, Color::new(0.85, 0.75, 0.5, 1.0));
+    text::draw("Ferryman", x + 12.0, y + 20.0, &hud_style(16.0, GOLD));
+    for (i, text_line) in lines.iter().enumerate() {
+        let baseline = y + 22.0 + (i + 1) as f32 * step;
+        text::draw(text_line, x + 12.0, baseline, &hud_style(size, WHITE));
+    }
+
+    for (i, label) in choices.iter().enumerate() {
+        let rect = pause::item_rect(i, choices.len(), area);
+        let (fill, ink) = if i == selected {
+            (Color::new(0.9, 0.8, 0.3, 0.9), BLACK)
+        } else {
+            (Color::new(0.15, 0.15, 0.2, 0.85), WHITE)
+        };
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+        let baseline = rect.y + rect.h * 0.7;
+        text::draw_centered(label, rect.center().x, baseline, &TextStyle::new(18.0, ink));
+    }
+}
+
+/// A short status line, drawn at the toast anchor.
+/// A note across the middle of the screen while a generated puzzle is
+/// drawn.
+pub fn draw_generating() {
+    let center = view_rect().center();
+    let style = TextStyle::new(32.0, WHITE).outline(BLACK);
+    text::draw_centered("Generating puzzle...", center.x, center.y, &style);
+}
+
+pub fn draw_toast(text: &str, layout: &HudLayout) {
+    let width = text::measure(text, 20.0);
+    if let Some(pos) = layout.place(HudElement::Toast, layout.frame(view_rect()), width) {
+        draw_rectangle(
+            pos.x - 8.0,
+            pos.y - 18.0,
+            width + 16.0,
+            26.0,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        text::draw(text, pos.x, pos.y, &TextStyle::new(20.0, WHITE));
+    }
+}
+
+/// "[1] rope x2   [2] lantern (lit)", leaving out what isn't carried.
+fn inventory_text(inventory: &Inventory) -> String {
+    let entries: Vec<String> = Item::ALL
+        .iter()
+        .enumerate()
+        .filter(|&(_, &item)| inventory.count(item) > 0)
+        .map(|(i, &item)| {
+            let note = match inventory.count(item) {
+                _ if item == Item::Lantern && inventory.lantern_lit => " (lit)".to_string(),
+                1 => String::new(),
+                count => format!(" x{count}"),
+            };
+            format!("[{}] {}{note}", i + 1, item.name())
+        })
+        .collect();
+    entries.join("   ")
+}
+
+fn draw_hud_text(
+    layout: &HudLayout,
+    element: HudElement,
+    view: Rect,
+    text: &str,
+    font_size: f32,
+    color: Color,
+) {
+    if let Some(pos) = layout.place(element, view, text::measure(text, font_size)) {
+        text::draw(text, pos.x, pos.y, &hud_style(font_size, color));
+    }
+}
+
+/// HUD text over the world, shadowed so it reads on grass and water alike.
+fn hud_style(size: f32, color: Color) -> TextStyle {
+    TextStyle::new(size, color).shadow(Color::new(0.0, 0.0, 0.0, 0.6))
+}
+
+/// The headline of the win and lose banners.
+fn banner_style(color: Color) -> TextStyle {
+    TextStyle::new(28.0, color).outline(BLACK)
+}
+
+/// The strip behind a win or lose headline, returning the headline's
+/// style: across the middle of `view`, shifted `dx` to the right. High
+/// contrast trades the green and red for a neutral strip with a check or a
+/// cross beside `last_line`.
+fn draw_result_banner(
+    won: bool,
+    last_line: &str,
+    view: Rect,
+    dx: f32,
+    options: &RenderOptions,
+) -> TextStyle {
+    let (x, y, w, h) = (view.x + dx, view.center().y - 50.0, view.w, 100.0);
+    if !options.high_contrast {
+        let (fill, ink) = if won {
+            (Color::new(0.0, 0.2, 0.0, 0.85), GREEN)
+        } else {
+            (Color::new(0.2, 0.0, 0.0, 0.85), RED)
+        };
+        draw_rectangle(x, y, w, h, fill);
+        return banner_style(ink);
+    }
+
+    let strip = Color::new(0.02, 0.02, 0.02, 0.92);
+    draw_rectangle(x, y, w, h, strip);
+    draw_line(x, y, x + w, y, 2.0, WHITE);
+    draw_line(x, y + h, x + w, y + h, 2.0, WHITE);
+    let style = banner_style(WHITE);
+    let icon = vec2(
+        x + w / 2.0 - text::measure(last_line, style.size) / 2.0 - 24.0,
+        y + 40.0,
+    );
+    let r = 10.0;
+    if won {
+        let (a, b, c) = (
+            icon + vec2(-r, 0.0),
+            icon + vec2(-r * 0.3, r * 0.7),
+            icon + vec2(r, -r),
+        );
+        draw_line(a.x, a.y, b.x, b.y, 4.0, WHITE);
+        draw_line(b.x, b.y, c.x, c.y, 4.0, WHITE);
+    } else {
+        draw_line(icon.x - r, icon.y - r, icon.x + r, icon.y + r, 4.0, WHITE);
+        draw_line(icon.x - r, icon.y + r, icon.x + r, icon.y - r, 4.0, WHITE);
+    }
+    style
+}
+
+/// Status strip shown while frame-advance debugging is active.
+pub fn draw_frame_advance_banner(text: &str) {
+    let view = view_rect();
+    let strip = Color::new(0.3, 0.0, 0.3, 0.7);
+    draw_rectangle(view.x, view.y + 40.0, view.w, 22.0, strip);
+    let style = TextStyle::new(18.0, WHITE);
+    text::draw_centered(text, view.center().x, view.y + 56.0, &style);
+}
+
+// ---------------------------------------------------------------------------
+// Pause menu
+// ---------------------------------------------------------------------------
+
+pub fn draw_pause_menu(menu: &PauseMenu) {
+    let (items, selected) = menu.items();
+    draw_menu(menu.title(), &items, selected);
+}
+
+pub fn draw_level_select(
+    select: &LevelSelect,
+    campaign: &Campaign,
+    thumbnails: &Thumbnails,
+    mutators: Mutators,
+) {
+    let (items, selected) = select.items(campaign, mutators);
+    draw_menu(select.title(), &items, selected);
+
+    // A preview of the highlighted level beside the list.
+    if let Some(texture) = thumbnails.textures.get(selected) {
+        let first = pause::item_rect(0, items.len(), view_rect());
+        let (x, y) = (first.right() + 24.0, first.y);
+        draw_rectangle(
+            x - 2.0,
+            y - 2.0,
+            texture.width() + 4.0,
+            texture.height() + 4.0,
+            WHITE,
+        );
+        draw_texture(texture, x, y, WHITE);
+    }
+}
+
+/// The mutators page, with what the highlighted one does under the list.
+pub fn draw_mutator_menu(menu: &MutatorMenu, chosen: Mutators) {
+    let (items, selected) = menu.items(chosen);
+    draw_menu(menu.title(), &items, selected);
+    draw_menu_description(menu.description(), items.len());
+}
+
+/// The boat upgrades page, with what the highlighted one does under the
+/// list.
+pub fn draw_upgrade_menu(menu: &UpgradeMenu, campaign: &Campaign) {
+    let (items, selected) = menu.items(campaign);
+    draw_menu(menu.title(), &items, selected);
+    draw_menu_description(menu.description(), items.len());
+}
+
+/// A line under a menu of `count` rows.
+fn draw_menu_description(description: Option<&str>, count: usize) {
+    if let Some(description) = description {
+        let last = pause::item_rect(count - 1, count, view_rect());
+        let style = TextStyle::new(18.0, LIGHTGRAY).shadow(BLACK);
+        text::draw_centered(description, last.center().x, last.bottom() + 30.0, &style);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rules reference
+// ---------------------------------------------------------------------------
+
+const RULES_ICON: f32 = 48.0;
+/// The graph's radius, at most; it shrinks to fit small windows.
+const RULES_RING: f32 = 150.0;
+const RULES_ARROW: Color = Color::new(0.95, 0.45, 0.35, 1.0);
+
+/// Who eats whom on this level, drawn as a graph from its eating rules:
+/// each kind about the level in a ring, an arrow from every eater to what
+/// it eats. The rules underneath spell it out, with anything else the level
+/// and the mutators add.
+pub fn draw_rules(state: &GameState, atlas: &SpriteAtlas) {
+    let level = &state.level;
+    let view = view_rect();
+    draw_backdrop(view);
+    let title_style = TextStyle::new(36.0, WHITE).shadow(BLACK);
+    text::draw_centered(
+        "Who eats whom",
+        view.center().x,
+        view.y + 60.0,
+        &title_style,
+    );
+
+    // The kinds on the level first, then any only the rules mention.
+    let mut kinds: Vec<KindId> = Vec::new();
+    let mentioned = level.entities.iter().map(|&(kind, _)| kind).chain(
+        level
+            .eating_rules
+            .iter()
+            .flat_map(|rule| [rule.eater, rule.eaten]),
+    );
+    for kind in mentioned {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    let center = vec2(view.center().x, view.y + view.h * 0.4);
+    let radius = RULES_RING.min(view.h * 0.22);
+    // Two face each other across the middle; more go round from the top.
+    let start = if kinds.len() == 2 {
+        std::f32::consts::PI
+    } else {
+        -std::f32::consts::FRAC_PI_2
+    };
+    let node = |kind: KindId| {
+        let i = kinds.iter().position(|&k| k == kind).unwrap_or(0);
+        if kinds.len() == 1 {
+            return center;
+        }
+        let angle = start + i as f32 / kinds.len() as f32 * std::f32::consts::TAU;
+        center + Vec2::from_angle(angle) * radius
+    };
+
+    let label_style = TextStyle::new(16.0, RULES_ARROW).shadow(BLACK);
+    for rule in &level.eating_rules {
+        let (from, to) = (node(rule.eater), node(rule.eaten));
+        if rule.eater == rule.eaten {
+            let loop_at = from - vec2(0.0, RULES_ICON * 0.9);
+            draw_circle_lines(loop_at.x, loop_at.y, RULES_ICON * 0.35, 2.5, RULES_ARROW);
+            text::draw_centered("eats", loop_at.x, loop_at.y + 5.0, &label_style);
+            continue;
+        }
+        let along = (to - from).normalize_or_zero();
+        let across = along.perp();
+        // Eating each other: the two arrows run side by side.
+        let mutual = level
+            .eating_rules
+            .iter()
+            .any(|other| other.eater == rule.eaten && other.eaten == rule.eater);
+        let shift = if mutual { across * 6.0 } else { Vec2::ZERO };
+        let gap = RULES_ICON * 0.75;
+        let (tail, tip) = (from + along * gap + shift, to - along * gap + shift);
+        draw_line(tail.x, tail.y, tip.x, tip.y, 2.5, RULES_ARROW);
+        draw_triangle(
+            tip,
+            tip - along * 12.0 + across * 6.0,
+            tip - along * 12.0 - across * 6.0,
+            RULES_ARROW,
+        );
+        let middle = (tail + tip) / 2.0 + across * if mutual { 18.0 } else { 12.0 };
+        text::draw_centered("eats", middle.x, middle.y + 5.0, &label_style);
+    }
+
+    let name_style = TextStyle::new(18.0, WHITE).shadow(BLACK);
+    for &kind in &kinds {
+        let at = node(kind);
+        draw_circle(
+            at.x,
+            at.y,
+            RULES_ICON * 0.7,
+            Color::new(0.15, 0.15, 0.2, 0.9),
+        );
+        let sprite = atlas.kind_still(level.kind(kind));
+        let width = sprite.width() * RULES_ICON / sprite.height().max(1.0);
+        let rect = Rect::new(
+            at.x - width / 2.0,
+            at.y - RULES_ICON / 2.0,
+            width,
+            RULES_ICON,
+        );
+        draw_sprite_in(atlas, sprite, rect, false, WHITE);
+        let name = &level.kind(kind).name;
+        text::draw_centered(name, at.x, at.y + RULES_ICON * 0.7 + 20.0, &name_style);
+    }
+
+    let mut lines: Vec<String> = level
+        .eating_rules
+        .iter()
+        .map(|rule| {
+            let (eater, eaten) = (&level.kind(rule.eater).name, &level.kind(rule.eaten).name);
+            if rule.eater == rule.eaten {
+                format!("A {eater} eats another {eaten}")
+            } else {
+                format!("The {eater} eats the {eaten}")
+            }
+        })
+        .collect();
+    lines.push(if level.eating_rules.is_empty() {
+        "Nothing here eats anything else".to_string()
+    } else {
+        "...but nobody eats with a farmer on the same bank".to_string()
+    });
+    if let Some(hunger) = state.hunger_seconds() {
+        lines.push(format!(
+            "An eater left alone {hunger:.0}s grows famished, and eats whoever is watching"
+        ));
+    }
+    if level.patrol.is_some() {
+        lines.push("A wild wolf walks its beat: don't let it catch you".to_string());
+    }
+    if level.loss_script.is_some() {
+        lines.push("This level has a rule of its own besides".to_string());
+    }
+    for mutator in state.mutators.iter() {
+        lines.push(format!("{}: {}", mutator.name(), mutator.describe()));
+    }
+    lines.push("[Esc] Back".to_string());
+    let rule_style = TextStyle::new(20.0, WHITE).shadow(BLACK);
+    let mut y = center.y + radius + RULES_ICON + 48.0;
+    for line in &lines {
+        text::draw_centered(line, view.center().x, y, &rule_style);
+        y += text::line_height(20.0);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Level thumbnails
+// ---------------------------------------------------------------------------
+
+const THUMBNAIL_WIDTH: f32 = 240.0;
+
+/// A small picture of each campaign level's starting layout, indexed like
+/// the campaign's levels. Rendered offscreen once, when the campaign loads.
+pub struct Thumbnails {
+    textures: Vec<Texture2D>,
+}
+
+impl Thumbnails {
+    pub fn render(campaign: &Campaign, atlas: &SpriteAtlas) -> Self {
+        let textures = campaign
+            .levels()
+            .iter()
+            .map(|entry| render_thumbnail(&entry.level, atlas))
+            .collect();
+        set_default_camera();
+        Self { textures }
+    }
+}
+
+fn render_thumbnail(level: &Arc<LevelDef>, atlas: &SpriteAtlas) -> Texture2D {
+    let bounds = camera::level_bounds(level);
+    let height = THUMBNAIL_WIDTH * bounds.h / bounds.w;
+    let target = render_target(THUMBNAIL_WIDTH as u32, height as u32);
+    target.texture.set_filter(FilterMode::Nearest);
+
+    // Render targets want the camera's own y-up orientation, so no flip.
+    let mut camera = Camera2D::from_display_rect(bounds);
+    camera.render_target = Some(target.clone());
+    set_camera(&camera);
+    clear_background(Color::new(0.05, 0.06, 0.12, 1.0));
+
+    let state = GameState::new(Arc::clone(level));
+    let anim = AnimState::new(&state);
+    draw_world(
+        &state,
+        atlas,
+        &anim,
+        &RenderOptions::default(),
+        None,
+        None,
+        0.0,
+    );
+    target.texture
+}
+
+pub fn draw_settings_screen(screen: &SettingsScreen, settings: &Settings) {
+    let (items, selected) = screen.items(settings);
+    draw_menu(&screen.title(), &items, selected);
+}
+
+/// A dimmed backdrop with a title over a column of items laid out by
+/// `pause::item_rect`.
+fn draw_menu(title: &str, items: &[impl AsRef<str>], selected: usize) {
+    let view = view_rect();
+    draw_backdrop(view);
+
+    let first = pause::item_rect(0, items.len(), view);
+    let title_style = TextStyle::new(36.0, WHITE).shadow(BLACK);
+    text::draw_centered(title, first.center().x, first.y - 24.0, &title_style);
+
+    for (i, label) in items.iter().enumerate() {
+        let rect = pause::item_rect(i, items.len(), view);
+        let (fill, ink) = if i == selected {
+            (Color::new(0.9, 0.8, 0.3, 0.9), BLACK)
+        } else {
+            (Color::new(0.15, 0.15, 0.2, 0.85), WHITE)
+        };
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+        let baseline = rect.y + rect.h * 0.7;
+        let style = TextStyle::new(22.0, ink);
+        text::draw_centered(label.as_ref(), rect.center().x, baseline, &style);
+    }
+}
+
+/// Dim the game under a menu.
+fn draw_backdrop(view: Rect) {
+    draw_rectangle(
+        view.x,
+        view.y,
+        view.w,
+        view.h,
+        Color::new(0.0, 0.0, 0.05, 0.6),
+    );
+}
+
+/// The virtual d-pad and action buttons, once the player has touched the
+/// screen.
+/// What an on-screen action button says on it.
+fn touch_label(button: TouchButton) -> &'static str {
+    if button == TouchButton::Interact {
+        "Use"
+    } else {
+        "Row"
+    }
+}
+
+pub fn draw_touch_controls(pad: &TouchPad) {
+    if !pad.is_enabled() {
+        return;
+    }
+    let view = view_rect();
+    for button in TouchButton::ALL {
+        let rect = button.rect(view);
+        let alpha = if pad.is_held(button) { 0.7 } else { 0.35 };
+        let fill = Color::new(0.9, 0.9, 1.0, alpha);
+        let mark = Color::new(0.05, 0.05, 0.1, 0.8);
+        let c = rect.center();
+        match button.direction() {
+            Some(direction) => {
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+                // An arrowhead pointing the way the button moves.
+                let (dx, dy) = match direction {
+                    world::Direction::Up => (0.0, -1.0),
+                    world::Direction::Down => (0.0, 1.0),
+                    world::Direction::Left => (-1.0, 0.0),
+                    world::Direction::Right => (1.0, 0.0),
+                };
+                let r = rect.w * 0.25;
+                let tip = c + vec2(dx, dy) * r;
+                let side = vec2(-dy, dx) * r;
+                let back = c - vec2(dx, dy) * r * 0.6;
+                draw_triangle(tip, back + side, back - side, mark);
+            }
+            None => {
+                draw_circle(c.x, c.y, rect.w / 2.0, fill);
+                let label = touch_label(button);
+                text::draw_centered(label, c.x, c.y + 6.0, &TextStyle::new(20.0, mark));
+            }
         }
     }
 }
 
 // ---------------------------------------------------------------------------
-// Camera
+// Tiles
 // ---------------------------------------------------------------------------
 
-pub fn setup_camera() {
-    let world_h = world::WORLD_HEIGHT;
-    let aspect = screen_width() / screen_height();
-    let world_w = world_h * aspect;
-
-    let offset_x = (world_w - 880.0) / 2.0;
+/// Every tile of `level`, back to front.
+fn tiles(level: &LevelDef) -> impl Iterator<Item = (GridPos, Tile)> + '_ {
+    (0..=(level.cols + level.rows - 2)).flat_map(move |depth| {
+        (0..level.cols)
+            .map(move |col| GridPos::new(col, depth - col))
+            .filter(|pos| (0..level.rows).contains(&pos.row))
+            .map(|pos| (pos, level.tile(pos)))
+    })
+}
 
-    let mut camera = Camera2D::from_display_rect(Rect {
-        x: -offset_x,
-        y: 0.0,
-        w: world_w,
-        h: world_h,
-    });
-    camera.zoom.y = -camera.zoom.y;
-    set_camera(&camera);
+/// Tile colors, as (fill, outline) pairs.
+struct Palette {
+    grass: [(Color, Color); 2],
+    shore: (Color, Color),
+    rock: (Color, Color),
+    water: (Color, Color),
+    deck: (Color, Color),
+    /// Multiplies the wave shader's blues.
+    water_tint: Color,
+    outline_width: f32,
+    /// Whether the river may use the wave shader, or stays flat tiles.
+    shaded_water: bool,
 }
 
-// ---------------------------------------------------------------------------
-// Drawing
-// ---------------------------------------------------------------------------
+/// Each biome's colors, in `Biome` order.
+const BIOME_PALETTES: [Palette; 4] = [
+    Palette::SUMMER,
+    Palette::AUTUMN,
+    Palette::WINTER,
+    Palette::SWAMP,
+];
 
-pub fn draw_world(state: &GameState, atlas: &SpriteAtlas, anim: &AnimState, time: f32) {
-    draw_tiles(time);
-    draw_trees(atlas);
-    draw_boat(state, atlas, anim);
-    draw_entities(state, atlas, anim);
-    draw_dock_markers(state, atlas);
-}
-
-fn draw_trees(atlas: &SpriteAtlas) {
-    let tree_positions = [
-        GridPos::new(0, 0),
-        GridPos::new(0, 1),
-        GridPos::new(0, 6),
-        GridPos::new(0, 7),
-        GridPos::new(1, 0),
-        GridPos::new(1, 7),
-        GridPos::new(11, 0),
-        GridPos::new(11, 1),
-        GridPos::new(11, 6),
-        GridPos::new(11, 7),
-        GridPos::new(10, 0),
-        GridPos::new(10, 7),
-    ];
-    for pos in &tree_positions {
-        let (x, y) = world::grid_to_iso(*pos);
-        draw_sprite(&atlas.tree, x, y, 2.5);
-    }
-}
+impl Palette {
+    const SUMMER: Self = Self {
+        grass: [
+            (
+                Color::new(0.35, 0.70, 0.25, 1.0),
+                Color::new(0.28, 0.58, 0.18, 1.0),
+            ),
+            (
+                Color::new(0.30, 0.63, 0.22, 1.0),
+                Color::new(0.25, 0.52, 0.16, 1.0),
+            ),
+        ],
+        shore: (
+            Color::new(0.55, 0.45, 0.28, 1.0),
+            Color::new(0.45, 0.38, 0.22, 1.0),
+        ),
+        rock: (
+            Color::new(0.52, 0.50, 0.47, 1.0),
+            Color::new(0.38, 0.36, 0.34, 1.0),
+        ),
+        water: (
+            Color::new(0.12, 0.30, 0.65, 1.0),
+            Color::new(0.08, 0.22, 0.50, 1.0),
+        ),
+        deck: (
+            Color::new(0.55, 0.38, 0.20, 1.0),
+            Color::new(0.40, 0.26, 0.12, 1.0),
+        ),
+        water_tint: WHITE,
+        outline_width: 1.0,
+        shaded_water: true,
+    };
 
-pub fn draw_hud(state: &GameState) {
-    if state.phase == GamePhase::Playing {
-        if let Some(hint) = interaction::describe_available_action(state) {
-            draw_text_centered(hint, 440.0, world::WORLD_HEIGHT - 20.0, 22.0, WHITE);
-        }
-
-        if state.player == PlayerLocation::OnBoat {
-            if let BoatState::Docked(_) = state.boat {
-                draw_text_centered(
-                    "[SPACE] Cross river",
-                    440.0,
-                    world::WORLD_HEIGHT - 42.0,
-                    20.0,
-                    YELLOW,
-                );
-            }
-        }
+    /// Straw-colored grass and a browner river.
+    const AUTUMN: Self = Self {
+        grass: [
+            (
+                Color::new(0.62, 0.58, 0.26, 1.0),
+                Color::new(0.50, 0.46, 0.20, 1.0),
+            ),
+            (
+                Color::new(0.58, 0.52, 0.24, 1.0),
+                Color::new(0.46, 0.40, 0.18, 1.0),
+            ),
+        ],
+        water: (
+            Color::new(0.14, 0.30, 0.55, 1.0),
+            Color::new(0.10, 0.22, 0.42, 1.0),
+        ),
+        water_tint: Color::new(0.92, 0.95, 0.85, 1.0),
+        ..Self::SUMMER
+    };
 
-        let count_text = format!("Crossings: {}", state.crossing_count);
-        draw_text(&count_text, 750.0, 18.0, 20.0, WHITE);
+    /// Snow on the banks and a cold, pale river.
+    const WINTER: Self = Self {
+        grass: [
+            (
+                Color::new(0.90, 0.93, 0.97, 1.0),
+                Color::new(0.72, 0.78, 0.86, 1.0),
+            ),
+            (
+                Color::new(0.84, 0.88, 0.94, 1.0),
+                Color::new(0.68, 0.74, 0.82, 1.0),
+            ),
+        ],
+        shore: (
+            Color::new(0.62, 0.60, 0.58, 1.0),
+            Color::new(0.50, 0.48, 0.46, 1.0),
+        ),
+        rock: (
+            Color::new(0.55, 0.56, 0.60, 1.0),
+            Color::new(0.40, 0.41, 0.45, 1.0),
+        ),
+        water: (
+            Color::new(0.35, 0.55, 0.75, 1.0),
+            Color::new(0.25, 0.42, 0.60, 1.0),
+        ),
+        water_tint: Color::new(0.95, 1.08, 1.15, 1.0),
+        ..Self::SUMMER
+    };
 
-        draw_text(
-            "WASD: Move   E: Interact   R: Restart",
-            10.0,
-            18.0,
-            16.0,
-            GRAY,
-        );
-    }
+    /// Dark, sodden ground and green-brown water.
+    const SWAMP: Self = Self {
+        grass: [
+            (
+                Color::new(0.30, 0.42, 0.22, 1.0),
+                Color::new(0.22, 0.32, 0.16, 1.0),
+            ),
+            (
+                Color::new(0.27, 0.38, 0.20, 1.0),
+                Color::new(0.20, 0.29, 0.14, 1.0),
+            ),
+        ],
+        shore: (
+            Color::new(0.36, 0.30, 0.20, 1.0),
+            Color::new(0.28, 0.23, 0.15, 1.0),
+        ),
+        rock: (
+            Color::new(0.40, 0.42, 0.36, 1.0),
+            Color::new(0.30, 0.32, 0.27, 1.0),
+        ),
+        water: (
+            Color::new(0.16, 0.28, 0.20, 1.0),
+            Color::new(0.10, 0.20, 0.14, 1.0),
+        ),
+        deck: (
+            Color::new(0.42, 0.32, 0.20, 1.0),
+            Color::new(0.30, 0.22, 0.12, 1.0),
+        ),
+        water_tint: Color::new(0.70, 0.90, 0.55, 1.0),
+        ..Self::SUMMER
+    };
 
-    match state.phase {
-        GamePhase::Won => {
-            draw_rectangle(
-                0.0,
-                world::WORLD_HEIGHT / 2.0 - 50.0,
-                900.0,
-                100.0,
-                Color::new(0.0, 0.2, 0.0, 0.85),
-            );
-            draw_text_centered(
-                "All items across! You win!",
-                440.0,
-                world::WORLD_HEIGHT / 2.0 - 5.0,
-                28.0,
-                GREEN,
-            );
-            draw_text_centered(
-                "[R] Play again",
-                440.0,
-                world::WORLD_HEIGHT / 2.0 + 25.0,
-                20.0,
-                WHITE,
-            );
-        }
-        GamePhase::Lost(reason) => {
-            draw_rectangle(
-                0.0,
-                world::WORLD_HEIGHT / 2.0 - 50.0,
-                900.0,
-                100.0,
-                Color::new(0.2, 0.0, 0.0, 0.85),
-            );
-            draw_text_centered(
-                reason.message(),
-                440.0,
-                world::WORLD_HEIGHT / 2.0 - 5.0,
-                28.0,
-                RED,
-            );
-            draw_text_centered(
-                "[R] Try again",
-                440.0,
-                world::WORLD_HEIGHT / 2.0 + 25.0,
-                20.0,
-                WHITE,
-            );
+    /// Light land, dark water and near-black edges, told apart by
+    /// brightness rather than hue.
+    const HIGH_CONTRAST: Self = Self {
+        grass: [
+            (
+                Color::new(0.88, 0.84, 0.62, 1.0),
+                Color::new(0.12, 0.12, 0.12, 1.0),
+            ),
+            (
+                Color::new(0.82, 0.78, 0.56, 1.0),
+                Color::new(0.12, 0.12, 0.12, 1.0),
+            ),
+        ],
+        shore: (
+            Color::new(0.95, 0.62, 0.15, 1.0),
+            Color::new(0.12, 0.12, 0.12, 1.0),
+        ),
+        rock: (
+            Color::new(0.30, 0.30, 0.32, 1.0),
+            Color::new(0.05, 0.05, 0.05, 1.0),
+        ),
+        water: (
+            Color::new(0.04, 0.16, 0.50, 1.0),
+            Color::new(0.02, 0.06, 0.20, 1.0),
+        ),
+        deck: (
+            Color::new(0.96, 0.96, 0.92, 1.0),
+            Color::new(0.05, 0.05, 0.05, 1.0),
+        ),
+        water_tint: WHITE,
+        outline_width: 2.0,
+        shaded_water: false,
+    };
+
+    /// High contrast whatever the biome, or else the biome's own colors.
+    fn for_options(options: &RenderOptions, biome: Biome) -> &'static Self {
+        if options.high_contrast {
+            &Self::HIGH_CONTRAST
+        } else {
+            &BIOME_PALETTES[biome as usize]
         }
-        GamePhase::Playing => {}
     }
 }
 
-fn draw_text_centered(text: &str, cx: f32, cy: f32, font_size: f32, color: Color) {
-    let dims = measure_text(text, None, font_size as u16, 1.0);
-    draw_text(text, cx - dims.width / 2.0, cy, font_size, color);
+/// The river, with the wave shader where the platform supports it. High
+/// contrast keeps to flat tiles in the palette's blue.
+fn draw_water(level: &LevelDef, palette: &Palette, time: f32) {
+    let _span = profiler::scope("render::water");
+    let light = WORLD_TINT.get();
+    let tint = palette.water_tint;
+    let tint = Color::new(
+        tint.r * light.r,
+        tint.g * light.g,
+        tint.b * light.b,
+        light.a,
+    );
+    if palette.shaded_water && water::draw_river(level, time, tint) {
+        return;
+    }
+    for (pos, tile) in tiles(level) {
+        if matches!(tile, Tile::Water | Tile::Bridge) {
+            draw_water_tile(palette, pos, time, level.weather.wind);
+        }
+    }
 }
 
-// ---------------------------------------------------------------------------
-// Tiles
-// ---------------------------------------------------------------------------
-
-fn draw_tiles(time: f32) {
-    for depth in 0..=(world::GRID_COLS + world::GRID_ROWS - 2) {
-        for col in 0..world::GRID_COLS {
-            let row = depth - col;
-            if row < 0 || row >= world::GRID_ROWS {
-                continue;
-            }
-            let pos = GridPos::new(col, row);
-
-            if col >= world::RIVER_COL_MIN && col <= world::RIVER_COL_MAX {
-                draw_water_tile(pos, time);
-            } else {
-                draw_land_tile(pos);
-            }
+/// Land tiles and bridge decks. Tiles don't overlap, so this can go after
+/// all the water.
+fn draw_land(level: &LevelDef, palette: &Palette) {
+    let _span = profiler::scope("render::tiles");
+    for (pos, tile) in tiles(level) {
+        match tile {
+            Tile::Water => {}
+            Tile::Bridge => draw_bridge_tile(palette, pos),
+            tile => draw_land_tile(level, palette, pos, tile),
         }
     }
 }
 
-fn draw_land_tile(pos: GridPos) {
+fn draw_land_tile(level: &LevelDef, palette: &Palette, pos: GridPos, tile: Tile) {
     let (cx, cy) = world::grid_to_iso(pos);
     let hw = world::TILE_WIDTH / 2.0;
     let hh = world::TILE_HEIGHT / 2.0;
 
-    let (base, dark) = if (pos.col + pos.row) % 2 == 0 {
-        (
-            Color::new(0.35, 0.70, 0.25, 1.0),
-            Color::new(0.28, 0.58, 0.18, 1.0),
-        )
-    } else {
-        (
-            Color::new(0.30, 0.63, 0.22, 1.0),
-            Color::new(0.25, 0.52, 0.16, 1.0),
-        )
-    };
-
-    let is_edge = pos.col == world::LEFT_BANK_COL_MAX || pos.col == world::RIGHT_BANK_COL_MIN;
-    let color = if is_edge {
-        Color::new(0.55, 0.45, 0.28, 1.0)
-    } else {
-        base
-    };
-    let outline = if is_edge {
-        Color::new(0.45, 0.38, 0.22, 1.0)
+    let is_edge = tile != Tile::Rock && level.is_shore(pos);
+    let (color, outline) = if tile == Tile::Rock {
+        palette.rock
+    } else if is_edge {
+        palette.shore
     } else {
-        dark
+        palette.grass[((pos.col + pos.row) % 2) as usize]
     };
 
     let top = vec2(cx, cy - hh);
@@ -247,23 +2472,59 @@ fn draw_land_tile(pos: GridPos) {
     let bottom = vec2(cx, cy + hh);
     let left = vec2(cx - hw, cy);
 
+    let (color, outline) = (tinted(color), tinted(outline));
     draw_triangle(top, right, bottom, color);
     draw_triangle(top, left, bottom, color);
 
-    draw_line(top.x, top.y, right.x, right.y, 1.0, outline);
-    draw_line(right.x, right.y, bottom.x, bottom.y, 1.0, outline);
-    draw_line(bottom.x, bottom.y, left.x, left.y, 1.0, outline);
-    draw_line(left.x, left.y, top.x, top.y, 1.0, outline);
+    let width = palette.outline_width;
+    draw_line(top.x, top.y, right.x, right.y, width, outline);
+    draw_line(right.x, right.y, bottom.x, bottom.y, width, outline);
+    draw_line(bottom.x, bottom.y, left.x, left.y, width, outline);
+    draw_line(left.x, left.y, top.x, top.y, width, outline);
+}
+
+/// A plank deck running from one column to the next, so the bridge reads
+/// as spanning the river.
+fn draw_bridge_tile(palette: &Palette, pos: GridPos) {
+    let (cx, cy) = world::grid_to_iso(pos);
+    let hw = world::TILE_WIDTH / 2.0;
+    let hh = world::TILE_HEIGHT / 2.0;
+    // Trim a strip off the two edges the deck runs alongside, leaving
+    // water either side of it.
+    let inset = 0.2;
+    let top = vec2(cx, cy - hh);
+    let right = vec2(cx + hw, cy);
+    let bottom = vec2(cx, cy + hh);
+    let left = vec2(cx - hw, cy);
+    let near = |a: Vec2, b: Vec2| a + (b - a) * inset;
+    let (a, b) = (near(top, left), near(left, top));
+    let (c, d) = (near(bottom, right), near(right, bottom));
+    let (deck, seam) = (tinted(palette.deck.0), tinted(palette.deck.1));
+    draw_triangle(a, b, c, deck);
+    draw_triangle(a, c, d, deck);
+    for i in 1..4 {
+        let t = i as f32 / 4.0;
+        let from = b + (c - b) * t;
+        let to = a + (d - a) * t;
+        draw_line(from.x, from.y, to.x, to.y, palette.outline_width, seam);
+    }
 }
 
-fn draw_water_tile(pos: GridPos, time: f32) {
+/// Plain water, for high contrast or when the shader can't be used.
+/// `wind` from -1 to 1 speeds the waves up, makes them choppier and pushes
+/// them along with it.
+fn draw_water_tile(palette: &Palette, pos: GridPos, time: f32, wind: f32) {
     let (cx, cy) = world::grid_to_iso(pos);
     let hw = world::TILE_WIDTH / 2.0;
     let hh = world::TILE_HEIGHT / 2.0;
+    let gust = 1.0 + wind.abs();
+    let time = time * gust;
 
-    let wave = ((time * 1.5 + pos.col as f32 * 0.7 + pos.row as f32 * 0.5).sin() * 0.06).abs();
-    let color = Color::new(0.12 + wave, 0.30 + wave * 0.5, 0.65, 1.0);
-    let outline = Color::new(0.08, 0.22, 0.50, 1.0);
+    let phase = time * 1.5 + pos.col as f32 * 0.7 + pos.row as f32 * 0.5;
+    let wave = (phase.sin() * 0.06 * gust).abs();
+    let (base, outline) = palette.water;
+    let color = tinted(Color::new(base.r + wave, base.g + wave * 0.5, base.b, 1.0));
+    let outline = tinted(outline);
 
     let top = vec2(cx, cy - hh);
     let right = vec2(cx + hw, cy);
@@ -273,12 +2534,13 @@ fn draw_water_tile(pos: GridPos, time: f32) {
     draw_triangle(top, right, bottom, color);
     draw_triangle(top, left, bottom, color);
 
-    let wave_offset = (time * 2.0 + pos.col as f32 + pos.row as f32).sin() * 2.0;
+    let wave_offset = (time * 2.0 + pos.col as f32 + pos.row as f32).sin() * 2.0 * gust;
+    let lean = wind * hw * 0.3;
     let wave_color = Color::new(0.25, 0.50, 0.80, 0.4);
     draw_line(
-        cx - hw * 0.4,
+        cx - hw * 0.4 + lean,
         cy + wave_offset,
-        cx + hw * 0.4,
+        cx + hw * 0.4 + lean,
         cy + wave_offset - 1.0,
         0.8,
         wave_color,
@@ -290,178 +2552,944 @@ fn draw_water_tile(pos: GridPos, time: f32) {
     draw_line(left.x, left.y, top.x, top.y, 0.5, outline);
 }
 
+// ---------------------------------------------------------------------------
+// Reflections
+// ---------------------------------------------------------------------------
+
+const REFLECTION_TINT: Color = Color::new(0.70, 0.85, 1.0, 0.35);
+/// Reflections are squashed vertically, as seen at a low angle.
+const REFLECTION_SQUASH: f32 = 0.6;
+
+/// Mirror images of the boat and of anyone standing at the water's edge.
+/// Drawn before the land, which covers whatever spills off the water.
+fn draw_reflections(state: &GameState, atlas: &SpriteAtlas, anim: &AnimState, time: f32) {
+    let level = &state.level;
+    let near_water = |(x, y): (f32, f32)| {
+        let pos = world::iso_to_grid(x, y);
+        [(0, 0), (1, 0), (0, 1), (1, 1)]
+            .into_iter()
+            .any(|(dc, dr)| {
+                let below = GridPos::new(pos.col + dc, pos.row + dr);
+                level.in_bounds(below) && matches!(level.tile(below), Tile::Water | Tile::Bridge)
+            })
+    };
+
+    for (i, (boat, boat_anim)) in state.boats.iter().zip(&anim.boats).enumerate() {
+        let (bx, by) = boat_screen_pos(state, BoatId(i));
+        let sprite = atlas.frame(BOAT_SET, &boat_anim.clip);
+        draw_reflection(atlas, sprite, bx, by, 2.5, false, time);
+        // Riders sit above the waterline, so their reflections hang as far
+        // below it.
+        for (j, &id) in boat.cargo.iter().enumerate() {
+            let sprite = atlas.kind_still(state.kind(id));
+            let x = bx + seat_offset(j, boat.cargo.len());
+            draw_reflection(atlas, sprite, x, by + 8.0, 1.8, false, time);
+        }
+        if state.player_boat() == Some(BoatId(i)) {
+            let rower = atlas.still(PLAYER_SET);
+            draw_reflection(atlas, rower, bx + 6.0, by + 10.0, 2.0, false, time);
+            if let Some(id) = state.player.follower {
+                let sprite = atlas.kind_still(state.kind(id));
+                draw_reflection(atlas, sprite, bx - 6.0, by + 8.0, 1.8, false, time);
+            }
+        }
+    }
+
+    for entity in &state.entities {
+        let aboard = is_aboard(state, entity.id);
+        let ea = anim.entity_anim(entity.id);
+        if !aboard && near_water(ea.pos) && !in_fog(state, entity.id) {
+            let sprite = atlas.kind_still(state.level.kind(entity.kind));
+            let (x, y) = ea.pos;
+            draw_reflection(atlas, sprite, x, y, 2.0, !ea.facing_right, time);
+        }
+    }
+    if let PlayerLocation::OnLand(_) = state.player.location
+        && near_water(anim.player_pos)
+    {
+        let (x, y) = anim.player_pos;
+        let flip = !anim.player_facing_right;
+        draw_reflection(atlas, atlas.still(PLAYER_SET), x, y, 2.0, flip, time);
+    }
+    if let Some(partner) = &anim.partner
+        && near_water(partner.pos)
+    {
+        let ((x, y), flip) = (partner.pos, !partner.facing_right);
+        draw_reflection(atlas, atlas.still(PLAYER_SET), x, y, 2.0, flip, time);
+    }
+}
+
+/// Draw `sprite` upside down below its anchor, rippling slightly.
+fn draw_reflection(
+    atlas: &SpriteAtlas,
+    sprite: Sprite,
+    iso_x: f32,
+    iso_y: f32,
+    scale: f32,
+    flip_x: bool,
+    time: f32,
+) {
+    let rect = sprite_rect(sprite, iso_x, iso_y, scale);
+    let ripple = (time * 2.4 + iso_y * 0.15).sin() * 1.2;
+    draw_texture_ex(
+        &atlas.texture,
+        rect.x + ripple,
+        iso_y,
+        tinted(REFLECTION_TINT),
+        DrawTextureParams {
+            dest_size: Some(vec2(rect.w, rect.h * REFLECTION_SQUASH)),
+            source: Some(sprite.rect),
+            flip_x,
+            flip_y: true,
+            ..Default::default()
+        },
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Dock markers
 // ---------------------------------------------------------------------------
 
-fn draw_dock_markers(state: &GameState, atlas: &SpriteAtlas) {
-    if let BoatState::Docked(bank) = state.boat {
-        let dock = world::dock_for(bank);
-        let (x, y) = world::grid_to_iso(dock);
-        draw_sprite(&atlas.highlight, x, y, 2.0);
+/// The highlight on each dock a boat is moored at, and in high contrast a
+/// plate on every dock. Both lie flat on the planks.
+fn queue_dock_markers(queue: &mut RenderQueue, state: &GameState, options: &RenderOptions) {
+    for i in 0..state.boats.len() {
+        if let Some(dock) = state.boat_dock(BoatId(i)) {
+            queue.push(
+                GROUND,
+                Drawable::DockHighlight,
+                world::grid_to_iso(dock),
+                2.0,
+            );
+        }
+    }
+    if !options.high_contrast {
+        return;
+    }
+    let level = &state.level;
+    for (i, def) in level.boats.iter().enumerate() {
+        for region in [RegionId(def.river), RegionId(def.river + 1)] {
+            let dock = level.dock(i, region);
+            let plate = Drawable::DockPlate {
+                letter: level
+                    .region_label(region)
+                    .to_uppercase()
+                    .chars()
+                    .next()
+                    .unwrap(),
+                left_side: region.0 == def.river,
+                docked: state.boat(BoatId(i)).state == BoatState::Docked(region),
+            };
+            queue.push(GROUND, plate, world::grid_to_iso(dock), 1.0);
+        }
+    }
+}
+
+/// A plate on the dock at (`x`, `y`) naming its region by letter, and by
+/// shape which side of the river it's on, circle for left and square for
+/// right; filled while the boat is moored there.
+fn draw_dock_plate(x: f32, y: f32, letter: char, left_side: bool, docked: bool) {
+    let (cx, cy) = (x, y + world::TILE_HEIGHT * 0.25);
+    let r = 7.0;
+    let (fill, ink) = if docked {
+        (BLACK, WHITE)
+    } else {
+        (WHITE, BLACK)
+    };
+    if left_side {
+        draw_circle(cx, cy, r, fill);
+        draw_circle_lines(cx, cy, r, 2.0, BLACK);
+    } else {
+        draw_rectangle(cx - r, cy - r, r * 2.0, r * 2.0, fill);
+        draw_rectangle_lines(cx - r, cy - r, r * 2.0, r * 2.0, 2.0, BLACK);
     }
+    let letter = letter.to_string();
+    text::draw_centered(&letter, cx, cy + 4.0, &TextStyle::new(11.0, ink));
 }
 
 // ---------------------------------------------------------------------------
 // Boat
 // ---------------------------------------------------------------------------
 
-fn draw_boat(state: &GameState, atlas: &SpriteAtlas, anim: &AnimState) {
-    let (bx, by) = boat_screen_pos(state);
-    draw_sprite(&atlas.boat, bx, by, 2.5);
+/// Whether `id` rides a boat: as cargo, or following the player aboard.
+fn is_aboard(state: &GameState, id: EntityId) -> bool {
+    state.boat_carrying(id).is_some()
+        || (state.player.follower == Some(id) && state.player_boat().is_some())
+}
 
-    // Draw cargo on the boat (idle frame)
-    if let Some(entity) = state.boat_cargo {
-        let tex = entity_frame(atlas, entity, 0);
-        draw_sprite(tex, bx, by - 8.0, 1.8);
+/// Every boat, and whoever is aboard sorted just in front of it, in the
+/// order they sit down.
+fn queue_boats<'a>(queue: &mut RenderQueue<'a>, state: &'a GameState, anim: &AnimState) {
+    for i in 0..state.boats.len() {
+        queue_boat(queue, state, anim, BoatId(i));
     }
+}
 
-    // Draw player on the boat (idle frame)
-    if state.player == PlayerLocation::OnBoat {
-        draw_sprite(&atlas.player[0], bx + 6.0, by - 10.0, 2.0);
-
-        // Draw follower on the boat
-        if let Some(entity) = state.follower {
-            let tex = entity_frame(atlas, entity, 0);
-            draw_sprite(tex, bx - 6.0, by - 8.0, 1.8);
+fn queue_boat<'a>(
+    queue: &mut RenderQueue<'a>,
+    state: &'a GameState,
+    anim: &AnimState,
+    boat: BoatId,
+) {
+    let (bx, by) = boat_screen_pos(state, boat);
+    let boat_anim = &anim.boats[boat.0];
+    queue.cmds.push(DrawCmd {
+        clip: boat_anim.clip,
+        squash: boat_anim.bump.value(),
+        ..DrawCmd::new(by, Drawable::Boat(boat), (bx, by), 2.5)
+    });
+    // Anyone still climbing in is drawn with those on land.
+    let mut seat = |entity: EntityId, order: f32| {
+        let pos = entity_seat(state, boat, entity);
+        let entity_anim = anim.entity_anim(entity);
+        if entity_anim.hop.is_none() {
+            let drawable = Drawable::Entity(entity, state.kind(entity));
+            queue.cmds.push(DrawCmd {
+                squash: entity_anim.pop.value(),
+                ..DrawCmd::new(by + order, drawable, pos, 1.8)
+            });
+        }
+    };
+    for (j, &id) in state.boat(boat).cargo.iter().enumerate() {
+        seat(id, 0.01 + j as f32 * 0.001);
+    }
+    if state.player_boat() == Some(boat) {
+        if let Some(id) = state.player.follower {
+            seat(id, 0.03);
         }
+        if anim.player_hop.is_none() {
+            let pos = player_seat(state, boat);
+            queue.push(by + 0.02, Drawable::Player, pos, 2.0);
+        }
+    }
+}
+
+/// Where the player sits aboard `boat`.
+pub fn player_seat(state: &GameState, boat: BoatId) -> (f32, f32) {
+    let (bx, by) = boat_screen_pos(state, boat);
+    (bx + 6.0, by - 10.0)
+}
+
+/// Where `id` sits aboard `boat`: cargo side by side in the order it was
+/// loaded, and the player's follower beside them.
+pub fn entity_seat(state: &GameState, boat: BoatId, id: EntityId) -> (f32, f32) {
+    let (bx, by) = boat_screen_pos(state, boat);
+    let cargo = &state.boat(boat).cargo;
+    let x = match cargo.iter().position(|&carried| carried == id) {
+        Some(j) => bx + seat_offset(j, cargo.len()),
+        None => bx - 6.0,
+    };
+    (x, by - 8.0)
+}
+
+/// How far from the middle of a boat its `j`th of `count` passengers sits.
+fn seat_offset(j: usize, count: usize) -> f32 {
+    (j as f32 - (count as f32 - 1.0) / 2.0) * 7.0
+}
+
+/// Streaks trailing a boat while its crossing is being fast-forwarded.
+fn draw_hurry_spray(level: &LevelDef, boat: BoatId, from: RegionId, at: Vec2, time: f32) {
+    let (bx, by) = (at.x, at.y);
+    let river = level.boats[boat.0].river;
+    let (fx, fy) = boat_dock_pos(level, boat, from);
+    let (tx, ty) = boat_dock_pos(level, boat, level.across(river, from));
+    let len = ((tx - fx).powi(2) + (ty - fy).powi(2)).sqrt();
+    let (dx, dy) = ((tx - fx) / len, (ty - fy) / len);
+
+    let spray = tinted(Color::new(0.85, 0.92, 1.0, 0.7));
+    for i in 0..3 {
+        let lateral = (i as f32 - 1.0) * 4.0;
+        let flicker = (time * 30.0 + i as f32 * 2.0).sin() * 3.0;
+        let start = (
+            bx - dx * 18.0 - dy * lateral,
+            by - dy * 18.0 + dx * lateral - 4.0,
+        );
+        let streak = 14.0 + flicker;
+        draw_line(
+            start.0,
+            start.1,
+            start.0 - dx * streak,
+            start.1 - dy * streak,
+            1.0,
+            spray,
+        );
     }
 }
 
-fn boat_screen_pos(state: &GameState) -> (f32, f32) {
-    match state.boat {
-        BoatState::Docked(bank) => boat_dock_pos(bank),
-        BoatState::Crossing { from, progress } => {
-            let (fx, fy) = boat_dock_pos(from);
-            let (tx, ty) = boat_dock_pos(from.opposite());
-            let t = smooth_step(progress);
-            (fx + (tx - fx) * t, fy + (ty - fy) * t)
+fn boat_screen_pos(state: &GameState, boat: BoatId) -> (f32, f32) {
+    let level = &state.level;
+    let river = state.river_of(boat);
+    match state.boat(boat).state {
+        BoatState::Docked(region) => boat_dock_pos(level, boat, region),
+        BoatState::Crossing {
+            from,
+            progress,
+            drift,
+            returning,
+        } => {
+            let (fx, fy) = boat_dock_pos(level, boat, from);
+            let (tx, ty) = boat_dock_pos(level, boat, level.across(river, from));
+            let t = Ease::SmoothStep.apply(progress);
+            // Heading back from a missed dock, the drift fades out so the
+            // boat lands where it started.
+            let rows = if returning { drift * (1.0 - t) } else { drift };
+            let ox = -world::TILE_WIDTH / 2.0 * rows;
+            let oy = world::TILE_HEIGHT / 2.0 * rows;
+            (tween::lerp(fx, tx, t) + ox, tween::lerp(fy, ty, t) + oy)
         }
     }
 }
 
-fn boat_dock_pos(bank: Bank) -> (f32, f32) {
-    let dock = world::dock_for(bank);
-    let river_col = match bank {
-        Bank::Left => world::RIVER_COL_MIN,
-        Bank::Right => world::RIVER_COL_MAX,
-    };
-    let river_pos = GridPos::new(river_col, world::DOCK_ROW);
+fn boat_dock_pos(level: &LevelDef, boat: BoatId, region: RegionId) -> (f32, f32) {
+    let dock = level.dock(boat.0, region);
+    let river = level.boats[boat.0].river;
+    let river_pos = GridPos::new(level.river_edge_col(river, region), dock.row);
     let (dx, dy) = world::grid_to_iso(dock);
     let (rx, ry) = world::grid_to_iso(river_pos);
     ((dx + rx) / 2.0, (dy + ry) / 2.0)
 }
 
-fn smooth_step(t: f32) -> f32 {
-    t * t * (3.0 - 2.0 * t)
-}
-
 // ---------------------------------------------------------------------------
-// Entities & Player (animated, depth-sorted)
+// Render queue (everything standing in the world, depth-sorted)
 // ---------------------------------------------------------------------------
 
+/// A ghost's see-through sprites.
+const GHOST_TINT: Color = Color::new(0.8, 0.9, 1.0, 0.45);
+
+/// Sets player two apart from the player.
+const PARTNER_TINT: Color = Color::new(0.75, 0.85, 1.0, 1.0);
+
+/// The ferryman wears the farmer's sprite in darker, older clothes.
+const FERRYMAN_TINT: Color = Color::new(0.45, 0.4, 0.55, 1.0);
+
+/// The wild wolf is a wolf gone dark and rangy.
+const PATROLLER_TINT: Color = Color::new(0.45, 0.45, 0.55, 1.0);
+
+/// The wild wolf's sprite set.
+const PATROLLER_SET: &str = "wolf";
+
+/// Depth of things lying flat on the land or the water, under everything
+/// standing on it.
+const GROUND: f32 = f32::NEG_INFINITY;
+
 #[derive(Clone, Copy)]
-enum Drawable {
-    Entity(Entity),
+enum Drawable<'a> {
+    Entity(EntityId, &'a EntityKind),
     Player,
+    /// Player two in co-op: the player's sprite, tinted.
+    Partner,
+    /// The NPC on the left bank, in the player's sprite too.
+    Ferryman,
+    /// The wild wolf walking its beat.
+    Patroller,
+    Tree,
+    /// `x`, `y` are where the boat floats.
+    Boat(BoatId),
+    /// The mark on a dock a boat is moored at.
+    DockHighlight,
+    /// A high-contrast dock plate: the region's initial, which side of the
+    /// river, and whether the boat is in.
+    DockPlate {
+        letter: char,
+        left_side: bool,
+        docked: bool,
+    },
+    /// An emote over someone's head; `x`, `y` are the top of the head.
+    Bubble(Emote),
+    /// A pulsing "!" over threatened prey; `y` is its baseline. True
+    /// when casting off now would get it eaten.
+    Danger(bool),
+    /// How hungry an eater is, 0 to 1; `x`, `y` are the top of its head.
+    Hunger(f32),
+    /// A tool lying on the map or holding an eater; `x`, `y` are where it
+    /// rests.
+    Item(Item),
+    Particle(&'a Particle),
 }
 
-struct DrawCmd {
+struct DrawCmd<'a> {
     depth: f32,
-    drawable: Drawable,
+    drawable: Drawable<'a>,
     x: f32,
     y: f32,
     scale: f32,
     flip_x: bool,
-    frame: usize,
+    /// What an animated drawable is playing.
+    clip: Playback,
+    /// Squashed flat below 0, stretched tall above, for sprites.
+    squash: f32,
+}
+
+impl<'a> DrawCmd<'a> {
+    /// Facing right, standing idle.
+    fn new(depth: f32, drawable: Drawable<'a>, (x, y): (f32, f32), scale: f32) -> Self {
+        Self {
+            depth,
+            drawable,
+            x,
+            y,
+            scale,
+            flip_x: false,
+            clip: Playback::new(),
+            squash: 0.0,
+        }
+    }
 }
 
-fn draw_entities(state: &GameState, atlas: &SpriteAtlas, anim: &AnimState) {
-    let mut cmds: Vec<DrawCmd> = Vec::new();
+/// Everything standing in the world this frame, drawn back to front so
+/// whatever is further down the screen covers what's behind it.
+#[derive(Default)]
+struct RenderQueue<'a> {
+    cmds: Vec<DrawCmd<'a>>,
+}
 
-    for &(entity, _loc) in &state.entities {
-        // Skip entities rendered by the boat
-        if state.boat_cargo == Some(entity) {
-            continue;
+impl<'a> RenderQueue<'a> {
+    fn push(&mut self, depth: f32, drawable: Drawable<'a>, pos: (f32, f32), scale: f32) {
+        self.cmds.push(DrawCmd::new(depth, drawable, pos, scale));
+    }
+
+    fn particles(&mut self, effects: &'a Effects) {
+        for particle in effects.particles() {
+            // It knows where it is.
+            let drawable = Drawable::Particle(particle);
+            self.push(particle.depth(), drawable, (0.0, 0.0), 1.0);
         }
-        if state.follower == Some(entity) && state.player == PlayerLocation::OnBoat {
+    }
+
+    /// Sort and draw it all, outlining `target`. Anything standing
+    /// outside `lit`, when given, is dimmed.
+    fn draw(
+        mut self,
+        state: &GameState,
+        atlas: &SpriteAtlas,
+        options: &RenderOptions,
+        target: Option<EntityId>,
+        lit: Option<RegionId>,
+        time: f32,
+    ) {
+        let _span = profiler::scope("render::queue");
+        // Stable, so equal depths keep the order they were queued in.
+        self.cmds.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+        let light = WORLD_TINT.get();
+        for cmd in &self.cmds {
+            let dimmed = lit.is_some_and(|region| {
+                state.level.region_of(world::iso_to_grid(cmd.x, cmd.y)) != Some(region)
+            });
+            WORLD_TINT.set(if dimmed { dimmed_light(light) } else { light });
+            draw_cmd(cmd, state, atlas, options, target, time);
+        }
+        WORLD_TINT.set(light);
+    }
+}
+
+fn queue_entities<'a>(
+    queue: &mut RenderQueue<'a>,
+    state: &'a GameState,
+    atlas: &SpriteAtlas,
+    anim: &AnimState,
+) {
+    for entity in &state.entities {
+        // Skip entities riding a boat; it queues them.
+        let ea = anim.entity_anim(entity.id);
+        if is_aboard(state, entity.id) && ea.hop.is_none() || in_fog(state, entity.id) {
             continue;
         }
 
-        let ea = anim.entity_anim(entity);
-        let frame = if ea.moving { 1 + anim.walk_frame } else { 0 };
-        let flip = !ea.facing_right;
+        // Prey shrinks away as it's eaten.
+        let left = match &anim.loss {
+            Some(scene) if scene.prey == entity.id => scene.prey_left(),
+            _ => 1.0,
+        };
+        if left <= 0.0 {
+            continue;
+        }
 
-        cmds.push(DrawCmd {
-            depth: ea.pos.1,
-            drawable: Drawable::Entity(entity),
-            x: ea.pos.0,
-            y: ea.pos.1,
-            scale: 2.0,
-            flip_x: flip,
-            frame,
+        let kind = state.level.kind(entity.kind);
+        queue.cmds.push(DrawCmd {
+            flip_x: !ea.facing_right,
+            clip: ea.clip,
+            squash: ea.pop.value(),
+            ..DrawCmd::new(
+                ea.pos.1,
+                Drawable::Entity(entity.id, kind),
+                ea.pos,
+                2.0 * left,
+            )
         });
+        if left < 1.0 {
+            continue;
+        }
+        if let Some(emote) = emote::entity_emote(state, anim, entity.id) {
+            let head = ea.pos.1 - atlas.kind_still(kind).height() * 2.0;
+            queue.cmds.push(bubble_cmd(emote, ea.pos, head));
+        }
     }
 
-    // Player on land
-    if let PlayerLocation::OnLand(_) = state.player {
-        let frame = if anim.player_moving {
-            1 + anim.walk_frame
-        } else {
-            0
-        };
-        cmds.push(DrawCmd {
-            depth: anim.player_pos.1,
-            drawable: Drawable::Player,
-            x: anim.player_pos.0,
-            y: anim.player_pos.1,
-            scale: 2.0,
+    // Player on land, or on the way into or out of a boat
+    if matches!(state.player.location, PlayerLocation::OnLand(_)) || anim.player_hop.is_some() {
+        let pos = anim.player_pos;
+        queue.cmds.push(DrawCmd {
             flip_x: !anim.player_facing_right,
-            frame,
+            clip: anim.player_clip,
+            ..DrawCmd::new(pos.1, Drawable::Player, pos, 2.0)
+        });
+        if let Some(emote) = emote::player_emote(state, anim) {
+            let head = pos.1 - atlas.still(PLAYER_SET).height() * 2.0;
+            queue.cmds.push(bubble_cmd(emote, pos, head));
+        }
+    }
+    if let Some(partner) = &anim.partner {
+        queue.cmds.push(DrawCmd {
+            flip_x: !partner.facing_right,
+            clip: partner.clip,
+            ..DrawCmd::new(partner.pos.1, Drawable::Partner, partner.pos, 2.0)
+        });
+    }
+    if let Some(spot) = state.level.ferryman() {
+        // Facing right, out over the river.
+        let pos = world::grid_to_iso(spot);
+        queue.push(pos.1, Drawable::Ferryman, pos, 2.0);
+    }
+    if let Some(patrol) = &state.patrol {
+        let (pos, leftward) = patroller_pos(patrol);
+        queue.cmds.push(DrawCmd {
+            flip_x: leftward,
+            clip: Playback {
+                clip: clips::WALK,
+                time: patrol.elapsed,
+            },
+            ..DrawCmd::new(pos.1, Drawable::Patroller, pos, 2.0)
         });
     }
+}
+
+/// Where the wild wolf is along its beat, and whether it's heading left.
+fn patroller_pos(patrol: &Patrol) -> ((f32, f32), bool) {
+    let (from, to) = patrol.leg();
+    let (from, to) = (world::grid_to_iso(from), world::grid_to_iso(to));
+    let t = patrol.progress;
+    let pos = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+    (pos, to.0 < from.0)
+}
+
+fn draw_cmd(
+    cmd: &DrawCmd,
+    state: &GameState,
+    atlas: &SpriteAtlas,
+    options: &RenderOptions,
+    target: Option<EntityId>,
+    time: f32,
+) {
+    let sprite = match cmd.drawable {
+        Drawable::Entity(_, e) => atlas.frame(&e.sprites.name, &cmd.clip),
+        Drawable::Player | Drawable::Partner | Drawable::Ferryman => {
+            atlas.frame(PLAYER_SET, &cmd.clip)
+        }
+        Drawable::Patroller => atlas.frame(PATROLLER_SET, &cmd.clip),
+        Drawable::Tree => atlas.tree(state.level.biome),
+        Drawable::Boat(boat) => {
+            if let BoatState::Crossing { from, .. } = state.boat(boat).state
+                && state.crossing_time_scale > 1.0
+            {
+                let at = vec2(cmd.x, cmd.y);
+                draw_hurry_spray(&state.level, boat, from, at, time);
+            }
+            atlas.frame(BOAT_SET, &cmd.clip)
+        }
+        Drawable::DockHighlight => atlas.highlight,
+        Drawable::DockPlate {
+            letter,
+            left_side,
+            docked,
+        } => {
+            draw_dock_plate(cmd.x, cmd.y, letter, left_side, docked);
+            return;
+        }
+        Drawable::Bubble(emote) => {
+            draw_bubble(emote, cmd.x, cmd.y, time);
+            return;
+        }
+        Drawable::Danger(fatal) => {
+            draw_danger_marker(cmd.x, cmd.y, fatal, options, time);
+            return;
+        }
+        Drawable::Hunger(hunger) => {
+            draw_hunger_bar(cmd.x, cmd.y, hunger, time);
+            return;
+        }
+        Drawable::Item(item) => {
+            draw_item(item, cmd.x, cmd.y);
+            return;
+        }
+        Drawable::Particle(particle) => {
+            particle.draw();
+            return;
+        }
+    };
+    let bob = if cmd.clip.clip == clips::WALK {
+        -1.5
+    } else {
+        0.0
+    };
+    let dest = sprite_rect(sprite, cmd.x, cmd.y + bob, cmd.scale);
+    let dest = squashed(dest, cmd.squash);
+    if let Drawable::Entity(id, _) = cmd.drawable
+        && target == Some(id)
+    {
+        draw_highlight(atlas, sprite, dest, cmd.flip_x, options, time);
+    }
+    let tint = match cmd.drawable {
+        Drawable::Partner => PARTNER_TINT,
+        Drawable::Ferryman => FERRYMAN_TINT,
+        Drawable::Patroller => PATROLLER_TINT,
+        _ => WHITE,
+    };
+    draw_sprite_in(atlas, sprite, dest, cmd.flip_x, tint);
+
+    if options.shape_badges
+        && let Drawable::Entity(_, e) = cmd.drawable
+    {
+        draw_entity_badge(e.badge, cmd.x, dest.y);
+    }
+}
+
+/// A bubble for `emote` over whoever stands at `pos`, with `head` the top of
+/// their sprite. Sorted just in front of them, so anyone nearer the camera
+/// still covers it.
+fn bubble_cmd(emote: Emote, pos: (f32, f32), head: f32) -> DrawCmd<'static> {
+    DrawCmd::new(pos.1 + 0.01, Drawable::Bubble(emote), (pos.0, head), 1.0)
+}
+
+/// A speech bubble up and to the right of the head at (`x`, `head`), its
+/// tail pointing down at it.
+fn draw_bubble(emote: Emote, x: f32, head: f32, time: f32) {
+    let size = 14.0;
+    let icon = emote.icon();
+    // Panic shakes; dozing drifts up and down.
+    let (dx, dy) = match emote {
+        Emote::Panic => ((time * 40.0).sin(), 0.0),
+        Emote::Alarm => (0.0, 0.0),
+        Emote::Doze => (0.0, (time * 2.0).sin() * 2.0),
+    };
+    let (cx, cy) = (x + 14.0 + dx, head - 14.0 + dy);
+    let w = text::measure(icon, size) + 10.0;
+    let h = 16.0;
+    let fill = tinted(Color::new(1.0, 1.0, 1.0, 0.92));
+    draw_triangle(
+        vec2(cx - 6.0, cy + h / 2.0 - 1.0),
+        vec2(cx - 1.0, cy + h / 2.0 - 1.0),
+        vec2(x + 4.0, head - 2.0),
+        fill,
+    );
+    draw_rectangle(cx - w / 2.0 + h / 2.0, cy - h / 2.0, w - h, h, fill);
+    draw_circle(cx - w / 2.0 + h / 2.0, cy, h / 2.0, fill);
+    draw_circle(cx + w / 2.0 - h / 2.0, cy, h / 2.0, fill);
+    let color = match emote {
+        Emote::Panic | Emote::Alarm => Color::new(0.8, 0.1, 0.05, 1.0),
+        Emote::Doze => Color::new(0.25, 0.3, 0.5, 1.0),
+    };
+    text::draw_centered(icon, cx, cy + 5.0, &TextStyle::new(size, tinted(color)));
+}
+
+/// Outline an entity the interact key would act on, drawn at `dest`.
+fn draw_highlight(
+    atlas: &SpriteAtlas,
+    sprite: Sprite,
+    dest: Rect,
+    flip_x: bool,
+    options: &RenderOptions,
+    time: f32,
+) {
+    let color = if options.high_contrast {
+        WHITE
+    } else {
+        Color::new(1.0, 0.85, 0.3, 0.9)
+    };
+    outline::draw_outline(&atlas.texture, sprite.rect, dest, flip_x, color, time);
+}
+
+/// A "!" over every animal sharing a bank with something that eats it,
+/// sorted just in front of it; a warning sign over those the boat can't
+/// leave behind.
+fn queue_danger_markers(
+    queue: &mut RenderQueue,
+    state: &GameState,
+    atlas: &SpriteAtlas,
+    anim: &AnimState,
+) {
+    if state.phase != GamePhase::Playing {
+        return;
+    }
+    let victims = state.crossing_victims();
+    let mut marked = Vec::new();
+    for (_, prey) in state.tense_pairs() {
+        if marked.contains(&prey) || in_fog(state, prey) {
+            continue;
+        }
+        marked.push(prey);
+        let (x, y) = anim.entity_anim(prey).pos;
+        let top = y - atlas.kind_still(state.kind(prey)).height() * 2.0;
+        // Clear of the shape badge when that is shown.
+        let danger = Drawable::Danger(victims.contains(&prey));
+        queue.push(y + 0.02, danger, (x, top - 14.0), 1.0);
+    }
+}
+
+/// Pulse a "!" centred on `x` at `baseline`, inside a warning triangle in
+/// high contrast. A `fatal` one is a red warning sign that swells and
+/// shrinks, in either mode.
+fn draw_danger_marker(x: f32, baseline: f32, fatal: bool, options: &RenderOptions, time: f32) {
+    let pulse = 0.6 + 0.4 * (time * 6.0).sin();
+    if fatal {
+        let size = 1.0 + 0.15 * (time * 8.0).sin();
+        let apex = vec2(x, baseline - 24.0 * size);
+        let (left, right) = (
+            vec2(x - 14.0 * size, baseline + 3.0 * size),
+            vec2(x + 14.0 * size, baseline + 3.0 * size),
+        );
+        draw_triangle(apex, left, right, tinted(Color::new(0.9, 0.1, 0.08, 0.95)));
+        draw_triangle_lines(apex, left, right, 2.0, tinted(WHITE));
+        let style = TextStyle::new(18.0 * size, tinted(WHITE));
+        text::draw_centered("!", x, baseline, &style);
+    } else if options.high_contrast {
+        let apex = vec2(x, baseline - 22.0);
+        let (left, right) = (
+            vec2(x - 13.0, baseline + 3.0),
+            vec2(x + 13.0, baseline + 3.0),
+        );
+        draw_triangle(
+            apex,
+            left,
+            right,
+            Color::new(1.0, 0.85, 0.1, 0.7 + 0.3 * pulse),
+        );
+        draw_triangle_lines(apex, left, right, 2.0, BLACK);
+        text::draw_centered("!", x, baseline, &TextStyle::new(18.0, BLACK));
+    } else {
+        let color = tinted(Color::new(0.95, 0.15, 0.1, pulse));
+        let style = TextStyle::new(24.0, color).outline(tinted(BLACK));
+        text::draw_centered("!", x, baseline, &style);
+    }
+}
+
+/// A hunger meter over every eater that has started to get hungry, on
+/// levels with hunger.
+fn queue_hunger_bars(
+    queue: &mut RenderQueue,
+    state: &GameState,
+    atlas: &SpriteAtlas,
+    anim: &AnimState,
+) {
+    if state.hunger_seconds().is_none() || state.phase != GamePhase::Playing {
+        return;
+    }
+    for entity in &state.entities {
+        if entity.hunger <= 0.0 || is_aboard(state, entity.id) || in_fog(state, entity.id) {
+            continue;
+        }
+        let (x, y) = anim.entity_anim(entity.id).pos;
+        let top = y - atlas.kind_still(state.kind(entity.id)).height() * 2.0;
+        queue.push(y + 0.015, Drawable::Hunger(entity.hunger), (x, top), 1.0);
+    }
+}
 
-    cmds.sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap());
+const HUNGER_BAR_WIDTH: f32 = 20.0;
+const HUNGER_BAR_HEIGHT: f32 = 3.0;
 
-    for cmd in &cmds {
-        let tex = match cmd.drawable {
-            Drawable::Entity(e) => entity_frame(atlas, e, cmd.frame),
-            Drawable::Player => &atlas.player[cmd.frame],
+/// A small bar centred on `x` just above `head`, filling from green to red
+/// and flashing once famished.
+fn draw_hunger_bar(x: f32, head: f32, hunger: f32, time: f32) {
+    let (left, top) = (x - HUNGER_BAR_WIDTH / 2.0, head - HUNGER_BAR_HEIGHT - 1.0);
+    let back = tinted(Color::new(0.05, 0.05, 0.05, 0.7));
+    draw_rectangle(
+        left - 1.0,
+        top - 1.0,
+        HUNGER_BAR_WIDTH + 2.0,
+        HUNGER_BAR_HEIGHT + 2.0,
+        back,
+    );
+    let mut fill = Color::new(0.3 + 0.65 * hunger, 0.85 - 0.7 * hunger, 0.2, 1.0);
+    if hunger >= 1.0 {
+        fill.a = 0.6 + 0.4 * (time * 8.0).sin();
+    }
+    let width = HUNGER_BAR_WIDTH * hunger;
+    draw_rectangle(left, top, width, HUNGER_BAR_HEIGHT, tinted(fill));
+}
+
+/// Items lying on the map, and the rope or fish holding each restrained
+/// eater, beside its head.
+fn queue_items(queue: &mut RenderQueue, state: &GameState, atlas: &SpriteAtlas, anim: &AnimState) {
+    for &(item, pos) in &state.pickups {
+        let (x, y) = world::grid_to_iso(pos);
+        queue.push(y, Drawable::Item(item), (x, y), 1.0);
+    }
+    for entity in &state.entities {
+        if !state.is_restrained(entity.id) || is_aboard(state, entity.id) {
+            continue;
+        }
+        let item = if state.crossing_count < entity.tied_until {
+            Item::Rope
+        } else {
+            Item::Fish
         };
-        let bob = if cmd.frame > 0 { -1.5 } else { 0.0 };
-        draw_sprite_ex(tex, cmd.x, cmd.y + bob, cmd.scale, cmd.flip_x);
+        let (x, y) = anim.entity_anim(entity.id).pos;
+        let top = y - atlas.kind_still(state.kind(entity.id)).height() * 2.0;
+        queue.push(y + 0.015, Drawable::Item(item), (x + 16.0, top + 6.0), 1.0);
     }
 }
 
-fn entity_frame<'a>(atlas: &'a SpriteAtlas, entity: Entity, frame: usize) -> &'a Texture2D {
-    match entity {
-        Entity::Wolf => &atlas.wolf[frame],
-        Entity::Sheep => &atlas.sheep[frame],
-        Entity::Cabbage => &atlas.cabbage,
+/// A small drawing of `item`, centred on `x` and standing on `y`.
+fn draw_item(item: Item, x: f32, y: f32) {
+    match item {
+        Item::Rope => {
+            let rope = tinted(Color::new(0.62, 0.45, 0.25, 1.0));
+            draw_circle_lines(x - 2.0, y - 5.0, 4.5, 2.0, rope);
+            draw_circle_lines(x + 2.0, y - 5.0, 4.5, 2.0, rope);
+        }
+        Item::Lantern => {
+            let flame = Color::new(1.0, 0.85, 0.4, 1.0);
+            draw_circle(x, y - 6.0, 9.0, tinted(Color { a: 0.25, ..flame }));
+            draw_rectangle(x - 3.0, y - 10.0, 6.0, 8.0, tinted(flame));
+            draw_rectangle_lines(x - 3.0, y - 10.0, 6.0, 8.0, 1.0, tinted(DARKGRAY));
+            draw_line(x - 2.0, y - 12.0, x + 2.0, y - 12.0, 1.0, tinted(DARKGRAY));
+        }
+        Item::Fish => {
+            let scales = tinted(Color::new(0.55, 0.68, 0.78, 1.0));
+            draw_ellipse(x - 1.0, y - 4.0, 6.0, 3.0, 0.0, scales);
+            draw_triangle(
+                vec2(x + 4.0, y - 4.0),
+                vec2(x + 8.0, y - 7.0),
+                vec2(x + 8.0, y - 1.0),
+                scales,
+            );
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shape badges
+// ---------------------------------------------------------------------------
+
+const BADGE_SIZE: f32 = 5.0;
+const BADGE_GAP: f32 = 3.0;
+
+/// Draw the entity's badge centered horizontally on `x`, resting just above
+/// `top_y` (the top edge of its sprite). Each kind picks its own shape.
+fn draw_entity_badge(badge: Badge, x: f32, top_y: f32) {
+    let cy = top_y - BADGE_GAP - BADGE_SIZE;
+    let r = BADGE_SIZE;
+    let fill = WHITE;
+    let outline = Color::new(0.05, 0.05, 0.05, 1.0);
+
+    match badge {
+        Badge::Triangle => {
+            let top = vec2(x, cy - r);
+            let left = vec2(x - r, cy + r * 0.8);
+            let right = vec2(x + r, cy + r * 0.8);
+            draw_triangle(top, left, right, fill);
+            draw_triangle_lines(top, left, right, 1.0, outline);
+        }
+        Badge::Circle => {
+            draw_circle(x, cy, r, fill);
+            draw_circle_lines(x, cy, r, 1.0, outline);
+        }
+        Badge::Square => {
+            let side = r * 1.7;
+            draw_rectangle(x - side / 2.0, cy - side / 2.0, side, side, fill);
+            draw_rectangle_lines(x - side / 2.0, cy - side / 2.0, side, side, 1.0, outline);
+        }
+        Badge::Diamond => {
+            let (top, bottom) = (vec2(x, cy - r), vec2(x, cy + r));
+            let (left, right) = (vec2(x - r, cy), vec2(x + r, cy));
+            draw_triangle(top, left, right, fill);
+            draw_triangle(bottom, left, right, fill);
+            for (a, b) in [(top, left), (left, bottom), (bottom, right), (right, top)] {
+                draw_line(a.x, a.y, b.x, b.y, 1.0, outline);
+            }
+        }
+    }
+}
+
+/// Work out what is drawn under a world-space point: the front-most entity
+/// sprite, then a boat, otherwise the tile underneath.
+pub fn pick(state: &GameState, atlas: &SpriteAtlas, anim: &AnimState, point: Vec2) -> ClickTarget {
+    let mut on_land: Vec<(&EntityInstance, (f32, f32))> = state
+        .entities
+        .iter()
+        .filter(|e| !is_aboard(state, e.id))
+        .map(|e| (e, anim.entity_anim(e.id).pos))
+        .collect();
+    // Nearest the viewer first, the reverse of draw order.
+    on_land.sort_by(|a, b| b.1.1.total_cmp(&a.1.1));
+    for (entity, (x, y)) in on_land {
+        let sprite = atlas.kind_still(state.level.kind(entity.kind));
+        if sprite_rect(sprite, x, y, 2.0).contains(point) {
+            return ClickTarget::Entity(entity.id);
+        }
+    }
+
+    for (i, boat) in state.boats.iter().enumerate() {
+        let (bx, by) = boat_screen_pos(state, BoatId(i));
+        for (j, &id) in boat.cargo.iter().enumerate() {
+            let x = bx + seat_offset(j, boat.cargo.len());
+            if sprite_rect(atlas.kind_still(state.kind(id)), x, by - 8.0, 1.8).contains(point) {
+                return ClickTarget::Entity(id);
+            }
+        }
+        if sprite_rect(atlas.still(BOAT_SET), bx, by, 2.5).contains(point) {
+            return ClickTarget::Boat(BoatId(i));
+        }
     }
+
+    ClickTarget::Tile(world::iso_to_grid(point.x, point.y))
 }
 
 // ---------------------------------------------------------------------------
 // Sprite drawing helpers
 // ---------------------------------------------------------------------------
 
-fn draw_sprite(texture: &Texture2D, iso_x: f32, iso_y: f32, scale: f32) {
-    draw_sprite_ex(texture, iso_x, iso_y, scale, false);
+/// Screen area covered by a sprite drawn with `draw_sprite_tinted` at these
+/// arguments.
+fn sprite_rect(sprite: Sprite, iso_x: f32, iso_y: f32, scale: f32) -> Rect {
+    let dest_w = sprite.width() * scale;
+    let dest_h = sprite.height() * scale;
+    Rect::new(iso_x - dest_w / 2.0, iso_y - dest_h, dest_w, dest_h)
+}
+
+/// `rect` squashed (`amount` below 0) or stretched (above 0), keeping its
+/// bottom center where it was.
+fn squashed(rect: Rect, amount: f32) -> Rect {
+    let (sx, sy) = tween::squash_stretch(amount);
+    let (w, h) = (rect.w * sx, rect.h * sy);
+    Rect::new(rect.center().x - w / 2.0, rect.bottom() - h, w, h)
 }
 
-fn draw_sprite_ex(texture: &Texture2D, iso_x: f32, iso_y: f32, scale: f32, flip_x: bool) {
-    let dest_w = texture.width() * scale;
-    let dest_h = texture.height() * scale;
-    let draw_x = iso_x - dest_w / 2.0;
-    let draw_y = iso_y - dest_h;
+/// Draw `sprite` from the atlas, anchored at its bottom center and
+/// multiplied by `color`.
+fn draw_sprite_tinted(
+    atlas: &SpriteAtlas,
+    sprite: Sprite,
+    iso_x: f32,
+    iso_y: f32,
+    scale: f32,
+    flip_x: bool,
+    color: Color,
+) {
+    let rect = sprite_rect(sprite, iso_x, iso_y, scale);
+    draw_sprite_in(atlas, sprite, rect, flip_x, color);
+}
 
+/// Draw `sprite` from the atlas stretched over `rect`, multiplied by
+/// `color`.
+fn draw_sprite_in(atlas: &SpriteAtlas, sprite: Sprite, rect: Rect, flip_x: bool, color: Color) {
     draw_texture_ex(
-        texture,
-        draw_x,
-        draw_y,
-        WHITE,
+        &atlas.texture,
+        rect.x,
+        rect.y,
+        tinted(color),
         DrawTextureParams {
-            dest_size: Some(vec2(dest_w, dest_h)),
+            dest_size: Some(rect.size()),
+            source: Some(sprite.rect),
             flip_x,
             ..Default::default()
         },
@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GamePhase, GameState};
+use crate::input::{HeldInput, InputEvent};
+use crate::inventory::Item;
+use crate::mutators::Mutators;
+use crate::rng::Rng;
+use crate::storage;
+use crate::upgrades::Upgrades;
+use crate::world::Direction;
+
+/// Where the last winning run is written.
+pub const REPLAY_PATH: &str = "replay.ron";
+
+/// The gameplay part of an input event; the rest don't touch the state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    #[default]
+    None,
+    Move(Direction),
+    Interact,
+    CycleTarget,
+    CrossRiver,
+    Restart,
+    UseItem(Item),
+}
+
+impl ReplayEvent {
+    fn from_input(event: InputEvent) -> Self {
+        match event {
+            InputEvent::Move(dir) => ReplayEvent::Move(dir),
+            InputEvent::Interact => ReplayEvent::Interact,
+            InputEvent::CycleTarget => ReplayEvent::CycleTarget,
+            InputEvent::CrossRiver => ReplayEvent::CrossRiver,
+            InputEvent::Restart => ReplayEvent::Restart,
+            InputEvent::UseItem(item) => ReplayEvent::UseItem(item),
+            _ => ReplayEvent::None,
+        }
+    }
+
+    pub fn to_input(self) -> InputEvent {
+        match self {
+            ReplayEvent::None => InputEvent::None,
+            ReplayEvent::Move(dir) => InputEvent::Move(dir),
+            ReplayEvent::Interact => InputEvent::Interact,
+            ReplayEvent::CycleTarget => InputEvent::CycleTarget,
+            ReplayEvent::CrossRiver => InputEvent::CrossRiver,
+            ReplayEvent::Restart => InputEvent::Restart,
+            ReplayEvent::UseItem(item) => InputEvent::UseItem(item),
+        }
+    }
+}
+
+/// One simulation step's input.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    pub dt: f32,
+    pub event: ReplayEvent,
+    /// Player two's, in co-op.
+    #[serde(default)]
+    pub partner: ReplayEvent,
+    pub hurry: bool,
+    pub steer: f32,
+    /// Which way each farmer glides, in analog movement.
+    #[serde(default)]
+    pub glide: (i8, i8),
+    #[serde(default)]
+    pub partner_glide: (i8, i8),
+}
+
+impl Frame {
+    /// What the player and player two held down.
+    pub fn held(&self) -> (HeldInput, HeldInput) {
+        let held = HeldInput {
+            hurry: self.hurry,
+            steer: self.steer,
+            glide: self.glide,
+        };
+        let partner = HeldInput {
+            glide: self.partner_glide,
+            ..HeldInput::default()
+        };
+        (held, partner)
+    }
+}
+
+/// A run from the start of a level: enough to play it back step for step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// The level's name. Playback looks it up in the campaign, unless it's
+    /// given a level file.
+    pub level: String,
+    pub timed: bool,
+    #[serde(default)]
+    pub coop: bool,
+    #[serde(default)]
+    pub analog: bool,
+    #[serde(default)]
+    pub assist: bool,
+    /// The mutators' names.
+    #[serde(default)]
+    pub mutators: Vec<String>,
+    /// The boat upgrades' names.
+    #[serde(default)]
+    pub upgrades: Vec<String>,
+    /// The random stream at the start, so animals wander the same way.
+    pub rng: u64,
+    pub frames: Vec<Frame>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = storage::read(path)?.ok_or_else(|| format!("{path}: no such file"))?;
+        ron::from_str(&source).map_err(|e| format!("{path}: {e}"))
+    }
+
+    fn save(&self, path: &str) -> Result<(), String> {
+        let source = ron::to_string(self).map_err(|e| e.to_string())?;
+        storage::write(path, &source)
+    }
+
+    /// Seconds from the start to the end of the run.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.dt).sum()
+    }
+
+    /// Give `state` the run's mode and random stream; both survive the
+    /// reset that starts the level.
+    pub fn prepare(&self, state: &mut GameState) {
+        state.timed = self.timed;
+        state.coop = self.coop;
+        state.analog = self.analog;
+        state.assist = self.assist;
+        state.mutators = Mutators::from_names(&self.mutators);
+        state.upgrades = Upgrades::from_names(&self.upgrades);
+        state.rng = Rng::new(self.rng);
+    }
+}
+
+/// Records each run from its start, and writes it out when it's won.
+pub struct Recorder {
+    /// None when there's no clean start to replay from.
+    replay: Option<Replay>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self { replay: None }
+    }
+
+    /// Start recording a run from `state`, which must have just been reset.
+    pub fn begin(&mut self, state: &GameState) {
+        self.replay = Some(Replay {
+            level: state.level.name.clone(),
+            timed: state.timed,
+            coop: state.coop,
+            analog: state.analog,
+            assist: state.assist,
+            mutators: state.mutators.names(),
+            upgrades: state.upgrades.names(),
+            rng: state.rng.state(),
+            frames: Vec::new(),
+        });
+    }
+
+    /// Stop recording until the next `begin`, after the state jumped in a
+    /// way a replay can't reproduce.
+    pub fn discard(&mut self) {
+        self.replay = None;
+    }
+
+    /// Note the input for one step about to be taken from `state`: the
+    /// player's event and player two's.
+    pub fn record(
+        &mut self,
+        state: &GameState,
+        (event, partner): (InputEvent, InputEvent),
+        (held, partner_held): (HeldInput, HeldInput),
+        dt: f32,
+    ) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        if matches!(state.phase, GamePhase::Menu | GamePhase::Paused) {
+            return;
+        }
+        replay.frames.push(Frame {
+            dt,
+            event: ReplayEvent::from_input(event),
+            partner: ReplayEvent::from_input(partner),
+            hurry: held.hurry,
+            steer: held.steer,
+            glide: held.glide,
+            partner_glide: partner_held.glide,
+        });
+    }
+
+    /// Steps recorded so far and the seconds they took. None while not
+    /// recording.
+    pub fn progress(&self) -> Option<(usize, f32)> {
+        let replay = self.replay.as_ref()?;
+        Some((replay.frames.len(), replay.duration()))
+    }
+
+    /// Once the recorded run is won, write it to `REPLAY_PATH` and stop.
+    /// Returns a line for the toast.
+    pub fn observe(&mut self, state: &GameState) -> Option<String> {
+        if state.phase != GamePhase::Won {
+            return None;
+        }
+        let replay = self.replay.take()?;
+        Some(match replay.save(REPLAY_PATH) {
+            Ok(()) => format!("Run saved to {REPLAY_PATH}"),
+            Err(e) => format!("Couldn't save the replay: {e}"),
+        })
+    }
+}
+
+/// Feeds a replay's frames back one per step.
+pub struct Playback {
+    frames: VecDeque<Frame>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            frames: replay.frames.into(),
+        }
+    }
+
+    pub fn next(&mut self) -> Option<Frame> {
+        self.frames.pop_front()
+    }
+}
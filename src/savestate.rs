@@ -0,0 +1,53 @@
+use crate::anim::AnimState;
+use crate::game::GameState;
+
+pub const SLOT_COUNT: usize = 4;
+
+/// In-memory quick savestates: full copies of the simulation and animation
+/// state, for practicing a tricky sequence without replaying from the start.
+pub struct SaveSlots {
+    slots: [Option<(GameState, AnimState)>; SLOT_COUNT],
+    selected: usize,
+}
+
+impl SaveSlots {
+    pub fn new() -> Self {
+        Self {
+            slots: Default::default(),
+            selected: 0,
+        }
+    }
+
+    /// Snapshot into the selected slot. Returns a feedback line for the HUD.
+    pub fn save(&mut self, state: &GameState, anim: &AnimState) -> String {
+        self.slots[self.selected] = Some((state.clone(), anim.clone()));
+        format!("Saved slot {}", self.selected + 1)
+    }
+
+    /// Restore the selected slot, if it holds a snapshot.
+    pub fn restore(&self, state: &mut GameState, anim: &mut AnimState) -> String {
+        match &self.slots[self.selected] {
+            Some((saved_state, saved_anim)) => {
+                *state = saved_state.clone();
+                *anim = saved_anim.clone();
+                format!("Loaded slot {}", self.selected + 1)
+            }
+            None => format!("Slot {} is empty", self.selected + 1),
+        }
+    }
+
+    /// Whether the selected slot has a state to load.
+    pub fn can_restore(&self) -> bool {
+        self.slots[self.selected].is_some()
+    }
+
+    pub fn select_next(&mut self) -> String {
+        self.selected = (self.selected + 1) % SLOT_COUNT;
+        let filled = if self.slots[self.selected].is_some() {
+            ""
+        } else {
+            " (empty)"
+        };
+        format!("Slot {}{filled}", self.selected + 1)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::campaign::{LevelSelect, MutatorMenu, UpgradeMenu};
+use crate::cutscene::Cutscene;
+use crate::game::GamePhase;
+use crate::input::InputContext;
+use crate::pause::PauseMenu;
+use crate::race::Race;
+use crate::settings::SettingsScreen;
+
+/// One layer of what's on screen. The game is always at the bottom, as
+/// Playing, Won or Lost; menus stack over it, and cutscenes and races go
+/// over whatever is there. Only the top layer takes input.
+pub enum Screen {
+    /// The level select, over the frozen level.
+    Menu(LevelSelect),
+    /// The mutators, opened from the level select.
+    Mutators(MutatorMenu),
+    /// The boat upgrades, opened from the level select.
+    Upgrades(UpgradeMenu),
+    Playing,
+    Paused(PauseMenu),
+    /// The display and sound settings, opened from the pause menu.
+    Settings(SettingsScreen),
+    /// Who eats whom on the level, from F1 or the pause menu.
+    Rules,
+    /// The win banner, counting down to the next level.
+    Won {
+        advance_in: f32,
+    },
+    Lost,
+    Cutscene(Box<Cutscene>),
+    /// Versus mode: two games of its own, with the one behind left waiting.
+    Race(Box<Race>),
+}
+
+impl Screen {
+    /// The phase a menu holds the game in. None for the game's own
+    /// layers, which follow the game instead, and for cutscenes and races,
+    /// which leave it as it was.
+    fn holds_phase(&self) -> Option<GamePhase> {
+        match self {
+            Screen::Menu(_) | Screen::Mutators(_) | Screen::Upgrades(_) => Some(GamePhase::Menu),
+            Screen::Paused(_) | Screen::Settings(_) | Screen::Rules => Some(GamePhase::Paused),
+            _ => None,
+        }
+    }
+
+    fn input_context(&self) -> InputContext {
+        match self {
+            Screen::Menu(_)
+            | Screen::Mutators(_)
+            | Screen::Upgrades(_)
+            | Screen::Paused(_)
+            | Screen::Settings(_)
+            | Screen::Rules => InputContext::Menu,
+            Screen::Playing | Screen::Won { .. } | Screen::Lost | Screen::Race(_) => {
+                InputContext::Gameplay
+            }
+            Screen::Cutscene(_) => InputContext::Cutscene,
+        }
+    }
+}
+
+/// A change to the stack, asked for during a frame and made after it.
+pub enum Transition {
+    Push(Screen),
+    Pop,
+    /// Close everything over the game.
+    PopToGame,
+}
+
+/// The screens, bottom first. Never empty: the game can't be popped.
+pub struct ScreenStack {
+    screens: Vec<Screen>,
+}
+
+impl ScreenStack {
+    pub fn new() -> Self {
+        Self {
+            screens: vec![Screen::Playing],
+        }
+    }
+
+    /// Take the top screen out to run its frame. Put it back with
+    /// `put_back` before making any transitions.
+    pub fn take_top(&mut self) -> Screen {
+        self.screens.pop().expect("the game is always on the stack")
+    }
+
+    pub fn put_back(&mut self, screen: Screen) {
+        self.screens.push(screen);
+    }
+
+    /// The phase the topmost menu holds the game in; None with no menu
+    /// open.
+    pub fn held_phase(&self) -> Option<GamePhase> {
+        self.screens.iter().rev().find_map(Screen::holds_phase)
+    }
+
+    pub fn has_cutscene(&self) -> bool {
+        self.screens
+            .iter()
+            .any(|screen| matches!(screen, Screen::Cutscene(_)))
+    }
+
+    /// The input context each screen stands for, bottom first.
+    pub fn input_contexts(&self) -> impl Iterator<Item = InputContext> + '_ {
+        self.screens.iter().map(Screen::input_context)
+    }
+
+    pub fn apply(&mut self, transition: Transition) {
+        match transition {
+            Transition::Push(screen) => self.screens.push(screen),
+            Transition::Pop if self.screens.len() > 1 => {
+                self.screens.pop();
+            }
+            Transition::Pop => {}
+            Transition::PopToGame => self.screens.truncate(1),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+#[cfg(feature = "scripting")]
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+
+/// Upper bound on the work one evaluation may do, so a runaway script
+/// can't hang the game.
+#[cfg(feature = "scripting")]
+const MAX_OPERATIONS: u64 = 50_000;
+
+/// A level's custom losing condition, written in Rhai.
+///
+/// The script runs once per unattended region with three variables in
+/// scope: `bank` ("left", "right", or "island" on levels with one; see
+/// `LevelDef::region_label`), `contents` (the kinds there, e.g.
+/// `["goat", "rope"]`) and `player` (a region, or "boat" while on the
+/// river, by boat or bridge). It
+/// evaluates to a string to end the game with that message, or to `()`
+/// when the region is fine.
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+pub struct LossScript {
+    source: String,
+    #[cfg(feature = "scripting")]
+    engine: Arc<rhai::Engine>,
+    #[cfg(feature = "scripting")]
+    ast: rhai::AST,
+    /// Every message the script has returned, so a loss can refer to one
+    /// by index and `LoseReason` stays `Copy`.
+    messages: Mutex<Vec<String>>,
+}
+
+impl Clone for LossScript {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            #[cfg(feature = "scripting")]
+            engine: Arc::clone(&self.engine),
+            #[cfg(feature = "scripting")]
+            ast: self.ast.clone(),
+            messages: Mutex::new(self.messages.lock().unwrap().clone()),
+        }
+    }
+}
+
+impl fmt::Debug for LossScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LossScript")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TryFrom<String> for LossScript {
+    type Error = String;
+
+    #[cfg(feature = "scripting")]
+    fn try_from(source: String) -> Result<Self, String> {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile(&source)
+            .map_err(|e| format!("loss script: {e}"))?;
+        Ok(Self {
+            source,
+            engine: Arc::new(engine),
+            ast,
+            messages: Mutex::new(Vec::new()),
+        })
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn try_from(_source: String) -> Result<Self, String> {
+        Err("loss scripts need a build with the `scripting` feature".to_string())
+    }
+}
+
+impl LossScript {
+    /// Run the script for one region. Returns the index of the loss message,
+    /// if the script reports one. A script that fails at runtime is
+    /// reported on stderr and treated as no loss.
+    #[cfg(feature = "scripting")]
+    pub fn evaluate(&self, bank: &str, contents: &[&str], player: Option<&str>) -> Option<u16> {
+        let mut scope = rhai::Scope::new();
+        scope.push_constant("bank", bank.to_string());
+        let contents: rhai::Array = contents.iter().map(|&name| name.into()).collect();
+        scope.push_constant("contents", contents);
+        scope.push_constant("player", player.unwrap_or("boat").to_string());
+
+        let result = self
+            .engine
+            .eval_ast_with_scope::<rhai::Dynamic>(&mut scope, &self.ast);
+        let message = match result {
+            Ok(value) if value.is_unit() => return None,
+            Ok(value) => match value.into_string() {
+                Ok(message) => message,
+                Err(kind) => {
+                    eprintln!("loss script returned a {kind}, not a string or ()");
+                    return None;
+                }
+            },
+            Err(e) => {
+                eprintln!("loss script failed: {e}");
+                return None;
+            }
+        };
+        Some(self.intern(message))
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    pub fn evaluate(&self, _bank: &str, _contents: &[&str], _player: Option<&str>) -> Option<u16> {
+        None
+    }
+
+    #[cfg(feature = "scripting")]
+    fn intern(&self, message: String) -> u16 {
+        let mut messages = self.messages.lock().unwrap();
+        let index = match messages.iter().position(|m| *m == message) {
+            Some(index) => index,
+            None => {
+                messages.push(message);
+                messages.len() - 1
+            }
+        };
+        index as u16
+    }
+
+    /// A message `evaluate` returned earlier.
+    pub fn message(&self, index: u16) -> String {
+        let messages = self.messages.lock().unwrap();
+        messages.get(index as usize).cloned().unwrap_or_default()
+    }
+}
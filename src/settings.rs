@@ -0,0 +1,607 @@
+use macroquad::prelude::{KeyCode, Rect};
+use serde::{Deserialize, Serialize};
+
+use crate::input::{self, Action};
+use crate::pacing::FPS_CAPS;
+use crate::pause::{self, MenuInput};
+use crate::storage;
+use crate::theme;
+
+const SETTINGS_PATH: &str = "settings.toml";
+
+/// Window sizes the settings screen cycles through.
+const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+const VOLUME_STEP: f32 = 0.1;
+/// HUD scales the settings screen cycles through; 0 is automatic.
+const HUD_SCALES: [f32; 6] = [0.0, 0.75, 1.0, 1.25, 1.5, 2.0];
+const SAFE_AREA_STEP: f32 = 0.025;
+const MAX_SAFE_AREA: f32 = 0.1;
+
+/// Player preferences, saved between runs. Missing fields fall back to
+/// their defaults, so older files keep loading.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window: WindowSettings,
+    pub audio: AudioSettings,
+    pub effects: EffectSettings,
+    pub theme: ThemeSettings,
+    pub accessibility: AccessibilitySettings,
+    pub hud: HudSettings,
+    pub keys: KeyBindings,
+    pub leaderboard: LeaderboardSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    /// Only read at startup; the swap interval can't change on a live window.
+    pub vsync: bool,
+    /// Most frames drawn a second; 0 leaves it to vsync.
+    pub fps_cap: u32,
+    /// Drop to a trickle of frames while nobody's touching the game.
+    pub low_power: bool,
+    /// Show the frame rate in a corner.
+    pub show_fps: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Master volume, 0 to 1.
+    pub volume: f32,
+}
+
+/// Which post-processing effects are on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EffectSettings {
+    pub vignette: bool,
+    /// Warm the picture on a win and drain it on a loss.
+    pub color_grading: bool,
+    /// Chunky pixels, for a retro look.
+    pub pixelate: bool,
+    /// Ride along with the boat, closer in, while it crosses.
+    pub crossing_camera: bool,
+}
+
+/// Which sprite pack draws the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    /// A manifest in `assets/themes/`, without the `.ron`; `default` draws
+    /// the game's own sprites.
+    pub name: String,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            name: theme::DEFAULT_THEME.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// A colorblind-friendly palette with bolder outlines and shape markers.
+    pub high_contrast: bool,
+    /// Describe the game in words, on screen and on stdout (or aloud, in
+    /// builds with text-to-speech).
+    pub narration: bool,
+}
+
+/// How big the HUD is and how far it keeps from the edges.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HudSettings {
+    /// Against the default size; 0 shrinks it to fit narrow windows and
+    /// leaves it alone otherwise.
+    pub scale: f32,
+    /// The share of the screen's width and height kept clear at each edge,
+    /// for TVs that crop the picture. 0 to 0.1.
+    pub safe_area: f32,
+}
+
+/// Where wins are sent, in builds with the leaderboard client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeaderboardSettings {
+    /// An `http://` URL to post wins to; empty keeps scores to yourself.
+    pub endpoint: String,
+    /// The name your times go up under.
+    pub player: String,
+}
+
+/// Key names for each gameplay action, as written in the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub move_up: Vec<String>,
+    pub move_down: Vec<String>,
+    pub move_left: Vec<String>,
+    pub move_right: Vec<String>,
+    pub interact: Vec<String>,
+    pub cross_river: Vec<String>,
+    pub restart: Vec<String>,
+}
+
+impl Default for LeaderboardSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            player: "Farmer".to_string(),
+        }
+    }
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            fullscreen: false,
+            vsync: true,
+            fps_cap: 0,
+            low_power: true,
+            show_fps: false,
+        }
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { volume: 0.8 }
+    }
+}
+
+impl Default for EffectSettings {
+    fn default() -> Self {
+        Self {
+            vignette: true,
+            color_grading: true,
+            pixelate: false,
+            crossing_camera: false,
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        Self {
+            move_up: keys(&["W", "Up"]),
+            move_down: keys(&["S", "Down"]),
+            move_left: keys(&["A", "Left"]),
+            move_right: keys(&["D", "Right"]),
+            interact: keys(&["E"]),
+            cross_river: keys(&["Space"]),
+            restart: keys(&["R"]),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[String] {
+        match action {
+            Action::MoveUp => &self.move_up,
+            Action::MoveDown => &self.move_down,
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::Interact => &self.interact,
+            Action::CrossRiver => &self.cross_river,
+            Action::Restart => &self.restart,
+        }
+    }
+
+    fn keys_mut(&mut self, action: Action) -> &mut Vec<String> {
+        match action {
+            Action::MoveUp => &mut self.move_up,
+            Action::MoveDown => &mut self.move_down,
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Interact => &mut self.interact,
+            Action::CrossRiver => &mut self.cross_river,
+            Action::Restart => &mut self.restart,
+        }
+    }
+
+    fn uses(&self, action: Action, name: &str) -> bool {
+        self.keys(action)
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(name))
+    }
+
+    /// The action other than `action` that already uses `name`, if any.
+    fn conflict(&self, action: Action, name: &str) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|&other| other != action && self.uses(other, name))
+    }
+
+    /// Make `key` the primary key for `action`, keeping its alternates.
+    /// Refuses keys with fixed jobs and keys another action already uses.
+    fn rebind(&mut self, action: Action, key: KeyCode) -> Result<(), String> {
+        let Some(name) = input::key_name(key) else {
+            return Err("That key can't be bound".to_string());
+        };
+        if input::is_reserved(key) {
+            return Err(format!("{name} is reserved for another control"));
+        }
+        if let Some(other) = self.conflict(action, name) {
+            return Err(format!("{name} is already bound to {}", other.label()));
+        }
+        if self.uses(action, name) {
+            return Ok(());
+        }
+        let keys = self.keys_mut(action);
+        match keys.first_mut() {
+            Some(primary) => *primary = name.to_string(),
+            None => keys.push(name.to_string()),
+        }
+        Ok(())
+    }
+}
+
+impl Settings {
+    /// Read the settings file. A missing file gives the defaults; a broken
+    /// one is reported and ignored.
+    pub fn load() -> Self {
+        let parsed = storage::read(SETTINGS_PATH).and_then(|source| match source {
+            Some(source) => toml::from_str(&source).map_err(|e| format!("{SETTINGS_PATH}: {e}")),
+            None => Ok(Self::default()),
+        });
+        parsed.unwrap_or_else(|e| {
+            macroquad::logging::warn!("{e}; using default settings");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let source = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        storage::write(SETTINGS_PATH, &source)
+    }
+}
+
+/// A setting the screen just changed, for the caller to apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsChange {
+    Resolution,
+    Fullscreen,
+    Vsync,
+    FramePacing,
+    Volume,
+    Effects,
+    Theme,
+    Accessibility,
+    Hud,
+    KeyBindings,
+    /// Nothing changed, but the player should be told why.
+    Message(String),
+    /// The screen was closed; time to save.
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Resolution,
+    Fullscreen,
+    Vsync,
+    FpsCap,
+    LowPower,
+    ShowFps,
+    Volume,
+    Vignette,
+    ColorGrading,
+    Pixelate,
+    CrossingCamera,
+    Theme,
+    HighContrast,
+    Narration,
+    HudScale,
+    SafeArea,
+    Controls,
+    Back,
+}
+
+const ROWS: [Row; 18] = [
+    Row::Resolution,
+    Row::Fullscreen,
+    Row::Vsync,
+    Row::FpsCap,
+    Row::LowPower,
+    Row::ShowFps,
+    Row::Volume,
+    Row::Vignette,
+    Row::ColorGrading,
+    Row::Pixelate,
+    Row::CrossingCamera,
+    Row::Theme,
+    Row::HighContrast,
+    Row::Narration,
+    Row::HudScale,
+    Row::SafeArea,
+    Row::Controls,
+    Row::Back,
+];
+
+/// The display and sound settings screen, opened from the pause menu.
+/// Left/right (or confirm) changes the selected row in place.
+pub struct SettingsScreen {
+    selected: usize,
+    controls: Option<ControlsScreen>,
+}
+
+impl SettingsScreen {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            controls: None,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match &self.controls {
+            Some(controls) => controls.title(),
+            None => "Display & sound".to_string(),
+        }
+    }
+
+    /// Row labels with their current values, and the selected index.
+    pub fn items(&self, settings: &Settings) -> (Vec<String>, usize) {
+        if let Some(controls) = &self.controls {
+            return controls.items(&settings.keys);
+        }
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        let labels = ROWS
+            .iter()
+            .map(|row| match row {
+                Row::Resolution => format!(
+                    "Resolution: {}x{}",
+                    settings.window.width, settings.window.height
+                ),
+                Row::Fullscreen => format!("Fullscreen: {}", on_off(settings.window.fullscreen)),
+                Row::Vsync => format!("VSync: {}", on_off(settings.window.vsync)),
+                Row::FpsCap if settings.window.fps_cap > 0 => {
+                    format!("FPS cap: {}", settings.window.fps_cap)
+                }
+                Row::FpsCap => "FPS cap: off".to_string(),
+                Row::LowPower => format!("Low-power mode: {}", on_off(settings.window.low_power)),
+                Row::ShowFps => format!("FPS counter: {}", on_off(settings.window.show_fps)),
+                Row::Volume => format!("Volume: {:.0}%", settings.audio.volume * 100.0),
+                Row::Vignette => format!("Vignette: {}", on_off(settings.effects.vignette)),
+                Row::ColorGrading => {
+                    format!("Color grading: {}", on_off(settings.effects.color_grading))
+                }
+                Row::Pixelate => format!("Pixelate: {}", on_off(settings.effects.pixelate)),
+                Row::CrossingCamera => format!(
+                    "Crossing camera: {}",
+                    on_off(settings.effects.crossing_camera)
+                ),
+                Row::Theme => format!("Sprites: {}", settings.theme.name),
+                Row::HighContrast => format!(
+                    "High contrast: {}",
+                    on_off(settings.accessibility.high_contrast)
+                ),
+                Row::Narration => {
+                    format!("Narration: {}", on_off(settings.accessibility.narration))
+                }
+                Row::HudScale if settings.hud.scale > 0.0 => {
+                    format!("HUD scale: {:.0}%", settings.hud.scale * 100.0)
+                }
+                Row::HudScale => "HUD scale: auto".to_string(),
+                Row::SafeArea => format!("Safe area: {:.1}%", settings.hud.safe_area * 100.0),
+                Row::Controls => "Controls".to_string(),
+                Row::Back => "Back".to_string(),
+            })
+            .collect();
+        (labels, self.selected)
+    }
+
+    pub fn handle(
+        &mut self,
+        input: &MenuInput,
+        view: Rect,
+        settings: &mut Settings,
+    ) -> Option<SettingsChange> {
+        if let Some(controls) = &mut self.controls {
+            let change = controls.handle(input, view, &mut settings.keys);
+            if controls.closed {
+                self.controls = None;
+            }
+            return change;
+        }
+        if input.back {
+            return Some(SettingsChange::Close);
+        }
+        let confirm = pause::navigate(&mut self.selected, ROWS.len(), input, view);
+
+        let steps = match (input.left, input.right || confirm) {
+            (true, _) => -1,
+            (false, true) => 1,
+            (false, false) => return None,
+        };
+        let window = &mut settings.window;
+        let effects = &mut settings.effects;
+        match ROWS[self.selected] {
+            Row::Resolution => {
+                let current = RESOLUTIONS
+                    .iter()
+                    .position(|&size| size == (window.width, window.height));
+                let next = match current {
+                    Some(i) => (i as i32 + steps).rem_euclid(RESOLUTIONS.len() as i32) as usize,
+                    None => 0,
+                };
+                (window.width, window.height) = RESOLUTIONS[next];
+                Some(SettingsChange::Resolution)
+            }
+            Row::Fullscreen => {
+                window.fullscreen = !window.fullscreen;
+                Some(SettingsChange::Fullscreen)
+            }
+            Row::Vsync => {
+                window.vsync = !window.vsync;
+                Some(SettingsChange::Vsync)
+            }
+            Row::FpsCap => {
+                let current = FPS_CAPS.iter().position(|&cap| cap == window.fps_cap);
+                let next = match current {
+                    Some(i) => (i as i32 + steps).rem_euclid(FPS_CAPS.len() as i32) as usize,
+                    None => 0,
+                };
+                window.fps_cap = FPS_CAPS[next];
+                Some(SettingsChange::FramePacing)
+            }
+            Row::LowPower => {
+                window.low_power = !window.low_power;
+                Some(SettingsChange::FramePacing)
+            }
+            Row::ShowFps => {
+                window.show_fps = !window.show_fps;
+                Some(SettingsChange::FramePacing)
+            }
+            Row::Volume => {
+                let volume = settings.audio.volume + steps as f32 * VOLUME_STEP;
+                settings.audio.volume = volume.clamp(0.0, 1.0);
+                Some(SettingsChange::Volume)
+            }
+            Row::Vignette => {
+                effects.vignette = !effects.vignette;
+                Some(SettingsChange::Effects)
+            }
+            Row::ColorGrading => {
+                effects.color_grading = !effects.color_grading;
+                Some(SettingsChange::Effects)
+            }
+            Row::Pixelate => {
+                effects.pixelate = !effects.pixelate;
+                Some(SettingsChange::Effects)
+            }
+            Row::CrossingCamera => {
+                effects.crossing_camera = !effects.crossing_camera;
+                Some(SettingsChange::Effects)
+            }
+            Row::Theme => {
+                let theme = &mut settings.theme;
+                theme.name = theme::cycle(&theme.name, steps).to_string();
+                Some(SettingsChange::Theme)
+            }
+            Row::HighContrast => {
+                let accessibility = &mut settings.accessibility;
+                accessibility.high_contrast = !accessibility.high_contrast;
+                Some(SettingsChange::Accessibility)
+            }
+            Row::Narration => {
+                let accessibility = &mut settings.accessibility;
+                accessibility.narration = !accessibility.narration;
+                Some(SettingsChange::Accessibility)
+            }
+            Row::HudScale => {
+                let hud = &mut settings.hud;
+                let current = HUD_SCALES.iter().position(|&scale| scale == hud.scale);
+                let next = match current {
+                    Some(i) => (i as i32 + steps).rem_euclid(HUD_SCALES.len() as i32) as usize,
+                    None => 0,
+                };
+                hud.scale = HUD_SCALES[next];
+                Some(SettingsChange::Hud)
+            }
+            Row::SafeArea => {
+                let hud = &mut settings.hud;
+                let area = hud.safe_area + steps as f32 * SAFE_AREA_STEP;
+                hud.safe_area = area.clamp(0.0, MAX_SAFE_AREA);
+                Some(SettingsChange::Hud)
+            }
+            Row::Controls if confirm => {
+                self.controls = Some(ControlsScreen::new());
+                None
+            }
+            Row::Back if confirm => Some(SettingsChange::Close),
+            Row::Controls | Row::Back => None,
+        }
+    }
+}
+
+/// Rows after the actions on the controls screen.
+const CONTROLS_EXTRA_ROWS: [&str; 2] = ["Reset to defaults", "Back"];
+
+/// Lists each action's keys. Confirming an action waits for the next key
+/// press and makes it that action's primary key.
+struct ControlsScreen {
+    selected: usize,
+    /// The action waiting for a key, if any.
+    capturing: Option<Action>,
+    closed: bool,
+}
+
+impl ControlsScreen {
+    fn new() -> Self {
+        Self {
+            selected: 0,
+            capturing: None,
+            closed: false,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.capturing {
+            Some(action) => format!("Press a key for {} (Esc cancels)", action.label()),
+            None => "Controls".to_string(),
+        }
+    }
+
+    fn items(&self, keys: &KeyBindings) -> (Vec<String>, usize) {
+        let labels = Action::ALL
+            .iter()
+            .map(|&action| format!("{}: {}", action.label(), keys.keys(action).join(", ")))
+            .chain(CONTROLS_EXTRA_ROWS.iter().map(|label| label.to_string()))
+            .collect();
+        (labels, self.selected)
+    }
+
+    fn handle(
+        &mut self,
+        input: &MenuInput,
+        view: Rect,
+        keys: &mut KeyBindings,
+    ) -> Option<SettingsChange> {
+        if let Some(action) = self.capturing {
+            let key = input.key?;
+            self.capturing = None;
+            if key == KeyCode::Escape {
+                return None;
+            }
+            return Some(match keys.rebind(action, key) {
+                Ok(()) => SettingsChange::KeyBindings,
+                Err(reason) => SettingsChange::Message(reason),
+            });
+        }
+        if input.back {
+            self.closed = true;
+            return None;
+        }
+
+        let count = Action::ALL.len() + CONTROLS_EXTRA_ROWS.len();
+        if !pause::navigate(&mut self.selected, count, input, view) {
+            return None;
+        }
+        match Action::ALL.get(self.selected) {
+            Some(&action) => {
+                self.capturing = Some(action);
+                None
+            }
+            None if self.selected == Action::ALL.len() => {
+                *keys = KeyBindings::default();
+                Some(SettingsChange::KeyBindings)
+            }
+            None => {
+                self.closed = true;
+                None
+            }
+        }
+    }
+}
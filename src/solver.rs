@@ -0,0 +1,505 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use crate::game::{
+    Action, BoatId, BoatState, EntityId, EntityLocation, GamePhase, GameState, PlayerLocation,
+};
+use crate::kinds::{EatingRule, KindId};
+use crate::level::LevelDef;
+use crate::world::RegionId;
+
+/// The puzzle reduced to which region everyone is on. A boat only leaves
+/// the farmer's side on levels with a bridge or island.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PuzzleState {
+    farmer: RegionId,
+    /// Bit `i` is set when boat `i` is on its river's right side.
+    boats: u16,
+    /// Four bits per entity: the region `EntityId(i)` is on sits at bit
+    /// `4 * i`.
+    regions: u64,
+}
+
+/// One trip across a river, optionally with passengers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crossing {
+    pub river: usize,
+    pub from: RegionId,
+    pub to: RegionId,
+    /// The boat rowed; None when walked over a bridge, which never has
+    /// passengers.
+    pub boat: Option<BoatId>,
+    /// Bit `i` is set when `EntityId(i)` rides along.
+    pub passengers: u16,
+}
+
+impl Crossing {
+    pub fn passengers(self) -> impl Iterator<Item = EntityId> {
+        (0..16)
+            .filter(move |i| self.passengers >> i & 1 == 1)
+            .map(EntityId)
+    }
+}
+
+/// One step of a solution in the game's own terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanStep {
+    /// What the interact key does.
+    Act(Action),
+    /// Row the boat over to `to`.
+    Row { to: RegionId },
+    /// Walk over the bridge to `to`.
+    Walk { to: RegionId },
+}
+
+/// The fixed facts a search needs: each entity's kind, by id, who eats
+/// whom, which rivers the farmer can walk across, any region a co-op
+/// partner always watches, the seats boat upgrades add, and the level for
+/// its rivers and loss script.
+pub struct Puzzle<'a> {
+    kinds: Vec<KindId>,
+    rules: &'a [EatingRule],
+    bridges: Vec<bool>,
+    guarded: Option<RegionId>,
+    extra_capacity: usize,
+    level: &'a LevelDef,
+}
+
+impl<'a> Puzzle<'a> {
+    pub fn new(level: &'a LevelDef) -> Self {
+        Self {
+            kinds: level.entities.iter().map(|&(kind, _)| kind).collect(),
+            rules: &level.eating_rules,
+            bridges: (0..level.rivers.len())
+                .map(|i| level.has_bridge(i))
+                .collect(),
+            guarded: None,
+            extra_capacity: 0,
+            level,
+        }
+    }
+
+    /// The puzzle a live game poses, with its partner's region in co-op
+    /// and its boats' upgrades.
+    pub fn for_game(state: &'a GameState) -> Self {
+        Self {
+            guarded: state.partner.as_ref().and_then(|partner| partner.home),
+            extra_capacity: state.upgrades.extra_capacity(),
+            ..Self::new(&state.level)
+        }
+    }
+
+    fn ids(&self) -> impl Iterator<Item = EntityId> + '_ {
+        (0..self.kinds.len()).map(EntityId)
+    }
+}
+
+impl PuzzleState {
+    pub fn initial(level: &LevelDef) -> Self {
+        let mut state = Self {
+            farmer: level.region_of(level.player_start).unwrap(),
+            boats: 0,
+            regions: 0,
+        };
+        for (i, &(_, pos)) in level.entities.iter().enumerate() {
+            state.set_region(EntityId(i), level.region_of(pos).unwrap());
+        }
+        state
+    }
+
+    /// Abstract a live game. None while a boat is crossing or the game is
+    /// over.
+    pub fn from_game(state: &GameState) -> Option<Self> {
+        if state.phase != GamePhase::Playing {
+            return None;
+        }
+        let mut abstracted = Self {
+            farmer: RegionId(0),
+            boats: 0,
+            regions: 0,
+        };
+        for (i, boat) in state.boats.iter().enumerate() {
+            let BoatState::Docked(region) = boat.state else {
+                return None;
+            };
+            abstracted.set_boat(&state.level, i, region);
+        }
+        let level = &state.level;
+        let boat_region = move |boat: BoatId| abstracted.boat(level, boat.0);
+        let farmer = match state.player.location {
+            PlayerLocation::OnLand(pos) => state.level.region_of(pos)?,
+            PlayerLocation::OnBoat(boat) => boat_region(boat),
+        };
+
+        abstracted.farmer = farmer;
+        for entity in &state.entities {
+            let region = match entity.location {
+                EntityLocation::OnBank { region, .. } => region,
+                // Player two's follower stays on their bank.
+                EntityLocation::FollowingPlayer => state
+                    .partner
+                    .as_ref()
+                    .filter(|partner| partner.follower == Some(entity.id))
+                    .and_then(|partner| partner.region(&state.level))
+                    .unwrap_or(farmer),
+                EntityLocation::OnBoat(boat) => boat_region(boat),
+            };
+            abstracted.set_region(entity.id, region);
+        }
+        Some(abstracted)
+    }
+
+    fn region(self, id: EntityId) -> RegionId {
+        RegionId((self.regions >> (4 * id.0) & 0xf) as usize)
+    }
+
+    fn set_region(&mut self, id: EntityId, region: RegionId) {
+        let shift = 4 * id.0;
+        self.regions = self.regions & !(0xf << shift) | (region.0 as u64) << shift;
+    }
+
+    /// Where boat `boat` is; it only ever sits at one of its two docks.
+    fn boat(self, level: &LevelDef, boat: usize) -> RegionId {
+        RegionId(level.boats[boat].river + (self.boats >> boat & 1) as usize)
+    }
+
+    fn set_boat(&mut self, level: &LevelDef, boat: usize, region: RegionId) {
+        if region.0 == level.boats[boat].river {
+            self.boats &= !(1 << boat);
+        } else {
+            self.boats |= 1 << boat;
+        }
+    }
+
+    fn is_won(self, puzzle: &Puzzle) -> bool {
+        let goal = puzzle.level.goal_region();
+        puzzle.ids().all(|id| self.region(id) == goal)
+    }
+
+    /// Whether leaving every region unattended (the farmer is on a boat
+    /// with `passengers`, or on a bridge) breaks an eating rule or the
+    /// level's loss script.
+    fn is_safe_departure(self, passengers: u16, puzzle: &Puzzle) -> bool {
+        puzzle.level.regions().all(|region| {
+            if puzzle.guarded == Some(region) {
+                return true;
+            }
+            let here: Vec<EntityId> = puzzle
+                .ids()
+                .filter(|&id| passengers >> id.0 & 1 == 0 && self.region(id) == region)
+                .collect();
+            let eaten = puzzle.rules.iter().any(|rule| {
+                here.iter().any(|&eater| {
+                    puzzle.kinds[eater.0] == rule.eater
+                        && here
+                            .iter()
+                            .any(|&eaten| eaten != eater && puzzle.kinds[eaten.0] == rule.eaten)
+                })
+            });
+            let scripted = puzzle.level.loss_script.as_ref().is_some_and(|script| {
+                let names: Vec<&str> = here
+                    .iter()
+                    .map(|id| puzzle.level.kind(puzzle.kinds[id.0]).name.as_str())
+                    .collect();
+                let label = puzzle.level.region_label(region);
+                script.evaluate(&label, &names, None).is_some()
+            });
+            !eaten && !scripted
+        })
+    }
+
+    /// Every load of at most `capacity` passengers from the farmer's side,
+    /// as bitmasks. Entities of one kind are interchangeable, so a load
+    /// takes the first few of each kind.
+    fn loads(self, capacity: usize, puzzle: &Puzzle) -> Vec<u16> {
+        let mut by_kind: Vec<(KindId, Vec<EntityId>)> = Vec::new();
+        for id in puzzle.ids().filter(|&id| self.region(id) == self.farmer) {
+            let kind = puzzle.kinds[id.0];
+            match by_kind.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, ids)) => ids.push(id),
+                None => by_kind.push((kind, vec![id])),
+            }
+        }
+        let mut loads = vec![(0u16, 0usize)];
+        for (_, ids) in &by_kind {
+            let mut more = Vec::new();
+            for &(load, count) in &loads {
+                let mut load = load;
+                for (taken, id) in ids.iter().enumerate().take(capacity - count) {
+                    load |= 1 << id.0;
+                    more.push((load, count + taken + 1));
+                }
+            }
+            loads.extend(more);
+        }
+        loads.into_iter().map(|(load, _)| load).collect()
+    }
+
+    fn successors(self, puzzle: &Puzzle) -> Vec<(Crossing, PuzzleState)> {
+        let level = puzzle.level;
+        let mut next_states = Vec::new();
+        for river in level.rivers_of(self.farmer) {
+            let to = level.across(river, self.farmer);
+            let crossing = Crossing {
+                river,
+                from: self.farmer,
+                to,
+                boat: None,
+                passengers: 0,
+            };
+            for boat in level.boats_on(river) {
+                if self.boat(level, boat) != self.farmer {
+                    continue;
+                }
+                let def = &level.boats[boat];
+                for passengers in self.loads(def.capacity + puzzle.extra_capacity, puzzle) {
+                    let weight = puzzle
+                        .ids()
+                        .filter(|id| passengers >> id.0 & 1 == 1)
+                        .map(|id| level.kind(puzzle.kinds[id.0]).weight)
+                        .sum();
+                    if def.sinks_under(weight) || !self.is_safe_departure(passengers, puzzle) {
+                        continue;
+                    }
+                    let mut next = self;
+                    next.farmer = to;
+                    next.set_boat(level, boat, to);
+                    for id in puzzle.ids().filter(|id| passengers >> id.0 & 1 == 1) {
+                        next.set_region(id, to);
+                    }
+                    let rowed = Crossing {
+                        boat: Some(BoatId(boat)),
+                        passengers,
+                        ..crossing
+                    };
+                    next_states.push((rowed, next));
+                }
+            }
+            if puzzle.bridges[river] && self.is_safe_departure(0, puzzle) {
+                let next = Self { farmer: to, ..self };
+                next_states.push((crossing, next));
+            }
+        }
+        next_states
+    }
+}
+
+/// Breadth-first search for the shortest sequence of crossings that gets
+/// everything to the right bank. None if the puzzle is unsolvable from here.
+pub fn solve(puzzle: &Puzzle, start: PuzzleState) -> Option<Vec<Crossing>> {
+    let mut came_from: HashMap<PuzzleState, (PuzzleState, Crossing)> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(current) = queue.pop_front() {
+        if current.is_won(puzzle) {
+            let mut path = Vec::new();
+            let mut at = current;
+            while let Some(&(prev, crossing)) = came_from.get(&at) {
+                path.push(crossing);
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for (crossing, next) in current.successors(puzzle) {
+            if next != start && !came_from.contains_key(&next) {
+                came_from.insert(next, (current, crossing));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// The fewest crossings that solve `level` from its starting layout.
+pub fn min_crossings(level: &LevelDef) -> Option<usize> {
+    solve(&Puzzle::new(level), PuzzleState::initial(level)).map(|path| path.len())
+}
+
+/// The shortest solution to the level `state` is playing, from its
+/// start; a co-op partner can make a puzzle shorter.
+pub fn game_solution(state: &GameState) -> Option<Vec<Crossing>> {
+    solve(&Puzzle::for_game(state), PuzzleState::initial(&state.level))
+}
+
+/// Spell `crossings`, from the level's start, out as the actions that
+/// play them: each interaction and trip across. Walking between the
+/// animals and the dock is left out.
+pub fn plan_steps(crossings: &[Crossing]) -> Vec<PlanStep> {
+    let mut steps = Vec::new();
+    // The boat the farmer is sitting in.
+    let mut on_boat = None;
+    for crossing in crossings {
+        let to = crossing.to;
+        let Some(boat) = crossing.boat else {
+            if on_boat.take().is_some() {
+                steps.push(PlanStep::Act(Action::UnboardBoat));
+            }
+            steps.push(PlanStep::Walk { to });
+            continue;
+        };
+        if crossing.passengers != 0 {
+            if on_boat.is_some() {
+                steps.push(PlanStep::Act(Action::UnboardBoat));
+            }
+            for id in crossing.passengers() {
+                steps.push(PlanStep::Act(Action::PickUp(id)));
+                steps.push(PlanStep::Act(Action::LoadOntoBoat(id)));
+            }
+            steps.push(PlanStep::Act(Action::BoardBoat));
+        } else if on_boat != Some(boat) {
+            if on_boat.is_some() {
+                steps.push(PlanStep::Act(Action::UnboardBoat));
+            }
+            steps.push(PlanStep::Act(Action::BoardBoat));
+        }
+        on_boat = Some(boat);
+        steps.push(PlanStep::Row { to });
+        for id in crossing.passengers() {
+            steps.push(PlanStep::Act(Action::UnloadFromBoat(id)));
+        }
+    }
+    if on_boat.is_some() {
+        steps.push(PlanStep::Act(Action::UnboardBoat));
+    }
+    steps
+}
+
+/// One to three stars for finishing in `crossings` when `optimal` is the
+/// best possible: three for a perfect run, two for at most two wasted
+/// round trips, one otherwise.
+pub fn star_rating(crossings: u32, optimal: usize) -> u8 {
+    let extra = (crossings as usize).saturating_sub(optimal);
+    match extra {
+        0 => 3,
+        1..=4 => 2,
+        _ => 1,
+    }
+}
+
+/// Everything `hint` goes by, to tell when it might say something new.
+#[derive(Debug)]
+pub struct HintKey {
+    /// Held on to, so no other level can take its place in memory.
+    level: Arc<LevelDef>,
+    guarded: Option<RegionId>,
+    extra_capacity: usize,
+    at: Option<PuzzleState>,
+    player_boat: Option<BoatId>,
+    follower: Option<EntityId>,
+    cargo: Vec<Vec<EntityId>>,
+}
+
+impl HintKey {
+    pub fn of(state: &GameState) -> Self {
+        let puzzle = Puzzle::for_game(state);
+        Self {
+            level: Arc::clone(&state.level),
+            guarded: puzzle.guarded,
+            extra_capacity: puzzle.extra_capacity,
+            at: PuzzleState::from_game(state),
+            player_boat: state.player_boat(),
+            follower: state.player.follower,
+            cargo: state.boats.iter().map(|boat| boat.cargo.clone()).collect(),
+        }
+    }
+}
+
+impl PartialEq for HintKey {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.level, &other.level)
+            && self.guarded == other.guarded
+            && self.extra_capacity == other.extra_capacity
+            && self.at == other.at
+            && self.player_boat == other.player_boat
+            && self.follower == other.follower
+            && self.cargo == other.cargo
+    }
+}
+
+/// A short instruction for the next step of an optimal solution from the
+/// current game state.
+pub fn hint(state: &GameState) -> Option<String> {
+    let abstracted = PuzzleState::from_game(state)?;
+    let plan = solve(&Puzzle::for_game(state), abstracted)?;
+    // The boat the next trip takes, or the player's own once they're done.
+    let boat = match plan.first() {
+        Some(next) => next.boat,
+        None => state.player_boat(),
+    };
+    let on_boat = boat.is_some() && state.player_boat() == boat;
+    // Entities of one kind are interchangeable, so talk in kinds.
+    let cargo: Vec<KindId> = boat
+        .map(|boat| {
+            state
+                .boat(boat)
+                .cargo
+                .iter()
+                .map(|&id| state.kind_of(id))
+                .collect()
+        })
+        .unwrap_or_default();
+    let follower = state.player.follower.map(|id| state.kind_of(id));
+    let name = |kind: KindId| &state.level.kind(kind).name;
+
+    let Some(next) = plan.first() else {
+        // Everything is across in principle; finish unloading.
+        return match (cargo.first(), follower) {
+            (Some(&e), _) => Some(format!("Unload the {}", name(e))),
+            (None, Some(e)) => Some(format!("Leave the {} on this bank", name(e))),
+            (None, None) => None,
+        };
+    };
+
+    if next.boat.is_none() {
+        let text = match follower {
+            _ if on_boat => "Get off and walk across the bridge".to_string(),
+            Some(e) => format!("Leave the {} and walk across the bridge", name(e)),
+            None => "Walk across the bridge".to_string(),
+        };
+        return Some(text);
+    }
+
+    // Who still has to get aboard, and who has to get off.
+    let passengers: Vec<KindId> = next.passengers().map(|id| state.kind_of(id)).collect();
+    let mut missing = passengers.clone();
+    let mut extra = Vec::new();
+    for kind in cargo {
+        match missing.iter().position(|&k| k == kind) {
+            Some(i) => {
+                missing.remove(i);
+            }
+            None => extra.push(kind),
+        }
+    }
+
+    let text = match (extra.first(), missing.first()) {
+        (Some(&e), _) if passengers.is_empty() => format!("Unload the {}", name(e)),
+        (Some(&e), _) => format!("Unload the {} first", name(e)),
+        (None, Some(_)) if follower.is_some_and(|e| missing.contains(&e)) => {
+            format!("Load the {} onto the boat", name(follower.unwrap()))
+        }
+        (None, Some(&e)) if on_boat => format!("Get off and fetch the {}", name(e)),
+        (None, Some(&e)) => match follower {
+            Some(other) => format!("Leave the {} and fetch the {}", name(other), name(e)),
+            None => format!("Fetch the {}", name(e)),
+        },
+        (None, None) if !passengers.is_empty() => {
+            if on_boat {
+                let names: Vec<String> = passengers
+                    .iter()
+                    .map(|&e| format!("the {}", name(e)))
+                    .collect();
+                format!("Cross the river with {}", names.join(" and "))
+            } else {
+                "Board the boat".to_string()
+            }
+        }
+        (None, None) => match follower {
+            Some(e) => format!("Leave the {} on this bank", name(e)),
+            None if on_boat => "Cross the river alone".to_string(),
+            None => "Board the boat and cross alone".to_string(),
+        },
+    };
+    Some(text)
+}
@@ -0,0 +1,34 @@
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+/// Environment variable naming the program that reads a line aloud.
+const COMMAND_VAR: &str = "SANZU_TTS";
+
+#[cfg(target_os = "macos")]
+const DEFAULT_COMMAND: &str = "say";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_COMMAND: &str = "espeak";
+
+/// A narrator sink that prints each line and speaks it through the
+/// system's text-to-speech program, `$SANZU_TTS` or the platform default,
+/// which gets the line as its only argument. Lines are spoken one after
+/// another on a background thread so the game never waits on speech. If
+/// the program can't be run, speech stops and printing carries on.
+pub fn sink() -> impl FnMut(&str) + 'static {
+    let (lines, queue) = mpsc::channel::<String>();
+    let command = std::env::var(COMMAND_VAR).unwrap_or_else(|_| DEFAULT_COMMAND.to_string());
+    thread::spawn(move || {
+        for line in queue {
+            if let Err(e) = Command::new(&command).arg(&line).status() {
+                eprintln!("{command}: {e}; narration will not be spoken");
+                return;
+            }
+        }
+    });
+    move |line| {
+        println!("{line}");
+        // The speech thread only goes away after reporting its error.
+        let _ = lines.send(line.to_string());
+    }
+}
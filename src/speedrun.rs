@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+
+use macroquad::logging::error;
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+use crate::game::{GamePhase, GameState};
+use crate::level::LevelDef;
+use crate::storage;
+
+const BESTS_PATH: &str = "speedrun.ron";
+
+/// The fastest finished run of one level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersonalBest {
+    /// Seconds from the start to the win.
+    pub time: f32,
+    /// Elapsed seconds at the end of each crossing.
+    pub splits: Vec<f32>,
+}
+
+/// Personal bests keyed by level name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Bests {
+    levels: BTreeMap<String, PersonalBest>,
+}
+
+impl Bests {
+    fn load() -> Self {
+        let parsed = storage::read(BESTS_PATH).and_then(|source| match source {
+            Some(source) => ron::from_str(&source).map_err(|e| format!("{BESTS_PATH}: {e}")),
+            None => Ok(Self::default()),
+        });
+        parsed.unwrap_or_else(|e| {
+            error!("{e}; starting with no personal bests");
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        storage::write(BESTS_PATH, &source)
+    }
+}
+
+/// Optional speedrun mode: times each attempt while `Playing`, so the
+/// pause menu and level select don't count, and splits on every crossing.
+/// Off by default; casual play never sees it.
+pub struct Speedrun {
+    enabled: bool,
+    elapsed: f32,
+    splits: Vec<f32>,
+    /// Set when the current attempt finished faster than the old best.
+    new_best: bool,
+    /// Whether the current attempt loaded a savestate or rewound.
+    tainted: bool,
+    bests: Bests,
+}
+
+impl Speedrun {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            elapsed: 0.0,
+            splits: Vec::new(),
+            new_best: false,
+            tainted: false,
+            bests: Bests::load(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn the mode on or off. The caller restarts the level so the
+    /// timer starts from a clean attempt.
+    pub fn toggle(&mut self) -> String {
+        self.enabled = !self.enabled;
+        self.reset_attempt();
+        let status = if self.enabled { "on" } else { "off" };
+        format!("Speedrun mode {status}; restarted")
+    }
+
+    fn reset_attempt(&mut self) {
+        self.elapsed = 0.0;
+        self.splits.clear();
+        self.new_best = false;
+        self.tainted = false;
+    }
+
+    /// Keep the current attempt from setting a best: it loaded a savestate
+    /// or rewound, so the timer no longer covers the whole run.
+    pub fn taint(&mut self) {
+        self.tainted = true;
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub fn splits(&self) -> &[f32] {
+        &self.splits
+    }
+
+    pub fn is_tainted(&self) -> bool {
+        self.tainted
+    }
+
+    pub fn is_new_best(&self) -> bool {
+        self.new_best
+    }
+
+    pub fn best(&self, level: &LevelDef) -> Option<&PersonalBest> {
+        self.bests.levels.get(&level.name)
+    }
+
+    /// How the latest split compares with the same split of the best run;
+    /// negative is ahead.
+    pub fn split_delta(&self, level: &LevelDef) -> Option<f32> {
+        let index = self.splits.len().checked_sub(1)?;
+        let best = self.best(level)?.splits.get(index)?;
+        Some(self.splits[index] - best)
+    }
+
+    /// Advance the timer and note this frame's `events`: a fresh run
+    /// restarts it, a crossing splits it and a win may set a best. Errs if
+    /// a new best couldn't be saved.
+    pub fn observe(
+        &mut self,
+        state: &GameState,
+        events: &[GameEvent],
+        dt: f32,
+    ) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let mut won = false;
+        for event in events {
+            match event {
+                GameEvent::Started => self.reset_attempt(),
+                GameEvent::Crossed => self.splits.push(self.elapsed),
+                GameEvent::Won => won = true,
+                _ => {}
+            }
+        }
+        if state.phase == GamePhase::Playing {
+            self.elapsed += dt;
+        }
+
+        // Assisted, upgraded and tainted runs are timed, but set no
+        // records.
+        let fair = !state.assist && state.upgrades.is_empty() && !self.tainted;
+        if !won || !fair {
+            return Ok(());
+        }
+        let faster = match self.best(&state.level) {
+            Some(best) => self.elapsed < best.time,
+            None => true,
+        };
+        if !faster {
+            return Ok(());
+        }
+        self.new_best = true;
+        let best = PersonalBest {
+            time: self.elapsed,
+            splits: self.splits.clone(),
+        };
+        self.bests.levels.insert(state.level.name.clone(), best);
+        self.bests.save()
+    }
+}
+
+/// `m:ss.cc`, the way speedrun timers usually read.
+pub fn format_time(seconds: f32) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u32;
+    format!(
+        "{}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
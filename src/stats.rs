@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use macroquad::logging::error;
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+use crate::game::{EntityLocation, GamePhase, GameState, LoseReason};
+use crate::level::LevelDef;
+use crate::storage;
+
+const STATS_PATH: &str = "stats.ron";
+
+/// Points for each entity on the far bank.
+const POINTS_PER_ENTITY: u32 = 100;
+/// For a win in the fewest crossings the level can be solved in.
+const FEWEST_CROSSINGS_BONUS: u32 = 300;
+/// Seconds a win may take for each crossing of the shortest solution
+/// before there's no time bonus, and the bonus for each second under.
+const PAR_SECONDS_PER_CROSSING: f32 = 8.0;
+const POINTS_PER_SECOND: f32 = 10.0;
+/// Taken off for each follower set down on a bank.
+const DROP_PENALTY: u32 = 50;
+
+/// An attempt's points as they stand. The bonuses only come with the win.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    pub delivered: u32,
+    pub crossings_bonus: u32,
+    pub time_bonus: u32,
+    pub drop_penalty: u32,
+}
+
+impl Score {
+    pub fn total(&self) -> u32 {
+        (self.delivered + self.crossings_bonus + self.time_bonus).saturating_sub(self.drop_penalty)
+    }
+}
+
+/// Tallies over some stretch of play: one session, or every session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Totals {
+    pub games_won: u32,
+    pub games_lost: u32,
+    pub total_crossings: u32,
+    /// Seconds of play in the quickest win.
+    pub fastest_solve: Option<f32>,
+    /// Losses by cause, keyed by `loss_key`.
+    pub losses: BTreeMap<String, u32>,
+    /// Points from every win.
+    pub total_score: u32,
+    /// The highest score on each level won, keyed by its name.
+    pub best_scores: BTreeMap<String, u32>,
+}
+
+impl Totals {
+    /// Count a win; `record` is its time, level and score, for a win that
+    /// may set records.
+    fn record_win(&mut self, record: Option<(f32, &str, u32)>) {
+        self.games_won += 1;
+        let Some((seconds, level, score)) = record else {
+            return;
+        };
+        self.fastest_solve = Some(match self.fastest_solve {
+            Some(best) => best.min(seconds),
+            None => seconds,
+        });
+        self.total_score += score;
+        let best = self.best_scores.entry(level.to_string()).or_insert(0);
+        *best = (*best).max(score);
+    }
+
+    fn record_loss(&mut self, key: &str) {
+        self.games_lost += 1;
+        *self.losses.entry(key.to_string()).or_insert(0) += 1;
+    }
+
+    /// The cause of most losses, with its count.
+    pub fn worst_loss(&self) -> Option<(&str, u32)> {
+        self.losses
+            .iter()
+            .max_by_key(|&(_, &count)| count)
+            .map(|(key, &count)| (key.as_str(), count))
+    }
+}
+
+/// Short name for a loss cause, e.g. "wolf ate sheep".
+fn loss_key(reason: LoseReason, level: &LevelDef) -> String {
+    match reason {
+        LoseReason::Eaten { eater, eaten } => {
+            format!("{} ate {}", level.kind(eater).name, level.kind(eaten).name)
+        }
+        LoseReason::Nightfall => "nightfall".to_string(),
+        LoseReason::BoatSank => "boat sank".to_string(),
+        LoseReason::Caught => "caught by the wolf".to_string(),
+        LoseReason::OutOfCrossings => "out of crossings".to_string(),
+        LoseReason::Scripted(_) => reason.message(level),
+    }
+}
+
+/// Counts wins, losses and crossings as they happen, and scores each
+/// attempt. Lifetime totals are saved after every finished game; session
+/// totals start from zero.
+pub struct Stats {
+    pub session: Totals,
+    pub lifetime: Totals,
+    /// Seconds spent playing the current attempt.
+    attempt_time: f32,
+    /// How long the last win took.
+    last_solve: Option<f32>,
+    /// Followers set down in the current attempt.
+    drops: u32,
+    /// Whether the current attempt loaded a savestate or rewound, so its
+    /// time and score say nothing.
+    tainted: bool,
+    /// What the last win scored.
+    last_score: Option<Score>,
+}
+
+impl Stats {
+    /// Read lifetime totals from disk. A missing or broken file starts
+    /// them at zero.
+    pub fn load() -> Self {
+        let parsed = storage::read(STATS_PATH).and_then(|source| match source {
+            Some(source) => ron::from_str(&source).map_err(|e| format!("{STATS_PATH}: {e}")),
+            None => Ok(Totals::default()),
+        });
+        let lifetime = parsed.unwrap_or_else(|e| {
+            error!("{e}; starting stats from zero");
+            Totals::default()
+        });
+        Self {
+            session: Totals::default(),
+            lifetime,
+            attempt_time: 0.0,
+            last_solve: None,
+            drops: 0,
+            tainted: false,
+            last_score: None,
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(&self.lifetime, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        storage::write(STATS_PATH, &source)
+    }
+
+    /// Seconds the last win took, while its summary is up. None for a win
+    /// that can't set records.
+    pub fn last_solve(&self) -> Option<f32> {
+        self.last_solve
+    }
+
+    /// Keep the current attempt out of the records: it loaded a savestate
+    /// or rewound.
+    pub fn taint(&mut self) {
+        self.tainted = true;
+    }
+
+    /// What the last win scored, while its summary is up.
+    pub fn last_score(&self) -> Option<Score> {
+        self.last_score
+    }
+
+    /// The current attempt's score: points for everyone across less the
+    /// drops, and once won, bonuses for matching `optimal`, the fewest
+    /// crossings, and for beating the time that allows.
+    pub fn score(&self, state: &GameState, optimal: Option<usize>) -> Score {
+        let goal = state.level.goal_region();
+        let across = state
+            .entities
+            .iter()
+            .filter(
+                |e| matches!(e.location, EntityLocation::OnBank { region, .. } if region == goal),
+            )
+            .count() as u32;
+        let mut score = Score {
+            delivered: across * POINTS_PER_ENTITY,
+            drop_penalty: self.drops * DROP_PENALTY,
+            ..Score::default()
+        };
+        if state.phase == GamePhase::Won
+            && let Some(optimal) = optimal
+        {
+            if state.crossing_count as usize <= optimal {
+                score.crossings_bonus = FEWEST_CROSSINGS_BONUS;
+            }
+            let par = optimal as f32 * PAR_SECONDS_PER_CROSSING;
+            score.time_bonus = ((par - self.attempt_time).max(0.0) * POINTS_PER_SECOND) as u32;
+        }
+        score
+    }
+
+    /// Count this frame's `events`, scoring a win against `optimal`
+    /// crossings. Errs if a finished game couldn't be saved.
+    pub fn observe(
+        &mut self,
+        state: &GameState,
+        events: &[GameEvent],
+        optimal: Option<usize>,
+        dt: f32,
+    ) -> Result<(), String> {
+        let mut finished = false;
+        for &event in events {
+            match event {
+                // A restart or a new level begins a fresh attempt.
+                GameEvent::Started => {
+                    self.attempt_time = 0.0;
+                    self.last_solve = None;
+                    self.drops = 0;
+                    self.tainted = false;
+                    self.last_score = None;
+                }
+                GameEvent::Dropped { .. } => self.drops += 1,
+                GameEvent::Crossed => {
+                    self.session.total_crossings += 1;
+                    self.lifetime.total_crossings += 1;
+                }
+                GameEvent::Won => {
+                    let score = self.score(state, optimal);
+                    // Assisted runs set no records either.
+                    let record = (!self.tainted && !state.assist).then_some((
+                        self.attempt_time,
+                        state.level.name.as_str(),
+                        score.total(),
+                    ));
+                    self.session.record_win(record);
+                    self.lifetime.record_win(record);
+                    self.last_solve = record.map(|(seconds, _, _)| seconds);
+                    self.last_score = Some(score);
+                    finished = true;
+                }
+                GameEvent::Lost(reason) => {
+                    let key = loss_key(reason, &state.level);
+                    self.session.record_loss(&key);
+                    self.lifetime.record_loss(&key);
+                    finished = true;
+                }
+                _ => {}
+            }
+        }
+        if state.phase == GamePhase::Playing {
+            self.attempt_time += dt;
+        }
+        if finished { self.save() } else { Ok(()) }
+    }
+}
@@ -0,0 +1,27 @@
+/// Contents of a file kept between runs, or None if it doesn't exist yet.
+/// Paths are relative to the working directory, like the assets.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(path: &str) -> Result<Option<String>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(source) => Ok(Some(source)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("{path}: {e}")),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(path: &str, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|e| format!("{path}: {e}"))
+}
+
+/// The web build has nowhere to keep files: reads find nothing and writes
+/// are dropped.
+#[cfg(target_arch = "wasm32")]
+pub fn read(_path: &str) -> Result<Option<String>, String> {
+    Ok(None)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(_path: &str, _contents: &str) -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::cell::OnceCell;
+
+use macroquad::logging::error;
+use macroquad::prelude::*;
+
+const FONT_PATH: &str = "assets/fonts/DejaVuSans.ttf";
+
+thread_local! {
+    /// The UI font, once loaded. Until then, or if it failed to load, text
+    /// falls back to macroquad's built-in font.
+    static FONT: OnceCell<Font> = const { OnceCell::new() };
+}
+
+/// Load the UI font. A missing or broken file is logged and the built-in
+/// font is used instead.
+pub async fn load_font() {
+    match load_ttf_font(FONT_PATH).await {
+        Ok(font) => FONT.with(|cell| {
+            let _ = cell.set(font);
+        }),
+        Err(e) => error!("{FONT_PATH}: {e}; using the built-in font"),
+    }
+}
+
+/// How a piece of text looks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    pub size: f32,
+    pub color: Color,
+    /// Drawn under the text, offset down and to the right.
+    pub shadow: Option<Color>,
+    /// Drawn around the text, one unit thick.
+    pub outline: Option<Color>,
+}
+
+impl TextStyle {
+    pub const fn new(size: f32, color: Color) -> Self {
+        Self {
+            size,
+            color,
+            shadow: None,
+            outline: None,
+        }
+    }
+
+    pub const fn shadow(self, color: Color) -> Self {
+        Self {
+            shadow: Some(color),
+            ..self
+        }
+    }
+
+    pub const fn outline(self, color: Color) -> Self {
+        Self {
+            outline: Some(color),
+            ..self
+        }
+    }
+}
+
+/// Font size and scale that draw `size` units tall under the current
+/// camera, rasterized at the on-screen pixel size so it stays crisp.
+fn params(font: Option<&Font>, size: f32, color: Color) -> TextParams<'_> {
+    let (font_size, font_scale, font_scale_aspect) = camera_font_scale(size);
+    TextParams {
+        font,
+        font_size,
+        font_scale,
+        font_scale_aspect,
+        color,
+        ..Default::default()
+    }
+}
+
+/// Width of `text` at `size` in the current camera's units.
+pub fn measure(text: &str, size: f32) -> f32 {
+    FONT.with(|cell| {
+        let font = cell.get();
+        let (font_size, font_scale, aspect) = camera_font_scale(size);
+        measure_text(text, font, font_size, font_scale).width * aspect
+    })
+}
+
+/// Draw `text` with its baseline at `y`, starting at `x`.
+pub fn draw(text: &str, x: f32, y: f32, style: &TextStyle) {
+    FONT.with(|cell| {
+        let font = cell.get();
+        if let Some(color) = style.shadow {
+            let offset = (style.size / 12.0).max(1.0);
+            draw_text_ex(
+                text,
+                x + offset,
+                y + offset,
+                params(font, style.size, color),
+            );
+        }
+        if let Some(color) = style.outline {
+            for (dx, dy) in [(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)] {
+                draw_text_ex(text, x + dx, y + dy, params(font, style.size, color));
+            }
+        }
+        draw_text_ex(text, x, y, params(font, style.size, style.color));
+    });
+}
+
+/// Draw `text` centered on `cx`, with its baseline at `y`.
+pub fn draw_centered(text: &str, cx: f32, y: f32, style: &TextStyle) {
+    draw(text, cx - measure(text, style.size) / 2.0, y, style);
+}
+
+/// Break `text` into lines no wider than `max_width` at `size`, at spaces
+/// where possible. A word wider than the line gets a line to itself.
+pub fn wrap(text: &str, max_width: f32, size: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+            if line.is_empty() || measure(&candidate, size) <= max_width {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Space between baselines for text at `size`.
+pub fn line_height(size: f32) -> f32 {
+    size * 1.25
+}
+
+/// Draw `text` wrapped to `max_width`, first baseline at `y`. Returns the
+/// height the lines took.
+pub fn draw_paragraph(text: &str, x: f32, y: f32, max_width: f32, style: &TextStyle) -> f32 {
+    let lines = wrap(text, max_width, style.size);
+    let step = line_height(style.size);
+    for (i, line) in lines.iter().enumerate() {
+        draw(line, x, y + i as f32 * step, style);
+    }
+    lines.len() as f32 * step
+}
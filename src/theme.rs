@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use macroquad::logging::error;
+use macroquad::prelude::load_string;
+use serde::Deserialize;
+
+/// The themes the settings screen cycles through, by file name under
+/// `assets/themes/`. A pack named in `settings.toml` loads too, whether or
+/// not it's listed here.
+pub const THEMES: [&str; 3] = [DEFAULT_THEME, "winter", "hd"];
+/// The game's own sprites; needs no manifest.
+pub const DEFAULT_THEME: &str = "default";
+
+/// A sprite pack: which file draws each sprite, by the logical names the
+/// game asks for (`tree`, `sheep_idle`, ...). Sprites it leaves out are
+/// drawn from `assets/sprites/` as usual.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Theme {
+    /// Texels per world unit: 2 for art drawn at twice the game's
+    /// resolution, so it shows sharper rather than bigger.
+    #[serde(default = "default_density")]
+    density: f32,
+    /// Files under `assets/`, by sprite name.
+    #[serde(default)]
+    sprites: BTreeMap<String, String>,
+}
+
+fn default_density() -> f32 {
+    1.0
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            density: default_density(),
+            sprites: BTreeMap::new(),
+        }
+    }
+}
+
+impl Theme {
+    fn manifest_path(id: &str) -> String {
+        format!("assets/themes/{id}.ron")
+    }
+
+    /// Load the theme `id`. The default theme, or one that can't be read,
+    /// gives the game's own sprites.
+    pub async fn load(id: &str) -> Self {
+        if id == DEFAULT_THEME {
+            return Self::default();
+        }
+        let path = Self::manifest_path(id);
+        let parsed = match load_string(&path).await {
+            Ok(source) => ron::from_str(&source).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        parsed.unwrap_or_else(|e| {
+            error!("{path}: {e}; using the default sprites");
+            Self::default()
+        })
+    }
+
+    /// The file that draws the sprite `name`.
+    pub fn path(&self, name: &str) -> String {
+        match self.sprites.get(name) {
+            Some(file) => format!("assets/{file}"),
+            None => format!("assets/sprites/{name}.png"),
+        }
+    }
+
+    pub fn density(&self) -> f32 {
+        self.density.max(f32::EPSILON)
+    }
+}
+
+/// The theme after (or before, for `steps` below 0) `current` in
+/// `THEMES`. One that isn't listed goes back to the first.
+pub fn cycle(current: &str, steps: i32) -> &'static str {
+    let next = match THEMES.iter().position(|&id| id == current) {
+        Some(i) => (i as i32 + steps).rem_euclid(THEMES.len() as i32) as usize,
+        None => 0,
+    };
+    THEMES[next]
+}
@@ -0,0 +1,40 @@
+/// Seconds of game time in one simulation tick.
+pub const STEP: f32 = 1.0 / 60.0;
+/// Most ticks caught up in one frame. Anything more after a stall (a
+/// dragged window, a breakpoint) is dropped rather than fast-forwarded.
+const MAX_TICKS: u32 = 8;
+
+/// Runs the game at a steady 60 ticks a second whatever the frame rate,
+/// banking the time left over from one frame for the next.
+pub struct FixedStep {
+    accumulator: f32,
+}
+
+impl FixedStep {
+    pub fn new() -> Self {
+        Self { accumulator: 0.0 }
+    }
+
+    /// Bank a frame's time. Returns how many ticks are due.
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt;
+        let ticks = (self.accumulator / STEP) as u32;
+        self.accumulator -= ticks as f32 * STEP;
+        if ticks > MAX_TICKS {
+            self.accumulator = 0.0;
+            return MAX_TICKS;
+        }
+        ticks
+    }
+
+    /// How far the frame is between the last tick and the next, 0 to 1,
+    /// for drawing in between.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / STEP).clamp(0.0, 1.0)
+    }
+
+    /// Forget banked time, when the simulation has been held still.
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
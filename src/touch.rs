@@ -0,0 +1,122 @@
+use macroquad::prelude::{Rect, Vec2, vec2};
+
+use crate::world::Direction;
+
+/// An on-screen control for touchscreens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchButton {
+    Up,
+    Down,
+    Left,
+    Right,
+    Interact,
+    Cross,
+}
+
+impl TouchButton {
+    pub const ALL: [TouchButton; 6] = [
+        TouchButton::Up,
+        TouchButton::Down,
+        TouchButton::Left,
+        TouchButton::Right,
+        TouchButton::Interact,
+        TouchButton::Cross,
+    ];
+
+    pub fn direction(self) -> Option<Direction> {
+        match self {
+            TouchButton::Up => Some(Direction::Up),
+            TouchButton::Down => Some(Direction::Down),
+            TouchButton::Left => Some(Direction::Left),
+            TouchButton::Right => Some(Direction::Right),
+            TouchButton::Interact | TouchButton::Cross => None,
+        }
+    }
+
+    /// Where the button sits in HUD coordinates: the d-pad in the bottom
+    /// left corner of `view`, the action buttons in the bottom right.
+    pub fn rect(self, view: Rect) -> Rect {
+        let pad = vec2(view.x + 80.0, view.bottom() - 80.0);
+        let action = vec2(view.right() - 80.0, view.bottom() - 80.0);
+        let (center, size) = match self {
+            TouchButton::Up => (pad + vec2(0.0, -DPAD_SIZE), DPAD_SIZE),
+            TouchButton::Down => (pad + vec2(0.0, DPAD_SIZE), DPAD_SIZE),
+            TouchButton::Left => (pad + vec2(-DPAD_SIZE, 0.0), DPAD_SIZE),
+            TouchButton::Right => (pad + vec2(DPAD_SIZE, 0.0), DPAD_SIZE),
+            TouchButton::Interact => (action + vec2(-70.0, 20.0), ACTION_SIZE),
+            TouchButton::Cross => (action + vec2(0.0, -30.0), ACTION_SIZE),
+        };
+        Rect::new(center.x - size / 2.0, center.y - size / 2.0, size, size)
+    }
+
+    fn at(point: Vec2, view: Rect) -> Option<TouchButton> {
+        Self::ALL
+            .into_iter()
+            .find(|button| button.rect(view).contains(point))
+    }
+}
+
+const DPAD_SIZE: f32 = 44.0;
+const ACTION_SIZE: f32 = 56.0;
+
+/// One finger on the screen this frame, in HUD coordinates.
+#[derive(Debug, Clone, Copy)]
+pub struct TouchPoint {
+    pub position: Vec2,
+    pub started: bool,
+}
+
+/// Which on-screen buttons are held. Stays hidden until the first touch,
+/// so desktop players never see it.
+#[derive(Debug, Clone, Default)]
+pub struct TouchPad {
+    enabled: bool,
+    held: [bool; TouchButton::ALL.len()],
+    pressed: [bool; TouchButton::ALL.len()],
+    /// A touch landed on a button this frame, so it isn't also a world tap.
+    consumed_tap: bool,
+}
+
+impl TouchPad {
+    pub fn update(&mut self, touches: &[TouchPoint], view: Rect) {
+        if !touches.is_empty() {
+            self.enabled = true;
+        }
+        let before = self.held;
+        self.held = Default::default();
+        self.consumed_tap = false;
+        for touch in touches {
+            if let Some(button) = TouchButton::at(touch.position, view) {
+                self.held[button as usize] = true;
+                self.consumed_tap |= touch.started;
+            }
+        }
+        // Sliding a finger onto a button presses it too.
+        for ((pressed, &held), &was_held) in self.pressed.iter_mut().zip(&self.held).zip(&before) {
+            *pressed = held && !was_held;
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_held(&self, button: TouchButton) -> bool {
+        self.held[button as usize]
+    }
+
+    pub fn is_pressed(&self, button: TouchButton) -> bool {
+        self.pressed[button as usize]
+    }
+
+    pub fn direction(&self) -> Option<Direction> {
+        TouchButton::ALL
+            .into_iter()
+            .filter(|&button| self.is_held(button))
+            .find_map(TouchButton::direction)
+    }
+
+    pub fn consumed_tap(&self) -> bool {
+        self.consumed_tap
+    }
+}
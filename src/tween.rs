@@ -0,0 +1,106 @@
+/// How a motion speeds up and slows down: maps how far along it is in
+/// time, 0 to 1, to how far along it is in space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    Linear,
+    /// Eases in and out: gentle at both ends.
+    SmoothStep,
+    /// Overshoots the end a little and settles back.
+    OutBack,
+    /// Drops onto the end and bounces to a stop.
+    OutBounce,
+    /// Springs past the end and wobbles to rest.
+    OutElastic,
+}
+
+impl Ease {
+    /// The eased value of `t`, clamped to 0..1 first. Starts at 0 and
+    /// ends at 1; in between, the springy curves stray outside that.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::SmoothStep => t * t * (3.0 - 2.0 * t),
+            Ease::OutBack => {
+                const OVERSHOOT: f32 = 1.70158;
+                let u = t - 1.0;
+                1.0 + (OVERSHOOT + 1.0) * u.powi(3) + OVERSHOOT * u.powi(2)
+            }
+            Ease::OutBounce => out_bounce(t),
+            Ease::OutElastic => {
+                if t == 0.0 || t == 1.0 {
+                    return t;
+                }
+                let period = std::f32::consts::TAU / 3.0;
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * period).sin() + 1.0
+            }
+        }
+    }
+}
+
+fn out_bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+pub fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// A number moving from one value to another over a set time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+    ease: Ease,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, duration: f32, ease: Ease) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+            ease,
+        }
+    }
+
+    /// Already at `value`, going nowhere.
+    pub fn settled(value: f32) -> Self {
+        Self {
+            elapsed: 1.0,
+            ..Self::new(value, value, 1.0, Ease::Linear)
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn value(&self) -> f32 {
+        let t = self.elapsed / self.duration.max(f32::EPSILON);
+        lerp(self.from, self.to, self.ease.apply(t))
+    }
+}
+
+/// Width and height scales for squashing (`amount` below 0) or
+/// stretching (above 0) a sprite, keeping its area the same.
+pub fn squash_stretch(amount: f32) -> (f32, f32) {
+    let tall = 1.0 + amount;
+    (1.0 / tall.max(0.1), tall)
+}
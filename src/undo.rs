@@ -0,0 +1,59 @@
+use crate::events::GameEvent;
+use crate::game::{GamePhase, GameState};
+
+/// Crossings kept to rewind through; older ones drop off the bottom.
+const MAX_CROSSINGS: usize = 64;
+
+/// The game as it stood before each crossing of this run, latest on top,
+/// for going back after a loss to try that crossing differently.
+pub struct UndoStack {
+    before: Vec<GameState>,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self { before: Vec::new() }
+    }
+
+    /// Forget the run: a new level, a restart or a loaded savestate.
+    pub fn clear(&mut self) {
+        self.before.clear();
+    }
+
+    /// Look at one tick: `was` is the game before it, and `events` what
+    /// the tick did. Keeps `was` if a boat cast off or a farmer stepped
+    /// onto a bridge, the moments a bank can be left unwatched.
+    pub fn observe(&mut self, was: &GameState, events: &[GameEvent]) {
+        // Restarted: there's nothing before the fresh run to go back to.
+        if events.contains(&GameEvent::Started) {
+            self.clear();
+            return;
+        }
+        if was.phase != GamePhase::Playing {
+            return;
+        }
+        let level = &was.level;
+        let crossing = events.iter().any(|event| match *event {
+            GameEvent::CrossingStarted(_) => true,
+            GameEvent::Moved { from, to, .. } => {
+                level.region_of(from).is_some() && level.region_of(to).is_none()
+            }
+            _ => false,
+        });
+        if crossing {
+            if self.before.len() == MAX_CROSSINGS {
+                self.before.remove(0);
+            }
+            self.before.push(was.clone());
+        }
+    }
+
+    pub fn can_rewind(&self) -> bool {
+        !self.before.is_empty()
+    }
+
+    /// The game just before the latest crossing, taken off the stack.
+    pub fn rewind(&mut self) -> Option<GameState> {
+        self.before.pop()
+    }
+}
@@ -0,0 +1,126 @@
+/// How much quicker the faster crossing upgrade makes every crossing.
+const CROSSING_SPEEDUP: f32 = 1.25;
+/// The share of the current's push the resistance upgrade leaves.
+const CURRENT_LEFT: f32 = 0.5;
+
+/// An improvement to the farmer's boats, unlocked with campaign stars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upgrade {
+    FasterCrossing,
+    ExtraSlot,
+    CurrentResistance,
+}
+
+impl Upgrade {
+    /// Every upgrade, cheapest first.
+    pub const ALL: [Upgrade; 3] = [
+        Upgrade::FasterCrossing,
+        Upgrade::ExtraSlot,
+        Upgrade::CurrentResistance,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Upgrade::FasterCrossing => "Faster crossing",
+            Upgrade::ExtraSlot => "Extra cargo slot",
+            Upgrade::CurrentResistance => "Current resistance",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Upgrade::FasterCrossing => "Boats cross the river a quarter faster",
+            Upgrade::ExtraSlot => "Every boat takes one more passenger",
+            Upgrade::CurrentResistance => "The current pushes boats half as hard",
+        }
+    }
+
+    /// Campaign stars it takes to unlock.
+    pub fn stars(self) -> u32 {
+        match self {
+            Upgrade::FasterCrossing => 6,
+            Upgrade::ExtraSlot => 12,
+            Upgrade::CurrentResistance => 18,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// The boat upgrades a run is played with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Upgrades(u8);
+
+impl Upgrades {
+    /// Every upgrade `stars` is enough for.
+    pub fn unlocked(stars: u32) -> Self {
+        let mut upgrades = Self::default();
+        for upgrade in Upgrade::ALL {
+            if upgrade.stars() <= stars {
+                upgrades.toggle(upgrade);
+            }
+        }
+        upgrades
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_on(self, upgrade: Upgrade) -> bool {
+        self.0 & upgrade.bit() != 0
+    }
+
+    pub fn toggle(&mut self, upgrade: Upgrade) {
+        self.0 ^= upgrade.bit();
+    }
+
+    /// Those of these that are also in `other`.
+    pub fn only(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Upgrade> {
+        Upgrade::ALL.into_iter().filter(move |&u| self.is_on(u))
+    }
+
+    /// The ones named in `names`; names it doesn't know are left out.
+    pub fn from_names(names: &[String]) -> Self {
+        let mut upgrades = Self::default();
+        for upgrade in Upgrade::ALL {
+            if names.iter().any(|name| name == upgrade.name()) {
+                upgrades.toggle(upgrade);
+            }
+        }
+        upgrades
+    }
+
+    pub fn names(self) -> Vec<String> {
+        self.iter().map(|u| u.name().to_string()).collect()
+    }
+
+    /// How many times quicker a crossing is.
+    pub fn crossing_speed(self) -> f32 {
+        if self.is_on(Upgrade::FasterCrossing) {
+            CROSSING_SPEEDUP
+        } else {
+            1.0
+        }
+    }
+
+    /// Passengers every boat takes beyond its own capacity.
+    pub fn extra_capacity(self) -> usize {
+        usize::from(self.is_on(Upgrade::ExtraSlot))
+    }
+
+    /// The share of the current's push a boat still feels.
+    pub fn current_factor(self) -> f32 {
+        if self.is_on(Upgrade::CurrentResistance) {
+            CURRENT_LEFT
+        } else {
+            1.0
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::cell::OnceCell;
+
+use macroquad::logging::error;
+use macroquad::prelude::*;
+
+use crate::level::LevelDef;
+use crate::world::{self, GridPos, Tile};
+
+const VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying highp vec2 grid;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    grid = texcoord;
+    color = color0 / 255.0;
+}"#;
+
+const FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying highp vec2 grid;
+varying lowp vec4 color;
+
+uniform float time;
+uniform float wind;
+uniform vec4 tint;
+
+void main() {
+    float gust = 1.0 + abs(wind);
+    float t = time * gust;
+    float swell = sin(grid.x * 1.9 + grid.y * 1.1 + t * 1.4);
+    float chop = sin(grid.x * -2.7 + grid.y * 3.3 - t * 2.3 + wind * 2.0);
+    float waves = (swell * 0.6 + chop * 0.4) * min(gust, 1.6);
+    vec3 water = mix(vec3(0.09, 0.25, 0.58), vec3(0.20, 0.44, 0.76), 0.5 + 0.35 * waves);
+
+    // Thin bright crests where the swell peaks.
+    float crest = smoothstep(0.92, 1.0, sin(grid.y * 5.0 - grid.x * 1.5 + swell * 1.5 + t * 1.8));
+    water += vec3(0.18) * crest;
+
+    // Foam along the banks, lapping with the waves.
+    float lap = 0.08 * sin(t * 2.5 + (grid.x + grid.y) * 3.0);
+    float foam = smoothstep(0.6, 0.9, color.r + lap);
+    water = mix(water, vec3(0.90, 0.95, 1.0), foam * 0.75);
+
+    gl_FragColor = vec4(water * tint.rgb, 1.0);
+}"#;
+
+thread_local! {
+    /// The river shader, compiled on first use. None if the platform
+    /// rejected it.
+    static MATERIAL: OnceCell<Option<Material>> = const { OnceCell::new() };
+}
+
+fn load() -> Option<Material> {
+    let params = MaterialParams {
+        uniforms: vec![
+            UniformDesc::new("time", UniformType::Float1),
+            UniformDesc::new("wind", UniformType::Float1),
+            UniformDesc::new("tint", UniformType::Float4),
+        ],
+        ..Default::default()
+    };
+    let shader = ShaderSource::Glsl {
+        vertex: VERTEX,
+        fragment: FRAGMENT,
+    };
+    load_material(shader, params)
+        .inspect_err(|e| error!("water shader: {e}; drawing plain water"))
+        .ok()
+}
+
+fn is_water(level: &LevelDef, pos: GridPos) -> bool {
+    matches!(level.tile(pos), Tile::Water | Tile::Bridge)
+}
+
+/// Whether any of the four tiles meeting at the grid corner up and left of
+/// `pos` is land on the grid.
+fn corner_touches_land(level: &LevelDef, pos: GridPos) -> bool {
+    [(-1, -1), (0, -1), (-1, 0), (0, 0)]
+        .into_iter()
+        .any(|(dc, dr)| {
+            let tile = GridPos::new(pos.col + dc, pos.row + dr);
+            level.in_bounds(tile) && !is_water(level, tile)
+        })
+}
+
+/// Every water tile as one mesh. Texture coordinates carry the grid
+/// position for the wave pattern, and the red channel how close a corner
+/// is to land, for the foam.
+fn river_mesh(level: &LevelDef) -> Mesh {
+    let hw = world::TILE_WIDTH / 2.0;
+    let hh = world::TILE_HEIGHT / 2.0;
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for row in 0..level.rows {
+        for col in 0..level.cols {
+            let pos = GridPos::new(col, row);
+            if !is_water(level, pos) {
+                continue;
+            }
+            let (cx, cy) = world::grid_to_iso(pos);
+            // Top, right, bottom and left corners, with the grid corner
+            // each one sits on.
+            let corners = [
+                ((cx, cy - hh), (col, row)),
+                ((cx + hw, cy), (col + 1, row)),
+                ((cx, cy + hh), (col + 1, row + 1)),
+                ((cx - hw, cy), (col, row + 1)),
+            ];
+            let base = vertices.len() as u16;
+            for ((x, y), (gc, gr)) in corners {
+                let shore = corner_touches_land(level, GridPos::new(gc, gr));
+                let foam = Color::new(if shore { 1.0 } else { 0.0 }, 0.0, 0.0, 1.0);
+                let (u, v) = (gc as f32 - 0.5, gr as f32 - 0.5);
+                vertices.push(Vertex::new(x, y, 0.0, u, v, foam));
+            }
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+    }
+    Mesh {
+        vertices,
+        indices,
+        texture: None,
+    }
+}
+
+/// Draw every water tile of `level` with the wave shader, lit by `tint`.
+/// Returns false, drawing nothing, if the shader isn't available.
+pub fn draw_river(level: &LevelDef, time: f32, tint: Color) -> bool {
+    let Some(material) = MATERIAL.with(|cell| cell.get_or_init(load).clone()) else {
+        return false;
+    };
+    gl_use_material(&material);
+    material.set_uniform("time", time);
+    material.set_uniform("wind", level.weather.wind);
+    material.set_uniform("tint", vec4(tint.r, tint.g, tint.b, tint.a));
+    draw_mesh(&river_mesh(level));
+    gl_use_default_material();
+    true
+}
@@ -0,0 +1,112 @@
+use macroquad::prelude::*;
+
+use crate::level::LevelDef;
+use crate::particles::{Emitter, Layer, ParticlePool, Shape};
+use crate::tween::Ease;
+
+const RAIN_POOL_SIZE: usize = 384;
+/// Drops per second at full rain.
+const RAIN_RATE: f32 = 160.0;
+const RAIN_FALL_SPEED: f32 = 420.0;
+/// Sideways speed of a drop at full wind.
+const WIND_DRIFT: f32 = 160.0;
+/// How far above and beside the level drops start, so the edges of the
+/// view are covered too.
+const RAIN_MARGIN: f32 = 240.0;
+
+/// Side of one fog cell, in world units.
+const FOG_CELL: f32 = 16.0;
+/// Around the player the fog is clear out to the inner radius and full
+/// beyond the outer one.
+const FOG_CLEAR_RADIUS: f32 = 70.0;
+const FOG_FULL_RADIUS: f32 = 190.0;
+/// How much further a lit lantern pushes the fog back.
+const LANTERN_REACH: f32 = 1.8;
+const FOG_COLOR: Color = Color::new(0.78, 0.80, 0.84, 1.0);
+
+const RAIN: Emitter = Emitter {
+    count: 1,
+    velocity: vec2(0.0, RAIN_FALL_SPEED),
+    spread: vec2(8.0, 40.0),
+    life: 1.4,
+    size: 1.0,
+    growth: 0.0,
+    gravity: 0.0,
+    colors: &[
+        Color::new(0.75, 0.85, 1.0, 0.55),
+        Color::new(0.65, 0.75, 0.95, 0.45),
+    ],
+    shape: Shape::Streak,
+    layer: Layer::Air,
+};
+
+/// Rain and fog drawn over the world for the current level's weather.
+/// Purely cosmetic.
+pub struct WeatherFx {
+    rain: ParticlePool,
+    /// Fractional drops carried over to the next frame.
+    pending_drops: f32,
+}
+
+impl WeatherFx {
+    pub fn new() -> Self {
+        Self {
+            rain: ParticlePool::new(RAIN_POOL_SIZE),
+            pending_drops: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, level: &LevelDef, dt: f32) {
+        let weather = level.weather;
+        self.pending_drops += weather.rain * RAIN_RATE * dt;
+        let (min_x, min_y, max_x, _) = level.iso_bounds();
+        let emitter = Emitter {
+            velocity: vec2(weather.wind * WIND_DRIFT, RAIN_FALL_SPEED),
+            ..RAIN
+        };
+        while self.pending_drops >= 1.0 {
+            self.pending_drops -= 1.0;
+            let x = rand::gen_range(min_x - RAIN_MARGIN, max_x + RAIN_MARGIN);
+            self.rain.emit(&emitter, vec2(x, min_y - RAIN_MARGIN));
+        }
+        self.rain.update(dt);
+    }
+
+    /// Draw with the world camera set, after everything else in the world.
+    /// `focus` is where the player is, which the fog leaves clear, and
+    /// further with a `lantern` lit.
+    pub fn draw(&self, level: &LevelDef, focus: Vec2, lantern: bool) {
+        self.rain.draw();
+        if level.weather.fog > 0.0 {
+            let reach = if lantern { LANTERN_REACH } else { 1.0 };
+            draw_fog(level, level.weather.fog, focus, reach);
+        }
+    }
+}
+
+/// A veil of cells over the level, thinning to nothing near `focus`;
+/// `reach` scales how far the clear patch goes.
+fn draw_fog(level: &LevelDef, density: f32, focus: Vec2, reach: f32) {
+    let (min_x, min_y, max_x, max_y) = level.iso_bounds();
+    let (clear, full) = (FOG_CLEAR_RADIUS * reach, FOG_FULL_RADIUS * reach);
+    let margin = full;
+    let mut y = min_y - margin;
+    while y < max_y + margin {
+        let mut x = min_x - margin;
+        while x < max_x + margin {
+            let center = vec2(x + FOG_CELL / 2.0, y + FOG_CELL / 2.0);
+            let dist = center.distance(focus);
+            let t = (dist - clear) / (full - clear);
+            let alpha = density * Ease::SmoothStep.apply(t);
+            if alpha > 0.0 {
+                let color = Color {
+                    a: alpha,
+                    ..FOG_COLOR
+                };
+                draw_rectangle(x, y, FOG_CELL, FOG_CELL, color);
+            }
+            x += FOG_CELL;
+        }
+        y += FOG_CELL;
+    }
+}
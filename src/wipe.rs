@@ -0,0 +1,142 @@
+use std::f32::consts::TAU;
+
+use macroquad::prelude::*;
+
+use crate::tween::Ease;
+
+/// Seconds to cover the screen, and again to uncover it.
+const HALF_TIME: f32 = 0.35;
+const FADE_COLOR: Color = Color::new(0.02, 0.02, 0.04, 1.0);
+const RIPPLE_COLOR: Color = Color::new(0.05, 0.12, 0.22, 1.0);
+/// Points around the iris and ripple's edge.
+const EDGE_SEGMENTS: usize = 64;
+/// Waves around the ripple's edge, and how deep they are as a share of
+/// its radius.
+const RIPPLE_WAVES: f32 = 9.0;
+const RIPPLE_DEPTH: f32 = 0.06;
+
+/// How a wipe covers the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WipeStyle {
+    /// Fade to black and back.
+    Fade,
+    /// A circle closing in on the middle of the screen and opening out.
+    Iris,
+    /// The river rising over the screen from the edges, its edge rippling.
+    Ripple,
+}
+
+/// A screen transition: the screen is covered, the change it's hiding is
+/// made, and the screen is uncovered on the result, instead of the game
+/// snapping from one picture to the next. `T` is the change, handed back
+/// from `update` when the screen is fully covered.
+pub struct ScreenWipe<T> {
+    style: WipeStyle,
+    /// Seconds since it started; None with no wipe going.
+    elapsed: Option<f32>,
+    /// The change still to make, until the screen is covered.
+    pending: Option<T>,
+}
+
+impl<T> ScreenWipe<T> {
+    pub fn new() -> Self {
+        Self {
+            style: WipeStyle::Fade,
+            elapsed: None,
+            pending: None,
+        }
+    }
+
+    /// Start covering the screen to make `change` out of sight. A change
+    /// the last wipe was still waiting to make is handed back to make now,
+    /// so the two keep their order.
+    pub fn start(&mut self, style: WipeStyle, change: T) -> Option<T> {
+        let earlier = self.pending.replace(change);
+        self.style = style;
+        self.elapsed = Some(match self.elapsed {
+            // Already on its way to covering: carry on from there.
+            Some(elapsed) if elapsed < HALF_TIME => elapsed,
+            Some(elapsed) => (2.0 * HALF_TIME - elapsed).max(0.0),
+            None => 0.0,
+        });
+        earlier
+    }
+
+    /// Move the wipe along. Returns the change to make once the screen is
+    /// covered.
+    pub fn update(&mut self, dt: f32) -> Option<T> {
+        let elapsed = self.elapsed.as_mut()?;
+        *elapsed += dt;
+        if *elapsed >= 2.0 * HALF_TIME {
+            self.elapsed = None;
+        }
+        match self.elapsed {
+            Some(elapsed) if elapsed < HALF_TIME => None,
+            _ => self.pending.take(),
+        }
+    }
+
+    /// Whether there's a change waiting for the screen to be covered.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Whether a wipe is covering or uncovering the screen.
+    pub fn is_running(&self) -> bool {
+        self.elapsed.is_some()
+    }
+
+    /// How much of the screen is covered, 0 to 1.
+    fn coverage(&self) -> f32 {
+        let Some(elapsed) = self.elapsed else {
+            return 0.0;
+        };
+        let t = if elapsed < HALF_TIME {
+            elapsed / HALF_TIME
+        } else {
+            2.0 - elapsed / HALF_TIME
+        };
+        Ease::SmoothStep.apply(t.clamp(0.0, 1.0))
+    }
+
+    /// Draw over everything else in `view`, with the HUD camera set.
+    pub fn draw(&self, view: Rect, time: f32) {
+        let covered = self.coverage();
+        if covered <= 0.0 {
+            return;
+        }
+        match self.style {
+            WipeStyle::Fade => {
+                let color = Color {
+                    a: covered,
+                    ..FADE_COLOR
+                };
+                draw_rectangle(view.x, view.y, view.w, view.h, color);
+            }
+            WipeStyle::Iris => draw_outside(view, covered, |_| 1.0, FADE_COLOR),
+            WipeStyle::Ripple => {
+                let wave =
+                    |angle: f32| 1.0 + RIPPLE_DEPTH * (angle * RIPPLE_WAVES + time * 6.0).sin();
+                draw_outside(view, covered, wave, RIPPLE_COLOR);
+            }
+        }
+    }
+}
+
+/// Cover `view` outside a circle about its middle that shrinks to nothing
+/// as `covered` reaches 1. `edge` scales the circle's radius at each angle.
+fn draw_outside(view: Rect, covered: f32, edge: impl Fn(f32) -> f32, color: Color) {
+    let center = view.center();
+    // Far enough out to clear the corners, waves and all.
+    let far = center.distance(view.point()) * (1.0 + RIPPLE_DEPTH) + 1.0;
+    let radius = far * (1.0 - covered);
+    let angle = |i: usize| i as f32 / EDGE_SEGMENTS as f32 * TAU;
+    let inner = |i: usize| center + Vec2::from_angle(angle(i)) * radius * edge(angle(i));
+    let outer = |i: usize| center + Vec2::from_angle(angle(i)) * far * 1.5;
+    for i in 0..EDGE_SEGMENTS {
+        let (inner, next_inner) = (inner(i), inner(i + 1));
+        let (outer, next_outer) = (outer(i), outer(i + 1));
+        draw_triangle(inner, outer, next_outer, color);
+        draw_triangle(inner, next_outer, next_inner, color);
+    }
+}
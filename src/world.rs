@@ -1,7 +1,12 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fmt;
 
-/// A position on the game grid.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+/// A position on the game grid. Level files write it as `(col, row)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(from = "(i32, i32)")]
 pub struct GridPos {
     pub col: i32,
     pub row: i32,
@@ -22,30 +27,62 @@ impl GridPos {
     }
 }
 
+impl From<(i32, i32)> for GridPos {
+    fn from((col, row): (i32, i32)) -> Self {
+        Self::new(col, row)
+    }
+}
+
 impl fmt::Display for GridPos {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "({}, {})", self.col, self.row)
     }
 }
 
-/// Which side of the river.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Bank {
-    Left,
-    Right,
+/// A stretch of land between rivers, numbered from the left: the left
+/// bank is region 0, any islands follow, and the far bank comes last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RegionId(pub usize);
+
+/// What covers a grid tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tile {
+    #[default]
+    Grass,
+    Rock,
+    Tree,
+    Water,
+    /// A footbridge over water, too narrow for anyone but the player.
+    Bridge,
 }
 
-impl Bank {
-    pub fn opposite(self) -> Bank {
-        match self {
-            Bank::Left => Bank::Right,
-            Bank::Right => Bank::Left,
+impl Tile {
+    /// Read a tile map character: `.` grass, `#` rock, `T` tree, `~` water,
+    /// `=` bridge.
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c {
+            '.' => Some(Tile::Grass),
+            '#' => Some(Tile::Rock),
+            'T' => Some(Tile::Tree),
+            '~' => Some(Tile::Water),
+            '=' => Some(Tile::Bridge),
+            _ => None,
         }
     }
+
+    /// Whether the player can walk here.
+    pub fn is_walkable(self) -> bool {
+        matches!(self, Tile::Grass | Tile::Bridge)
+    }
+
+    /// Whether animals and goods can stand here.
+    pub fn is_land(self) -> bool {
+        self == Tile::Grass
+    }
 }
 
 /// Movement directions on the grid.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     Up,    // row - 1
     Down,  // row + 1
@@ -54,6 +91,13 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
     pub fn delta(self) -> (i32, i32) {
         match self {
             Direction::Up => (0, -1),
@@ -62,29 +106,16 @@ impl Direction {
             Direction::Right => (1, 0),
         }
     }
-}
 
-// --- Grid layout constants ---
-
-pub const GRID_COLS: i32 = 12;
-pub const GRID_ROWS: i32 = 8;
-
-pub const LEFT_BANK_COL_MIN: i32 = 0;
-pub const LEFT_BANK_COL_MAX: i32 = 3;
-pub const RIVER_COL_MIN: i32 = 4;
-pub const RIVER_COL_MAX: i32 = 7;
-pub const RIGHT_BANK_COL_MIN: i32 = 8;
-pub const RIGHT_BANK_COL_MAX: i32 = 11;
-
-pub const DOCK_ROW: i32 = 4;
-pub const LEFT_DOCK: GridPos = GridPos::new(3, DOCK_ROW);
-pub const RIGHT_DOCK: GridPos = GridPos::new(8, DOCK_ROW);
-
-// Starting positions
-pub const PLAYER_START: GridPos = GridPos::new(2, 4);
-pub const WOLF_START: GridPos = GridPos::new(1, 2);
-pub const SHEEP_START: GridPos = GridPos::new(1, 4);
-pub const CABBAGE_START: GridPos = GridPos::new(1, 6);
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
 
 // --- Isometric rendering constants ---
 
@@ -93,56 +124,22 @@ pub const TILE_WIDTH: f32 = 64.0;
 pub const TILE_HEIGHT: f32 = 22.0;
 
 /// The virtual world dimensions that the camera maps to screen.
+pub const WORLD_WIDTH: f32 = 880.0;
 pub const WORLD_HEIGHT: f32 = 500.0;
 
-/// Check if a grid position is walkable land.
-pub fn is_walkable(pos: GridPos) -> bool {
-    pos.row >= 0
-        && pos.row < GRID_ROWS
-        && pos.col >= 0
-        && pos.col < GRID_COLS
-        && !(pos.col >= RIVER_COL_MIN && pos.col <= RIVER_COL_MAX)
-}
-
-/// Determine which bank a position is on, if any.
-pub fn bank_of(pos: GridPos) -> Option<Bank> {
-    if pos.col >= LEFT_BANK_COL_MIN && pos.col <= LEFT_BANK_COL_MAX {
-        Some(Bank::Left)
-    } else if pos.col >= RIGHT_BANK_COL_MIN && pos.col <= RIGHT_BANK_COL_MAX {
-        Some(Bank::Right)
-    } else {
-        None
-    }
-}
-
 /// Check if two positions are adjacent (Manhattan distance <= 1).
 pub fn is_adjacent(a: GridPos, b: GridPos) -> bool {
     (a.col - b.col).abs() + (a.row - b.row).abs() <= 1
 }
 
-/// Check if a position is the dock for the given bank.
-pub fn is_dock_position(pos: GridPos, bank: Bank) -> bool {
-    match bank {
-        Bank::Left => pos == LEFT_DOCK,
-        Bank::Right => pos == RIGHT_DOCK,
-    }
-}
-
-/// Get the dock position for a bank.
-pub fn dock_for(bank: Bank) -> GridPos {
-    match bank {
-        Bank::Left => LEFT_DOCK,
-        Bank::Right => RIGHT_DOCK,
-    }
-}
-
 /// Convert grid (col, row) to isometric world coordinates.
 /// Returns the center of the tile's top diamond face.
 pub fn grid_to_iso(pos: GridPos) -> (f32, f32) {
-    // Center the grid horizontally in the world.
-    // Total iso width = (GRID_COLS + GRID_ROWS) * TILE_WIDTH / 2 = 20 * 32 = 640
-    // Total iso height = (GRID_COLS + GRID_ROWS) * TILE_HEIGHT / 2 = 20 * 11 = 220
+    // Center the classic 12x8 grid horizontally in the world.
+    // Total iso width = (cols + rows) * TILE_WIDTH / 2 = 20 * 32 = 640
+    // Total iso height = (cols + rows) * TILE_HEIGHT / 2 = 20 * 11 = 220
     // We want this centered with padding for sprites above tiles and HUD below.
+    // Other grid sizes keep the same origin; the camera frames them.
     let x_origin = 440.0; // roughly center for 16:9 aspect
     let y_origin = 100.0;
 
@@ -151,3 +148,92 @@ pub fn grid_to_iso(pos: GridPos) -> (f32, f32) {
 
     (iso_x, iso_y)
 }
+
+/// Convert isometric world coordinates back to the grid tile under them.
+pub fn iso_to_grid(iso_x: f32, iso_y: f32) -> GridPos {
+    let (x_origin, y_origin) = grid_to_iso(GridPos::new(0, 0));
+    // col - row and col + row, in tiles.
+    let diff = (iso_x - x_origin) / (TILE_WIDTH / 2.0);
+    let sum = (iso_y - y_origin) / (TILE_HEIGHT / 2.0);
+    GridPos::new(
+        ((sum + diff) / 2.0).round() as i32,
+        ((sum - diff) / 2.0).round() as i32,
+    )
+}
+
+/// Breadth-first search from `from` to the nearest tile satisfying `is_goal`,
+/// stepping only onto walkable tiles. Returns the moves to get there.
+pub fn grid_path(
+    from: GridPos,
+    is_goal: impl Fn(GridPos) -> bool,
+    is_walkable: impl Fn(GridPos) -> bool,
+) -> Option<Vec<Direction>> {
+    let mut came_from: HashMap<GridPos, (GridPos, Direction)> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(current) = queue.pop_front() {
+        if is_goal(current) {
+            let mut path = Vec::new();
+            let mut at = current;
+            while let Some(&(prev, dir)) = came_from.get(&at) {
+                path.push(dir);
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for dir in Direction::ALL {
+            let next = current.step(dir);
+            if next != from && !came_from.contains_key(&next) && is_walkable(next) {
+                came_from.insert(next, (current, dir));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// A* from `from` to `to`, stepping only onto tiles `is_open` allows; `to`
+/// itself is always allowed. Returns the tiles to walk through in order,
+/// ending with `to`.
+pub fn find_path(
+    from: GridPos,
+    to: GridPos,
+    is_open: impl Fn(GridPos) -> bool,
+) -> Option<Vec<GridPos>> {
+    let estimate = |pos: GridPos| (pos.col - to.col).abs() + (pos.row - to.row).abs();
+    let mut came_from: HashMap<GridPos, GridPos> = HashMap::new();
+    let mut cost: HashMap<GridPos, i32> = HashMap::from([(from, 0)]);
+    // Ordered by estimated total cost; the tile breaks ties.
+    let mut open = BinaryHeap::from([Reverse((estimate(from), from.col, from.row))]);
+
+    while let Some(Reverse((_, col, row))) = open.pop() {
+        let current = GridPos::new(col, row);
+        if current == to {
+            let mut path = vec![current];
+            let mut at = current;
+            while let Some(&prev) = came_from.get(&at) {
+                if prev != from {
+                    path.push(prev);
+                }
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let next_cost = cost[&current] + 1;
+        for dir in Direction::ALL {
+            let next = current.step(dir);
+            if next != to && !is_open(next) {
+                continue;
+            }
+            if cost.get(&next).is_some_and(|&known| known <= next_cost) {
+                continue;
+            }
+            cost.insert(next, next_cost);
+            came_from.insert(next, current);
+            open.push(Reverse((next_cost + estimate(next), next.col, next.row)));
+        }
+    }
+    None
+}